@@ -6,7 +6,7 @@ extern crate serde_json;
 
 use neon::prelude::*;
 
-use ratel::Module;
+use ratel::{Module, Arena};
 use ratel::error::{Error, ParseError};
 
 #[inline]
@@ -41,7 +41,8 @@ fn ast(mut cx: FunctionContext) -> JsResult<JsString> {
     let source = cx.argument::<JsString>(0)?;
     let minify = cx.argument::<JsBoolean>(1)?;
 
-    let module = match ratel::parse(&source.value()) {
+    let arena = Arena::new();
+    let module = match ratel::parse_in(&arena, &source.value()) {
         Err(errors) => {
             let str = format_errors(errors, source).join("\n");
             return cx.throw_type_error(&str)
@@ -62,7 +63,8 @@ fn transform(mut cx: FunctionContext) -> JsResult<JsString> {
     let source = cx.argument::<JsString>(0)?;
     let minify = cx.argument::<JsBoolean>(1)?;
 
-    let module = match ratel::parse(&source.value()) {
+    let arena = Arena::new();
+    let module = match ratel::parse_in(&arena, &source.value()) {
         Err(errors) => {
             let str = format_errors(errors, source).join("\n");
             return cx.throw_type_error(&str)
@@ -82,7 +84,8 @@ fn parse(mut cx: FunctionContext) -> JsResult<JsString> {
 
     let source = cx.argument::<JsString>(0)?;
 
-    let module = match ratel::parse(&source.value()) {
+    let arena = Arena::new();
+    let module = match ratel::parse_in(&arena, &source.value()) {
         Err(errors) => {
             let str = format_errors(errors, source).join("\n");
             return cx.throw_type_error(&str)
@@ -55,6 +55,7 @@ describe('Ratel ffi', () => {
       const json = JSON.parse(result);
       assert.deepEqual(json, {
           "type": "Program",
+          "sourceType": "module",
           "body": [
               {
                   "type": "ExpressionStatement",
@@ -76,8 +77,7 @@ describe('Ratel ffi', () => {
       const source = `const foo = 2; let bar=4; foo**bar === 16`;
       const ast = Ratel.ast(source, true);
       const tree = JSON.parse(ast);
-      const acornAST = acorn.parse(source);
-      delete acornAST['sourceType'];
+      const acornAST = acorn.parse(source, { sourceType: 'module' });
       assert.deepEqual(tree, acornAST);
     });
   });
@@ -0,0 +1,46 @@
+use ratel::ast::StatementNode;
+
+/// Index of a `BasicBlock` in the `Cfg`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockId(pub(crate) usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Unconditional jump, such as falling through to the next statement,
+    /// `break`, `continue` or going back to the head of a loop.
+    Jump,
+    /// Taken when the test of an `if` or a loop is truthy.
+    True,
+    /// Taken when the test of an `if` or a loop is falsy.
+    False,
+    /// From the discriminant of a `switch` to one of its cases.
+    Case,
+    /// `return`, or the end of the body, going to the exit.
+    Return,
+    /// `throw`, going to the closest handler or the exit.
+    Throw,
+    /// Any statement of the block can throw into the handler of the
+    /// enclosing `try`.
+    Exception,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub target: BlockId,
+    pub kind: EdgeKind,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BasicBlock<'ast> {
+    /// Statements that start in this block, in source order. Compound
+    /// statements start in the block that evaluates their test, and the
+    /// statements nested in them are listed in the blocks they start in.
+    /// Statements of a `finally` are listed once for every path through it.
+    pub statements: Vec<StatementNode<'ast>>,
+
+    /// Blocks control can go to once this block is done.
+    pub successors: Vec<Edge>,
+
+    /// Blocks that have this block as a successor.
+    pub predecessors: Vec<BlockId>,
+}
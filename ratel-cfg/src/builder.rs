@@ -0,0 +1,506 @@
+use std::mem;
+
+use ratel::ast::{Statement, StatementNode, StatementList, Expression, ExpressionNode, Literal, Node};
+use ratel::ast::statement::*;
+
+use {address, Cfg, Placement};
+use block::{BasicBlock, BlockId, Edge, EdgeKind};
+
+/// Statement that `break` or `continue` can go to.
+struct JumpTarget<'ast> {
+    labels: Vec<&'ast str>,
+
+    /// Whether an unlabeled `break` can go to this statement, which is
+    /// the case for loops and `switch`.
+    breakable: bool,
+
+    break_to: BlockId,
+    continue_to: Option<BlockId>,
+
+    /// Number of enclosing `finally` blocks at the statement.
+    finalizers: usize,
+}
+
+/// `finally` block of a `try` currently being built.
+struct Finalizer {
+    /// Jumps leaving the `try`, which have to go through the `finally`
+    /// first: the block jumping, the target, the number of `finally` blocks
+    /// enclosing the target, and the kind of the jump.
+    pending: Vec<(BlockId, BlockId, usize, EdgeKind)>,
+}
+
+/// Lowers statements into basic blocks. A `finally` is built once for every
+/// way it can be entered, so that each copy only continues to where control
+/// was going before it.
+pub struct Builder<'ast> {
+    cfg: Cfg<'ast>,
+    current: BlockId,
+
+    targets: Vec<JumpTarget<'ast>>,
+    finalizers: Vec<Finalizer>,
+
+    /// Entries of the `catch` and `finally` blocks that exceptions go to.
+    handlers: Vec<BlockId>,
+
+    /// Labels for the next loop.
+    labels: Vec<&'ast str>,
+
+    /// Placement of the statement being built.
+    parent: Option<usize>,
+}
+
+/// Value of a test that is known at compile time.
+#[inline]
+fn constant(test: &ExpressionNode) -> Option<bool> {
+    match test.item {
+        Expression::Literal(Literal::True) => Some(true),
+        Expression::Literal(Literal::False) => Some(false),
+        _ => None,
+    }
+}
+
+impl<'ast> Builder<'ast> {
+    pub fn new() -> Self {
+        let mut builder = Builder {
+            cfg: Cfg::default(),
+            current: BlockId(0),
+            targets: Vec::new(),
+            finalizers: Vec::new(),
+            handlers: Vec::new(),
+            labels: Vec::new(),
+            parent: None,
+        };
+
+        let entry = builder.block();
+        let exit = builder.block();
+
+        builder.cfg.entry = entry;
+        builder.cfg.exit = exit;
+        builder.current = entry;
+
+        builder
+    }
+
+    pub fn build(mut self, body: StatementList<'ast>) -> Cfg<'ast> {
+        self.statements(body);
+
+        let (end, exit) = (self.current, self.cfg.exit);
+
+        self.edge(end, exit, EdgeKind::Return);
+        self.cfg.end = end;
+
+        for index in 0..self.cfg.blocks.len() {
+            for edge in self.cfg.blocks[index].successors.clone() {
+                self.cfg.blocks[edge.target.0].predecessors.push(BlockId(index));
+            }
+        }
+
+        self.cfg.reachable = vec![false; self.cfg.blocks.len()];
+
+        let mut stack = vec![self.cfg.entry];
+
+        while let Some(block) = stack.pop() {
+            if self.cfg.reachable[block.0] {
+                continue;
+            }
+
+            self.cfg.reachable[block.0] = true;
+            stack.extend(self.cfg.blocks[block.0].successors.iter().map(|edge| edge.target));
+        }
+
+        self.cfg
+    }
+
+    /// New empty block, which can throw into the current handler.
+    fn block(&mut self) -> BlockId {
+        let id = BlockId(self.cfg.blocks.len());
+
+        self.cfg.blocks.push(BasicBlock::default());
+
+        if let Some(&handler) = self.handlers.last() {
+            self.edge(id, handler, EdgeKind::Exception);
+        }
+
+        id
+    }
+
+    #[inline]
+    fn edge(&mut self, from: BlockId, target: BlockId, kind: EdgeKind) {
+        self.cfg.blocks[from.0].successors.push(Edge { target, kind });
+    }
+
+    /// Current handler for exceptions, the exit if not in a `try`.
+    #[inline]
+    fn handler(&self) -> BlockId {
+        self.handlers.last().cloned().unwrap_or(self.cfg.exit)
+    }
+
+    /// Jump from the current block, going through all `finally` blocks
+    /// entered since the target. Statements following the jump end up in
+    /// a new, unreachable block.
+    fn jump(&mut self, target: BlockId, finalizers: usize, kind: EdgeKind) {
+        let current = self.current;
+
+        if self.finalizers.len() > finalizers {
+            let finalizer = self.finalizers.last_mut().unwrap();
+
+            finalizer.pending.push((current, target, finalizers, kind));
+        } else {
+            self.edge(current, target, kind);
+        }
+
+        self.current = self.block();
+    }
+
+    /// Index of the statement in the placements, adding the current block.
+    fn place(&mut self, node: StatementNode<'ast>) -> usize {
+        let current = self.current;
+
+        self.cfg.blocks[current.0].statements.push(node);
+
+        let index = match self.cfg.lookup.get(&address(&node)) {
+            Some(&index) => index,
+            None => {
+                self.cfg.placements.push(Placement {
+                    node,
+                    parent: self.parent,
+                    blocks: Vec::new(),
+                });
+                self.cfg.lookup.insert(address(&node), self.cfg.placements.len() - 1);
+                self.cfg.placements.len() - 1
+            }
+        };
+
+        self.cfg.placements[index].blocks.push(current);
+
+        index
+    }
+
+    fn statements(&mut self, body: StatementList<'ast>) {
+        for statement in body.iter() {
+            self.statement(*statement);
+        }
+    }
+
+    /// Build the body of a loop or `switch` with a jump target for it.
+    fn with_target<F>(&mut self, breakable: bool, break_to: BlockId, continue_to: Option<BlockId>, build: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let labels = mem::take(&mut self.labels);
+        let finalizers = self.finalizers.len();
+
+        self.targets.push(JumpTarget { labels, breakable, break_to, continue_to, finalizers });
+        build(self);
+        self.targets.pop();
+    }
+
+    /// Target of a `break` or `continue`, with the given label or the closest
+    /// matching statement.
+    fn target(&self, label: Option<&str>, is_continue: bool) -> Option<(BlockId, usize)> {
+        self.targets.iter().rev().filter(|target| match label {
+            Some(label) => target.labels.contains(&label),
+            None => target.breakable,
+        }).filter_map(|target| {
+            let to = if is_continue { target.continue_to } else { Some(target.break_to) };
+
+            to.map(|to| (to, target.finalizers))
+        }).next()
+    }
+
+    fn statement(&mut self, node: StatementNode<'ast>) {
+        let index = self.place(node);
+        let parent = self.parent.replace(index);
+
+        match node.item {
+            Statement::Return(_) => {
+                let exit = self.cfg.exit;
+
+                self.jump(exit, 0, EdgeKind::Return);
+            },
+            Statement::Throw(_) => {
+                let (current, handler) = (self.current, self.handler());
+
+                self.edge(current, handler, EdgeKind::Throw);
+                self.current = self.block();
+            },
+            Statement::Break(BreakStatement { label }) => {
+                match self.target(label.map(|label| label.item), false) {
+                    Some((target, finalizers)) => self.jump(target, finalizers, EdgeKind::Jump),
+                    None => self.current = self.block(),
+                }
+            },
+            Statement::Continue(ContinueStatement { label }) => {
+                match self.target(label.map(|label| label.item), true) {
+                    Some((target, finalizers)) => self.jump(target, finalizers, EdgeKind::Jump),
+                    None => self.current = self.block(),
+                }
+            },
+            Statement::If(IfStatement { ref test, consequent, alternate }) => {
+                let test = constant(test);
+                let current = self.current;
+                let after = self.block();
+
+                let then = self.block();
+                if test != Some(false) {
+                    self.edge(current, then, EdgeKind::True);
+                }
+                self.current = then;
+                self.statement(consequent);
+                self.end_at(after);
+
+                let otherwise = match alternate {
+                    Some(_) => self.block(),
+                    None => after,
+                };
+                if test != Some(true) {
+                    self.edge(current, otherwise, EdgeKind::False);
+                }
+                if let Some(alternate) = alternate {
+                    self.current = otherwise;
+                    self.statement(alternate);
+                    self.end_at(after);
+                }
+
+                self.current = after;
+            },
+            Statement::While(WhileStatement { ref test, body }) => {
+                let head = self.block();
+                self.end_at(head);
+                self.branch(head, Some(test), body, head);
+            },
+            Statement::For(ForStatement { ref test, update, body, .. }) => {
+                let head = self.block();
+                self.end_at(head);
+
+                let next = match update {
+                    Some(_) => {
+                        let update = self.block();
+                        self.edge(update, head, EdgeKind::Jump);
+                        update
+                    },
+                    None => head,
+                };
+
+                self.branch(head, test.as_ref(), body, next);
+            },
+            Statement::ForIn(ForInStatement { body, .. }) |
+            Statement::ForOf(ForOfStatement { body, .. }) => {
+                let head = self.block();
+                self.end_at(head);
+                self.branch(head, None, body, head);
+
+                // Unlike `for (;;)`, running out of items leaves the loop
+                let after = self.current;
+                self.edge(head, after, EdgeKind::False);
+            },
+            Statement::Do(DoStatement { body, ref test }) => {
+                let start = self.block();
+                let next = self.block();
+                let after = self.block();
+                let test = constant(test);
+
+                self.end_at(start);
+                self.with_target(true, after, Some(next), |builder| {
+                    builder.statement(body);
+                    builder.end_at(next);
+                });
+
+                if test != Some(false) {
+                    self.edge(next, start, EdgeKind::True);
+                }
+                if test != Some(true) {
+                    self.edge(next, after, EdgeKind::False);
+                }
+
+                self.current = after;
+            },
+            Statement::Labeled(LabeledStatement { label, body }) => {
+                match body.item {
+                    Statement::While(_) | Statement::Do(_) | Statement::For(_) |
+                    Statement::ForIn(_) | Statement::ForOf(_) | Statement::Labeled(_) => {
+                        self.labels.push(label);
+                        self.statement(body);
+                    },
+                    _ => {
+                        self.labels.push(label);
+
+                        let after = self.block();
+
+                        self.with_target(false, after, None, |builder| {
+                            builder.statement(body);
+                            builder.end_at(after);
+                        });
+
+                        self.current = after;
+                    }
+                }
+            },
+            Statement::Switch(SwitchStatement { discriminant: _, cases }) => {
+                let dispatch = self.current;
+                let after = self.block();
+                let cases: Vec<_> = cases.body.iter().cloned().collect();
+
+                self.with_target(true, after, None, |builder| {
+                    let mut default = false;
+
+                    for (index, case) in cases.iter().enumerate() {
+                        let entry = builder.block();
+
+                        builder.edge(dispatch, entry, EdgeKind::Case);
+
+                        if index > 0 {
+                            builder.end_at(entry);
+                        }
+
+                        default |= case.test.is_none();
+
+                        builder.current = entry;
+                        builder.statements(case.consequent);
+
+                        if index + 1 < cases.len() && !case.consequent.is_empty() {
+                            builder.cfg.fall_through.push((*case, builder.current));
+                        }
+                    }
+
+                    if !cases.is_empty() {
+                        builder.end_at(after);
+                    }
+
+                    if !default {
+                        builder.edge(dispatch, after, EdgeKind::Jump);
+                    }
+                });
+
+                self.current = after;
+            },
+            Statement::Try(TryStatement { block, handler, finalizer }) => {
+                self.try_statement(block.body, handler, finalizer.map(|finalizer| finalizer.body));
+            },
+            Statement::Block(BlockStatement { body }) => self.statements(body),
+            Statement::Empty | Statement::Debugger | Statement::Expression(_) |
+            Statement::Declaration(_) | Statement::Function(_) | Statement::Class(_) |
+            Statement::Import(_) => {},
+        }
+
+        self.parent = parent;
+    }
+
+    /// Jump from the current block to the given block.
+    #[inline]
+    fn end_at(&mut self, target: BlockId) {
+        let current = self.current;
+
+        self.edge(current, target, EdgeKind::Jump);
+    }
+
+    /// Build a loop with the test evaluated at `head`, and `next` being where
+    /// the body goes once done, as well as the target of `continue`. Leaves
+    /// the current block after the loop. A missing test is always truthy.
+    fn branch(&mut self, head: BlockId, test: Option<&ExpressionNode<'ast>>, body: StatementNode<'ast>, next: BlockId) {
+        let test = match test {
+            Some(test) => constant(test),
+            None => Some(true),
+        };
+        let start = self.block();
+        let after = self.block();
+
+        if test != Some(false) {
+            self.edge(head, start, EdgeKind::True);
+        }
+        if test != Some(true) {
+            self.edge(head, after, EdgeKind::False);
+        }
+
+        self.current = start;
+        self.with_target(true, after, Some(next), |builder| {
+            builder.statement(body);
+            builder.end_at(next);
+        });
+
+        self.current = after;
+    }
+
+    fn try_statement(
+        &mut self,
+        block: StatementList<'ast>,
+        handler: Option<Node<'ast, CatchClause<'ast>>>,
+        finalizer: Option<StatementList<'ast>>,
+    ) {
+        // Entered when an exception isn't handled by the `catch`
+        let rethrow = finalizer.map(|_| {
+            let entry = self.block();
+
+            self.finalizers.push(Finalizer { pending: Vec::new() });
+            self.handlers.push(entry);
+
+            entry
+        });
+
+        let catch = handler.map(|_| {
+            let entry = self.block();
+
+            self.handlers.push(entry);
+
+            entry
+        });
+
+        let start = self.block();
+
+        self.end_at(start);
+        self.current = start;
+        self.statements(block);
+
+        let mut completions = vec![self.current];
+
+        if let (Some(handler), Some(entry)) = (handler, catch) {
+            self.handlers.pop();
+            self.current = entry;
+            self.statements(handler.body.body);
+            completions.push(self.current);
+        }
+
+        let after = match (finalizer, rethrow) {
+            (Some(body), Some(rethrow)) => {
+                self.handlers.pop();
+
+                let pending = self.finalizers.pop().map(|finalizer| finalizer.pending).unwrap_or_default();
+
+                let entry = self.block();
+                for completion in completions {
+                    self.edge(completion, entry, EdgeKind::Jump);
+                }
+                self.current = entry;
+                self.statements(body);
+
+                let after = self.block();
+                self.end_at(after);
+
+                self.current = rethrow;
+                self.statements(body);
+                let (current, handler) = (self.current, self.handler());
+                self.edge(current, handler, EdgeKind::Throw);
+
+                for (from, target, finalizers, kind) in pending {
+                    let entry = self.block();
+
+                    self.edge(from, entry, kind);
+                    self.current = entry;
+                    self.statements(body);
+                    self.jump(target, finalizers, kind);
+                }
+
+                after
+            },
+            _ => {
+                let after = self.block();
+                for completion in completions {
+                    self.edge(completion, after, EdgeKind::Jump);
+                }
+                after
+            }
+        };
+
+        self.current = after;
+    }
+}
@@ -0,0 +1,349 @@
+//! Control flow graph of a function body: the statements lowered into basic
+//! blocks connected by the jumps between them, answering whether code is
+//! reachable and whether the body always returns.
+//!
+//! ```
+//! extern crate ratel;
+//! extern crate ratel_cfg;
+//!
+//! use ratel::ast::Statement;
+//!
+//! fn main() {
+//!     let arena = ratel::Arena::new();
+//!     let module = ratel::parse_in(&arena, "function foo(a) { if (a) { return 1; } else { throw a; } a; }").unwrap();
+//!
+//!     let body = match module.body().only_element().unwrap().item {
+//!         Statement::Function(ref function) => function.body,
+//!         _ => unreachable!(),
+//!     };
+//!
+//!     let cfg = ratel_cfg::build(&body);
+//!
+//!     assert_eq!(cfg.always_returns(), true);
+//!     assert_eq!(cfg.unreachable_statements().len(), 1);
+//! }
+//! ```
+
+#[cfg(test)]
+#[macro_use]
+extern crate pretty_assertions;
+extern crate ratel;
+
+use std::collections::HashMap;
+
+use ratel::Module;
+use ratel::ast::{Node, Loc, BlockNode, Statement, StatementNode};
+use ratel::ast::statement::SwitchCase;
+
+mod block;
+mod builder;
+
+pub use block::{BasicBlock, BlockId, Edge, EdgeKind};
+
+/// Build the graph of a function body.
+#[inline]
+pub fn build<'ast>(body: &BlockNode<'ast, Statement<'ast>>) -> Cfg<'ast> {
+    builder::Builder::new().build(body.body)
+}
+
+/// Build the graph of the top level of a module.
+#[inline]
+pub fn build_module<'ast>(module: &Module<'ast>) -> Cfg<'ast> {
+    builder::Builder::new().build(module.body())
+}
+
+/// Basic blocks of a function body. Statements of the body can be looked up
+/// by identity, so the graph goes stale if the AST is modified.
+#[derive(Clone, Debug, Default)]
+pub struct Cfg<'ast> {
+    blocks: Vec<BasicBlock<'ast>>,
+    entry: BlockId,
+    exit: BlockId,
+
+    // Block falling off the end of the body
+    end: BlockId,
+
+    reachable: Vec<bool>,
+
+    // Every statement with the blocks it starts in, in source order
+    placements: Vec<Placement<'ast>>,
+    lookup: HashMap<usize, usize>,
+
+    // Cases that don't end with a jump, with the block at their end
+    fall_through: Vec<(Node<'ast, SwitchCase<'ast>>, BlockId)>,
+}
+
+#[derive(Clone, Debug)]
+struct Placement<'ast> {
+    node: StatementNode<'ast>,
+    parent: Option<usize>,
+    blocks: Vec<BlockId>,
+}
+
+impl Default for BlockId {
+    #[inline]
+    fn default() -> Self {
+        BlockId(0)
+    }
+}
+
+#[inline]
+fn address<T>(node: &Node<T>) -> usize {
+    &**node as *const Loc<T> as usize
+}
+
+impl<'ast> Cfg<'ast> {
+    /// The block control enters the body at.
+    #[inline]
+    pub fn entry(&self) -> BlockId {
+        self.entry
+    }
+
+    /// The empty block `return`, uncaught `throw` and the end of the body
+    /// go to.
+    #[inline]
+    pub fn exit(&self) -> BlockId {
+        self.exit
+    }
+
+    #[inline]
+    pub fn block(&self, id: BlockId) -> &BasicBlock<'ast> {
+        &self.blocks[id.0]
+    }
+
+    /// All blocks, the entry and exit first.
+    #[inline]
+    pub fn blocks(&self) -> &[BasicBlock<'ast>] {
+        &self.blocks
+    }
+
+    /// Whether there is a path from the entry to the block. Tests of `if`
+    /// and loops are assumed to go both ways, unless they are `true` or
+    /// `false` literals.
+    #[inline]
+    pub fn is_reachable(&self, id: BlockId) -> bool {
+        self.reachable[id.0]
+    }
+
+    /// Whether the statement is reachable, `None` if it isn't part of the
+    /// body, or is nested in a function within it.
+    pub fn is_statement_reachable(&self, node: &StatementNode<'ast>) -> Option<bool> {
+        self.lookup.get(&address(node)).map(|&index| self.is_placement_reachable(index))
+    }
+
+    #[inline]
+    fn is_placement_reachable(&self, index: usize) -> bool {
+        self.placements[index].blocks.iter().any(|&block| self.is_reachable(block))
+    }
+
+    /// Unreachable statements in source order, leaving out the statements
+    /// nested in them.
+    pub fn unreachable_statements(&self) -> Vec<StatementNode<'ast>> {
+        self.placements.iter().enumerate().filter(|&(index, placement)| {
+            !self.is_placement_reachable(index) && placement.parent.is_none_or(|parent| self.is_placement_reachable(parent))
+        }).map(|(_, placement)| placement.node).collect()
+    }
+
+    /// Reachable `switch` cases whose statements run into the next case,
+    /// leaving out empty cases sharing statements with the next one.
+    pub fn fall_through_cases(&self) -> Vec<Node<'ast, SwitchCase<'ast>>> {
+        self.fall_through
+            .iter()
+            .filter(|&&(_, end)| self.is_reachable(end))
+            .map(|&(case, _)| case)
+            .collect()
+    }
+
+    /// Whether every path through the body ends with `return` or `throw`, or
+    /// never ends, as opposed to running off the end of the body.
+    #[inline]
+    pub fn always_returns(&self) -> bool {
+        !self.is_reachable(self.end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::{parse_in, Arena};
+    use ratel::ast::{Expression, OperatorKind};
+
+    fn function_cfg<'ast>(arena: &'ast Arena, source: &str) -> Cfg<'ast> {
+        let module = parse_in(arena, source).unwrap();
+
+        match module.body().only_element().unwrap().item {
+            Statement::Function(ref function) => build(&function.body),
+            _ => panic!("Expected a function"),
+        }
+    }
+
+    /// Names of the unreachable `marker;` statements, `?` for any other
+    /// statement.
+    fn unreachable(cfg: &Cfg) -> Vec<String> {
+        cfg.unreachable_statements().iter().map(|statement| match statement.item {
+            Statement::Expression(expression) => match expression.item {
+                Expression::Identifier(name) => name.to_string(),
+                _ => "?".to_string(),
+            },
+            _ => "?".to_string(),
+        }).collect()
+    }
+
+    #[test]
+    fn should_find_code_after_jumps() {
+        let arena = Arena::new();
+        let cfg = function_cfg(&arena, r#"
+            function foo(x) {
+                while (x) {
+                    if (x) { break; a; }
+                    if (x) { continue; b; }
+                    c;
+                }
+                if (x) { return; d; } else { throw x; e; }
+                f;
+                if (x) { g; }
+            }
+        "#);
+
+        assert_eq!(unreachable(&cfg), vec!["a", "b", "d", "e", "f", "?"]);
+        assert_eq!(cfg.always_returns(), true);
+    }
+
+    #[test]
+    fn should_check_that_every_path_returns() {
+        let arena = Arena::new();
+        let returns = |source| function_cfg(&arena, source).always_returns();
+
+        assert_eq!(returns("function f(x) { if (x) { return 1; } }"), false);
+        assert_eq!(returns("function f(x) { if (x) { return 1; } return 2; }"), true);
+        assert_eq!(returns("function f(x) { while (true) {} }"), true);
+        assert_eq!(returns("function f(x) { while (true) { break; } }"), false);
+        assert_eq!(returns("function f(x) { for (;;) { if (x) { return; } } }"), true);
+        assert_eq!(returns("function f(x) { for (y of x) { return; } }"), false);
+        assert_eq!(returns("function f(x) { do { return; } while (x); }"), true);
+        assert_eq!(returns("function f(x) { switch (x) { case 1: return; default: throw x; } }"), true);
+        assert_eq!(returns("function f(x) { switch (x) { case 1: return; } }"), false);
+    }
+
+    #[test]
+    fn should_follow_labels() {
+        let arena = Arena::new();
+        let cfg = function_cfg(&arena, r#"
+            function foo(x) {
+                outer: for (;;) {
+                    while (x) {
+                        continue outer;
+                        a;
+                    }
+                    inner: {
+                        break inner;
+                        b;
+                    }
+                    break outer;
+                    c;
+                }
+                return;
+            }
+        "#);
+
+        assert_eq!(unreachable(&cfg), vec!["a", "b", "c"]);
+        assert_eq!(cfg.always_returns(), true);
+    }
+
+    #[test]
+    fn should_find_fall_through_cases() {
+        let arena = Arena::new();
+        let cfg = function_cfg(&arena, r#"
+            function foo(x) {
+                switch (x) {
+                    case 1:
+                    case 2:
+                        a;
+                    case 3:
+                        b;
+                        break;
+                    case 4:
+                        if (x) { return; }
+                    case 5:
+                        throw x;
+                    default:
+                        c;
+                }
+            }
+        "#);
+
+        let cases: Vec<_> = cfg.fall_through_cases().iter().map(|case| match case.test {
+            Some(test) => match test.item {
+                Expression::Literal(literal) => format!("{:?}", literal),
+                _ => "?".to_string(),
+            },
+            None => "default".to_string(),
+        }).collect();
+
+        assert_eq!(cases, vec!["Number(\"2\")", "Number(\"4\")"]);
+        assert_eq!(cfg.always_returns(), false);
+    }
+
+    #[test]
+    fn should_route_jumps_through_finally() {
+        let arena = Arena::new();
+        let cfg = function_cfg(&arena, r#"
+            function foo(x) {
+                if (x) {
+                    try {
+                        return;
+                    } finally {
+                        a;
+                    }
+                    b;
+                }
+                try {
+                    throw x;
+                    c;
+                } catch (err) {
+                    d;
+                }
+                while (x) {
+                    try {
+                        break;
+                    } finally {
+                        e;
+                    }
+                    f;
+                }
+                try {} finally { return; }
+                g;
+            }
+        "#);
+
+        assert_eq!(unreachable(&cfg), vec!["b", "c", "f", "g"]);
+        assert_eq!(cfg.always_returns(), true);
+    }
+
+    #[test]
+    fn should_link_blocks() {
+        let arena = Arena::new();
+        let cfg = function_cfg(&arena, "function foo(x) { if (x) { x = 1; } x; }");
+
+        let kinds: Vec<_> = cfg.block(cfg.entry()).successors.iter().map(|edge| edge.kind).collect();
+
+        assert_eq!(kinds, vec![EdgeKind::True, EdgeKind::False]);
+        assert_eq!(cfg.block(cfg.entry()).statements.len(), 1);
+        assert_eq!(cfg.block(cfg.exit()).successors, vec![]);
+        assert_eq!(cfg.block(cfg.exit()).predecessors.len(), 1);
+
+        let then = cfg.block(cfg.entry()).successors[0].target;
+        let assignment = cfg.block(then).statements[1];
+
+        match assignment.item {
+            Statement::Expression(expression) => match expression.item {
+                Expression::Binary(ref binary) => assert_eq!(binary.operator, OperatorKind::Assign),
+                _ => panic!("Expected an assignment"),
+            },
+            _ => panic!("Expected an expression statement"),
+        }
+
+        assert_eq!(cfg.is_statement_reachable(&assignment), Some(true));
+        assert_eq!(build_module(&parse_in(&arena, "a; throw b; c;").unwrap()).unreachable_statements().len(), 1);
+    }
+}
@@ -93,7 +93,8 @@ module.exports = {
 
 #[bench]
 fn codegen_from_ast(b: &mut Bencher) {
-    let module = ratel::parse(SOURCE).expect("Must parse");
+    let arena = ratel::Arena::new();
+    let module = ratel::parse_in(&arena, SOURCE).expect("Must parse");
     let output = ratel_codegen::codegen(&module, true);
 
     b.bytes = output.len() as u64;
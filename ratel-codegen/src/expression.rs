@@ -1,4 +1,4 @@
-use ratel::ast::{Expression, Literal, OperatorKind, OperatorCategory, Property, PropertyKey, Pattern};
+use ratel::ast::{Expression, ExpressionNode, Literal, OperatorKind, OperatorCategory, Property, PropertyKey, Pattern};
 use ratel::ast::expression::*;
 
 use {ToCode, Generator};
@@ -12,6 +12,7 @@ impl<'ast, G: Generator> ToCode<G> for Expression<'ast> {
         match *self {
             Void                         => {},
             This(_)                      => gen.write_bytes(b"this"),
+            Super(_)                     => gen.write_bytes(b"super"),
             Identifier(ref ident)        => gen.write(ident),
             Literal(ref value)           => gen.write(value),
             Sequence(ref sequence)       => gen.write(sequence),
@@ -91,7 +92,7 @@ impl<'ast, G: Generator> ToCode<G> for Property<'ast> {
                 gen.write(key);
                 gen.write_byte(b':');
                 gen.write_pretty(b' ');
-                gen.write(value);
+                gen.write_expression(value, 1);
             },
             Method {
                 ref key,
@@ -121,7 +122,7 @@ impl<'ast, G: Generator> ToCode<G> for ArrayExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
         gen.write_byte(b'[');
-        gen.write_list(&self.body);
+        gen.write_expression_list(&self.body);
         gen.write_byte(b']');
     }
 }
@@ -129,7 +130,7 @@ impl<'ast, G: Generator> ToCode<G> for ArrayExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for MemberExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write_expression(&self.object, 19);
+        write_object(gen, &self.object);
         gen.write_byte(b'.');
         gen.write(&self.property);
     }
@@ -138,13 +139,26 @@ impl<'ast, G: Generator> ToCode<G> for MemberExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for ComputedMemberExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write_expression(&self.object, 19);
+        write_object(gen, &self.object);
         gen.write_byte(b'[');
         gen.write(&self.property);
         gen.write_byte(b']');
     }
 }
 
+/// Object of a member expression, members and calls chain without
+/// parentheses, anything binding looser needs them.
+#[inline]
+fn write_object<'ast, G: Generator>(gen: &mut G, object: &ExpressionNode<'ast>) {
+    match object.item {
+        Expression::Member(_)         |
+        Expression::ComputedMember(_) |
+        Expression::MetaProperty(_)   |
+        Expression::Call(_)           => gen.write(object),
+        _                             => gen.write_expression(object, 19),
+    }
+}
+
 impl<'ast, G: Generator> ToCode<G> for MetaPropertyExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
@@ -159,7 +173,7 @@ impl<'ast, G: Generator> ToCode<G> for CallExpression<'ast> {
     fn to_code(&self, gen: &mut G) {
         gen.write(&self.callee);
         gen.write_byte(b'(');
-        gen.write_list(&self.arguments);
+        gen.write_expression_list(&self.arguments);
         gen.write_byte(b')');
     }
 }
@@ -215,6 +229,18 @@ impl<'ast, G: Generator> ToCode<G> for BinaryExpression<'ast> {
     }
 }
 
+/// Whether a call is part of the callee of a `new` expression, which
+/// would otherwise take the arguments of the call as its own.
+fn has_call(expression: &ExpressionNode) -> bool {
+    match expression.item {
+        Expression::Call(_) => true,
+        Expression::Member(MemberExpression { object, .. })                 |
+        Expression::ComputedMember(ComputedMemberExpression { object, .. }) => has_call(&object),
+        Expression::TaggedTemplate(TaggedTemplateExpression { tag, .. })    => has_call(&tag),
+        _ => false,
+    }
+}
+
 impl<'ast, G: Generator> ToCode<G> for PrefixExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
@@ -222,7 +248,30 @@ impl<'ast, G: Generator> ToCode<G> for PrefixExpression<'ast> {
         if self.operator.category() == OperatorCategory::Word {
             gen.write_byte(b' ');
         }
-        gen.write(&self.operand);
+
+        if self.operator != OperatorKind::New {
+            gen.write_expression(&self.operand, 15);
+
+            return;
+        }
+
+        // `new (a())()` and `new (a().b)`
+        match self.operand.item {
+            Expression::Call(CallExpression { callee, arguments }) if has_call(&callee) => {
+                gen.write_byte(b'(');
+                gen.write(&callee);
+                gen.write_byte(b')');
+                gen.write_byte(b'(');
+                gen.write_expression_list(arguments);
+                gen.write_byte(b')');
+            },
+            Expression::Member(_) | Expression::ComputedMember(_) | Expression::TaggedTemplate(_) if has_call(&self.operand) => {
+                gen.write_byte(b'(');
+                gen.write(&self.operand);
+                gen.write_byte(b')');
+            },
+            _ => gen.write_expression(&self.operand, 15),
+        }
     }
 }
 
@@ -237,15 +286,15 @@ impl<'ast, G: Generator> ToCode<G> for PostfixExpression<'ast> {
 impl<'ast, G: Generator> ToCode<G> for ConditionalExpression<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
-        gen.write(&self.test);
+        gen.write_expression(&self.test, 5);
         gen.write_pretty(b' ');
         gen.write_byte(b'?');
         gen.write_pretty(b' ');
-        gen.write(&self.consequent);
+        gen.write_expression(&self.consequent, 1);
         gen.write_pretty(b' ');
         gen.write_byte(b':');
         gen.write_pretty(b' ');
-        gen.write(&self.alternate);
+        gen.write_expression(&self.alternate, 1);
     }
 }
 
@@ -298,7 +347,7 @@ impl<'ast, G: Generator> ToCode<G> for ArrowBody<'ast> {
     #[inline]
     fn to_code(&self, gen: &mut G) {
         match *self {
-            ArrowBody::Expression(ref expression) => gen.write(expression),
+            ArrowBody::Expression(ref expression) => gen.write_expression(expression, 1),
             ArrowBody::Block(ref block)           => gen.write(block),
         }
     }
@@ -388,6 +437,10 @@ mod test {
         assert_min("(1+2),3,4;", "1+2,3,4;");
         assert_min("1+(2,3,4);", "1+(2,3,4);");
         assert_min("(1,2,3)+4;", "(1,2,3)+4;");
+        assert_min("[(1, 2), 3];", "[(1,2),3];");
+        assert_min("({ a: (1, 2) });", "({a:(1,2)});");
+        assert_min("(1, 2) ? (3, 4) : (5, 6);", "(1,2)?(3,4):(5,6);");
+        assert_min("() => (1, 2);", "()=>(1,2);");
     }
 
     #[test]
@@ -431,8 +484,14 @@ mod test {
         assert_min("++foo", "++foo;");
         assert_min("--foo", "--foo;");
         assert_min("new foo", "new foo;");
+        assert_min("new foo.bar(baz)", "new foo.bar(baz);");
+        assert_min("new (foo())()", "new (foo())();");
+        assert_min("new (foo().bar[baz])(qux)", "new (foo().bar[baz])(qux);");
+        assert_min("new (foo().bar)", "new (foo().bar);");
         assert_min("void foo", "void foo;");
         assert_min("typeof foo", "typeof foo;");
+        assert_min("!(foo instanceof bar)", "!(foo instanceof bar);");
+        assert_min("-(foo + bar)", "-(foo+bar);");
     }
 
     #[test]
@@ -443,7 +502,10 @@ mod test {
 
     #[test]
     fn conditional_expression() {
-        assert_min("true ? foo : bar", "true?foo:bar;")
+        assert_min("true ? foo : bar", "true?foo:bar;");
+        assert_min("(a ? b : c) ? d : e", "(a?b:c)?d:e;");
+        assert_min("(a = b) ? c : d", "(a=b)?c:d;");
+        assert_min("a || b ? c : d ? e : f", "a||b?c:d?e:f;");
     }
 
     #[test]
@@ -465,6 +527,8 @@ mod test {
     fn call_expression() {
         assert_min("foobar();", "foobar();");
         assert_min("foobar(1, 2, 3);", "foobar(1,2,3);");
+        assert_min("foobar((a, b), c);", "foobar((a,b),c);");
+        assert_min("new Foo((a, b));", "new Foo((a,b));");
     }
 
     #[test]
@@ -474,6 +538,12 @@ mod test {
         assert_min("10..fooz", "10..fooz;");
         assert_min("foo[10]", "foo[10];");
         assert_min(r#"foo["bar"]"#, r#"foo["bar"];"#);
+        assert_min("foo.bar.baz", "foo.bar.baz;");
+        assert_min("foo.bar().baz[qux]", "foo.bar().baz[qux];");
+        assert_min("(foo + bar).baz", "(foo+bar).baz;");
+        assert_min("(() => x).call(this)", "(()=>x).call(this);");
+        assert_min("(() => x)[k]", "(()=>x)[k];");
+        assert_min("new.target.foo", "new.target.foo;");
     }
 
     #[test]
@@ -155,5 +155,6 @@ mod test {
         assert_min("class Foo { static method(a, b) { debug; } }", "class Foo{static method(a,b){debug;}}");
         assert_min("class Foo { a = 10; b = 20; }", "class Foo{a=10;b=20;}");
         assert_min("class Foo { static a = 10; b = 20; }", "class Foo{static a=10;b=20;}");
+        assert_min("class Foo extends Bar { constructor() { super(); super.a[b]; } }", "class Foo extends Bar{constructor(){super();super.a[b];}}");
     }
 }
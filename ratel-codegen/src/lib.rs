@@ -55,6 +55,25 @@ pub trait Generator: Sized {
         }
     }
 
+    /// Comma separated expressions, such as arguments, with sequences
+    /// in parenthesis so that they stay a single item of the list
+    #[inline]
+    fn write_expression_list<'a, I>(&mut self, items: I) where
+        I: IntoIterator<Item = &'a ExpressionNode<'a>>,
+    {
+        let mut items = items.into_iter();
+
+        if let Some(item) = items.next() {
+            self.write_expression(item, 1);
+        }
+
+        for item in items {
+            self.write_byte(b',');
+            self.write_pretty(b' ');
+            self.write_expression(item, 1);
+        }
+    }
+
     #[inline]
     fn write_block<'a, T, I>(&mut self, items: I) where
         T: ToCode<Self> + 'a,
@@ -344,18 +363,20 @@ fn extend_from_slice(dst: &mut Vec<u8>, src: &[u8]) {
 
 #[cfg(test)]
 fn assert_min(source: &str, expected: &str) {
-    use ratel::parse;
+    use ratel::{parse_in, Arena};
 
-    let module = parse(source).unwrap();
+    let arena = Arena::new();
+    let module = parse_in(&arena, source).unwrap();
 
     assert_eq!(codegen(&module, true).as_str(), expected);
 }
 
 #[cfg(test)]
 fn assert_pretty(source: &str, expected: &str) {
-    use ratel::parse;
+    use ratel::{parse_in, Arena};
 
-    let module = parse(source).unwrap();
+    let arena = Arena::new();
+    let module = parse_in(&arena, source).unwrap();
 
     assert_eq!(codegen(&module, false).as_str(), expected);
 }
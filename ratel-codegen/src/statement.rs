@@ -72,7 +72,7 @@ impl<'ast, G: Generator> ToCode<G> for Declarator<'ast> {
             gen.write_pretty(b' ');
             gen.write_byte(b'=');
             gen.write_pretty(b' ');
-            gen.write(init);
+            gen.write_expression(init, 1);
         }
     }
 }
@@ -257,6 +257,8 @@ impl<'ast, G: Generator> ToCode<G> for TryStatement<'ast> {
             gen.write(&handler.body);
         }
         if let Some(ref finalizer) = self.finalizer {
+            gen.write_pretty(b' ');
+            gen.write_bytes(b"finally");
             gen.write_pretty(b' ');
             gen.write(finalizer);
         }
@@ -387,6 +389,15 @@ mod test {
         assert_min("debugger;", "debugger");
     }
 
+    #[test]
+    fn expression_statement() {
+        assert_min("(function () {})();", "(function(){}());");
+        assert_min("(class {}).name;", "(class{}.name);");
+        assert_min("({}).a = 1, b;", "({}.a=1,b);");
+        assert_min("({})[a]`b` ? c : d;", "({}[a]`b`?c:d);");
+        assert_min("a.b();", "a.b();");
+    }
+
     #[test]
     fn block_statement() {
         assert_min("{}", "{}");
@@ -419,6 +430,7 @@ mod test {
         assert_min("let foo = 10, bar = 20;", "let foo=10,bar=20;");
         assert_min("const foo = 10, bar = 20;", "const foo=10,bar=20;");
         assert_min("const a = {...foo};", "const a={...foo};");
+        assert_min("var foo = (1, 2), bar;", "var foo=(1,2),bar;");
     }
 
     #[test]
@@ -454,6 +466,13 @@ mod test {
         assert_min("for (let foo of bar){}", "for(let foo of bar){}");
     }
 
+    #[test]
+    fn try_statement() {
+        assert_min("try { foo; } catch (err) { bar; }", "try{foo;}catch(err){bar;}");
+        assert_min("try { foo; } finally { bar; }", "try{foo;}finally{bar;}");
+        assert_min("try {} catch (err) {} finally {}", "try{}catch(err){}finally{}");
+    }
+
     #[test]
     fn import_statement() {
         assert_min("import 'fuga'", "import 'fuga'");
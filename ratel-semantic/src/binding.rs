@@ -0,0 +1,49 @@
+use scope::ScopeId;
+use reference::ReferenceId;
+
+/// Index of a `Binding` in the `Semantic` model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BindingId(pub(crate) usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingKind {
+    Var,
+    Let,
+    Const,
+    Param,
+    /// Function declaration, or the name of a function expression
+    /// within its own scope.
+    Function,
+    /// Class declaration, or the name of a class expression within
+    /// its own scope.
+    Class,
+    Import,
+    /// Parameter of a `catch` clause.
+    Catch,
+}
+
+impl BindingKind {
+    /// Whether declaring the same name again in the same scope refers to
+    /// the existing binding, as opposed to being an error (or shadowing it,
+    /// in case of function and class expression names).
+    #[inline]
+    pub fn is_redeclarable(&self) -> bool {
+        matches!(*self, BindingKind::Var | BindingKind::Param | BindingKind::Function)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding<'ast> {
+    pub name: &'ast str,
+    pub kind: BindingKind,
+
+    /// Scope the binding is declared in, after hoisting.
+    pub scope: ScopeId,
+
+    /// References resolved to this binding, in source order.
+    pub references: Vec<ReferenceId>,
+
+    /// Span of the first declaration.
+    pub start: u32,
+    pub end: u32,
+}
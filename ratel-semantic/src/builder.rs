@@ -0,0 +1,519 @@
+use ratel::ast::{Node, Loc, Pattern, Property, PropertyKey, Expression, ExpressionNode};
+use ratel::ast::{Statement, StatementNode, StatementList, BlockNode, DeclarationKind, OperatorKind};
+use ratel::ast::{Function, Class, ClassMember, Name, IdentifierNode, NodeList};
+use ratel::ast::expression::*;
+use ratel::ast::statement::*;
+
+use {address, Semantic};
+use binding::{Binding, BindingId, BindingKind};
+use reference::{Reference, ReferenceId, ReferenceFlags};
+use scope::{Scope, ScopeId, ScopeKind};
+
+/// Walks the AST, declaring bindings and recording references. References
+/// are only resolved once the walk is done, since hoisted declarations can
+/// come after their uses.
+pub struct Builder<'ast> {
+    semantic: Semantic<'ast>,
+    current: ScopeId,
+}
+
+impl<'ast> Builder<'ast> {
+    #[inline]
+    pub fn new() -> Self {
+        Builder {
+            semantic: Semantic::default(),
+            current: ScopeId(0),
+        }
+    }
+
+    pub fn build(mut self, body: StatementList<'ast>) -> Semantic<'ast> {
+        let end = body.iter().last().map(|statement| statement.end).unwrap_or(0);
+
+        self.semantic.scopes.push(Scope::new(ScopeKind::Module, None, 0, end));
+        self.statements(body);
+
+        for index in 0..self.semantic.references.len() {
+            let binding = {
+                let reference = &self.semantic.references[index];
+
+                self.semantic.resolve(reference.scope, reference.name)
+            };
+
+            self.semantic.references[index].binding = binding;
+
+            if let Some(binding) = binding {
+                self.semantic.bindings[binding.0].references.push(ReferenceId(index));
+            }
+        }
+
+        self.semantic
+    }
+
+    fn enter<T>(&mut self, kind: ScopeKind, node: &Loc<T>) {
+        let id = ScopeId(self.semantic.scopes.len());
+
+        self.semantic.scopes.push(Scope::new(kind, Some(self.current), node.start, node.end));
+        self.semantic.scopes[self.current.0].children.push(id);
+        self.current = id;
+    }
+
+    #[inline]
+    fn leave(&mut self) {
+        self.current = self.semantic.scopes[self.current.0].parent.expect("Can't leave the module scope");
+    }
+
+    fn declare<T>(&mut self, name: &'ast str, kind: BindingKind, node: &Node<'ast, T>) -> BindingId {
+        let mut scope = self.current;
+
+        if kind == BindingKind::Var {
+            while !self.semantic.scopes[scope.0].kind.is_var_scope() {
+                scope = self.semantic.scopes[scope.0].parent.expect("Module is a var scope");
+            }
+        }
+
+        let existing = self.semantic.scopes[scope.0].get(name).filter(|&existing| {
+            let existing = self.semantic.bindings[existing.0].kind;
+
+            existing.is_redeclarable() && (kind == BindingKind::Var || kind == BindingKind::Function)
+        });
+
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                let id = BindingId(self.semantic.bindings.len());
+
+                self.semantic.bindings.push(Binding {
+                    name,
+                    kind,
+                    scope,
+                    references: Vec::new(),
+                    start: node.start,
+                    end: node.end,
+                });
+                self.semantic.scopes[scope.0].insert(name, id);
+
+                id
+            }
+        };
+
+        self.semantic.declarations.insert(address(node), id);
+
+        id
+    }
+
+    fn reference<T>(&mut self, name: &'ast str, flags: ReferenceFlags, node: &Node<'ast, T>) {
+        let id = ReferenceId(self.semantic.references.len());
+
+        self.semantic.references.push(Reference {
+            name,
+            scope: self.current,
+            flags,
+            binding: None,
+            start: node.start,
+            end: node.end,
+        });
+        self.semantic.scopes[self.current.0].references.push(id);
+        self.semantic.uses.insert(address(node), id);
+    }
+
+    #[inline]
+    fn statements(&mut self, body: StatementList<'ast>) {
+        for statement in body.iter() {
+            self.statement(statement);
+        }
+    }
+
+    fn block(&mut self, block: &BlockNode<'ast, Statement<'ast>>) {
+        self.enter(ScopeKind::Block, block);
+        self.statements(block.body);
+        self.leave();
+    }
+
+    fn statement(&mut self, node: &StatementNode<'ast>) {
+        use ratel::ast::Statement::*;
+
+        match node.item {
+            Empty | Debugger | Break(_) | Continue(_) => {},
+            Expression(ref expression) => self.expression(expression),
+            Declaration(ref declaration) => self.declaration(declaration),
+            Return(ReturnStatement { ref value }) => {
+                if let Some(ref value) = *value {
+                    self.expression(value);
+                }
+            },
+            Throw(ThrowStatement { ref value }) => self.expression(value),
+            If(IfStatement { ref test, ref consequent, ref alternate }) => {
+                self.expression(test);
+                self.statement(consequent);
+
+                if let Some(ref alternate) = *alternate {
+                    self.statement(alternate);
+                }
+            },
+            While(WhileStatement { ref test, ref body }) => {
+                self.semantic.loops.push((node.start, node.end));
+                self.expression(test);
+                self.statement(body);
+            },
+            Do(DoStatement { ref body, ref test }) => {
+                self.semantic.loops.push((node.start, node.end));
+                self.statement(body);
+                self.expression(test);
+            },
+            For(ForStatement { ref init, ref test, ref update, ref body }) => {
+                self.semantic.loops.push((node.start, node.end));
+                self.enter(ScopeKind::For, node);
+
+                if let Some(ref init) = *init {
+                    self.for_init(init, false);
+                }
+                if let Some(ref test) = *test {
+                    self.expression(test);
+                }
+                if let Some(ref update) = *update {
+                    self.expression(update);
+                }
+                self.statement(body);
+                self.leave();
+            },
+            ForIn(ForInStatement { ref left, ref right, ref body }) |
+            ForOf(ForOfStatement { ref left, ref right, ref body }) => {
+                self.semantic.loops.push((node.start, node.end));
+                self.enter(ScopeKind::For, node);
+                self.for_init(left, true);
+                self.expression(right);
+                self.statement(body);
+                self.leave();
+            },
+            Try(TryStatement { ref block, ref handler, ref finalizer }) => {
+                self.block(block);
+
+                if let Some(ref handler) = *handler {
+                    self.enter(ScopeKind::Catch, handler);
+                    self.pattern(&handler.param, BindingKind::Catch);
+                    self.block(&handler.body);
+                    self.leave();
+                }
+                if let Some(ref finalizer) = *finalizer {
+                    self.block(finalizer);
+                }
+            },
+            Block(ref block) => {
+                self.enter(ScopeKind::Block, node);
+                self.statements(block.body);
+                self.leave();
+            },
+            Labeled(LabeledStatement { ref body, .. }) => self.statement(body),
+            Function(ref function) => {
+                let id = self.semantic.scopes.len();
+
+                self.declare(function.name.0.item, BindingKind::Function, &function.name.0);
+                self.function(function, ScopeKind::Function, None, node);
+                self.semantic.scopes[id].hoisted = true;
+            },
+            Class(ref class) => {
+                self.declare(class.name.0.item, BindingKind::Class, &class.name.0);
+                self.class(class, None, node);
+            },
+            Switch(SwitchStatement { ref discriminant, ref cases }) => {
+                self.expression(discriminant);
+                self.enter(ScopeKind::Block, cases);
+
+                for case in cases.body.iter() {
+                    if let Some(ref test) = case.test {
+                        self.expression(test);
+                    }
+                    self.statements(case.consequent);
+                }
+
+                self.leave();
+            },
+            Import(ImportDeclaration { ref specifiers, .. }) => {
+                for specifier in specifiers.iter() {
+                    let local = match specifier.item {
+                        ForImportSpecifier::ImportSpecifier(ImportSpecifier { ref local, .. }) |
+                        ForImportSpecifier::ImportDefaultSpecifier(ImportDefaultSpecifier { ref local }) |
+                        ForImportSpecifier::ImportNamespaceSpecifier(ImportNamespaceSpecifier { ref local }) => local,
+                    };
+
+                    self.declare(local.item, BindingKind::Import, local);
+                }
+            },
+        }
+    }
+
+    fn declaration(&mut self, declaration: &DeclarationStatement<'ast>) {
+        let kind = match declaration.kind {
+            DeclarationKind::Var => BindingKind::Var,
+            DeclarationKind::Let => BindingKind::Let,
+            DeclarationKind::Const => BindingKind::Const,
+        };
+
+        for declarator in declaration.declarators.iter() {
+            self.pattern(&declarator.id, kind);
+
+            if let Some(ref init) = declarator.init {
+                self.expression(init);
+            }
+        }
+    }
+
+    /// The head of `for in` and `for of` loops assigns to expressions.
+    fn for_init(&mut self, init: &Node<'ast, ForInit<'ast>>, assigns: bool) {
+        match init.item {
+            ForInit::Declaration(ref declaration) => self.declaration(declaration),
+            ForInit::Expression(ref expression) if assigns => self.target(expression, ReferenceFlags::WRITE),
+            ForInit::Expression(ref expression) => self.expression(expression),
+        }
+    }
+
+    fn function<N, T>(&mut self, function: &Function<'ast, N>, kind: ScopeKind, name: Option<&IdentifierNode<'ast>>, node: &Loc<T>)
+    where
+        N: Name<'ast>,
+    {
+        self.enter(kind, node);
+
+        if let Some(name) = name {
+            self.declare(name.item, BindingKind::Function, name);
+        }
+
+        self.params(function.params);
+        // The body shares the scope with parameters
+        self.statements(function.body.body);
+        self.leave();
+    }
+
+    fn params(&mut self, params: NodeList<'ast, Pattern<'ast>>) {
+        for param in params.iter() {
+            self.pattern(param, BindingKind::Param);
+        }
+    }
+
+    fn class<N, T>(&mut self, class: &Class<'ast, N>, name: Option<&IdentifierNode<'ast>>, node: &Loc<T>)
+    where
+        N: Name<'ast>,
+    {
+        self.enter(ScopeKind::Class, node);
+
+        if let Some(name) = name {
+            self.declare(name.item, BindingKind::Class, name);
+        }
+
+        if let Some(ref extends) = class.extends {
+            self.expression(extends);
+        }
+
+        for member in class.body.body.iter() {
+            match member.item {
+                ClassMember::Error => {},
+                ClassMember::Method { ref key, ref value, .. } => {
+                    self.property_key(key);
+                    self.function(value, ScopeKind::Function, None, value);
+                },
+                ClassMember::Literal { ref key, ref value, .. } => {
+                    self.property_key(key);
+                    self.expression(value);
+                },
+            }
+        }
+
+        self.leave();
+    }
+
+    #[inline]
+    fn property_key(&mut self, key: &Node<'ast, PropertyKey<'ast>>) {
+        if let PropertyKey::Computed(ref expression) = key.item {
+            self.expression(expression);
+        }
+    }
+
+    /// Declare the bindings of a pattern.
+    fn pattern(&mut self, node: &Node<'ast, Pattern<'ast>>, kind: BindingKind) {
+        match node.item {
+            Pattern::Void => {},
+            Pattern::Identifier(name) => {
+                self.declare(name, kind, node);
+            },
+            Pattern::ObjectPattern { properties } => self.object_pattern(properties, kind),
+            Pattern::ArrayPattern { elements } => {
+                for element in elements.iter() {
+                    self.pattern(element, kind);
+                }
+            },
+            Pattern::RestElement { ref argument } => {
+                self.declare(argument.item, kind, argument);
+            },
+            Pattern::AssignmentPattern { ref left, ref right } => {
+                self.pattern(left, kind);
+                self.expression(right);
+            },
+        }
+    }
+
+    fn object_pattern(&mut self, properties: NodeList<'ast, Property<'ast>>, kind: BindingKind) {
+        for property in properties.iter() {
+            match property.item {
+                Property::Shorthand(name) => {
+                    self.declare(name, kind, property);
+                },
+                Property::Literal { ref key, ref value } => {
+                    self.property_key(key);
+                    self.pattern_expression(value, kind);
+                },
+                Property::Spread { ref argument } => self.pattern_expression(argument, kind),
+                Property::Method { .. } => {},
+            }
+        }
+    }
+
+    /// Declare the bindings of a pattern nested in an object pattern, which
+    /// the parser stores as an expression.
+    fn pattern_expression(&mut self, node: &ExpressionNode<'ast>, kind: BindingKind) {
+        match node.item {
+            Expression::Identifier(name) => {
+                self.declare(name, kind, node);
+            },
+            Expression::Array(ArrayExpression { body }) => {
+                for element in body.iter() {
+                    self.pattern_expression(element, kind);
+                }
+            },
+            Expression::Object(ObjectExpression { body }) => self.object_pattern(body, kind),
+            Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, ref left, ref right }) => {
+                self.pattern_expression(left, kind);
+                self.expression(right);
+            },
+            Expression::Spread(SpreadExpression { ref argument }) => self.pattern_expression(argument, kind),
+            Expression::Void => {},
+            _ => self.expression(node),
+        }
+    }
+
+    /// Record the references of an assignment target.
+    fn target(&mut self, node: &ExpressionNode<'ast>, flags: ReferenceFlags) {
+        match node.item {
+            Expression::Identifier(name) => self.reference(name, flags, node),
+            Expression::Array(ArrayExpression { body }) => {
+                for element in body.iter() {
+                    self.target(element, ReferenceFlags::WRITE);
+                }
+            },
+            Expression::Object(ObjectExpression { body }) => {
+                for property in body.iter() {
+                    match property.item {
+                        Property::Shorthand(name) => self.reference(name, ReferenceFlags::WRITE, property),
+                        Property::Literal { ref key, ref value } => {
+                            self.property_key(key);
+                            self.target(value, ReferenceFlags::WRITE);
+                        },
+                        Property::Spread { ref argument } => self.target(argument, ReferenceFlags::WRITE),
+                        Property::Method { .. } => {},
+                    }
+                }
+            },
+            Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, ref left, ref right }) => {
+                self.target(left, ReferenceFlags::WRITE);
+                self.expression(right);
+            },
+            Expression::Spread(SpreadExpression { ref argument }) => self.target(argument, flags),
+            _ => self.expression(node),
+        }
+    }
+
+    /// Record a call, constructor or tag.
+    #[inline]
+    fn callee(&mut self, node: &ExpressionNode<'ast>) {
+        match node.item {
+            Expression::Identifier(name) => self.reference(name, ReferenceFlags::CALL, node),
+            _ => self.expression(node),
+        }
+    }
+
+    #[inline]
+    fn expressions(&mut self, list: NodeList<'ast, Expression<'ast>>) {
+        for expression in list.iter() {
+            self.expression(expression);
+        }
+    }
+
+    fn expression(&mut self, node: &ExpressionNode<'ast>) {
+        use ratel::ast::Expression::*;
+
+        match node.item {
+            Void | Literal(_) => {},
+            This(_) => self.semantic.scopes[self.current.0].uses_this = true,
+            Super(_) => self.semantic.scopes[self.current.0].uses_super = true,
+            Identifier(name) => self.reference(name, ReferenceFlags::READ, node),
+            MetaProperty(MetaPropertyExpression { ref meta, ref property }) => {
+                if meta.item == "new" && property.item == "target" {
+                    self.semantic.scopes[self.current.0].uses_new_target = true;
+                }
+            },
+            Sequence(SequenceExpression { body }) |
+            Array(ArrayExpression { body }) => self.expressions(body),
+            Member(MemberExpression { ref object, .. }) => self.expression(object),
+            ComputedMember(ComputedMemberExpression { ref object, ref property }) => {
+                self.expression(object);
+                self.expression(property);
+            },
+            Call(CallExpression { ref callee, arguments }) => {
+                self.callee(callee);
+                self.expressions(arguments);
+            },
+            Binary(BinaryExpression { operator, ref left, ref right }) => {
+                match operator {
+                    OperatorKind::Assign => self.target(left, ReferenceFlags::WRITE),
+                    _ if operator.assignment() => self.target(left, ReferenceFlags::READ_WRITE),
+                    _ => self.expression(left),
+                }
+                self.expression(right);
+            },
+            Prefix(PrefixExpression { operator, ref operand }) |
+            Postfix(PostfixExpression { operator, ref operand }) => match operator {
+                OperatorKind::Increment | OperatorKind::Decrement => self.target(operand, ReferenceFlags::READ_WRITE),
+                OperatorKind::New => self.callee(operand),
+                _ => self.expression(operand),
+            },
+            Conditional(ConditionalExpression { ref test, ref consequent, ref alternate }) => {
+                self.expression(test);
+                self.expression(consequent);
+                self.expression(alternate);
+            },
+            Template(TemplateLiteral { expressions, .. }) => self.expressions(expressions),
+            TaggedTemplate(TaggedTemplateExpression { ref tag, ref quasi }) => {
+                self.callee(tag);
+                self.expressions(quasi.expressions);
+            },
+            Spread(SpreadExpression { ref argument }) => self.expression(argument),
+            Arrow(ArrowExpression { params, ref body }) => {
+                self.enter(ScopeKind::Arrow, node);
+                self.params(params);
+
+                match *body {
+                    ArrowBody::Expression(ref expression) => self.expression(expression),
+                    ArrowBody::Block(ref block) => self.statements(block.body),
+                }
+
+                self.leave();
+            },
+            Object(ObjectExpression { body }) => {
+                for property in body.iter() {
+                    match property.item {
+                        Property::Shorthand(name) => self.reference(name, ReferenceFlags::READ, property),
+                        Property::Literal { ref key, ref value } => {
+                            self.property_key(key);
+                            self.expression(value);
+                        },
+                        Property::Method { ref key, ref value } => {
+                            self.property_key(key);
+                            self.function(value, ScopeKind::Function, None, value);
+                        },
+                        Property::Spread { ref argument } => self.expression(argument),
+                    }
+                }
+            },
+            Function(ref function) => self.function(function, ScopeKind::Function, function.name.0.as_ref(), node),
+            Class(ref class) => self.class(class, class.name.0.as_ref(), node),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use Semantic;
+use binding::{Binding, BindingId};
+use reference::Reference;
+use scope::{ScopeId, ScopeKind};
+
+/// Free variables of a function and its use of the implicit bindings,
+/// see `Semantic::closure`. Captures need every reference resolved to its
+/// binding, with its position and whether it writes, which the name sets
+/// of the transformer's scope tree don't record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Closure<'ast> {
+    /// The `Function` or `Arrow` scope of the function.
+    pub scope: ScopeId,
+
+    /// Bindings declared outside of the function and referenced within it,
+    /// including within nested functions, in order of the scope they are first
+    /// referenced in.
+    pub captures: Vec<Capture>,
+
+    /// Names of unresolved references within the function, other than the
+    /// implicit `arguments` of functions, in the same order as `captures`.
+    pub globals: Vec<&'ast str>,
+
+    /// Usage of `this`, `arguments`, `super` and `new.target`. Arrow functions
+    /// nested within count towards the function, other functions don't, since
+    /// they have their own.
+    pub uses_this: bool,
+    pub uses_arguments: bool,
+    pub uses_super: bool,
+    pub uses_new_target: bool,
+}
+
+/// Binding captured by a `Closure`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capture {
+    pub binding: BindingId,
+
+    /// Read within the function.
+    pub read: bool,
+
+    /// Written within the function.
+    pub write: bool,
+
+    /// Written anywhere, within the function or outside of it.
+    pub reassigned: bool,
+
+    /// Written after the function is created, so the function may not see
+    /// the value the binding had at that point. These are writes within the
+    /// function, later in source order, within other functions, or earlier
+    /// in a loop that creates the function again.
+    pub mutated_after_capture: bool,
+}
+
+impl<'ast> Closure<'ast> {
+    /// Capture of the given binding, if any.
+    #[inline]
+    pub fn capture(&self, binding: BindingId) -> Option<&Capture> {
+        self.captures.iter().find(|capture| capture.binding == binding)
+    }
+
+    /// Whether any of the implicit bindings are used, which matters when
+    /// moving the body of the function elsewhere.
+    #[inline]
+    pub fn uses_context(&self) -> bool {
+        self.uses_this || self.uses_arguments || self.uses_super || self.uses_new_target
+    }
+}
+
+pub(crate) fn analyze<'ast>(semantic: &Semantic<'ast>, scope: ScopeId) -> Closure<'ast> {
+    let mut closure = Closure {
+        scope,
+        captures: Vec::new(),
+        globals: Vec::new(),
+        uses_this: false,
+        uses_arguments: false,
+        uses_super: false,
+        uses_new_target: false,
+    };
+
+    // Scopes of the function in the order they are entered, along with
+    // whether the implicit bindings in them belong to this function
+    let mut scopes = Vec::new();
+    let mut stack = vec![(scope, true)];
+
+    while let Some((id, own)) = stack.pop() {
+        scopes.push((id, own));
+
+        for &child in semantic.scope(id).children.iter().rev() {
+            stack.push((child, own && semantic.scope(child).kind != ScopeKind::Function));
+        }
+    }
+
+    let inside = |id: ScopeId| semantic.ancestors(id).any(|ancestor| ancestor == scope);
+
+    for &(id, own) in &scopes {
+        let current = semantic.scope(id);
+
+        if own {
+            closure.uses_this |= current.uses_this;
+            closure.uses_super |= current.uses_super;
+            closure.uses_new_target |= current.uses_new_target;
+        }
+
+        for &id in &current.references {
+            let reference = semantic.reference(id);
+
+            let binding = match reference.binding {
+                Some(binding) => binding,
+                None => {
+                    if semantic.is_implicit_arguments(id) {
+                        closure.uses_arguments |= own;
+                    } else if !closure.globals.contains(&reference.name) {
+                        closure.globals.push(reference.name);
+                    }
+                    continue;
+                }
+            };
+
+            if inside(semantic.binding(binding).scope) {
+                continue;
+            }
+
+            let index = match closure.captures.iter().position(|capture| capture.binding == binding) {
+                Some(index) => index,
+                None => {
+                    closure.captures.push(Capture {
+                        binding,
+                        read: false,
+                        write: false,
+                        reassigned: semantic.is_reassigned(binding),
+                        mutated_after_capture: false,
+                    });
+                    closure.captures.len() - 1
+                }
+            };
+
+            let capture = &mut closure.captures[index];
+
+            capture.read |= reference.flags.read;
+            capture.write |= reference.flags.write;
+        }
+    }
+
+    let current = semantic.scope(scope);
+
+    // Function declarations exist as soon as the enclosing scope is entered
+    let created = match current.parent {
+        Some(parent) if current.hoisted => semantic.scope(parent).start,
+        _ => current.start,
+    };
+
+    for capture in &mut closure.captures {
+        let binding = semantic.binding(capture.binding);
+
+        capture.mutated_after_capture = binding.references.iter()
+            .map(|&reference| semantic.reference(reference))
+            .any(|reference| reference.flags.write && is_written_after(semantic, binding, reference, created));
+    }
+
+    closure
+}
+
+/// Whether the write to the binding can happen after the position where
+/// a function is created.
+fn is_written_after(semantic: &Semantic, binding: &Binding, write: &Reference, created: u32) -> bool {
+    if write.start >= created {
+        return true;
+    }
+
+    // Other functions can be called at any time
+    let deferred = semantic.ancestors(write.scope)
+        .take_while(|&ancestor| ancestor != binding.scope)
+        .any(|ancestor| matches!(semantic.scope(ancestor).kind, ScopeKind::Function | ScopeKind::Arrow));
+
+    if deferred {
+        return true;
+    }
+
+    // The next iteration of a loop within the scope of the binding runs
+    // the write again. Bindings in the head of a `for` loop are copied
+    // for every iteration instead.
+    let scope = semantic.scope(binding.scope);
+
+    semantic.loops().iter().any(|&(start, end)| {
+        start >= scope.start && end <= scope.end && start <= write.start && created < end
+            && !(scope.kind == ScopeKind::For && scope.start == start)
+    })
+}
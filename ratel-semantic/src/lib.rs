@@ -0,0 +1,607 @@
+//! Semantic model of a JavaScript module: its scopes, the bindings declared
+//! in them, and the binding every identifier reference resolves to.
+//!
+//! ```
+//! extern crate ratel;
+//! extern crate ratel_semantic;
+//!
+//! use ratel_semantic::BindingKind;
+//!
+//! fn main() {
+//!     let arena = ratel::Arena::new();
+//!     let module = ratel::parse_in(&arena, "let a = 1; a += b;").unwrap();
+//!     let semantic = ratel_semantic::analyze(&module);
+//!
+//!     let a = semantic.resolve(semantic.root(), "a").unwrap();
+//!
+//!     assert_eq!(semantic.binding(a).kind, BindingKind::Let);
+//!     assert_eq!(semantic.is_reassigned(a), true);
+//!     assert_eq!(semantic.globals(), vec!["b"]);
+//! }
+//! ```
+
+#[cfg(test)]
+#[macro_use]
+extern crate pretty_assertions;
+extern crate ratel;
+
+use std::collections::HashMap;
+
+use ratel::Module;
+use ratel::ast::{Node, Loc};
+
+mod binding;
+mod builder;
+mod closure;
+mod reference;
+mod scope;
+
+pub use binding::{Binding, BindingId, BindingKind};
+pub use closure::{Closure, Capture};
+pub use reference::{Reference, ReferenceId, ReferenceFlags};
+pub use scope::{Scope, ScopeId, ScopeKind};
+
+/// Analyze the module, resolving all references.
+#[inline]
+pub fn analyze<'ast>(module: &Module<'ast>) -> Semantic<'ast> {
+    builder::Builder::new().build(module.body())
+}
+
+/// Scopes, bindings and references of a module. Nodes of the analyzed AST can
+/// be looked up by identity, so the model goes stale if the AST is modified.
+#[derive(Clone, Debug, Default)]
+pub struct Semantic<'ast> {
+    scopes: Vec<Scope<'ast>>,
+    bindings: Vec<Binding<'ast>>,
+    references: Vec<Reference<'ast>>,
+
+    // Spans of all loops, in source order
+    loops: Vec<(u32, u32)>,
+
+    // Addresses of the nodes that declare bindings and make references
+    declarations: HashMap<usize, BindingId>,
+    uses: HashMap<usize, ReferenceId>,
+}
+
+#[inline]
+fn address<T>(node: &Node<T>) -> usize {
+    &**node as *const Loc<T> as usize
+}
+
+impl<'ast> Semantic<'ast> {
+    /// The module scope.
+    #[inline]
+    pub fn root(&self) -> ScopeId {
+        ScopeId(0)
+    }
+
+    #[inline]
+    pub fn scope(&self, id: ScopeId) -> &Scope<'ast> {
+        &self.scopes[id.0]
+    }
+
+    #[inline]
+    pub fn binding(&self, id: BindingId) -> &Binding<'ast> {
+        &self.bindings[id.0]
+    }
+
+    #[inline]
+    pub fn reference(&self, id: ReferenceId) -> &Reference<'ast> {
+        &self.references[id.0]
+    }
+
+    /// All scopes, in the order they are entered.
+    #[inline]
+    pub fn scopes(&self) -> &[Scope<'ast>] {
+        &self.scopes
+    }
+
+    /// All bindings, in the order they are declared.
+    #[inline]
+    pub fn bindings(&self) -> &[Binding<'ast>] {
+        &self.bindings
+    }
+
+    /// All references, in source order.
+    #[inline]
+    pub fn references(&self) -> &[Reference<'ast>] {
+        &self.references
+    }
+
+    /// Find the binding the name refers to from within the given scope.
+    pub fn resolve(&self, mut scope: ScopeId, name: &str) -> Option<BindingId> {
+        loop {
+            let current = self.scope(scope);
+
+            if let Some(binding) = current.get(name) {
+                return Some(binding);
+            }
+
+            scope = current.parent?;
+        }
+    }
+
+    /// Iterate over the scope and the scopes enclosing it, up to the module.
+    #[inline]
+    pub fn ancestors(&self, scope: ScopeId) -> Ancestors<'_, 'ast> {
+        Ancestors {
+            semantic: self,
+            next: Some(scope),
+        }
+    }
+
+    /// Binding declared by the node, which can be a `Pattern`, a shorthand
+    /// `Property` in an object pattern, or the name of a function, class
+    /// or import specifier.
+    #[inline]
+    pub fn declaration_at<T>(&self, node: &Node<'ast, T>) -> Option<BindingId> {
+        self.declarations.get(&address(node)).cloned()
+    }
+
+    /// Reference made by the node, which can be an identifier `Expression`,
+    /// or a shorthand `Property` in an object expression.
+    #[inline]
+    pub fn reference_at<T>(&self, node: &Node<'ast, T>) -> Option<ReferenceId> {
+        self.uses.get(&address(node)).cloned()
+    }
+
+    /// Whether the binding is written to by any of its references.
+    #[inline]
+    pub fn is_reassigned(&self, binding: BindingId) -> bool {
+        self.binding(binding).references.iter().any(|&reference| self.reference(reference).flags.write)
+    }
+
+    /// Whether the reference is to the `arguments` object of an enclosing
+    /// function rather than to a global. Arrow functions don't have their
+    /// own, so `arguments` in an arrow at the top level is a global.
+    pub fn is_implicit_arguments(&self, reference: ReferenceId) -> bool {
+        let reference = self.reference(reference);
+
+        reference.name == "arguments"
+            && reference.binding.is_none()
+            && self.ancestors(reference.scope).any(|scope| self.scope(scope).kind == ScopeKind::Function)
+    }
+
+    /// Spans of all loop statements, in source order.
+    #[inline]
+    pub(crate) fn loops(&self) -> &[(u32, u32)] {
+        &self.loops
+    }
+
+    /// Captured bindings and implicit binding usage of the function or arrow
+    /// function with the given scope, `None` for any other kind of scope.
+    pub fn closure(&self, scope: ScopeId) -> Option<Closure<'ast>> {
+        match self.scope(scope).kind {
+            ScopeKind::Function | ScopeKind::Arrow => Some(closure::analyze(self, scope)),
+            _ => None,
+        }
+    }
+
+    /// Closures of all functions and arrow functions, in the order their
+    /// scopes are entered.
+    pub fn closures(&self) -> Vec<Closure<'ast>> {
+        (0..self.scopes.len()).filter_map(|index| self.closure(ScopeId(index))).collect()
+    }
+
+    /// Names of all unresolved references, in order of their first use.
+    /// The implicit `arguments` of functions is not a global.
+    pub fn globals(&self) -> Vec<&'ast str> {
+        let mut globals = Vec::new();
+
+        for (index, reference) in self.references.iter().enumerate() {
+            if !reference.is_global() || self.is_implicit_arguments(ReferenceId(index)) {
+                continue;
+            }
+
+            if !globals.contains(&reference.name) {
+                globals.push(reference.name);
+            }
+        }
+
+        globals
+    }
+}
+
+/// Iterator over a scope and its enclosing scopes, see `Semantic::ancestors`.
+pub struct Ancestors<'s, 'ast: 's> {
+    semantic: &'s Semantic<'ast>,
+    next: Option<ScopeId>,
+}
+
+impl<'s, 'ast> Iterator for Ancestors<'s, 'ast> {
+    type Item = ScopeId;
+
+    #[inline]
+    fn next(&mut self) -> Option<ScopeId> {
+        let scope = self.next?;
+
+        self.next = self.semantic.scope(scope).parent;
+
+        Some(scope)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::{parse_in, Arena};
+    use ScopeKind::*;
+
+    fn analyze_source<'ast>(arena: &'ast Arena, source: &str) -> Semantic<'ast> {
+        analyze(&parse_in(arena, source).unwrap())
+    }
+
+    /// Kinds of the scope and its direct children, recursively.
+    fn tree(semantic: &Semantic, scope: ScopeId) -> String {
+        let scope = semantic.scope(scope);
+        let mut result = format!("{:?}", scope.kind);
+
+        if !scope.children.is_empty() {
+            let children: Vec<_> = scope.children.iter().map(|&child| tree(semantic, child)).collect();
+
+            result.push_str(&format!("({})", children.join(", ")));
+        }
+
+        result
+    }
+
+    /// Names and kinds of the bindings declared in the scope.
+    fn bindings<'ast>(semantic: &Semantic<'ast>, scope: ScopeId) -> Vec<(&'ast str, BindingKind)> {
+        semantic.scope(scope).bindings.iter().map(|&binding| {
+            let binding = semantic.binding(binding);
+
+            (binding.name, binding.kind)
+        }).collect()
+    }
+
+    /// Scope the binding of the named reference is declared in, for every
+    /// reference with that name.
+    fn resolved_in(semantic: &Semantic, name: &str) -> Vec<Option<ScopeKind>> {
+        semantic.references().iter().filter(|reference| reference.name == name).map(|reference| {
+            reference.binding.map(|binding| semantic.scope(semantic.binding(binding).scope).kind)
+        }).collect()
+    }
+
+    #[test]
+    fn should_build_scope_tree() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            function foo() { { } }
+            for (let i = 0; i < 1; i++) {}
+            try {} catch (err) {}
+            class Bar { baz() {} }
+            switch (qux) { case 1: }
+            var fn = () => {};
+        "#);
+
+        assert_eq!(
+            tree(&semantic, semantic.root()),
+            "Module(Function(Block), For(Block), Block, Catch(Block), Class(Function), Block, Arrow)"
+        );
+    }
+
+    #[test]
+    fn should_declare_binding_kinds() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            var a, [b, [c]] = d, { e, f: { g } } = i;
+            let j = 1;
+            const k = 2;
+            function l(m, n = 1, ...o) {}
+            class P {}
+            try {} catch (q) {}
+        "#);
+
+        assert_eq!(bindings(&semantic, semantic.root()), vec![
+            ("a", BindingKind::Var),
+            ("b", BindingKind::Var),
+            ("c", BindingKind::Var),
+            ("e", BindingKind::Var),
+            ("g", BindingKind::Var),
+            ("j", BindingKind::Let),
+            ("k", BindingKind::Const),
+            ("l", BindingKind::Function),
+            ("P", BindingKind::Class),
+        ]);
+
+        let function = semantic.scope(semantic.root()).children[0];
+        let catch = semantic.scope(semantic.root()).children[3];
+
+        assert_eq!(bindings(&semantic, function), vec![
+            ("m", BindingKind::Param),
+            ("n", BindingKind::Param),
+            ("o", BindingKind::Param),
+        ]);
+        assert_eq!(bindings(&semantic, catch), vec![("q", BindingKind::Catch)]);
+    }
+
+    #[test]
+    fn should_hoist_var_and_keep_lexical_declarations_in_blocks() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            function foo() {
+                a; b; c;
+                {
+                    var a = 1;
+                    let b = 2;
+                    function c() {}
+                    a; b; c;
+                }
+                for (var d = 0;;) {}
+                for (let e of f) {}
+                d; e;
+            }
+        "#);
+
+        assert_eq!(resolved_in(&semantic, "a"), vec![Some(Function), Some(Function)]);
+        assert_eq!(resolved_in(&semantic, "b"), vec![None, Some(Block)]);
+        assert_eq!(resolved_in(&semantic, "c"), vec![None, Some(Block)]);
+        assert_eq!(resolved_in(&semantic, "d"), vec![Some(Function)]);
+        assert_eq!(resolved_in(&semantic, "e"), vec![None]);
+        assert_eq!(semantic.globals(), vec!["b", "c", "f", "e"]);
+    }
+
+    #[test]
+    fn should_resolve_shadowed_names() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            let a = 1;
+            function foo(a) { return a; }
+            var bar = function a() { a; };
+            var baz = class a { qux() { a; } };
+            try {} catch (a) { a; }
+            a;
+        "#);
+
+        assert_eq!(resolved_in(&semantic, "a"), vec![
+            Some(Function),
+            Some(Function),
+            Some(Class),
+            Some(Catch),
+            Some(Module),
+        ]);
+
+        let foo = semantic.scope(semantic.root()).children[0];
+        let reference = semantic.scope(foo).references[0];
+
+        assert_eq!(semantic.binding(semantic.reference(reference).binding.unwrap()).kind, BindingKind::Param);
+    }
+
+    #[test]
+    fn should_flag_references() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            a = b;
+            c += 1;
+            d++;
+            --e;
+            f(g);
+            new h;
+            new i(j);
+            k`l`;
+            [m, { n, o: p }] = q;
+            for (r in s) {}
+            ({ t });
+            typeof u;
+        "#);
+
+        let flags: Vec<_> = semantic.references().iter().map(|reference| {
+            let flags = reference.flags;
+            let mut code = String::new();
+
+            if flags.read { code.push('r'); }
+            if flags.write { code.push('w'); }
+            if flags.call { code.push('c'); }
+
+            (reference.name, code)
+        }).collect();
+
+        let expected: Vec<_> = vec![
+            ("a", "w"), ("b", "r"),
+            ("c", "rw"),
+            ("d", "rw"),
+            ("e", "rw"),
+            ("f", "rc"), ("g", "r"),
+            ("h", "rc"),
+            ("i", "rc"), ("j", "r"),
+            ("k", "rc"),
+            ("m", "w"), ("n", "w"), ("p", "w"), ("q", "r"),
+            ("r", "w"), ("s", "r"),
+            ("t", "r"),
+            ("u", "r"),
+        ].into_iter().map(|(name, code)| (name, code.to_string())).collect();
+
+        assert_eq!(flags, expected);
+    }
+
+    /// Names and read, write, reassigned and mutated after capture flags
+    /// of the captured bindings.
+    fn captures<'ast>(semantic: &Semantic<'ast>, closure: &Closure) -> Vec<(&'ast str, String)> {
+        closure.captures.iter().map(|capture| {
+            let mut code = String::new();
+
+            if capture.read { code.push('r'); }
+            if capture.write { code.push('w'); }
+            if capture.reassigned { code.push('m'); }
+            if capture.mutated_after_capture { code.push('a'); }
+
+            (semantic.binding(capture.binding).name, code)
+        }).collect()
+    }
+
+    #[test]
+    fn should_report_closure_captures() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            let a = 1, b = 2, c = 3;
+            function outer(d) {
+                var e = d;
+                const inner = () => a + e;
+                function nested() { b = c; return d + f; }
+                e++;
+                return g;
+            }
+            a;
+        "#);
+
+        let outer = semantic.scope(semantic.root()).children[0];
+        let inner = semantic.scope(outer).children[0];
+        let nested = semantic.scope(outer).children[1];
+
+        let closure = semantic.closure(outer).unwrap();
+
+        assert_eq!(captures(&semantic, &closure), vec![
+            ("a", "r".to_string()),
+            ("b", "wma".to_string()),
+            ("c", "r".to_string()),
+        ]);
+        assert_eq!(closure.globals, vec!["g", "f"]);
+
+        let closure = semantic.closure(inner).unwrap();
+
+        assert_eq!(captures(&semantic, &closure), vec![
+            ("a", "r".to_string()),
+            ("e", "rma".to_string()),
+        ]);
+        assert_eq!(closure.globals, Vec::<&str>::new());
+
+        let closure = semantic.closure(nested).unwrap();
+
+        assert_eq!(captures(&semantic, &closure), vec![
+            ("b", "wma".to_string()),
+            ("c", "r".to_string()),
+            ("d", "r".to_string()),
+        ]);
+        assert_eq!(semantic.closure(semantic.root()), None);
+        assert_eq!(semantic.closures().len(), 3);
+    }
+
+    #[test]
+    fn should_report_writes_after_capture() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            var a = 1, b = 1, c = 1, d = 1, e = 1;
+            a = 2;
+            function reset() { b = 2; }
+            while (c) { c = 2; var f = () => a + b + c + d; }
+            for (let i = 0; i < e; i++) { g(() => i); }
+            function hoisted() { return e; }
+            e = 2;
+            d = 2;
+        "#);
+
+        let closures = semantic.closures();
+
+        assert_eq!(captures(&semantic, &closures[1]), vec![
+            ("a", "rm".to_string()),
+            ("b", "rma".to_string()),
+            ("c", "rma".to_string()),
+            ("d", "rma".to_string()),
+        ]);
+        assert_eq!(captures(&semantic, &closures[2]), vec![("i", "rm".to_string())]);
+        assert_eq!(captures(&semantic, &closures[3]), vec![("e", "rma".to_string())]);
+
+        let semantic = analyze_source(&arena, r#"
+            let a = 1;
+            a = 2;
+            function f() { return a; }
+        "#);
+
+        // Function declarations are created before the write
+        assert_eq!(captures(&semantic, &semantic.closures()[0]), vec![("a", "rma".to_string())]);
+
+        let semantic = analyze_source(&arena, r#"
+            let a = 1;
+            a = 2;
+            const f = () => a;
+        "#);
+
+        assert_eq!(captures(&semantic, &semantic.closures()[0]), vec![("a", "rm".to_string())]);
+    }
+
+    #[test]
+    fn should_report_implicit_bindings() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            function outer() {
+                const inner = () => this.a + arguments[0];
+                function nested() { return new.target; }
+                class Foo { bar() { this; arguments; } }
+            }
+            const top = () => { new.target; };
+        "#);
+
+        let closures = semantic.closures();
+        let flags: Vec<_> = closures.iter().map(|closure| {
+            (
+                semantic.scope(closure.scope).kind,
+                closure.uses_this,
+                closure.uses_arguments,
+                closure.uses_new_target,
+            )
+        }).collect();
+
+        assert_eq!(flags, vec![
+            (Function, true, true, false),
+            (Arrow, true, true, false),
+            (Function, false, false, true),
+            (Function, true, true, false),
+            (Arrow, false, false, true),
+        ]);
+        assert_eq!(closures.iter().all(|closure| closure.globals.is_empty()), true);
+        assert_eq!(closures[0].uses_context(), true);
+        assert_eq!(semantic.globals(), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn should_only_bind_arguments_in_functions() {
+        let arena = Arena::new();
+        let semantic = analyze_source(&arena, r#"
+            function foo() { return () => arguments; }
+            const bar = () => arguments;
+            arguments;
+        "#);
+
+        let closures = semantic.closures();
+
+        assert_eq!(semantic.globals(), vec!["arguments"]);
+        assert_eq!(closures[0].globals, Vec::<&str>::new());
+        assert_eq!(closures[1].uses_arguments, true);
+        assert_eq!(closures[2].globals, vec!["arguments"]);
+        assert_eq!(closures[2].uses_arguments, false);
+    }
+
+    #[test]
+    fn should_look_up_nodes() {
+        use ratel::ast::{Statement, Expression};
+        use ratel::ast::statement::DeclarationStatement;
+
+        let arena = Arena::new();
+        let module = parse_in(&arena, "var a = 1; a = a + 1;").unwrap();
+        let semantic = analyze(&module);
+
+        let mut statements = module.body().iter();
+
+        let declaration = match statements.next().unwrap().item {
+            Statement::Declaration(DeclarationStatement { declarators, .. }) => declarators.only_element().unwrap().id,
+            _ => panic!("Expected a declaration"),
+        };
+        let (left, right) = match statements.next().unwrap().item {
+            Statement::Expression(expression) => match expression.item {
+                Expression::Binary(ref binary) => (binary.left, binary.right),
+                _ => panic!("Expected an assignment"),
+            },
+            _ => panic!("Expected an expression statement"),
+        };
+
+        let binding = semantic.declaration_at(&declaration).unwrap();
+        let write = semantic.reference_at(&left).unwrap();
+
+        assert_eq!(semantic.reference(write).binding, Some(binding));
+        assert_eq!(semantic.reference(write).flags, ReferenceFlags::WRITE);
+        assert_eq!(semantic.reference_at(&right), None);
+        assert_eq!(semantic.binding(binding).references.len(), 2);
+        assert_eq!(semantic.is_reassigned(binding), true);
+        assert_eq!((semantic.binding(binding).start, semantic.binding(binding).end), (4, 5));
+    }
+}
@@ -0,0 +1,46 @@
+use scope::ScopeId;
+use binding::BindingId;
+
+/// Index of a `Reference` in the `Semantic` model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReferenceId(pub(crate) usize);
+
+/// How a reference uses the binding. Compound assignments and updates,
+/// such as `a += 1` or `a++`, both read and write.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReferenceFlags {
+    pub read: bool,
+    pub write: bool,
+    /// Called as a function or constructor, or used as a template tag.
+    pub call: bool,
+}
+
+impl ReferenceFlags {
+    pub const READ: ReferenceFlags = ReferenceFlags { read: true, write: false, call: false };
+    pub const WRITE: ReferenceFlags = ReferenceFlags { read: false, write: true, call: false };
+    pub const READ_WRITE: ReferenceFlags = ReferenceFlags { read: true, write: true, call: false };
+    pub const CALL: ReferenceFlags = ReferenceFlags { read: true, write: false, call: true };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reference<'ast> {
+    pub name: &'ast str,
+
+    /// Scope the reference is made in.
+    pub scope: ScopeId,
+
+    pub flags: ReferenceFlags,
+
+    /// Binding the reference resolves to, `None` for globals.
+    pub binding: Option<BindingId>,
+
+    pub start: u32,
+    pub end: u32,
+}
+
+impl<'ast> Reference<'ast> {
+    #[inline]
+    pub fn is_global(&self) -> bool {
+        self.binding.is_none()
+    }
+}
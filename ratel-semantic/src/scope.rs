@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use binding::BindingId;
+use reference::ReferenceId;
+
+/// Index of a `Scope` in the `Semantic` model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScopeId(pub(crate) usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// Top level of the module.
+    Module,
+    /// Parameters and body of a function, method or class constructor.
+    Function,
+    /// Parameters and body of an arrow function, which doesn't bind its
+    /// own `this`, `arguments`, `super` or `new.target`.
+    Arrow,
+    /// Block statement, or the cases of a `switch`.
+    Block,
+    /// Parameter of a `catch` clause, the body is a `Block` inside it.
+    Catch,
+    /// Head of a `for`, `for in` or `for of` loop, holding `let` and
+    /// `const` declarations of the head along with the body.
+    For,
+    /// Heritage and body of a class, with the name of class expressions.
+    Class,
+}
+
+impl ScopeKind {
+    /// Whether `var` declarations are hoisted to this scope.
+    #[inline]
+    pub fn is_var_scope(&self) -> bool {
+        matches!(*self, ScopeKind::Module | ScopeKind::Function | ScopeKind::Arrow)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scope<'ast> {
+    pub kind: ScopeKind,
+
+    /// Enclosing scope, `None` for the module scope.
+    pub parent: Option<ScopeId>,
+
+    /// Scopes nested directly in this scope, in source order.
+    pub children: Vec<ScopeId>,
+
+    /// Bindings declared in this scope, in source order. Hoisted `var`
+    /// declarations belong to the closest `Function`, `Arrow` or `Module`.
+    pub bindings: Vec<BindingId>,
+
+    /// References made directly in this scope, in source order.
+    pub references: Vec<ReferenceId>,
+
+    /// `this` is used directly in this scope.
+    pub uses_this: bool,
+
+    /// `super` is used directly in this scope.
+    pub uses_super: bool,
+
+    /// `new.target` is used directly in this scope.
+    pub uses_new_target: bool,
+
+    /// The scope is a function declaration, which is created as soon as
+    /// the enclosing scope is entered rather than where it is written.
+    pub hoisted: bool,
+
+    pub start: u32,
+    pub end: u32,
+
+    names: HashMap<&'ast str, BindingId>,
+}
+
+impl<'ast> Scope<'ast> {
+    #[inline]
+    pub(crate) fn new(kind: ScopeKind, parent: Option<ScopeId>, start: u32, end: u32) -> Self {
+        Scope {
+            kind,
+            parent,
+            children: Vec::new(),
+            bindings: Vec::new(),
+            references: Vec::new(),
+            uses_this: false,
+            uses_super: false,
+            uses_new_target: false,
+            hoisted: false,
+            start,
+            end,
+            names: HashMap::new(),
+        }
+    }
+
+    /// Binding declared in this scope under the given name, ignoring
+    /// the enclosing scopes.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<BindingId> {
+        self.names.get(name).cloned()
+    }
+
+    #[inline]
+    pub(crate) fn insert(&mut self, name: &'ast str, binding: BindingId) {
+        self.bindings.push(binding);
+        self.names.insert(name, binding);
+    }
+}
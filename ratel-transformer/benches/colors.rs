@@ -95,8 +95,8 @@ module.exports = {
 fn scope_analysis(b: &mut Bencher) {
     use ratel_transformer::scope::analyze;
 
-    let module = ratel::parse(SOURCE).expect("Must parse");
-    let arena = module.arena();
+    let arena = ratel::Arena::new();
+    let module = ratel::parse_in(&arena, SOURCE).expect("Must parse");
     let offset = unsafe { arena.offset() };
 
     b.iter(|| {
@@ -0,0 +1,32 @@
+use std::fmt::{self, Display};
+
+/// Error found in the module while transforming it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// Assignment to a `const` binding, such as `a = 1` or `a++`.
+    ConstAssignment {
+        name: Box<str>,
+        start: u32,
+        end: u32,
+    },
+
+    /// Arrow function using `super` outside of a class, such as within
+    /// a method of an object literal, which isn't lowered.
+    SuperOutsideClass {
+        start: u32,
+        end: u32,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::ConstAssignment { ref name, start, end } => {
+                write!(f, "Assignment to constant `{}` at {}:{}", name, start, end)
+            },
+            Error::SuperOutsideClass { start, end } => {
+                write!(f, "Arrow function using `super` outside of a class at {}:{}", start, end)
+            },
+        }
+    }
+}
@@ -1,24 +1,209 @@
-use ratel::ast::{NodeList, ExpressionNode, Function, Name, OptionalName, Block};
-use ratel::ast::expression::{ArrowExpression, ArrowBody};
+use std::rc::Rc;
+
+use ratel::ast::{NodeList, Expression, ExpressionNode, Statement, Function, Name, OptionalName, Block, Identifier};
+use ratel::ast::expression::{ArrowExpression, ArrowBody, MetaPropertyExpression};
 use ratel::ast::statement::ReturnStatement;
-use ratel_visitor::Visitor;
+use ratel::build::Builder;
+use ratel_semantic::{Semantic, ScopeKind};
+use ratel_visitor::{Visitor, VisitAction, Path, Pass, NodeRef};
 
 use TransformerCtxt;
+use names::Target;
+use error::Error;
 
+/// Lowers arrow functions to function expressions. Uses of `this`, `arguments`
+/// and `new.target` inside arrows are replaced by variables declared in the
+/// closest function that isn't an arrow, such as `var _this = this;`.
+///
+/// `super` can't be captured in a variable, so arrows using it are left as
+/// they are, for the class transform to take care of. Arrows using `super`
+/// outside of a class are reported by `check`.
 pub struct TransformArrow<'ast> {
-    ctx: TransformerCtxt<'ast>
+    ctx: TransformerCtxt<'ast>,
+    semantic: Rc<Semantic<'ast>>,
+
+    /// Arrows being visited, by the address of their node, and whether
+    /// they use `super`.
+    arrows: Vec<(usize, bool)>,
+
+    /// Variables declared so far, by the function and the value captured.
+    captures: Vec<(Target<'ast>, &'static str, &'ast str)>,
 }
 
 impl<'ast> TransformArrow<'ast> {
-    pub fn new(ctx: TransformerCtxt<'ast>) -> TransformArrow<'ast> {
+    pub fn new(ctx: TransformerCtxt<'ast>, semantic: Rc<Semantic<'ast>>) -> TransformArrow<'ast> {
         TransformArrow {
-            ctx
+            ctx,
+            semantic,
+            arrows: Vec::new(),
+            captures: Vec::new(),
+        }
+    }
+
+    /// Addresses of the arrows the current node is in, up to the closest
+    /// function that isn't an arrow.
+    fn enclosing_arrows(path: &Path<'ast>) -> Vec<usize> {
+        let mut arrows = Vec::new();
+
+        for node in path.ancestors() {
+            match node {
+                NodeRef::Expression(node) => match node.item {
+                    Expression::Arrow(_) => arrows.push(address(node)),
+                    Expression::Function(_) => break,
+                    _ => {},
+                },
+                NodeRef::Statement(node) => if let Statement::Function(_) = node.item {
+                    break;
+                },
+                NodeRef::Method(_) => break,
+                _ => {},
+            }
+        }
+
+        arrows
+    }
+
+    /// Name of the variable holding the value of `init` in the closest
+    /// function that isn't an arrow, `None` if the current node isn't in
+    /// an arrow. Values other than `this` aren't captured at the top level
+    /// of the module, where they have no meaning.
+    fn capture(&mut self, path: &Path<'ast>, hint: &'static str, init: ExpressionNode<'ast>) -> Option<&'ast str> {
+        if Self::enclosing_arrows(path).is_empty() {
+            return None;
+        }
+
+        let target = Target::of(path);
+
+        if target == Target::Module && hint != "this" {
+            return None;
+        }
+
+        if let Some(&(_, _, name)) = self.captures.iter().find(|&&(other, kind, _)| other == target && kind == hint) {
+            return Some(name);
+        }
+
+        let name = self.ctx.names.declare_init(target, hint, Some(init));
+
+        self.captures.push((target, hint, name));
+
+        Some(name)
+    }
+
+    /// Whether the identifier doesn't resolve to a declared binding. Nodes
+    /// created by other transforms aren't in the semantic model and refer
+    /// to the implicit bindings.
+    fn is_implicit(&self, ptr: &ExpressionNode<'ast>) -> bool {
+        match self.semantic.reference_at(ptr) {
+            Some(reference) => self.semantic.reference(reference).binding.is_none(),
+            None => true,
+        }
+    }
+}
+
+/// Arrows using `super` in a function that isn't a class method, such as
+/// a method of an object literal, where nothing would lower `super`.
+pub fn check(semantic: &Semantic) -> Vec<Error> {
+    let mut errors = Vec::new();
+    let mut stack = vec![semantic.root()];
+
+    while let Some(id) = stack.pop() {
+        let scope = semantic.scope(id);
+
+        stack.extend(scope.children.iter().rev().cloned());
+
+        if scope.kind != ScopeKind::Arrow {
+            continue;
+        }
+
+        // Nested arrows are reported along with the outermost one
+        let owner = semantic.ancestors(id).skip(1).find(|&ancestor| matches!(
+            semantic.scope(ancestor).kind,
+            ScopeKind::Arrow | ScopeKind::Function | ScopeKind::Class | ScopeKind::Module
+        ));
+
+        let in_class = match owner.map(|owner| semantic.scope(owner)) {
+            Some(owner) if owner.kind == ScopeKind::Arrow || owner.kind == ScopeKind::Class => continue,
+            Some(owner) if owner.kind == ScopeKind::Function => {
+                owner.parent.map(|parent| semantic.scope(parent).kind) == Some(ScopeKind::Class)
+            },
+            _ => false,
+        };
+
+        if !in_class && semantic.closure(id).is_some_and(|closure| closure.uses_super) {
+            errors.push(Error::SuperOutsideClass {
+                start: scope.start,
+                end: scope.end,
+            });
         }
     }
+
+    errors
+}
+
+#[inline]
+fn address(node: &ExpressionNode) -> usize {
+    node as *const ExpressionNode as usize
 }
 
 impl<'ast> Visitor<'ast> for TransformArrow<'ast> {
-    fn on_arrow_expression(&mut self, node: &ArrowExpression<'ast>, ptr: &'ast ExpressionNode<'ast>) {
+    fn on_arrow_expression(&mut self, _: &ArrowExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        self.arrows.push((address(ptr), false));
+
+        VisitAction::Continue
+    }
+
+    fn on_this_expression(&mut self, ptr: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        let this = Builder::new(self.ctx.arena).this();
+
+        if let Some(name) = self.capture(path, "this", this) {
+            self.ctx.swap(ptr, Expression::Identifier(name));
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_identifier_expression(&mut self, item: &Identifier<'ast>, ptr: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        // Only the implicit binding, not a variable named `arguments`
+        if *item == "arguments" && self.is_implicit(ptr) {
+            let arguments = Builder::new(self.ctx.arena).ident("arguments");
+
+            if let Some(name) = self.capture(path, "arguments", arguments) {
+                self.ctx.swap(ptr, Expression::Identifier(name));
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_super_expression(&mut self, _: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        let arrows = Self::enclosing_arrows(path);
+
+        for arrow in self.arrows.iter_mut().filter(|arrow| arrows.contains(&arrow.0)) {
+            arrow.1 = true;
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_meta_property(&mut self, item: &MetaPropertyExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        if item.meta.item == "new" && item.property.item == "target" {
+            let target = Builder::new(self.ctx.arena).meta_property("new", "target");
+
+            if let Some(name) = self.capture(path, "newTarget", target) {
+                self.ctx.swap(ptr, Expression::Identifier(name));
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_arrow_expression_exit(&mut self, node: &ArrowExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        let (_, uses_super) = self.arrows.pop().expect("Arrow expressions are entered before they are left");
+
+        if uses_super {
+            return VisitAction::Continue;
+        }
+
         let body = match node.body {
             ArrowBody::Block(block)     => block,
             ArrowBody::Expression(expr) => {
@@ -32,11 +217,70 @@ impl<'ast> Visitor<'ast> for TransformArrow<'ast> {
             }
         };
 
-        self.ctx.swap(*ptr, Function {
+        self.ctx.swap(ptr, Function {
             name: OptionalName::empty(),
             generator: false,
             params: node.params,
             body,
         });
+
+        VisitAction::Continue
+    }
+}
+
+impl<'ast> Pass<'ast> for TransformArrow<'ast> {
+    fn name(&self) -> &'static str {
+        "arrow"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratel::parse_in;
+    use ratel::Arena;
+    use es2015::test::lower;
+    use {transform, Error, Options};
+
+    #[test]
+    fn should_lower_arrows() {
+        assert_eq!(lower("var f = (a, b) => a + b;", Options::default()), "var f=function(a,b){return a+b;};");
+        assert_eq!(lower("var f = () => { g(); };", Options::default()), "var f=function(){g();};");
+        assert_eq!(lower("var f = () => () => 1;", Options::default()), "var f=function(){return function(){return 1;};};");
+    }
+
+    #[test]
+    fn should_capture_this_arguments_and_new_target() {
+        assert_eq!(
+            lower("function f() { return () => [this, arguments, new.target, () => this]; }", Options::default()),
+            "function f(){var _this=this,_arguments=arguments,_newTarget=new.target;\
+             return function(){return [_this,_arguments,_newTarget,function(){return _this;}];};}"
+        );
+        assert_eq!(
+            lower("var _this; var f = () => this; function g() { this; }", Options::default()),
+            "var _this2=this;var _this;var f=function(){return _this2;};function g(){this;}"
+        );
+        assert_eq!(
+            lower("var o = { m() { return () => arguments; } }; () => arguments;", Options::default()),
+            "var o={m(){var _arguments=arguments;return function(){return _arguments;};}};(function(){return arguments;});"
+        );
+        assert_eq!(
+            lower("function f() { var arguments = 1; return () => arguments; }", Options::default()),
+            "function f(){var arguments=1;return function(){return arguments;};}"
+        );
+        assert_eq!(
+            lower("function f(arguments) { return () => arguments; }", Options::default()),
+            "function f(arguments){return function(){return arguments;};}"
+        );
+    }
+    #[test]
+    fn should_report_super_outside_of_classes() {
+        let arena = Arena::new();
+        let mut module = parse_in(&arena, "var o = { m() { return () => () => super.x; } };").unwrap();
+
+        assert_eq!(transform(&mut module), Err(vec![
+            Error::SuperOutsideClass { start: 23, end: 42 },
+        ]));
+
+        assert!(!lower("class A extends B { m() { return () => super.x; } }", Options::default()).contains("=>"));
     }
 }
@@ -0,0 +1,891 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use ratel::ast::{Node, Loc, Expression, ExpressionNode, Statement, StatementNode, StatementList};
+use ratel::ast::{Pattern, Property, Identifier, DeclarationKind, OperatorKind};
+use ratel::ast::statement::{DeclarationStatement, ForInit, ForStatement, ForInStatement, ForOfStatement};
+use ratel::ast::statement::{WhileStatement, DoStatement, BlockStatement, ReturnStatement, BreakStatement};
+use ratel::ast::statement::{ContinueStatement, LabeledStatement};
+use ratel::build::Builder;
+use ratel_semantic::{Semantic, BindingId, BindingKind, ReferenceId, ScopeId, ScopeKind};
+use ratel_visitor::{Visitor, Visitable, VisitAction, Path, Pass, NodeRef};
+
+use TransformerCtxt;
+use super::{labeled, is_labeled};
+use error::Error;
+
+/// Assignments to `const` bindings, which are errors at runtime, reported
+/// ahead of time since they would silently succeed once lowered to `var`.
+pub fn check(semantic: &Semantic) -> Vec<Error> {
+    semantic.references()
+        .iter()
+        .filter(|reference| reference.flags.write)
+        .filter(|reference| {
+            reference.binding.is_some_and(|binding| semantic.binding(binding).kind == BindingKind::Const)
+        })
+        .map(|reference| Error::ConstAssignment {
+            name: reference.name.into(),
+            start: reference.start,
+            end: reference.end,
+        })
+        .collect()
+}
+
+/// Lowers `let` and `const` declarations to `var`.
+///
+/// Once hoisted to the closest function, a block scoped binding could clash
+/// with another binding of the same name, or shadow a name used in that
+/// function. Such bindings are renamed, the first one declared keeps its name.
+///
+/// Loops declaring bindings that are captured by closures are extracted to a
+/// `_loop` function called on every iteration, so each iteration still gets
+/// its own bindings. Jumps out of the loop body are returned from `_loop`
+/// and replayed after the call. Bindings declared in the head of a `for` loop
+/// are passed to `_loop` by value. Those the body writes to are renamed in
+/// the body, and written back to the head before the next iteration. `var`
+/// declarations in the body belong to the function the loop is in, so they
+/// are declared before `_loop` and left as assignments in it.
+///
+/// `let` declarations without a value inside a loop are initialized to
+/// `void 0`, since a `var` would keep the value of the previous iteration.
+/// The temporal dead zone isn't checked.
+pub struct TransformBlockScope<'ast> {
+    ctx: TransformerCtxt<'ast>,
+    semantic: Rc<Semantic<'ast>>,
+
+    /// New names of the bindings that have to be renamed.
+    renames: HashMap<BindingId, &'ast str>,
+
+    /// Addresses of the loops calling an extracted `_loop`, which must not
+    /// be extracted again.
+    extracted: Vec<usize>,
+
+    /// Names of the `_loop` parameters standing in for head bindings the
+    /// body writes to, by the references to them in the body.
+    params: HashMap<ReferenceId, &'ast str>,
+}
+
+impl<'ast> TransformBlockScope<'ast> {
+    pub fn new(ctx: TransformerCtxt<'ast>, semantic: Rc<Semantic<'ast>>) -> TransformBlockScope<'ast> {
+        let renames = clashing(&semantic)
+            .into_iter()
+            .map(|binding| (binding, ctx.names.generate(semantic.binding(binding).name)))
+            .collect();
+
+        TransformBlockScope {
+            ctx,
+            semantic,
+            renames,
+            extracted: Vec::new(),
+            params: HashMap::new(),
+        }
+    }
+
+    /// New name of the binding declared or referenced by the node.
+    fn renamed<T>(&self, node: &Node<'ast, T>) -> Option<&'ast str> {
+        let reference = self.semantic.reference_at(node);
+
+        if let Some(&name) = reference.and_then(|reference| self.params.get(&reference)) {
+            return Some(name);
+        }
+
+        let binding = match reference {
+            Some(reference) => self.semantic.reference(reference).binding,
+            None            => self.semantic.declaration_at(node),
+        };
+
+        binding.and_then(|binding| self.renames.get(&binding).cloned())
+    }
+
+    /// Name the binding ends up with.
+    fn name_of(&self, binding: BindingId) -> &'ast str {
+        self.renames.get(&binding).cloned().unwrap_or(self.semantic.binding(binding).name)
+    }
+
+    /// Whether the binding is referenced from a function other than the one
+    /// it's declared in.
+    fn is_captured(&self, binding: BindingId) -> bool {
+        let binding = self.semantic.binding(binding);
+        let scope = var_scope(&self.semantic, binding.scope);
+
+        binding.references.iter().any(|&reference| {
+            var_scope(&self.semantic, self.semantic.reference(reference).scope) != scope
+        })
+    }
+
+    fn on_loop(&mut self, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        // Labeled loops are extracted along with their labels
+        if is_labeled(path) {
+            return VisitAction::Continue;
+        }
+
+        self.extract(node, Vec::new(), node)
+    }
+
+    /// Extract the body of the loop to a `_loop` function if it declares
+    /// bindings captured by closures. `outer` is the loop, or the outermost
+    /// of the labeled statements around it, which will be replaced.
+    fn extract(&mut self, outer: &'ast StatementNode<'ast>, labels: Vec<&'ast str>, ptr: &'ast StatementNode<'ast>) -> VisitAction {
+        if self.extracted.contains(&address(ptr)) {
+            return VisitAction::Continue;
+        }
+
+        let mut collector = Collector {
+            semantic: &self.semantic,
+            root: address(ptr),
+            labels: labels.clone(),
+            bindings: Vec::new(),
+            vars: Vec::new(),
+            declarations: Vec::new(),
+            heads: Vec::new(),
+            exits: Vec::new(),
+        };
+
+        ptr.visit_with(&mut collector);
+
+        if !collector.bindings.iter().any(|&binding| self.is_captured(binding)) {
+            return VisitAction::Continue;
+        }
+
+        let builder = Builder::new(self.ctx.arena);
+        let body = match ptr.item {
+            Statement::While(WhileStatement { body, .. }) |
+            Statement::Do(DoStatement { body, .. }) |
+            Statement::For(ForStatement { body, .. }) |
+            Statement::ForIn(ForInStatement { body, .. }) |
+            Statement::ForOf(ForOfStatement { body, .. }) => body,
+            _ => return VisitAction::Continue,
+        };
+
+        let in_body = |start: u32, end: u32| start >= body.start && end <= body.end;
+
+        // Bindings of the head, by their name outside and inside of `_loop`
+        let mut params = Vec::new();
+        let mut writes = Vec::new();
+
+        for &binding in &collector.bindings {
+            if self.semantic.scope(self.semantic.binding(binding).scope).kind != ScopeKind::For {
+                continue;
+            }
+
+            let name = self.name_of(binding);
+            let references: Vec<ReferenceId> = self.semantic.binding(binding).references
+                .iter()
+                .cloned()
+                .filter(|&reference| {
+                    let reference = self.semantic.reference(reference);
+
+                    in_body(reference.start, reference.end)
+                })
+                .collect();
+
+            if !references.iter().any(|&reference| self.semantic.reference(reference).flags.write) {
+                params.push((name, name));
+                continue;
+            }
+
+            let param = self.ctx.names.generate(name);
+
+            for reference in references {
+                self.params.insert(reference, param);
+            }
+
+            params.push((name, param));
+            writes.push((name, param));
+        }
+
+        // Statements writing the values of the parameters back to the head
+        let write_back = || writes.iter().map(|&(name, param)| {
+            builder.expression_statement(builder.assign(builder.ident(name), builder.ident(param)))
+        });
+
+        let vars: Vec<&'ast str> = collector.vars
+            .iter()
+            .filter(|&&binding| {
+                let binding = self.semantic.binding(binding);
+
+                in_body(binding.start, binding.end)
+            })
+            .map(|&binding| self.name_of(binding))
+            .collect();
+
+        for &node in &collector.declarations {
+            if let Statement::Declaration(DeclarationStatement { declarators, .. }) = node.item {
+                let assignments: Vec<_> = declarators
+                    .iter()
+                    .filter_map(|declarator| declarator.init.map(|init| builder.assign(pattern_expression(&builder, declarator.id), init)))
+                    .collect();
+
+                let statement = match assignments.len() {
+                    0 => builder.empty_statement(),
+                    1 => builder.expression_statement(assignments[0]),
+                    _ => builder.expression_statement(builder.sequence(builder.list(assignments))),
+                };
+
+                self.ctx.swap(node, statement.item);
+            }
+        }
+
+        for &node in &collector.heads {
+            if let ForInit::Declaration(DeclarationStatement { declarators, .. }) = node.item {
+                let expressions: Vec<_> = declarators.iter().map(|declarator| {
+                    let left = pattern_expression(&builder, declarator.id);
+
+                    match declarator.init {
+                        Some(init) => builder.assign(left, init),
+                        None       => left,
+                    }
+                }).collect();
+
+                let expression = match expressions.len() {
+                    1 => expressions[0],
+                    _ => builder.sequence(builder.list(expressions)),
+                };
+
+                self.ctx.swap(node, ForInit::Expression(expression));
+            }
+        }
+
+        let mut exits = Vec::new();
+
+        for &(node, exit) in &collector.exits {
+            let value = match exit {
+                Exit::Continue        => None,
+                Exit::Break           => Some(builder.string("break")),
+                Exit::Jump(kind, label) => Some(builder.string(&format!("{}|{}", kind, label))),
+                Exit::Return(value)   => {
+                    let value = value.unwrap_or_else(|| void(&builder));
+                    let key = builder.key("v");
+
+                    Some(builder.object(builder.list_from(builder.property(key, value))))
+                },
+            };
+
+            // Head bindings are out of scope once the loop is left
+            if writes.is_empty() || !same_exit(exit, Exit::Continue) {
+                self.ctx.swap(node, ReturnStatement { value });
+            } else {
+                let statements = write_back().chain(Some(builder.return_statement(value)));
+
+                self.ctx.swap(node, BlockStatement { body: builder.list(statements.collect::<Vec<_>>()) });
+            }
+
+            if !same_exit(exit, Exit::Continue) && !exits.iter().any(|other| same_exit(*other, exit)) {
+                exits.push(exit);
+            }
+        }
+
+        let statements = match body.item {
+            Statement::Block(BlockStatement { body }) => body,
+            _ => builder.list_from(body),
+        };
+        let statements = builder.list(statements.iter().cloned().chain(write_back()).collect::<Vec<_>>());
+
+        let name = self.ctx.names.generate("loop");
+        let function = builder.arrow_block(builder.list(params.iter().map(|&(_, param)| builder.pattern(param))), statements);
+        let call = builder.call(builder.ident(name), builder.list(params.iter().map(|&(name, _)| builder.ident(name))));
+
+        let calls = if exits.is_empty() {
+            builder.list_from(builder.expression_statement(call))
+        } else {
+            let ret = self.ctx.names.generate("ret");
+
+            builder.prepend(builder.var(ret, Some(call)), replay(&builder, ret, &exits))
+        };
+
+        let calls = builder.block_statement(calls);
+        let item = match ptr.item {
+            Statement::While(WhileStatement { test, .. }) => Statement::While(WhileStatement { test, body: calls }),
+            Statement::Do(DoStatement { test, .. }) => Statement::Do(DoStatement { body: calls, test }),
+            Statement::For(ForStatement { init, test, update, .. }) => {
+                Statement::For(ForStatement { init, test, update, body: calls })
+            },
+            Statement::ForIn(ForInStatement { left, right, .. }) => Statement::ForIn(ForInStatement { left, right, body: calls }),
+            Statement::ForOf(ForOfStatement { left, right, .. }) => Statement::ForOf(ForOfStatement { left, right, body: calls }),
+            _ => return VisitAction::Continue,
+        };
+
+        let loc = self.ctx.arena.alloc(Loc::new(ptr.start, ptr.end, item));
+
+        self.extracted.push(loc as *const Loc<Statement> as usize);
+
+        // Labels stay on the loop, continuing a labeled block is an error
+        let mut statement = Node::new(loc);
+
+        for &label in labels.iter().rev() {
+            statement = builder.node(LabeledStatement { label, body: statement });
+        }
+
+        let mut statements = vec![builder.var(name, Some(function)), statement];
+
+        if !vars.is_empty() {
+            let declarators = vars.iter().map(|&var| builder.declarator(builder.pattern(var), None));
+
+            statements.insert(0, builder.declaration_statement(DeclarationKind::Var, builder.list(declarators)));
+        }
+
+        let statements = builder.list(statements);
+
+        self.ctx.swap(outer, BlockStatement { body: statements });
+
+        VisitAction::Revisit
+    }
+}
+
+/// Statements replaying the exits returned from `_loop` as `ret`.
+fn replay<'ast>(builder: &Builder<'ast>, ret: &'ast str, exits: &[Exit<'ast>]) -> StatementList<'ast> {
+    let statements = exits.iter().map(|&exit| {
+        let (test, jump) = match exit {
+            Exit::Break => (
+                builder.binary(OperatorKind::StrictEquality, builder.ident(ret), builder.string("break")),
+                builder.break_statement(None),
+            ),
+            Exit::Jump(kind, label) => {
+                let value = builder.string(&format!("{}|{}", kind, label));
+                let jump = if kind == "break" {
+                    builder.break_statement(Some(label))
+                } else {
+                    builder.continue_statement(Some(label))
+                };
+
+                (builder.binary(OperatorKind::StrictEquality, builder.ident(ret), value), jump)
+            },
+            Exit::Return(_) => (
+                builder.binary(
+                    OperatorKind::StrictEquality,
+                    builder.prefix(OperatorKind::Typeof, builder.ident(ret)),
+                    builder.string("object"),
+                ),
+                builder.return_statement(Some(builder.member(builder.ident(ret), "v"))),
+            ),
+            Exit::Continue => unreachable!("Continuing doesn't need to be replayed"),
+        };
+
+        builder.if_statement(test, jump, None)
+    });
+
+    builder.list(statements.collect::<Vec<_>>())
+}
+
+/// Expression assigning to the same bindings as the pattern.
+fn pattern_expression<'ast>(builder: &Builder<'ast>, pattern: Node<'ast, Pattern<'ast>>) -> ExpressionNode<'ast> {
+    match pattern.item {
+        Pattern::Void => builder.node(Expression::Void),
+        Pattern::Identifier(name) => builder.ident(name),
+        Pattern::ObjectPattern { properties } => builder.object(properties),
+        Pattern::ArrayPattern { elements } => {
+            builder.array(builder.list(elements.iter().map(|&element| pattern_expression(builder, element))))
+        },
+        Pattern::RestElement { argument } => builder.spread(builder.ident(argument.item)),
+        Pattern::AssignmentPattern { left, right } => builder.assign(pattern_expression(builder, left), right),
+    }
+}
+
+#[inline]
+fn void<'ast>(builder: &Builder<'ast>) -> ExpressionNode<'ast> {
+    builder.prefix(OperatorKind::Void, builder.integer(0))
+}
+
+#[inline]
+fn address<T>(node: &Node<T>) -> usize {
+    &**node as *const Loc<T> as usize
+}
+
+/// Closest function or module scope of the scope.
+fn var_scope(semantic: &Semantic, scope: ScopeId) -> ScopeId {
+    semantic.ancestors(scope)
+        .find(|&scope| semantic.scope(scope).kind.is_var_scope())
+        .expect("The module is a var scope")
+}
+
+/// Whether the binding is a `let` or `const` declared in a block, as
+/// opposed to the top of a function or the module.
+fn is_block_scoped(semantic: &Semantic, binding: BindingId) -> bool {
+    let binding = semantic.binding(binding);
+
+    match binding.kind {
+        BindingKind::Let | BindingKind::Const => !semantic.scope(binding.scope).kind.is_var_scope(),
+        _ => false,
+    }
+}
+
+/// Block scoped bindings that have to be renamed once hoisted to the
+/// closest function, in the order they are declared.
+fn clashing(semantic: &Semantic) -> Vec<BindingId> {
+    let mut scopes = vec![semantic.root()];
+    let mut bindings = Vec::new();
+
+    while let Some(scope) = scopes.pop() {
+        let scope = semantic.scope(scope);
+
+        bindings.extend(scope.bindings.iter().cloned());
+        scopes.extend(scope.children.iter().cloned());
+    }
+
+    bindings.sort();
+
+    // Names taken in each function, by bindings that aren't block scoped,
+    // and by references to bindings declared outside of it
+    let mut taken: HashMap<ScopeId, HashSet<&str>> = HashMap::new();
+
+    for &binding in bindings.iter().filter(|&&binding| !is_block_scoped(semantic, binding)) {
+        let binding = semantic.binding(binding);
+
+        taken.entry(var_scope(semantic, binding.scope)).or_default().insert(binding.name);
+    }
+
+    for reference in semantic.references() {
+        let declared = reference.binding.map(|binding| semantic.binding(binding).scope);
+
+        for function in semantic.ancestors(reference.scope).filter(|&scope| semantic.scope(scope).kind.is_var_scope()) {
+            let outside = match declared {
+                Some(declared) => declared != function && semantic.ancestors(function).any(|scope| scope == declared),
+                None           => true,
+            };
+
+            if outside {
+                taken.entry(function).or_default().insert(reference.name);
+            }
+        }
+    }
+
+    bindings
+        .into_iter()
+        .filter(|&binding| is_block_scoped(semantic, binding))
+        .filter(|&binding| {
+            let binding = semantic.binding(binding);
+
+            !taken.entry(var_scope(semantic, binding.scope)).or_default().insert(binding.name)
+        })
+        .collect()
+}
+
+/// Whether the current node is in the body of a loop, in the same function.
+fn in_loop(path: &Path) -> bool {
+    for node in path.ancestors() {
+        match node {
+            NodeRef::Statement(node) => match node.item {
+                Statement::While(_) | Statement::Do(_) | Statement::For(_) |
+                Statement::ForIn(_) | Statement::ForOf(_) => return true,
+                Statement::Function(_) => return false,
+                _ => {},
+            },
+            NodeRef::Expression(node) => match node.item {
+                Expression::Function(_) | Expression::Arrow(_) => return false,
+                _ => {},
+            },
+            NodeRef::Method(_) => return false,
+            _ => {},
+        }
+    }
+
+    false
+}
+
+impl<'ast> Visitor<'ast> for TransformBlockScope<'ast> {
+    fn on_pattern(&mut self, item: &Pattern<'ast>, node: &'ast Node<'ast, Pattern<'ast>>, _: &Path<'ast>) -> VisitAction {
+        match *item {
+            Pattern::Identifier(_) => if let Some(name) = self.renamed(node) {
+                self.ctx.swap(node, Pattern::Identifier(name));
+            },
+            Pattern::RestElement { argument } => if let Some(name) = self.renamed(&argument) {
+                let argument = Builder::new(self.ctx.arena).identifier(name);
+
+                self.ctx.swap(node, Pattern::RestElement { argument });
+            },
+            _ => {},
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_identifier_expression(&mut self, _: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        if let Some(name) = self.renamed(node) {
+            self.ctx.swap(node, Expression::Identifier(name));
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_property(&mut self, item: &Property<'ast>, node: &'ast Node<'ast, Property<'ast>>, _: &Path<'ast>) -> VisitAction {
+        if let Property::Shorthand(key) = *item {
+            if let Some(name) = self.renamed(node) {
+                let builder = Builder::new(self.ctx.arena);
+
+                self.ctx.swap(node, Property::Literal {
+                    key: builder.key(key),
+                    value: builder.ident(name),
+                });
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_declaration_statement(&mut self, _: &DeclarationStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        let declaration = match node.item {
+            Statement::Declaration(declaration) => declaration,
+            _ => return VisitAction::Continue,
+        };
+
+        if declaration.kind == DeclarationKind::Var {
+            return VisitAction::Continue;
+        }
+
+        let mut declarators = declaration.declarators;
+
+        if declaration.kind == DeclarationKind::Let && in_loop(path) {
+            let builder = Builder::new(self.ctx.arena);
+
+            declarators = builder.list(declarators.iter().map(|declarator| match declarator.init {
+                None => builder.declarator(declarator.id, Some(void(&builder))),
+                Some(_) => *declarator,
+            }).collect::<Vec<_>>());
+        }
+
+        self.ctx.swap(node, DeclarationStatement {
+            kind: DeclarationKind::Var,
+            declarators,
+        });
+
+        VisitAction::Revisit
+    }
+
+    fn on_for_init(&mut self, item: &ForInit<'ast>, node: &'ast Node<'ast, ForInit<'ast>>, _: &Path<'ast>) -> VisitAction {
+        if let ForInit::Declaration(declaration) = *item {
+            if declaration.kind != DeclarationKind::Var {
+                self.ctx.swap(node, ForInit::Declaration(DeclarationStatement {
+                    kind: DeclarationKind::Var,
+                    declarators: declaration.declarators,
+                }));
+
+                return VisitAction::Revisit;
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_labeled_statement(&mut self, _: &LabeledStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        if is_labeled(path) {
+            return VisitAction::Continue;
+        }
+
+        let (labels, ptr) = labeled(node);
+
+        match ptr.item {
+            Statement::While(_) | Statement::Do(_) | Statement::For(_) |
+            Statement::ForIn(_) | Statement::ForOf(_) => self.extract(node, labels, ptr),
+            _ => VisitAction::Continue,
+        }
+    }
+
+    fn on_while_statement(&mut self, _: &WhileStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        self.on_loop(node, path)
+    }
+
+    fn on_do_statement(&mut self, _: &DoStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        self.on_loop(node, path)
+    }
+
+    fn on_for_statement(&mut self, _: &ForStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        self.on_loop(node, path)
+    }
+
+    fn on_for_in_statement(&mut self, _: &ForInStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        self.on_loop(node, path)
+    }
+
+    fn on_for_of_statement(&mut self, _: &ForOfStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        self.on_loop(node, path)
+    }
+}
+
+impl<'ast> Pass<'ast> for TransformBlockScope<'ast> {
+    fn name(&self) -> &'static str {
+        "block-scope"
+    }
+}
+
+/// Way out of the body of an extracted loop.
+#[derive(Clone, Copy)]
+enum Exit<'ast> {
+    /// `continue` the extracted loop.
+    Continue,
+
+    /// `break` out of the extracted loop.
+    Break,
+
+    /// `break` or `continue` to a label outside of the extracted loop.
+    Jump(&'static str, &'ast str),
+
+    /// `return` from the function the loop is in.
+    Return(Option<ExpressionNode<'ast>>),
+}
+
+/// Whether both exits are replayed by the same statement.
+fn same_exit(a: Exit, b: Exit) -> bool {
+    match (a, b) {
+        (Exit::Continue, Exit::Continue) |
+        (Exit::Break, Exit::Break) |
+        (Exit::Return(_), Exit::Return(_)) => true,
+        (Exit::Jump(a, x), Exit::Jump(b, y)) => a == b && x == y,
+        _ => false,
+    }
+}
+
+/// Whether the statement is a loop.
+fn is_loop(statement: &Statement) -> bool {
+    matches!(
+        *statement,
+        Statement::While(_) | Statement::Do(_) | Statement::For(_) | Statement::ForIn(_) | Statement::ForOf(_)
+    )
+}
+
+/// Bindings and jumps of a loop about to be extracted.
+struct Collector<'s, 'ast: 's> {
+    semantic: &'s Semantic<'ast>,
+
+    /// Address of the loop.
+    root: usize,
+
+    /// Labels of the loop.
+    labels: Vec<&'ast str>,
+
+    /// `let` and `const` bindings declared in the loop, outside of nested
+    /// loops and functions.
+    bindings: Vec<BindingId>,
+
+    /// `var` bindings declared in the loop, outside of nested functions.
+    vars: Vec<BindingId>,
+
+    /// `var` declarations in the body of the loop, and in the heads of
+    /// loops nested in it.
+    declarations: Vec<&'ast StatementNode<'ast>>,
+    heads: Vec<&'ast Node<'ast, ForInit<'ast>>>,
+
+    /// Jump statements leaving the body of the loop.
+    exits: Vec<(&'ast StatementNode<'ast>, Exit<'ast>)>,
+}
+
+impl<'s, 'ast> Collector<'s, 'ast> {
+    /// Whether the current node is inside the loop, and the jump doesn't
+    /// stop at any statement between the two. Jumps never leave functions.
+    fn leaves<F>(&self, path: &Path<'ast>, stops: F) -> bool where
+        F: Fn(&Statement<'ast>) -> bool,
+    {
+        for node in path.ancestors() {
+            match node {
+                NodeRef::Statement(node) => {
+                    if address(node) == self.root {
+                        return true;
+                    }
+
+                    match node.item {
+                        Statement::Function(_) => return false,
+                        ref statement => if stops(statement) {
+                            return false;
+                        },
+                    }
+                },
+                NodeRef::Expression(node) => match node.item {
+                    Expression::Function(_) | Expression::Arrow(_) => return false,
+                    _ => {},
+                },
+                NodeRef::Method(_) => return false,
+                _ => {},
+            }
+        }
+
+        false
+    }
+
+    /// Exit to a label, or to the loop if it's one of its labels.
+    fn jump(&self, kind: &'static str, label: Option<&'ast str>, own: Exit<'ast>) -> Exit<'ast> {
+        match label {
+            Some(label) if !self.labels.contains(&label) => Exit::Jump(kind, label),
+            _ => own,
+        }
+    }
+
+    fn declare<T>(&mut self, node: &Node<'ast, T>, path: &Path<'ast>) {
+        let binding = match self.semantic.declaration_at(node) {
+            Some(binding) => binding,
+            None          => return,
+        };
+
+        // Loops and functions inside the loop have bindings of their own,
+        // only functions have `var` bindings of their own
+        match self.semantic.binding(binding).kind {
+            BindingKind::Let | BindingKind::Const
+                if self.leaves(path, is_loop) && !self.bindings.contains(&binding) =>
+            {
+                self.bindings.push(binding);
+            },
+            BindingKind::Var if self.leaves(path, |_| false) && !self.vars.contains(&binding) => {
+                self.vars.push(binding);
+            },
+            _ => {},
+        }
+    }
+}
+
+impl<'s, 'ast> Visitor<'ast> for Collector<'s, 'ast> {
+    fn on_pattern(&mut self, item: &Pattern<'ast>, node: &'ast Node<'ast, Pattern<'ast>>, path: &Path<'ast>) -> VisitAction {
+        match *item {
+            Pattern::Identifier(_) => self.declare(node, path),
+            Pattern::RestElement { ref argument } => self.declare(argument, path),
+            _ => {},
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_identifier_expression(&mut self, _: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        self.declare(node, path);
+
+        VisitAction::Continue
+    }
+
+    fn on_property(&mut self, item: &Property<'ast>, node: &'ast Node<'ast, Property<'ast>>, path: &Path<'ast>) -> VisitAction {
+        if let Property::Shorthand(_) = *item {
+            self.declare(node, path);
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_declaration_statement(&mut self, item: &DeclarationStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        if item.kind == DeclarationKind::Var && self.leaves(path, |_| false) {
+            self.declarations.push(node);
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_for_init(&mut self, item: &ForInit<'ast>, node: &'ast Node<'ast, ForInit<'ast>>, path: &Path<'ast>) -> VisitAction {
+        let own = match path.parent() {
+            Some(NodeRef::Statement(parent)) => address(parent) == self.root,
+            _ => false,
+        };
+
+        if let ForInit::Declaration(DeclarationStatement { kind: DeclarationKind::Var, .. }) = *item {
+            if !own && self.leaves(path, |_| false) {
+                self.heads.push(node);
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_break_statement(&mut self, _: &BreakStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        let label = match node.item {
+            Statement::Break(BreakStatement { label }) => label.map(|label| label.item),
+            _ => None,
+        };
+        let leaves = self.leaves(path, |statement| match *statement {
+            Statement::Labeled(LabeledStatement { label: other, .. }) => Some(other) == label,
+            Statement::Switch(_) => label.is_none(),
+            ref statement => label.is_none() && is_loop(statement),
+        });
+
+        if leaves {
+            let exit = self.jump("break", label, Exit::Break);
+
+            self.exits.push((node, exit));
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_continue_statement(&mut self, _: &ContinueStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        let label = match node.item {
+            Statement::Continue(ContinueStatement { label }) => label.map(|label| label.item),
+            _ => None,
+        };
+        let leaves = self.leaves(path, |statement| match *statement {
+            Statement::Labeled(LabeledStatement { label: other, .. }) => Some(other) == label,
+            ref statement => label.is_none() && is_loop(statement),
+        });
+
+        if leaves {
+            let exit = self.jump("continue", label, Exit::Continue);
+
+            self.exits.push((node, exit));
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_return_statement(&mut self, _: &ReturnStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        if let Statement::Return(ReturnStatement { value }) = node.item {
+            if self.leaves(path, |_| false) {
+                self.exits.push((node, Exit::Return(value)));
+            }
+        }
+
+        VisitAction::Continue
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratel::parse_in;
+    use ratel::Arena;
+    use es2015::test::lower;
+    use {transform, Error, Options};
+
+    #[test]
+    fn should_rename_clashing_bindings() {
+        assert_eq!(lower("let a = 1; { let a = 2; let {b} = o; } var b;", Options::default()), "var a=1;{var _a=2;var _b=o.b;}var b;");
+        assert_eq!(lower("let x = 1; function f() { { let x = 2; } return x; }", Options::default()), "var x=1;function f(){{var _x=2;}return x;}");
+        assert_eq!(lower("for (let i = 0; i < 3; i++) { let j; f(j); }", Options::default()), "for(var i=0;i<3;i++){var j=void 0;f(j);}");
+    }
+
+    #[test]
+    fn should_extract_loops_with_captured_bindings() {
+        assert_eq!(
+            lower("for (let i = 0; i < 3; i++) { fns.push(() => i); }", Options::default()),
+            "{var _loop=function(i){fns.push(function(){return i;});};for(var i=0;i<3;i++){_loop(i);}}"
+        );
+        assert_eq!(
+            lower("function f(xs) { outer: for (const x of xs) { while (x) { if (g(() => x)) continue outer; if (x) break; return x; } } }", Options::default()),
+            "function f(xs){{var _loop=function(x){while(x){if(g(function(){return x;}))return;if(x)break;return {v:x};}};\
+             {var _iteratorNormalCompletion=true,_didIteratorError=false,_iteratorError=void 0;\
+             try{outer:for(var _iterator=xs[Symbol.iterator](),_step;!(_iteratorNormalCompletion=(_step=_iterator.next()).done);\
+             _iteratorNormalCompletion=true){var x=_step.value;var _ret=_loop(x);if(typeof _ret===\"object\")return _ret.v;}}\
+             catch(_err){_didIteratorError=true;_iteratorError=_err;}\
+             finally{try{if(!_iteratorNormalCompletion&&_iterator.return!=null)_iterator.return();}\
+             finally{if(_didIteratorError)throw _iteratorError;}}}}}"
+        );
+    }
+
+    #[test]
+    fn should_hoist_vars_out_of_extracted_loops() {
+        assert_eq!(
+            lower("for (let i = 0; i < 3; i++) { let y = i; var x = y, z; fns.push(() => y); for (var k in o) {} } f(x);", Options::default()),
+            "{var x,z,k;var _loop=function(i){var y=i;x=y;fns.push(function(){return y;});for(k in o){}};\
+             for(var i=0;i<3;i++){_loop(i);}}f(x);"
+        );
+    }
+
+    #[test]
+    fn should_write_back_head_bindings_written_in_extracted_loops() {
+        assert_eq!(
+            lower("for (let i = 0; i < 3; i++) { fns.push(() => i); if (g(i)) continue; if (h(i)) break; i += 1; }", Options::default()),
+            "{var _loop=function(_i){fns.push(function(){return _i;});if(g(_i)){i=_i;return;}if(h(_i))return \"break\";_i+=1;i=_i;};\
+             for(var i=0;i<3;i++){var _ret=_loop(i);if(_ret===\"break\")break;}}"
+        );
+    }
+
+    #[test]
+    fn should_report_const_assignments() {
+        let arena = Arena::new();
+        let mut module = parse_in(&arena, "const a = 1; a += 1; { const b = 2; b++; }").unwrap();
+
+        assert_eq!(transform(&mut module), Err(vec![
+            Error::ConstAssignment { name: "a".into(), start: 13, end: 14 },
+            Error::ConstAssignment { name: "b".into(), start: 36, end: 37 },
+        ]));
+    }
+}
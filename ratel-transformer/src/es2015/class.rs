@@ -0,0 +1,574 @@
+use std::ptr;
+
+use ratel::ast::{Loc, Node, NodeList, Name, Expression, ExpressionNode, Statement, StatementNode};
+use ratel::ast::{Function, Class, ClassMember, MethodKind, Method, MandatoryName, OptionalName};
+use ratel::ast::{PropertyKey, Literal, OperatorKind, DeclarationKind};
+use ratel::ast::expression::{ClassExpression, CallExpression, MemberExpression, BinaryExpression};
+use ratel::ast::expression::{PrefixExpression, PostfixExpression};
+use ratel::ast::expression::{ComputedMemberExpression, MetaPropertyExpression};
+use ratel::ast::statement::ClassStatement;
+use ratel::build::Builder;
+use ratel_visitor::{Visitor, VisitAction, Path, Pass, NodeRef};
+
+use TransformerCtxt;
+use names::Target;
+use helpers::Helper;
+use super::is_in_pattern;
+
+/// Lowers classes to constructor functions, wrapped in a function called
+/// right away with the class being extended:
+///
+/// ```js
+/// var Foo = function (_Bar) {
+///     _inherits(Foo, _Bar);
+///     function Foo() {
+///         _classCallCheck(this, Foo);
+///         _Bar.apply(this, arguments);
+///     }
+///     Foo.prototype.baz = function () {
+///         return _Bar.prototype.baz.call(this);
+///     };
+///     return Foo;
+/// }(Bar);
+/// ```
+///
+/// Methods are assigned to the prototype, or to the constructor if static,
+/// and accessors are defined with `Object.defineProperty`. Uses of `super`
+/// and `new.target` in the members are replaced as the lowered class is
+/// walked.
+pub struct TransformClass<'ast> {
+    ctx: TransformerCtxt<'ast>,
+
+    /// Functions lowered from class members.
+    members: Vec<Member<'ast>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MemberKind {
+    Constructor,
+    Prototype,
+    Static,
+}
+
+/// A function lowered from a class member, with the names `super` and
+/// `new.target` are lowered to in its body.
+#[derive(Clone, Copy)]
+struct Member<'ast> {
+    target: Target<'ast>,
+    kind: MemberKind,
+    class: &'ast str,
+    parent: Option<&'ast str>,
+}
+
+/// Getter and setter sharing a key.
+struct Accessor<'ast> {
+    is_static: bool,
+    key: Node<'ast, PropertyKey<'ast>>,
+    get: Option<ExpressionNode<'ast>>,
+    set: Option<ExpressionNode<'ast>>,
+}
+
+impl<'ast> TransformClass<'ast> {
+    pub fn new(ctx: TransformerCtxt<'ast>) -> TransformClass<'ast> {
+        TransformClass {
+            ctx,
+            members: Vec::new(),
+        }
+    }
+
+    /// Member whose body the current node is in, skipping arrow functions.
+    fn member(&self, path: &Path<'ast>) -> Option<Member<'ast>> {
+        let target = Target::of(path);
+
+        self.members.iter().find(|member| member.target == target).cloned()
+    }
+
+    /// Function expression for a method, recording it as a member.
+    fn method(&mut self, method: &Method<'ast>, kind: MemberKind, class: &'ast str, parent: Option<&'ast str>) -> ExpressionNode<'ast> {
+        let loc = self.ctx.arena.alloc(Loc::new(method.body.start, method.body.end, Expression::Function(Function {
+            name: OptionalName::empty(),
+            generator: method.generator,
+            params: method.params,
+            body: method.body,
+        })));
+
+        if let Expression::Function(ref function) = loc.item {
+            self.members.push(Member { target: Target::Function(&function.body), kind, class, parent });
+        }
+
+        Node::new(loc)
+    }
+
+    fn lower<N: Name<'ast>>(&mut self, class: &Class<'ast, N>, name: Option<&'ast str>) -> ExpressionNode<'ast> {
+        let builder = Builder::new(self.ctx.arena);
+        let name = name.unwrap_or_else(|| self.ctx.names.generate("class"));
+
+        let parent = class.extends.map(|extends| self.ctx.names.generate(match extends.item {
+            Expression::Identifier(parent) => parent,
+            _ => "super",
+        }));
+
+        let mut body = Vec::new();
+
+        if let Some(parent) = parent {
+            let inherits = builder.ident(self.ctx.helpers.get(Helper::Inherits));
+
+            body.push(builder.expression_statement(builder.call(inherits, builder.list(vec![
+                builder.ident(name),
+                builder.ident(parent),
+            ]))));
+        }
+
+        body.push(self.constructor(class, name, parent));
+
+        let mut accessors: Vec<Accessor<'ast>> = Vec::new();
+        let mut statics = Vec::new();
+
+        for member in class.body.body.iter() {
+            match member.item {
+                ClassMember::Method { kind: MethodKind::Constructor, .. } | ClassMember::Error => {},
+                ClassMember::Method { is_static, key, kind: MethodKind::Method, value } => {
+                    let kind = if is_static { MemberKind::Static } else { MemberKind::Prototype };
+                    let object = owner(&builder, name, is_static);
+                    let function = self.method(&value, kind, name, parent);
+
+                    body.push(builder.expression_statement(builder.assign(member_of(&builder, object, key), function)));
+                },
+                ClassMember::Method { is_static, key, kind, value } => {
+                    let function = self.method(&value, if is_static { MemberKind::Static } else { MemberKind::Prototype }, name, parent);
+
+                    let index = match accessors.iter().position(|accessor| accessor.is_static == is_static && same_key(&accessor.key, &key)) {
+                        Some(index) => index,
+                        None => {
+                            accessors.push(Accessor { is_static, key, get: None, set: None });
+                            accessors.len() - 1
+                        }
+                    };
+
+                    match kind {
+                        MethodKind::Get => accessors[index].get = Some(function),
+                        _               => accessors[index].set = Some(function),
+                    }
+                },
+                ClassMember::Literal { is_static: true, key, value } => {
+                    statics.push(builder.expression_statement(builder.assign(member_of(&builder, builder.ident(name), key), value)));
+                },
+                ClassMember::Literal { .. } => {},
+            }
+        }
+
+        for accessor in accessors {
+            let mut properties = Vec::new();
+
+            if let Some(get) = accessor.get {
+                properties.push(builder.property(builder.key("get"), get));
+            }
+
+            if let Some(set) = accessor.set {
+                properties.push(builder.property(builder.key("set"), set));
+            }
+
+            properties.push(builder.property(builder.key("configurable"), builder.boolean(true)));
+
+            let define = builder.member(builder.ident("Object"), "defineProperty");
+
+            body.push(builder.expression_statement(builder.call(define, builder.list(vec![
+                owner(&builder, name, accessor.is_static),
+                key_of(&builder, accessor.key),
+                builder.object(builder.list(properties)),
+            ]))));
+        }
+
+        body.extend(statics);
+        body.push(builder.return_statement(Some(builder.ident(name))));
+
+        let (params, arguments) = match (parent, class.extends) {
+            (Some(parent), Some(extends)) => (builder.list_from(builder.pattern(parent)), builder.list_from(extends)),
+            _ => (NodeList::empty(), NodeList::empty()),
+        };
+
+        builder.call(builder.function_expression(None, params, builder.list(body)), arguments)
+    }
+
+    /// Constructor function declaration, starting with the class call check
+    /// and initializing instance fields once `super` has been called.
+    fn constructor<N: Name<'ast>>(&mut self, class: &Class<'ast, N>, name: &'ast str, parent: Option<&'ast str>) -> StatementNode<'ast> {
+        let builder = Builder::new(self.ctx.arena);
+
+        let check = builder.ident(self.ctx.helpers.get(Helper::ClassCallCheck));
+        let check = builder.expression_statement(builder.call(check, builder.list(vec![builder.this(), builder.ident(name)])));
+
+        let fields: Vec<_> = class.body.body.iter().filter_map(|member| match member.item {
+            ClassMember::Literal { is_static: false, key, value } => {
+                Some(builder.expression_statement(builder.assign(member_of(&builder, builder.this(), key), value)))
+            },
+            _ => None,
+        }).collect();
+
+        let constructor = class.body.body.iter().filter_map(|member| match member.item {
+            ClassMember::Method { kind: MethodKind::Constructor, value, .. } => Some(value),
+            _ => None,
+        }).next();
+
+        // Fields go after the `super` call at the top of the constructor
+        // of a derived class, or right after the check otherwise
+        let (params, statements, split, (start, end)) = match constructor {
+            Some(constructor) => {
+                let statements: Vec<_> = constructor.body.body.iter().cloned().collect();
+                let split = match parent {
+                    Some(_) => statements.iter().position(is_super_call).map_or(0, |index| index + 1),
+                    None => 0,
+                };
+
+                (constructor.params, statements, split, (constructor.start, constructor.end))
+            },
+            None => {
+                let statements = match parent {
+                    Some(parent) => {
+                        let apply = builder.member(builder.ident(parent), "apply");
+
+                        vec![builder.expression_statement(builder.call(apply, builder.list(vec![
+                            builder.this(),
+                            builder.ident("arguments"),
+                        ])))]
+                    },
+                    None => Vec::new(),
+                };
+
+                let split = statements.len();
+
+                (NodeList::empty(), statements, split, (0, 0))
+            }
+        };
+
+        let mut body = vec![check];
+
+        body.extend_from_slice(&statements[..split]);
+        body.extend(fields);
+        body.extend_from_slice(&statements[split..]);
+
+        let loc = self.ctx.arena.alloc(Loc::new(start, end, Statement::Function(Function {
+            name: MandatoryName(builder.identifier(name)),
+            generator: false,
+            params,
+            body: builder.block(builder.list(body)),
+        })));
+
+        if let Statement::Function(ref function) = loc.item {
+            self.members.push(Member {
+                target: Target::Function(&function.body),
+                kind: MemberKind::Constructor,
+                class: name,
+                parent,
+            });
+        }
+
+        Node::new(loc)
+    }
+
+    /// What `super` stands for when looking up properties in the member.
+    fn super_object(&self, member: Member<'ast>) -> ExpressionNode<'ast> {
+        let builder = Builder::new(self.ctx.arena);
+
+        match (member.kind, member.parent) {
+            (MemberKind::Static, Some(parent)) => builder.ident(parent),
+            (MemberKind::Static, None) => builder.member(builder.ident("Function"), "prototype"),
+            (_, Some(parent)) => builder.member(builder.ident(parent), "prototype"),
+            (_, None) => builder.member(builder.ident("Object"), "prototype"),
+        }
+    }
+
+    /// Lowered `super.foo` or `super[foo]`, `None` for other expressions.
+    /// Writes to `super` properties are made to `this`, which is where
+    /// assignments to them end up, short of setters of the parent.
+    fn super_property(&self, expression: ExpressionNode<'ast>, path: &Path<'ast>, write: bool) -> Option<ExpressionNode<'ast>> {
+        let builder = Builder::new(self.ctx.arena);
+        let object = |member| if write { builder.this() } else { self.super_object(member) };
+
+        match expression.item {
+            Expression::Member(MemberExpression { object: target, property }) if is_super(target) => {
+                let member = self.member(path)?;
+
+                Some(builder.member(object(member), property.item))
+            },
+            Expression::ComputedMember(ComputedMemberExpression { object: target, property }) if is_super(target) => {
+                let member = self.member(path)?;
+
+                Some(builder.computed_member(object(member), property))
+            },
+            _ => None,
+        }
+    }
+}
+
+#[inline]
+fn is_super(expression: ExpressionNode) -> bool {
+    matches!(expression.item, Expression::Super(_))
+}
+
+/// Whether the current expression is assigned to, updated, or the head
+/// of a `for-in` or `for-of` loop.
+fn is_written(path: &Path) -> bool {
+    let node = match path.node() {
+        Some(NodeRef::Expression(node)) => node,
+        _ => return false,
+    };
+
+    match path.parent() {
+        Some(NodeRef::Expression(parent)) => match parent.item {
+            Expression::Binary(BinaryExpression { operator, left, .. }) if operator.assignment() && operator != OperatorKind::Assign => {
+                ptr::eq::<Loc<Expression>>(&*left, &**node)
+            },
+            Expression::Prefix(PrefixExpression { operator, .. }) |
+            Expression::Postfix(PostfixExpression { operator, .. }) => {
+                operator == OperatorKind::Increment || operator == OperatorKind::Decrement
+            },
+            _ => is_in_pattern(path),
+        },
+        Some(NodeRef::ForInit(_)) => match path.ancestors().nth(1) {
+            Some(NodeRef::Statement(statement)) => matches!(statement.item, Statement::ForIn(_) | Statement::ForOf(_)),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether the statement is a `super(...)` call.
+fn is_super_call(statement: &StatementNode) -> bool {
+    match statement.item {
+        Statement::Expression(expression) => match expression.item {
+            Expression::Call(CallExpression { callee, .. }) => is_super(callee),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Object members of the class are defined on.
+fn owner<'ast>(builder: &Builder<'ast>, class: &'ast str, is_static: bool) -> ExpressionNode<'ast> {
+    match is_static {
+        true  => builder.ident(class),
+        false => builder.member(builder.ident(class), "prototype"),
+    }
+}
+
+/// Whether the key is a name, as opposed to a string or a number.
+fn is_name(raw: &str) -> bool {
+    match raw.chars().next() {
+        Some(ch) => ch == '_' || ch == '$' || ch.is_alphabetic(),
+        None => false,
+    }
+}
+
+/// Key of a member as an expression, names becoming strings.
+fn key_of<'ast>(builder: &Builder<'ast>, key: Node<'ast, PropertyKey<'ast>>) -> ExpressionNode<'ast> {
+    match key.item {
+        PropertyKey::Computed(expression) => expression,
+        PropertyKey::Binary(raw) => builder.literal(Literal::Binary(raw)),
+        PropertyKey::Literal(name) if is_name(name) => builder.string(name),
+        PropertyKey::Literal(raw) => match raw.starts_with('"') || raw.starts_with('\'') {
+            true  => builder.literal(Literal::String(raw)),
+            false => builder.literal(Literal::Number(raw)),
+        },
+    }
+}
+
+/// Member expression for the key on the object, `object.name` for names
+/// and `object[key]` for anything else.
+fn member_of<'ast>(builder: &Builder<'ast>, object: ExpressionNode<'ast>, key: Node<'ast, PropertyKey<'ast>>) -> ExpressionNode<'ast> {
+    match key.item {
+        PropertyKey::Literal(name) if is_name(name) => builder.member(object, name),
+        _ => builder.computed_member(object, key_of(builder, key)),
+    }
+}
+
+/// Whether the keys are the same, computed keys never are.
+fn same_key<'ast>(a: &PropertyKey<'ast>, b: &PropertyKey<'ast>) -> bool {
+    match (*a, *b) {
+        (PropertyKey::Computed(_), _) | (_, PropertyKey::Computed(_)) => false,
+        (a, b) => a == b,
+    }
+}
+
+impl<'ast> Visitor<'ast> for TransformClass<'ast> {
+    fn on_class_statement(&mut self, item: &ClassStatement<'ast>, ptr: &'ast StatementNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        let builder = Builder::new(self.ctx.arena);
+        let name = item.name.0.item;
+        let class = self.lower(item, Some(name));
+
+        self.ctx.swap(ptr, builder.declaration(DeclarationKind::Var, builder.list_from(
+            builder.declarator(builder.pattern(name), Some(class))
+        )));
+
+        VisitAction::Revisit
+    }
+
+    fn on_class_expression(&mut self, item: &ClassExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        let class = self.lower(item, item.name.0.map(|name| name.item));
+
+        self.ctx.swap(ptr, class.item);
+
+        VisitAction::Revisit
+    }
+
+    fn on_call_expression(&mut self, item: &CallExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        let builder = Builder::new(self.ctx.arena);
+
+        // `super(...)` calls the parent constructor on `this`
+        if is_super(item.callee) {
+            return match self.member(path) {
+                Some(Member { kind: MemberKind::Constructor, parent: Some(parent), .. }) => {
+                    let callee = builder.member(builder.ident(parent), "call");
+
+                    self.ctx.swap(ptr, CallExpression {
+                        callee,
+                        arguments: builder.prepend(builder.this(), item.arguments),
+                    });
+
+                    VisitAction::Revisit
+                },
+                _ => VisitAction::Continue,
+            };
+        }
+
+        // `super.foo(...)` calls the method of the parent on `this`
+        match self.super_property(item.callee, path, false) {
+            Some(method) => {
+                self.ctx.swap(ptr, CallExpression {
+                    callee: builder.member(method, "call"),
+                    arguments: builder.prepend(builder.this(), item.arguments),
+                });
+
+                VisitAction::Revisit
+            },
+            None => VisitAction::Continue,
+        }
+    }
+
+    fn on_member_expression(&mut self, _: &MemberExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        match self.super_property(*ptr, path, is_written(path)) {
+            Some(property) => {
+                self.ctx.swap(ptr, property.item);
+
+                VisitAction::Revisit
+            },
+            None => VisitAction::Continue,
+        }
+    }
+
+    fn on_computed_member_expression(&mut self, _: &ComputedMemberExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        match self.super_property(*ptr, path, is_written(path)) {
+            Some(property) => {
+                self.ctx.swap(ptr, property.item);
+
+                VisitAction::Revisit
+            },
+            None => VisitAction::Continue,
+        }
+    }
+
+    fn on_meta_property(&mut self, item: &MetaPropertyExpression<'ast>, ptr: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        if item.meta.item != "new" || item.property.item != "target" {
+            return VisitAction::Continue;
+        }
+
+        let builder = Builder::new(self.ctx.arena);
+
+        // Only the constructor can be called with `new`
+        let target = match self.member(path) {
+            Some(Member { kind: MemberKind::Constructor, class, .. }) => builder.conditional(
+                builder.binary(OperatorKind::Instanceof, builder.this(), builder.ident(class)),
+                builder.member(builder.this(), "constructor"),
+                builder.prefix(OperatorKind::Void, builder.integer(0)),
+            ),
+            Some(_) => builder.prefix(OperatorKind::Void, builder.integer(0)),
+            None => return VisitAction::Continue,
+        };
+
+        self.ctx.swap(ptr, target.item);
+
+        VisitAction::Revisit
+    }
+}
+
+impl<'ast> Pass<'ast> for TransformClass<'ast> {
+    fn name(&self) -> &'static str {
+        "class"
+    }
+
+    /// Arrows within members see the lowered `super` and `new.target`.
+    fn before(&self) -> &[&'static str] {
+        &["arrow"]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use es2015::test::{lower, lower_without_helpers};
+    use Options;
+
+    #[test]
+    fn should_lower_classes_to_constructor_functions() {
+        assert_eq!(
+            lower_without_helpers("class Foo { constructor(a) { this.a = a; } bar() {} static baz() {} get qux() {} set qux(v) {} x = 1; static y = 2; }", Options::default()),
+            "var Foo=function(){function Foo(a){_classCallCheck(this,Foo);this.x=1;this.a=a;}\
+             Foo.prototype.bar=function(){};Foo.baz=function(){};\
+             Object.defineProperty(Foo.prototype,\"qux\",{get:function(){},set:function(v){},configurable:true});\
+             Foo.y=2;return Foo;}();"
+        );
+        assert_eq!(
+            lower_without_helpers("var Foo = class { 0() {} [bar]() {} }; var Baz = class Qux {};", Options::default()),
+            "var Foo=function(){function _class(){_classCallCheck(this,_class);}\
+             _class.prototype[0]=function(){};_class.prototype[bar]=function(){};return _class;}();\
+             var Baz=function(){function Qux(){_classCallCheck(this,Qux);}return Qux;}();"
+        );
+    }
+
+    #[test]
+    fn should_parenthesize_class_expressions_in_statements() {
+        assert_eq!(
+            lower_without_helpers("(class {}).name;", Options::default()),
+            "(function(){function _class(){_classCallCheck(this,_class);}return _class;}().name);"
+        );
+        assert_eq!(
+            lower_without_helpers("(class A {});", Options::default()),
+            "(function(){function A(){_classCallCheck(this,A);}return A;}());"
+        );
+    }
+
+    #[test]
+    fn should_lower_super_and_new_target() {
+        assert_eq!(
+            lower_without_helpers("class Foo extends Bar { constructor() { super(1); this.a = () => [this, new.target]; } bar() { return super.bar() + super['baz']; } static qux() { return super.qux; } }", Options::default()),
+            "var Foo=function(_Bar){_inherits(Foo,_Bar);\
+             function Foo(){var _this=this;_classCallCheck(this,Foo);_Bar.call(this,1);\
+             this.a=function(){return [_this,_this instanceof Foo?_this.constructor:void 0];};}\
+             Foo.prototype.bar=function(){return _Bar.prototype.bar.call(this)+_Bar.prototype['baz'];};\
+             Foo.qux=function(){return _Bar.qux;};return Foo;}(Bar);"
+        );
+    }
+
+    #[test]
+    fn should_write_super_properties_to_this() {
+        assert_eq!(
+            lower_without_helpers("class Foo extends Bar { bar() { super.x = 1; super[k] += super[k]; super.y++; [super.z] = a; } }", Options::default()),
+            "var Foo=function(_Bar){_inherits(Foo,_Bar);\
+             function Foo(){_classCallCheck(this,Foo);_Bar.apply(this,arguments);}\
+             Foo.prototype.bar=function(){this.x=1;this[k]+=_Bar.prototype[k];this.y++;this.z=a[0];};\
+             return Foo;}(Bar);"
+        );
+        assert_eq!(
+            lower_without_helpers("var Foo = class extends foo.Bar { x = 1; };", Options::default()),
+            "var Foo=function(_super){_inherits(_class,_super);\
+             function _class(){_classCallCheck(this,_class);_super.apply(this,arguments);this.x=1;}\
+             return _class;}(foo.Bar);"
+        );
+    }
+
+    #[test]
+    fn should_declare_helpers_after_directives() {
+        let output = lower("'use strict'; class Foo {}", Options::default());
+
+        assert!(output.starts_with("'use strict';function _classCallCheck("), "{}", output);
+    }
+}
@@ -0,0 +1,620 @@
+use ratel::ast::{Node, NodeList, Expression, ExpressionNode, Statement, StatementNode, Declarator};
+use ratel::ast::{Pattern, Property, PropertyKey, Literal, DeclarationKind, OperatorKind};
+use ratel::ast::expression::{BinaryExpression, ObjectExpression, ArrayExpression, SpreadExpression};
+use ratel::ast::statement::{DeclarationStatement, ForInit, ForInStatement, ForOfStatement, CatchClause};
+use ratel::build::Builder;
+use ratel_visitor::{Visitor, VisitAction, Path, Pass};
+
+use TransformerCtxt;
+use super::{prepend, is_in_pattern};
+use helpers::Helper;
+use names::Target;
+
+/// Lowers destructuring in declarations, assignments, `for-in` and `for-of`
+/// heads and `catch` parameters to reads of properties and indexes:
+///
+/// ```js
+/// var {a, b: [c, ...d] = []} = obj;
+/// // becomes
+/// var a = obj.a, _ref = obj.b, _ref2 = _ref === void 0 ? [] : _ref, c = _ref2[0], d = Array.prototype.slice.call(_ref2, 1);
+/// ```
+///
+/// Values read more than once are stored in temporaries, which assignment
+/// expressions declare at the top of the function. Array patterns assume
+/// the value is an array. Object rest elements copy the object without the
+/// listed keys with the `_objectWithoutProperties` helper.
+///
+/// Patterns are lowered when their nodes are left, once other transforms are
+/// done with them, so that the parameter transform can leave the patterns of
+/// parameters in declarations for this one.
+pub struct TransformDestructuring<'ast> {
+    ctx: TransformerCtxt<'ast>,
+}
+
+/// Target of a destructured value. Patterns in declarations are patterns,
+/// patterns in assignments and values of properties in object patterns are
+/// expressions.
+#[derive(Clone, Copy)]
+enum Place<'ast> {
+    /// Temporary holding a value that's read more than once.
+    Temp(&'ast str),
+    Pattern(Node<'ast, Pattern<'ast>>),
+    Expression(ExpressionNode<'ast>),
+}
+
+/// What a place destructures the value into.
+enum Shape<'ast> {
+    /// Identifier or member expression, assigned the value as it is.
+    Simple,
+    Default(Place<'ast>, ExpressionNode<'ast>),
+    Object(Vec<(PropertyKey<'ast>, Place<'ast>)>, Option<Place<'ast>>),
+    Array(Vec<Option<Place<'ast>>>, Option<Place<'ast>>),
+}
+
+/// Whether the pattern is an object or array pattern.
+fn is_pattern(pattern: &Node<Pattern>) -> bool {
+    matches!(pattern.item, Pattern::ObjectPattern { .. } | Pattern::ArrayPattern { .. })
+}
+
+/// Whether the expression is an object or array pattern on the left side
+/// of an assignment.
+fn is_expression_pattern(expression: &ExpressionNode) -> bool {
+    matches!(expression.item, Expression::Object(_) | Expression::Array(_))
+}
+
+/// Assignments of a single destructuring, in the order they are made.
+struct Destructure<'ast> {
+    ctx: TransformerCtxt<'ast>,
+    builder: Builder<'ast>,
+
+    /// Function to declare the temporaries in, `None` if they are declared
+    /// along with the targets.
+    temps: Option<Target<'ast>>,
+
+    /// Names assigned by the pattern, which can't be read in place of
+    /// a temporary since they could change while the pattern is assigned.
+    assigned: Vec<&'ast str>,
+
+    steps: Vec<(Place<'ast>, ExpressionNode<'ast>)>,
+}
+
+impl<'ast> Destructure<'ast> {
+    fn new(ctx: TransformerCtxt<'ast>, temps: Option<Target<'ast>>, pattern: Place<'ast>) -> Self {
+        let mut destructure = Destructure {
+            ctx,
+            builder: Builder::new(ctx.arena),
+            temps,
+            assigned: Vec::new(),
+            steps: Vec::new(),
+        };
+
+        destructure.collect(pattern);
+        destructure
+    }
+
+    fn collect(&mut self, place: Place<'ast>) {
+        match self.shape(place) {
+            Shape::Simple => match place {
+                Place::Pattern(node) => if let Pattern::Identifier(name) = node.item {
+                    self.assigned.push(name);
+                },
+                Place::Expression(node) => if let Expression::Identifier(name) = node.item {
+                    self.assigned.push(name);
+                },
+                Place::Temp(_) => {},
+            },
+            Shape::Default(place, _) => self.collect(place),
+            Shape::Object(properties, rest) => {
+                for (_, place) in properties {
+                    self.collect(place);
+                }
+
+                if let Some(rest) = rest {
+                    self.collect(rest);
+                }
+            },
+            Shape::Array(elements, rest) => {
+                for place in elements.into_iter().flatten() {
+                    self.collect(place);
+                }
+
+                if let Some(rest) = rest {
+                    self.collect(rest);
+                }
+            },
+        }
+    }
+
+    fn shape(&self, place: Place<'ast>) -> Shape<'ast> {
+        match place {
+            Place::Pattern(node) => match node.item {
+                Pattern::AssignmentPattern { left, right } => Shape::Default(Place::Pattern(left), right),
+                Pattern::ObjectPattern { properties } => self.object(properties),
+                Pattern::ArrayPattern { elements } => {
+                    let mut places = Vec::new();
+                    let mut rest = None;
+
+                    for element in elements.iter() {
+                        match element.item {
+                            Pattern::Void => places.push(None),
+                            Pattern::RestElement { argument } => {
+                                rest = Some(Place::Pattern(self.builder.pattern(argument.item)));
+                            },
+                            _ => places.push(Some(Place::Pattern(*element))),
+                        }
+                    }
+
+                    Shape::Array(places, rest)
+                },
+                _ => Shape::Simple,
+            },
+            Place::Expression(node) => match node.item {
+                Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, left, right }) => {
+                    Shape::Default(Place::Expression(left), right)
+                },
+                Expression::Object(ObjectExpression { body }) => self.object(body),
+                Expression::Array(ArrayExpression { body }) => {
+                    let mut places = Vec::new();
+                    let mut rest = None;
+
+                    for element in body.iter() {
+                        match element.item {
+                            Expression::Void => places.push(None),
+                            Expression::Spread(SpreadExpression { argument }) => rest = Some(Place::Expression(argument)),
+                            _ => places.push(Some(Place::Expression(*element))),
+                        }
+                    }
+
+                    Shape::Array(places, rest)
+                },
+                _ => Shape::Simple,
+            },
+            Place::Temp(_) => Shape::Simple,
+        }
+    }
+
+    fn object(&self, properties: NodeList<'ast, Property<'ast>>) -> Shape<'ast> {
+        let mut places = Vec::new();
+        let mut rest = None;
+
+        for property in properties.iter() {
+            match property.item {
+                Property::Shorthand(name) => {
+                    places.push((PropertyKey::Literal(name), Place::Expression(self.builder.ident(name))));
+                },
+                Property::Literal { key, value } => places.push((key.item, Place::Expression(value))),
+                Property::Spread { argument } => rest = Some(Place::Expression(argument)),
+                Property::Method { .. } => {},
+            }
+        }
+
+        Shape::Object(places, rest)
+    }
+
+    /// Name of a temporary holding the value.
+    fn temp(&mut self, value: ExpressionNode<'ast>) -> &'ast str {
+        let name = match self.temps {
+            Some(target) => self.ctx.names.declare(target, "ref"),
+            None         => self.ctx.names.generate("ref"),
+        };
+
+        self.steps.push((Place::Temp(name), value));
+
+        name
+    }
+
+    /// Name that can be read for the value more than once.
+    fn reference(&mut self, value: ExpressionNode<'ast>) -> &'ast str {
+        match value.item {
+            Expression::Identifier(name) if !self.assigned.contains(&name) => name,
+            _ => self.temp(value),
+        }
+    }
+
+    fn assign(&mut self, place: Place<'ast>, value: ExpressionNode<'ast>) {
+        let builder = self.builder;
+
+        match self.shape(place) {
+            Shape::Simple => self.steps.push((place, value)),
+            Shape::Default(place, default) => {
+                let temp = self.temp(value);
+                let void = builder.prefix(OperatorKind::Void, builder.integer(0));
+                let value = builder.conditional(
+                    builder.binary(OperatorKind::StrictEquality, builder.ident(temp), void),
+                    default,
+                    builder.ident(temp),
+                );
+
+                self.assign(place, value);
+            },
+            Shape::Object(properties, rest) => {
+                // Empty patterns still evaluate the value
+                if properties.is_empty() && rest.is_none() {
+                    self.temp(value);
+
+                    return;
+                }
+
+                let object = self.reference(value);
+                let mut keys = Vec::new();
+
+                for (key, place) in properties {
+                    let value = match key {
+                        // Quoted and numeric keys are kept as they are in the source
+                        PropertyKey::Literal(raw) if raw.starts_with(['"', '\'']) => {
+                            keys.push(builder.literal(Literal::String(raw)));
+
+                            builder.computed_member(builder.ident(object), builder.literal(Literal::String(raw)))
+                        },
+                        PropertyKey::Literal(raw) if raw.starts_with(|ch: char| ch == '.' || ch.is_ascii_digit()) => {
+                            keys.push(builder.call(builder.ident("String"), builder.list_from(builder.number(raw))));
+
+                            builder.computed_member(builder.ident(object), builder.number(raw))
+                        },
+                        PropertyKey::Literal(name) => {
+                            keys.push(builder.string(name));
+
+                            builder.member(builder.ident(object), name)
+                        },
+                        PropertyKey::Binary(number) => {
+                            keys.push(builder.call(builder.ident("String"), builder.list_from(builder.number(number))));
+
+                            builder.computed_member(builder.ident(object), builder.number(number))
+                        },
+                        PropertyKey::Computed(key) => {
+                            // Keys excluded from the rest are evaluated once
+                            let key = match rest {
+                                Some(_) => {
+                                    let name = self.temp(key);
+
+                                    keys.push(builder.ident(name));
+                                    builder.ident(name)
+                                },
+                                None => key,
+                            };
+
+                            builder.computed_member(builder.ident(object), key)
+                        },
+                    };
+
+                    self.assign(place, value);
+                }
+
+                if let Some(rest) = rest {
+                    let helper = self.ctx.helpers.get(Helper::ObjectWithoutProperties);
+                    let arguments = builder.list(vec![builder.ident(object), builder.array(builder.list(keys))]);
+
+                    self.assign(rest, builder.call(builder.ident(helper), arguments));
+                }
+            },
+            Shape::Array(elements, rest) => {
+                // Empty patterns and patterns of holes still evaluate the value
+                if elements.iter().all(Option::is_none) && rest.is_none() {
+                    self.temp(value);
+
+                    return;
+                }
+
+                let array = self.reference(value);
+                let length = elements.len() as u64;
+
+                for (index, place) in elements.into_iter().enumerate() {
+                    if let Some(place) = place {
+                        self.assign(place, builder.computed_member(builder.ident(array), builder.integer(index as u64)));
+                    }
+                }
+
+                if let Some(rest) = rest {
+                    // Array.prototype.slice.call(array, length)
+                    let slice = builder.member(builder.member(builder.ident("Array"), "prototype"), "slice");
+                    let arguments = builder.list(vec![builder.ident(array), builder.integer(length)]);
+
+                    self.assign(rest, builder.call(builder.member(slice, "call"), arguments));
+                }
+            },
+        }
+    }
+
+    /// Steps as declarators of a `var`, `let` or `const` declaration.
+    fn declarators(self) -> Vec<Node<'ast, Declarator<'ast>>> {
+        let builder = self.builder;
+
+        self.steps.into_iter().map(|(place, value)| {
+            let id = match place {
+                Place::Temp(name) => builder.pattern(name),
+                Place::Pattern(pattern) => pattern,
+                Place::Expression(expression) => match expression.item {
+                    Expression::Identifier(name) => builder.pattern(name),
+                    _ => unreachable!("Declarations only assign to identifiers"),
+                },
+            };
+
+            builder.declarator(id, Some(value))
+        }).collect()
+    }
+
+    /// Steps as assignment expressions.
+    fn assignments(self) -> Vec<ExpressionNode<'ast>> {
+        let builder = self.builder;
+
+        self.steps.into_iter().map(|(place, value)| {
+            let target = match place {
+                Place::Temp(name) => builder.ident(name),
+                Place::Expression(expression) => expression,
+                Place::Pattern(pattern) => match pattern.item {
+                    Pattern::Identifier(name) => builder.ident(name),
+                    _ => unreachable!("Patterns are destructured down to identifiers"),
+                },
+            };
+
+            builder.assign(target, value)
+        }).collect()
+    }
+}
+
+impl<'ast> TransformDestructuring<'ast> {
+    pub fn new(ctx: TransformerCtxt<'ast>) -> TransformDestructuring<'ast> {
+        TransformDestructuring {
+            ctx,
+        }
+    }
+
+    /// Declarators with the patterns destructured, `None` if there are no
+    /// patterns with values to destructure.
+    fn declarators(&self, declarators: NodeList<'ast, Declarator<'ast>>) -> Option<NodeList<'ast, Declarator<'ast>>> {
+        let has_patterns = declarators.iter().any(|declarator| {
+            is_pattern(&declarator.id) && declarator.init.is_some()
+        });
+
+        if !has_patterns {
+            return None;
+        }
+
+        let mut lowered = Vec::new();
+
+        for declarator in declarators.iter() {
+            match declarator.init {
+                Some(init) if is_pattern(&declarator.id) => {
+                    let mut destructure = Destructure::new(self.ctx, None, Place::Pattern(declarator.id));
+
+                    destructure.assign(Place::Pattern(declarator.id), init);
+                    lowered.extend(destructure.declarators());
+                },
+                _ => lowered.push(*declarator),
+            }
+        }
+
+        Some(Builder::new(self.ctx.arena).list(lowered))
+    }
+
+    /// Head of a `for-in` or `for-of` loop assigning to a temporary instead
+    /// of a pattern, and the body destructuring the temporary.
+    fn head(&self, left: Node<'ast, ForInit<'ast>>, body: StatementNode<'ast>, path: &Path<'ast>) -> Option<(Node<'ast, ForInit<'ast>>, StatementNode<'ast>)> {
+        let builder = Builder::new(self.ctx.arena);
+
+        let (left, statement) = match left.item {
+            ForInit::Declaration(DeclarationStatement { kind, declarators }) => {
+                let declarator = declarators.first_element()?;
+
+                if !is_pattern(&declarator.id) {
+                    return None;
+                }
+
+                let name = self.ctx.names.generate("ref");
+                let mut destructure = Destructure::new(self.ctx, None, Place::Pattern(declarator.id));
+
+                destructure.assign(Place::Pattern(declarator.id), builder.ident(name));
+
+                let declarators = builder.list(destructure.declarators());
+                let left = builder.declaration(kind, builder.list_from(builder.declarator(builder.pattern(name), None)));
+
+                (builder.for_init(left), builder.declaration_statement(kind, declarators))
+            },
+            ForInit::Expression(expression) => {
+                if !is_expression_pattern(&expression) {
+                    return None;
+                }
+
+                let target = Target::of(path);
+                let name = self.ctx.names.declare(target, "ref");
+                let mut destructure = Destructure::new(self.ctx, Some(target), Place::Expression(expression));
+
+                destructure.assign(Place::Expression(expression), builder.ident(name));
+
+                let assignments = builder.sequence(builder.list(destructure.assignments()));
+
+                (builder.for_init(builder.ident(name)), builder.expression_statement(assignments))
+            },
+        };
+
+        Some((left, prepend(&builder, statement, body)))
+    }
+}
+
+impl<'ast> Visitor<'ast> for TransformDestructuring<'ast> {
+    fn on_declaration_statement_exit(&mut self, _: &DeclarationStatement, node: &'ast StatementNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        if let Statement::Declaration(DeclarationStatement { kind, declarators }) = node.item {
+            if let Some(declarators) = self.declarators(declarators) {
+                self.ctx.swap(node, DeclarationStatement { kind, declarators });
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_for_init_exit(&mut self, _: &ForInit<'ast>, node: &'ast Node<'ast, ForInit<'ast>>, _: &Path<'ast>) -> VisitAction {
+        if let ForInit::Declaration(DeclarationStatement { kind, declarators }) = node.item {
+            if let Some(declarators) = self.declarators(declarators) {
+                self.ctx.swap(node, ForInit::Declaration(DeclarationStatement { kind, declarators }));
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_for_in_statement_exit(&mut self, _: &ForInStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        if let Statement::ForIn(ForInStatement { left, right, body }) = node.item {
+            if let Some((left, body)) = self.head(left, body, path) {
+                self.ctx.swap(node, ForInStatement { left, right, body });
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_for_of_statement_exit(&mut self, _: &ForOfStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        if let Statement::ForOf(ForOfStatement { left, right, body }) = node.item {
+            if let Some((left, body)) = self.head(left, body, path) {
+                self.ctx.swap(node, ForOfStatement { left, right, body });
+            }
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_catch_clause_exit(&mut self, item: &CatchClause<'ast>, node: &'ast Node<'ast, CatchClause<'ast>>, _: &Path<'ast>) -> VisitAction {
+        if !is_pattern(&item.param) {
+            return VisitAction::Continue;
+        }
+
+        let builder = Builder::new(self.ctx.arena);
+        let name = self.ctx.names.generate("ref");
+        let mut destructure = Destructure::new(self.ctx, None, Place::Pattern(item.param));
+
+        destructure.assign(Place::Pattern(item.param), builder.ident(name));
+
+        let statement = builder.declaration_statement(DeclarationKind::Var, builder.list(destructure.declarators()));
+
+        self.ctx.swap(node, CatchClause {
+            param: builder.pattern(name),
+            body: builder.block(builder.prepend(statement, item.body.body)),
+        });
+
+        VisitAction::Continue
+    }
+
+    fn on_binary_expression_exit(&mut self, _: &BinaryExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        let (left, right) = match node.item {
+            Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, left, right }) => (left, right),
+            _ => return VisitAction::Continue,
+        };
+
+        if !is_expression_pattern(&left) || is_in_pattern(path) {
+            return VisitAction::Continue;
+        }
+
+        let builder = Builder::new(self.ctx.arena);
+        let mut destructure = Destructure::new(self.ctx, Some(Target::of(path)), Place::Expression(left));
+        let value = destructure.reference(right);
+
+        destructure.assign(Place::Expression(left), builder.ident(value));
+
+        let mut expressions = destructure.assignments();
+
+        // The value of the assignment is the value destructured
+        if !path.is_statement_position() {
+            expressions.push(builder.ident(value));
+        }
+
+        let expression = match expressions.len() {
+            1 => expressions[0],
+            _ => builder.sequence(builder.list(expressions)),
+        };
+
+        self.ctx.swap(node, expression.item);
+
+        VisitAction::Continue
+    }
+}
+
+impl<'ast> Pass<'ast> for TransformDestructuring<'ast> {
+    fn name(&self) -> &'static str {
+        "destructuring"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use es2015::test::lower;
+    use Options;
+
+    #[test]
+    fn should_lower_destructuring_declarations() {
+        assert_eq!(
+            lower("var {a, b: [c, , d] = []} = obj, e = 1;", Options::default()),
+            "var a=obj.a,_ref=obj.b,_ref2=_ref===void 0?[]:_ref,c=_ref2[0],d=_ref2[2],e=1;"
+        );
+        assert_eq!(
+            lower("const [a, [b = 1] = []] = f();", Options::default()),
+            "var _ref=f(),a=_ref[0],_ref2=_ref[1],_ref3=_ref2===void 0?[]:_ref2,_ref4=_ref3[0],b=_ref4===void 0?1:_ref4;"
+        );
+        assert_eq!(lower("var {a: obj, b} = obj;", Options::default()), "var _ref=obj,obj=_ref.a,b=_ref.b;");
+    }
+
+    #[test]
+    fn should_lower_destructuring_assignments() {
+        assert_eq!(lower("[a, b] = [b, a];", Options::default()), "var _ref;_ref=[b,a],a=_ref[0],b=_ref[1];");
+        assert_eq!(
+            lower("function f() { return {a, b: o.b} = g(); }", Options::default()),
+            "function f(){var _ref;return _ref=g(),a=_ref.a,o.b=_ref.b,_ref;}"
+        );
+        assert_eq!(
+            lower("var r = ([x, y] = arr);", Options::default()),
+            "var r=(x=arr[0],y=arr[1],arr);"
+        );
+    }
+
+    #[test]
+    fn should_lower_destructuring_in_heads_and_params() {
+        assert_eq!(
+            lower("for (var [k, v] of entries) f(k, v);", Options::default()),
+            "{var _iteratorNormalCompletion=true,_didIteratorError=false,_iteratorError=void 0;\
+             try{for(var _iterator=entries[Symbol.iterator](),_step;!(_iteratorNormalCompletion=(_step=_iterator.next()).done);\
+             _iteratorNormalCompletion=true){var _ref=_step.value;var k=_ref[0],v=_ref[1];f(k,v);}}\
+             catch(_err){_didIteratorError=true;_iteratorError=_err;}\
+             finally{try{if(!_iteratorNormalCompletion&&_iterator.return!=null)_iterator.return();}\
+             finally{if(_didIteratorError)throw _iteratorError;}}}"
+        );
+        assert_eq!(
+            lower("try {} catch ({message}) { log(message); }", Options::default()),
+            "try{}catch(_ref){var message=_ref.message;log(message);}"
+        );
+        assert_eq!(
+            lower("function f({a}, [b]) {}", Options::default()),
+            "function f(_ref,_ref2){var a=_ref.a;var b=_ref2[0];}"
+        );
+    }
+
+    #[test]
+    fn should_evaluate_values_of_empty_array_patterns() {
+        assert_eq!(lower("const [] = arr;", Options::default()), "var _ref=arr;");
+        assert_eq!(lower("const [,] = arr;", Options::default()), "var _ref=arr;");
+        assert_eq!(lower("function f([]) {}", Options::default()), "function f(_ref){var _ref2=_ref;}");
+    }
+
+    #[test]
+    fn should_lower_array_rest() {
+        assert_eq!(lower("[a, ...b] = c;", Options::default()), "a=c[0],b=Array.prototype.slice.call(c,1);");
+    }
+
+    #[test]
+    fn should_lower_object_rest() {
+        assert_eq!(
+            lower("({a, ...b} = o);", Options::default()),
+            "function _objectWithoutProperties(source,excluded){var target={};for(var key in source){\
+             if(excluded.indexOf(key)>=0)continue;if(!Object.prototype.hasOwnProperty.call(source,key))continue;\
+             target[key]=source[key];}return target;}\
+             a=o.a,b=_objectWithoutProperties(o,[\"a\"]);"
+        );
+    }
+
+    #[test]
+    fn should_lower_quoted_and_numeric_keys() {
+        assert_eq!(
+            lower("var {'a-b': a, \"c\": c, 1: d} = o;", Options::default()),
+            "var a=o['a-b'],c=o[\"c\"],d=o[1];"
+        );
+        assert!(lower("var {'a-b': a, 1: d, ...f} = o;", Options::default())
+            .ends_with("var a=o['a-b'],d=o[1],f=_objectWithoutProperties(o,['a-b',String(1)]);"));
+    }
+}
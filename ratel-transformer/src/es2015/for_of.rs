@@ -0,0 +1,216 @@
+use ratel::ast::{ExpressionNode, Statement, StatementNode, DeclarationKind, OperatorKind};
+use ratel::ast::statement::{DeclarationStatement, ForInit, ForOfStatement, LabeledStatement};
+use ratel::build::Builder;
+use ratel_visitor::{Visitor, VisitAction, Path, Pass};
+
+use TransformerCtxt;
+use super::{labeled, is_labeled, prepend};
+
+/// Lowers `for-of` loops to loops over the iterator of the value:
+///
+/// ```js
+/// for (var x of xs) f(x);
+/// // becomes
+/// var _iteratorNormalCompletion = true, _didIteratorError = false, _iteratorError = void 0;
+/// try {
+///     for (var _iterator = xs[Symbol.iterator](), _step; !(_iteratorNormalCompletion = (_step = _iterator.next()).done); _iteratorNormalCompletion = true) {
+///         var x = _step.value;
+///         f(x);
+///     }
+/// } catch (_err) {
+///     _didIteratorError = true;
+///     _iteratorError = _err;
+/// } finally {
+///     try {
+///         if (!_iteratorNormalCompletion && _iterator.return != null) _iterator.return();
+///     } finally {
+///         if (_didIteratorError) throw _iteratorError;
+///     }
+/// }
+/// ```
+///
+/// Leaving the loop early with `break`, `return` or an exception closes the
+/// iterator with its `return` method. With `Options::loose_for_of` the value
+/// is assumed to be an array and is indexed instead:
+///
+/// ```js
+/// for (var _i = 0, _arr = xs; _i < _arr.length; _i++) {
+///     var x = _arr[_i];
+///     f(x);
+/// }
+/// ```
+///
+/// Loops are lowered once the block scope and destructuring transforms are
+/// done with them, so the head declares a single `var`.
+pub struct TransformForOf<'ast> {
+    ctx: TransformerCtxt<'ast>,
+}
+
+impl<'ast> TransformForOf<'ast> {
+    pub fn new(ctx: TransformerCtxt<'ast>) -> TransformForOf<'ast> {
+        TransformForOf {
+            ctx,
+        }
+    }
+
+    /// Loop with the labels around it, `None` if it isn't a `for-of` loop.
+    fn lower(&self, node: &'ast StatementNode<'ast>, labels: &[&'ast str]) -> Option<StatementNode<'ast>> {
+        let ForOfStatement { left, right, body } = match node.item {
+            Statement::ForOf(for_of) => for_of,
+            _ => return None,
+        };
+
+        let builder = Builder::new(self.ctx.arena);
+
+        // Statement assigning the value of the current iteration to the head
+        let assign = |value: ExpressionNode<'ast>| match left.item {
+            ForInit::Declaration(DeclarationStatement { kind, declarators }) => {
+                let declarators = declarators.iter().map(|declarator| builder.declarator(declarator.id, Some(value)));
+
+                builder.declaration_statement(kind, builder.list(declarators.collect::<Vec<_>>()))
+            },
+            ForInit::Expression(expression) => builder.expression_statement(builder.assign(expression, value)),
+        };
+
+        let label = |statement| labels.iter().rev().fold(statement, |body, &label| builder.node(LabeledStatement { label, body }));
+
+        if self.ctx.options.loose_for_of {
+            let index = self.ctx.names.generate("i");
+            let array = self.ctx.names.generate("arr");
+
+            let init = builder.declaration(DeclarationKind::Var, builder.list(vec![
+                builder.declarator(builder.pattern(index), Some(builder.integer(0))),
+                builder.declarator(builder.pattern(array), Some(right)),
+            ]));
+            let test = builder.binary(OperatorKind::Lesser, builder.ident(index), builder.member(builder.ident(array), "length"));
+            let update = builder.postfix(OperatorKind::Increment, builder.ident(index));
+            let body = prepend(&builder, assign(builder.computed_member(builder.ident(array), builder.ident(index))), body);
+
+            return Some(label(builder.for_statement(Some(builder.for_init(init)), Some(test), Some(update), body)));
+        }
+
+        let normal = self.ctx.names.generate("iteratorNormalCompletion");
+        let errored = self.ctx.names.generate("didIteratorError");
+        let error = self.ctx.names.generate("iteratorError");
+        let iterator = self.ctx.names.generate("iterator");
+        let step = self.ctx.names.generate("step");
+        let err = self.ctx.names.generate("err");
+
+        let set = |name, value| builder.expression_statement(builder.assign(builder.ident(name), value));
+
+        let state = builder.declaration_statement(DeclarationKind::Var, builder.list(vec![
+            builder.declarator(builder.pattern(normal), Some(builder.boolean(true))),
+            builder.declarator(builder.pattern(errored), Some(builder.boolean(false))),
+            builder.declarator(builder.pattern(error), Some(builder.prefix(OperatorKind::Void, builder.integer(0)))),
+        ]));
+
+        // var _iterator = xs[Symbol.iterator](), _step
+        let method = builder.computed_member(right, builder.member(builder.ident("Symbol"), "iterator"));
+        let init = builder.declaration(DeclarationKind::Var, builder.list(vec![
+            builder.declarator(builder.pattern(iterator), Some(builder.call(method, builder.list(Vec::new())))),
+            builder.declarator(builder.pattern(step), None),
+        ]));
+
+        // !(_iteratorNormalCompletion = (_step = _iterator.next()).done)
+        let next = builder.call(builder.member(builder.ident(iterator), "next"), builder.list(Vec::new()));
+        let done = builder.member(builder.assign(builder.ident(step), next), "done");
+        let test = builder.prefix(OperatorKind::LogicalNot, builder.assign(builder.ident(normal), done));
+        let update = builder.assign(builder.ident(normal), builder.boolean(true));
+        let body = prepend(&builder, assign(builder.member(builder.ident(step), "value")), body);
+
+        let for_statement = label(builder.for_statement(Some(builder.for_init(init)), Some(test), Some(update), body));
+
+        let handler = builder.catch_clause(builder.pattern(err), builder.list(vec![
+            set(errored, builder.boolean(true)),
+            set(error, builder.ident(err)),
+        ]));
+
+        // if (!_iteratorNormalCompletion && _iterator.return != null) _iterator.return();
+        let method = || builder.member(builder.ident(iterator), "return");
+        let close = builder.if_statement(
+            builder.binary(
+                OperatorKind::LogicalAnd,
+                builder.prefix(OperatorKind::LogicalNot, builder.ident(normal)),
+                builder.binary(OperatorKind::Inequality, method(), builder.null()),
+            ),
+            builder.expression_statement(builder.call(method(), builder.list(Vec::new()))),
+            None,
+        );
+        let rethrow = builder.if_statement(builder.ident(errored), builder.throw_statement(builder.ident(error)), None);
+        let finalizer = builder.try_statement(builder.list_from(close), None, Some(builder.list_from(rethrow)));
+
+        let try_statement = builder.try_statement(builder.list_from(for_statement), Some(handler), Some(builder.list_from(finalizer)));
+
+        Some(builder.block_statement(builder.list(vec![state, try_statement])))
+    }
+}
+
+impl<'ast> Visitor<'ast> for TransformForOf<'ast> {
+    fn on_for_of_statement_exit(&mut self, _: &ForOfStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        // Labeled loops are lowered along with their labels
+        if is_labeled(path) {
+            return VisitAction::Continue;
+        }
+
+        if let Some(statement) = self.lower(node, &[]) {
+            self.ctx.swap(node, statement.item);
+        }
+
+        VisitAction::Continue
+    }
+
+    fn on_labeled_statement_exit(&mut self, _: &LabeledStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        if is_labeled(path) {
+            return VisitAction::Continue;
+        }
+
+        let (labels, ptr) = labeled(node);
+
+        if let Some(statement) = self.lower(ptr, &labels) {
+            self.ctx.swap(node, statement.item);
+        }
+
+        VisitAction::Continue
+    }
+}
+
+impl<'ast> Pass<'ast> for TransformForOf<'ast> {
+    fn name(&self) -> &'static str {
+        "for-of"
+    }
+
+    fn after(&self) -> &[&'static str] {
+        &["block-scope", "destructuring"]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use es2015::test::lower;
+    use Options;
+
+    #[test]
+    fn should_lower_for_of_to_iterators() {
+        assert_eq!(
+            lower("for (let x of xs) f(x);", Options::default()),
+            "{var _iteratorNormalCompletion=true,_didIteratorError=false,_iteratorError=void 0;\
+             try{for(var _iterator=xs[Symbol.iterator](),_step;!(_iteratorNormalCompletion=(_step=_iterator.next()).done);\
+             _iteratorNormalCompletion=true){var x=_step.value;f(x);}}\
+             catch(_err){_didIteratorError=true;_iteratorError=_err;}\
+             finally{try{if(!_iteratorNormalCompletion&&_iterator.return!=null)_iterator.return();}\
+             finally{if(_didIteratorError)throw _iteratorError;}}}"
+        );
+    }
+
+    #[test]
+    fn should_lower_for_of_to_indexed_loops() {
+        assert_eq!(
+            lower("for (x of xs) { f(x); }", Options { loose_for_of: true, ..Options::default() }),
+            "for(var _i=0,_arr=xs;_i<_arr.length;_i++){x=_arr[_i];f(x);}"
+        );
+        assert_eq!(
+            lower("outer: for (const [a, b] of xs) { for (;;) continue outer; }", Options { loose_for_of: true, ..Options::default() }),
+            "outer:for(var _i=0,_arr=xs;_i<_arr.length;_i++){var _ref=_arr[_i];var a=_ref[0],b=_ref[1];for(;;)continue outer;}"
+        );
+    }
+}
@@ -1,5 +1,157 @@
 mod arrow;
+mod class;
+mod block_scope;
+mod template;
+mod parameters;
+mod destructuring;
+mod for_of;
 
+use std::ptr;
+use std::rc::Rc;
+
+use ratel::ast::{Loc, Statement, StatementNode, Expression, OperatorKind};
+use ratel::ast::expression::BinaryExpression;
+use ratel::ast::statement::BlockStatement;
+use ratel::build::Builder;
+use ratel_semantic::Semantic;
+use ratel_visitor::{Composite, Pass, Path, NodeRef};
+
+use TransformerCtxt;
+use error::Error;
 use self::arrow::TransformArrow;
+use self::class::TransformClass;
+use self::block_scope::TransformBlockScope;
+use self::template::TransformTemplate;
+use self::parameters::TransformParameters;
+use self::destructuring::TransformDestructuring;
+use self::for_of::TransformForOf;
+
+
+pub type PresetES2015<'ast> = Composite<'ast, 'ast>;
+
+/// All ES2015 transforms, run in a single traversal. The semantic model
+/// must be of the module before it's transformed.
+pub fn preset<'ast>(ctx: TransformerCtxt<'ast>, semantic: Semantic<'ast>) -> PresetES2015<'ast> {
+    let semantic = Rc::new(semantic);
+    let passes: Vec<Box<dyn Pass<'ast> + 'ast>> = vec![
+        Box::new(TransformArrow::new(ctx, semantic.clone())),
+        Box::new(TransformClass::new(ctx)),
+        Box::new(TransformBlockScope::new(ctx, semantic)),
+        Box::new(TransformTemplate::new(ctx)),
+        Box::new(TransformParameters::new(ctx)),
+        Box::new(TransformDestructuring::new(ctx)),
+        Box::new(TransformForOf::new(ctx)),
+    ];
+
+    Composite::new(passes).expect("ES2015 passes are ordered")
+}
+
+/// Errors in the module that can be found before transforming it.
+pub fn check(semantic: &Semantic) -> Vec<Error> {
+    let mut errors = block_scope::check(semantic);
+
+    errors.extend(arrow::check(semantic));
+
+    errors
+}
+
+/// Labels of the labeled statements around a loop, from the outermost one,
+/// and the node of the loop.
+fn labeled<'ast>(outer: &'ast StatementNode<'ast>) -> (Vec<&'ast str>, &'ast StatementNode<'ast>) {
+    let mut labels = Vec::new();
+    let mut node = outer;
+
+    while let Statement::Labeled(ref labeled) = node.item {
+        labels.push(labeled.label);
+        node = &labeled.body;
+    }
+
+    (labels, node)
+}
+
+/// Whether the current node is the body of a labeled statement.
+fn is_labeled(path: &Path) -> bool {
+    match path.parent() {
+        Some(NodeRef::Statement(node)) => matches!(node.item, Statement::Labeled(_)),
+        _ => false,
+    }
+}
+
+/// Body of a loop or a block with the statement in front of it.
+fn prepend<'ast>(builder: &Builder<'ast>, statement: StatementNode<'ast>, body: StatementNode<'ast>) -> StatementNode<'ast> {
+    match body.item {
+        Statement::Block(BlockStatement { body }) => builder.block_statement(builder.prepend(statement, body)),
+        _ => builder.block_statement(builder.list(vec![statement, body])),
+    }
+}
+
+/// Whether the current node is part of a pattern, such as the default value
+/// of an element or a property, rather than an expression on its own.
+fn is_in_pattern(path: &Path) -> bool {
+    let mut child = match path.node() {
+        Some(NodeRef::Expression(node)) => node,
+        _ => return false,
+    };
+
+    for ancestor in path.ancestors() {
+        match ancestor {
+            NodeRef::Pattern(_) => return true,
+            NodeRef::Property(_) => {},
+            NodeRef::Expression(node) => match node.item {
+                Expression::Array(_) | Expression::Object(_) | Expression::Spread(_) => {},
+                Expression::Binary(BinaryExpression { operator: OperatorKind::Assign, left, .. }) => {
+                    return ptr::eq::<Loc<Expression>>(&*left, &**child);
+                },
+                _ => return false,
+            },
+            _ => return false,
+        }
+
+        if let NodeRef::Expression(node) = ancestor {
+            child = node;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use ratel::{Module, parse_in};
+    use ratel::Arena;
+    use ratel::ast::Statement;
+    use ratel::build::Builder;
+    use ratel_codegen::codegen;
+    use {transform_with, Options};
+
+    fn transform<'ast>(arena: &'ast Arena, source: &str, options: Options) -> Module<'ast> {
+        let mut module = parse_in(arena, source).unwrap();
+
+        transform_with(&mut module, options).unwrap();
+
+        module
+    }
+
+    /// Lowered source.
+    pub fn lower(source: &str, options: Options) -> String {
+        let arena = Arena::new();
+        let module = transform(&arena, source, options);
+
+        codegen(&module, true)
+    }
+
+    /// Lowered source, without the helpers at the top.
+    pub fn lower_without_helpers(source: &str, options: Options) -> String {
+        let arena = Arena::new();
+        let mut module = transform(&arena, source, options);
+
+        let body = module.body().iter().filter(|statement| match statement.item {
+            Statement::Function(ref function) => !function.name.0.item.starts_with('_'),
+            _ => true,
+        }).cloned();
+
+        module.set_body(Builder::new(&arena).list(body));
 
-pub type PresetES2015<'ast> = TransformArrow<'ast>;
+        codegen(&module, true)
+    }
+}
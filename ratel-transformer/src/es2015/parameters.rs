@@ -0,0 +1,259 @@
+use std::hint;
+
+use ratel::ast::{Node, Block, Expression, ExpressionNode, StatementNode, Function, Name, EmptyName};
+use ratel::ast::{Pattern, DeclarationKind, OperatorKind};
+use ratel::ast::expression::{ArrowExpression, FunctionExpression};
+use ratel::ast::statement::FunctionStatement;
+use ratel::build::Builder;
+use ratel_visitor::{Visitor, VisitAction, Path, Pass};
+
+use TransformerCtxt;
+
+/// Lowers default, rest and destructured parameters to simple parameters
+/// and declarations at the top of the function body.
+///
+/// Parameters from the first one with a default value or a rest element
+/// on are read from `arguments`, so that `Function.length` is preserved:
+///
+/// ```js
+/// function f(a, b = 1, ...c) {}
+/// // becomes
+/// function f(a) {
+///     var b = arguments.length > 1 && arguments[1] !== void 0 ? arguments[1] : 1;
+///     var c = Array.prototype.slice.call(arguments, 2);
+/// }
+/// ```
+///
+/// With `Options::loose_parameters` all parameters other than the rest element are kept, with
+/// defaults assigned as `if (b === void 0) b = 1;`. Destructured parameters
+/// are replaced by a `_ref` parameter, their patterns are left for the
+/// destructuring transform.
+///
+/// Arrows are handled once they have been lowered to functions, since the
+/// `arguments` of an arrow belong to the function around it.
+pub struct TransformParameters<'ast> {
+    ctx: TransformerCtxt<'ast>,
+}
+
+impl<'ast> TransformParameters<'ast> {
+    pub fn new(ctx: TransformerCtxt<'ast>) -> TransformParameters<'ast> {
+        TransformParameters {
+            ctx,
+        }
+    }
+
+    /// Rewrites the parameters of the function to simple ones, `Continue`
+    /// if it has them already.
+    ///
+    /// The parameters and the body are changed in place rather than by
+    /// replacing the function, since the class transform finds the methods
+    /// it has lowered by their body.
+    fn lower<N>(&self, function: &Function<'ast, N>) -> VisitAction where
+        N: Name<'ast>,
+    {
+        let is_simple = function.params.iter().all(|param| matches!(param.item, Pattern::Identifier(_)));
+
+        if is_simple {
+            return VisitAction::Continue;
+        }
+
+        let builder = Builder::new(self.ctx.arena);
+        let void = || builder.prefix(OperatorKind::Void, builder.integer(0));
+        let declare = |pattern, init| {
+            builder.declaration_statement(DeclarationKind::Var, builder.list_from(builder.declarator(pattern, Some(init))))
+        };
+
+        let mut params = Vec::new();
+        let mut prologue: Vec<StatementNode<'ast>> = Vec::new();
+
+        // Whether the parameters so far count towards `Function.length`
+        let mut counted = true;
+
+        for (index, param) in function.params.iter().enumerate() {
+            let index = index as u64;
+            let (pattern, default) = match param.item {
+                Pattern::AssignmentPattern { left, right } => (left, Some(right)),
+                Pattern::RestElement { argument } => {
+                    // Array.prototype.slice.call(arguments, index)
+                    let slice = builder.member(builder.member(builder.ident("Array"), "prototype"), "slice");
+                    let arguments = builder.list(vec![builder.ident("arguments"), builder.integer(index)]);
+
+                    prologue.push(declare(builder.pattern(argument.item), builder.call(builder.member(slice, "call"), arguments)));
+
+                    break;
+                },
+                _ => (*param, None),
+            };
+
+            if default.is_some() {
+                counted = false;
+            }
+
+            if !counted && !self.ctx.options.loose_parameters {
+                // arguments.length > index && arguments[index] !== void 0 ? arguments[index] : default
+                let argument = || builder.computed_member(builder.ident("arguments"), builder.integer(index));
+                let passed = builder.binary(
+                    OperatorKind::Greater,
+                    builder.member(builder.ident("arguments"), "length"),
+                    builder.integer(index),
+                );
+
+                let init = match default {
+                    Some(default) => builder.conditional(
+                        builder.binary(
+                            OperatorKind::LogicalAnd,
+                            passed,
+                            builder.binary(OperatorKind::StrictInequality, argument(), void()),
+                        ),
+                        argument(),
+                        default,
+                    ),
+                    None => builder.conditional(passed, argument(), void()),
+                };
+
+                prologue.push(declare(pattern, init));
+
+                continue;
+            }
+
+            match (pattern.item, default) {
+                (Pattern::Identifier(_), None) => params.push(pattern),
+                (Pattern::Identifier(name), Some(default)) => {
+                    let test = builder.binary(OperatorKind::StrictEquality, builder.ident(name), void());
+                    let assign = builder.expression_statement(builder.assign(builder.ident(name), default));
+
+                    params.push(pattern);
+                    prologue.push(builder.if_statement(test, assign, None));
+                },
+                (_, default) => {
+                    let name = self.ctx.names.generate("ref");
+                    let init = match default {
+                        Some(default) => builder.conditional(
+                            builder.binary(OperatorKind::StrictEquality, builder.ident(name), void()),
+                            default,
+                            builder.ident(name),
+                        ),
+                        None => builder.ident(name),
+                    };
+
+                    params.push(builder.pattern(name));
+                    prologue.push(declare(pattern, init));
+                },
+            }
+        }
+
+        prologue.extend(function.body.body.iter().cloned());
+
+        self.ctx.swap(&function.body, Block { body: builder.list(prologue) });
+
+        // `List::prepend` writes through a shared reference, hide that it is
+        // the same list every time so that its root is read anew
+        function.params.clear();
+
+        for param in params.into_iter().rev() {
+            hint::black_box(&function.params).prepend(self.ctx.arena, param);
+        }
+
+        VisitAction::Revisit
+    }
+}
+
+impl<'ast> Visitor<'ast> for TransformParameters<'ast> {
+    fn on_function_statement(&mut self, item: &FunctionStatement<'ast>, _: &'ast StatementNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        self.lower(item)
+    }
+
+    fn on_function_expression(&mut self, item: &FunctionExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        self.lower(item)
+    }
+
+    fn on_method(&mut self, item: &Function<'ast, EmptyName>, _: &'ast Node<'ast, Function<'ast, EmptyName>>, _: &Path<'ast>) -> VisitAction {
+        self.lower(item)
+    }
+
+    fn on_arrow_expression_exit(&mut self, _: &ArrowExpression<'ast>, node: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        // The arrow transform has replaced the arrow by now, unless it uses `super`
+        match node.item {
+            Expression::Function(ref function) => self.lower(function),
+            _ => VisitAction::Continue,
+        }
+    }
+}
+
+impl<'ast> Pass<'ast> for TransformParameters<'ast> {
+    fn name(&self) -> &'static str {
+        "parameters"
+    }
+
+    fn after(&self) -> &[&'static str] {
+        &["arrow"]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use es2015::test::{lower, lower_without_helpers};
+    use Options;
+
+    #[test]
+    fn should_lower_default_and_rest_parameters() {
+        assert_eq!(
+            lower("function f(a, b = 1, c, ...d) { return d; }", Options::default()),
+            "function f(a){var b=arguments.length>1&&arguments[1]!==void 0?arguments[1]:1;\
+             var c=arguments.length>2?arguments[2]:void 0;\
+             var d=Array.prototype.slice.call(arguments,3);return d;}"
+        );
+        assert_eq!(
+            lower("function f(a, b = 1, ...c) {}", Options { loose_parameters: true, ..Options::default() }),
+            "function f(a,b){if(b===void 0)b=1;var c=Array.prototype.slice.call(arguments,2);}"
+        );
+    }
+
+    #[test]
+    fn should_lower_arrow_parameters() {
+        assert_eq!(
+            lower("function f() { return (x = 1) => [x, arguments]; }", Options::default()),
+            "function f(){var _arguments=arguments;\
+             return function(){var x=arguments.length>0&&arguments[0]!==void 0?arguments[0]:1;return [x,_arguments];};}"
+        );
+    }
+
+    #[test]
+    fn should_replace_destructured_parameters() {
+        assert_eq!(
+            lower("function f({a}, [b] = [], c) {}", Options::default()),
+            "function f(_ref){var a=_ref.a;var _ref2=arguments.length>1&&arguments[1]!==void 0?arguments[1]:[],b=_ref2[0];\
+             var c=arguments.length>2?arguments[2]:void 0;}"
+        );
+        assert_eq!(
+            lower("function f({a}, [b] = []) {}", Options { loose_parameters: true, ..Options::default() }),
+            "function f(_ref,_ref2){var a=_ref.a;var _ref3=_ref2===void 0?[]:_ref2,b=_ref3[0];}"
+        );
+    }
+
+    #[test]
+    fn should_lower_parameters_of_class_members() {
+        assert_eq!(
+            lower_without_helpers("class A extends B { constructor(a = 1, ...rest) { super(a, rest); } }", Options::default()),
+            "var A=function(_B){_inherits(A,_B);function A(){\
+             var a=arguments.length>0&&arguments[0]!==void 0?arguments[0]:1;\
+             var rest=Array.prototype.slice.call(arguments,1);\
+             _classCallCheck(this,A);_B.call(this,a,rest);}return A;}(B);"
+        );
+        assert_eq!(
+            lower_without_helpers("class A extends B { m({a}, [b] = [], ...c) { return super.m(a, b, c); } static s(x = super.s) { return () => super.t(x); } }", Options::default()),
+            "var A=function(_B){_inherits(A,_B);function A(){_classCallCheck(this,A);_B.apply(this,arguments);}\
+             A.prototype.m=function(_ref){var a=_ref.a;\
+             var _ref2=arguments.length>1&&arguments[1]!==void 0?arguments[1]:[],b=_ref2[0];\
+             var c=Array.prototype.slice.call(arguments,2);return _B.prototype.m.call(this,a,b,c);};\
+             A.s=function(){var _this=this;var x=arguments.length>0&&arguments[0]!==void 0?arguments[0]:_B.s;\
+             return function(){return _B.t.call(_this,x);};};return A;}(B);"
+        );
+        assert_eq!(
+            lower_without_helpers("class A extends B { set x({v}) { super.y = v; } }", Options::default()),
+            "var A=function(_B){_inherits(A,_B);function A(){_classCallCheck(this,A);_B.apply(this,arguments);}\
+             Object.defineProperty(A.prototype,\"x\",{set:function(_ref){var v=_ref.v;this.y=v;},configurable:true});\
+             return A;}(B);"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use ratel::ast::{NodeList, Expression, ExpressionNode, OperatorKind};
+use ratel::ast::expression::{TemplateLiteral, TaggedTemplateExpression, PrefixExpression, cook};
+use ratel::build::Builder;
+use ratel_visitor::{Visitor, VisitAction, Path, Pass, NodeRef};
+
+use TransformerCtxt;
+use helpers::Helper;
+use names::Target;
+
+/// Lowers template literals to string concatenation, `"a".concat(b, "c")`,
+/// or `"a" + b + "c"` with `Options::loose_template`.
+///
+/// Tagged templates become calls to the tag, with the strings of the template
+/// frozen and cached in a variable at the top of the module, so that the tag
+/// gets the same array every time the template is evaluated.
+pub struct TransformTemplate<'ast> {
+    ctx: TransformerCtxt<'ast>,
+}
+
+impl<'ast> TransformTemplate<'ast> {
+    pub fn new(ctx: TransformerCtxt<'ast>) -> TransformTemplate<'ast> {
+        TransformTemplate {
+            ctx,
+        }
+    }
+
+    /// String literal with the value of the quasi, `void 0` if it has an
+    /// invalid escape sequence, which is only allowed in tagged templates.
+    fn cooked(&self, raw: &str) -> ExpressionNode<'ast> {
+        let builder = Builder::new(self.ctx.arena);
+
+        match cook(raw) {
+            Some(value) => builder.string(&value),
+            None        => builder.prefix(OperatorKind::Void, builder.integer(0)),
+        }
+    }
+
+    /// String literal with the raw source of the quasi.
+    fn raw(&self, raw: &str) -> ExpressionNode<'ast> {
+        Builder::new(self.ctx.arena).string(&raw.replace("\r\n", "\n").replace('\r', "\n"))
+    }
+}
+
+impl<'ast> Visitor<'ast> for TransformTemplate<'ast> {
+    fn on_template_literal_exit(&mut self, item: &TemplateLiteral<'ast>, node: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+        let builder = Builder::new(self.ctx.arena);
+        let mut quasis = item.quasis.iter();
+
+        let first = match quasis.next() {
+            Some(quasi) => self.cooked(quasi.item),
+            None        => return VisitAction::Continue,
+        };
+
+        // Empty strings are left out, other than the first that makes sure
+        // the result is a string
+        let mut parts = Vec::new();
+
+        for (quasi, expression) in quasis.zip(item.expressions.iter()) {
+            parts.push(*expression);
+
+            if !quasi.item.is_empty() {
+                parts.push(self.cooked(quasi.item));
+            }
+        }
+
+        let result = if parts.is_empty() {
+            first
+        } else if self.ctx.options.loose_template {
+            parts.into_iter().fold(first, |left, right| builder.binary(OperatorKind::Addition, left, right))
+        } else {
+            builder.call(builder.member(first, "concat"), builder.list(parts))
+        };
+
+        self.ctx.swap(node, result.item);
+
+        VisitAction::Continue
+    }
+
+    fn on_tagged_template_expression_exit(&mut self, item: &TaggedTemplateExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+        let builder = Builder::new(self.ctx.arena);
+        let quasis = item.quasi.quasis;
+
+        let strings = builder.array(builder.list(quasis.iter().map(|quasi| self.cooked(quasi.item)).collect::<Vec<_>>()));
+        let mut arguments = vec![strings];
+
+        // The helper copies the strings when they are the same as raw
+        if quasis.iter().any(|quasi| quasi.item.contains('\\')) {
+            arguments.push(builder.array(builder.list(quasis.iter().map(|quasi| self.raw(quasi.item)).collect::<Vec<_>>())));
+        }
+
+        let helper = self.ctx.helpers.get(Helper::TaggedTemplateLiteral);
+        let cache = self.ctx.names.declare(Target::Module, "templateObject");
+
+        // _templateObject || (_templateObject = _taggedTemplateLiteral([...]))
+        let strings = builder.binary(
+            OperatorKind::LogicalOr,
+            builder.ident(cache),
+            builder.assign(builder.ident(cache), builder.call(builder.ident(helper), builder.list(arguments))),
+        );
+
+        let arguments = builder.prepend(strings, item.quasi.expressions);
+        let call = builder.call(item.tag, arguments);
+
+        // The tag of `new Foo`x`` is called before `new`, `new (Foo(...))()`
+        let call = match path.parent() {
+            Some(NodeRef::Expression(parent)) => match parent.item {
+                Expression::Prefix(PrefixExpression { operator: OperatorKind::New, .. }) => builder.call(call, NodeList::empty()),
+                _ => call,
+            },
+            _ => call,
+        };
+
+        self.ctx.swap(node, call.item);
+
+        VisitAction::Continue
+    }
+}
+
+impl<'ast> Pass<'ast> for TransformTemplate<'ast> {
+    fn name(&self) -> &'static str {
+        "template"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use es2015::test::{lower, lower_without_helpers};
+    use Options;
+
+    #[test]
+    fn should_lower_template_literals() {
+        let loose = Options { loose_template: true, ..Options::default() };
+
+        assert_eq!(lower("`foo`;", Options::default()), "\"foo\";");
+        assert_eq!(lower("`a${b}c${d}`;", Options::default()), "\"a\".concat(b,\"c\",d);");
+        assert_eq!(lower("`${a + b}\"\\``;", Options::default()), "\"\".concat(a+b,\"\\\"`\");");
+        assert_eq!(lower("`a${b}c${d}`;", loose), "\"a\"+b+\"c\"+d;");
+        assert_eq!(lower("`${a, b}`;", loose), "\"\"+(a,b);");
+        assert_eq!(lower("`a${b, c}${d ? e : f}`;", Options::default()), "\"a\".concat((b,c),d?e:f);");
+        assert_eq!(lower("`a${b + c}${d ? e : f}${g = h}`;", loose), "\"a\"+(b+c)+(d?e:f)+(g=h);");
+    }
+
+    #[test]
+    fn should_cook_escape_sequences() {
+        assert_eq!(lower("`\\u{1F600}${a}\\x41\\\n`;", Options::default()), "\"\u{1F600}\".concat(a,\"A\");");
+        assert_eq!(lower("`\\t\\\\\\'`;", Options::default()), "\"\\t\\\\'\";");
+    }
+
+    #[test]
+    fn should_lower_tagged_templates() {
+        assert_eq!(
+            lower("tag`a${b}\\n`; tag`c`;", Options::default()),
+            "function _taggedTemplateLiteral(strings,raw){if(!raw){raw=strings.slice(0);}\
+             return Object.freeze(Object.defineProperties(strings,{raw:{value:Object.freeze(raw)}}));}\
+             var _templateObject,_templateObject2;\
+             tag(_templateObject||(_templateObject=_taggedTemplateLiteral([\"a\",\"\\n\"],[\"a\",\"\\\\n\"])),b);\
+             tag(_templateObject2||(_templateObject2=_taggedTemplateLiteral([\"c\"])));"
+        );
+        assert_eq!(
+            lower_without_helpers("new Foo`x`; new Foo`\\01`(a);", Options::default()),
+            "var _templateObject,_templateObject2;\
+             new (Foo(_templateObject||(_templateObject=_taggedTemplateLiteral([\"x\"]))))();\
+             new (Foo(_templateObject2||(_templateObject2=_taggedTemplateLiteral([void 0],[\"\\\\01\"]))))(a);"
+        );
+    }
+}
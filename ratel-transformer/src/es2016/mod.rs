@@ -1,6 +1,6 @@
 use ratel::ast::{Node, Loc, Expression, ExpressionNode, OperatorKind};
 use ratel::ast::expression::{BinaryExpression, MemberExpression, CallExpression};
-use ratel_visitor::Visitor;
+use ratel_visitor::{Visitor, VisitAction, Path};
 
 use TransformerCtxt;
 
@@ -20,7 +20,7 @@ static POW: &Loc<&str> = &Loc {
 };
 
 impl<'ast> Visitor<'ast> for PresetES2016<'ast> {
-    fn on_binary_expression(&mut self, node: &BinaryExpression<'ast>, ptr: &ExpressionNode<'ast>) {
+    fn on_binary_expression(&mut self, node: &BinaryExpression<'ast>, ptr: &ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
         match node.operator {
             OperatorKind::Exponent => {
                 let callee = self.ctx.alloc(MemberExpression {
@@ -55,5 +55,7 @@ impl<'ast> Visitor<'ast> for PresetES2016<'ast> {
 
             _ => {}
         }
+
+        VisitAction::Continue
     }
 }
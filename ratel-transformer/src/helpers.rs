@@ -0,0 +1,142 @@
+use ratel::{Module, parse_in};
+use ratel::build::Builder;
+use toolshed::Arena;
+use toolshed::list::GrowableList;
+
+use names::{NameGenerator, insert_after_directives};
+
+/// Functions the output of transforms calls at runtime.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Helper {
+    /// `_classCallCheck(instance, Constructor)`, throws if a class
+    /// constructor is called without `new`.
+    ClassCallCheck,
+
+    /// `_inherits(subClass, superClass)`, sets up the prototype chain
+    /// of a class that `extends` another.
+    Inherits,
+
+    /// `_taggedTemplateLiteral(strings, raw)`, freezes the strings passed
+    /// to the tag of a template, with their `raw` values.
+    TaggedTemplateLiteral,
+
+    /// `_objectWithoutProperties(source, excluded)`, copies the own
+    /// enumerable properties of an object, other than the excluded keys.
+    ObjectWithoutProperties,
+}
+
+impl Helper {
+    fn hint(self) -> &'static str {
+        match self {
+            Helper::ClassCallCheck          => "classCallCheck",
+            Helper::Inherits                => "inherits",
+            Helper::TaggedTemplateLiteral   => "taggedTemplateLiteral",
+            Helper::ObjectWithoutProperties => "objectWithoutProperties",
+        }
+    }
+
+    fn source(self, name: &str) -> String {
+        match self {
+            Helper::ClassCallCheck => format!(
+                "function {}(instance, Constructor) {{\
+                    if (!(instance instanceof Constructor)) {{\
+                        throw new TypeError(\"Cannot call a class as a function\");\
+                    }}\
+                }}",
+                name
+            ),
+            Helper::Inherits => format!(
+                "function {}(subClass, superClass) {{\
+                    if (typeof superClass !== \"function\" && superClass !== null) {{\
+                        throw new TypeError(\"Super expression must either be null or a function\");\
+                    }}\
+                    subClass.prototype = Object.create(superClass && superClass.prototype, {{\
+                        constructor: {{ value: subClass, writable: true, configurable: true }}\
+                    }});\
+                    if (superClass) {{\
+                        subClass.__proto__ = superClass;\
+                    }}\
+                }}",
+                name
+            ),
+            Helper::TaggedTemplateLiteral => format!(
+                "function {}(strings, raw) {{\
+                    if (!raw) {{\
+                        raw = strings.slice(0);\
+                    }}\
+                    return Object.freeze(Object.defineProperties(strings, {{\
+                        raw: {{ value: Object.freeze(raw) }}\
+                    }}));\
+                }}",
+                name
+            ),
+            Helper::ObjectWithoutProperties => format!(
+                "function {}(source, excluded) {{\
+                    var target = {{}};\
+                    for (var key in source) {{\
+                        if (excluded.indexOf(key) >= 0) continue;\
+                        if (!Object.prototype.hasOwnProperty.call(source, key)) continue;\
+                        target[key] = source[key];\
+                    }}\
+                    return target;\
+                }}",
+                name
+            ),
+        }
+    }
+}
+
+/// Helper functions used by the transforms. Each is declared once at the
+/// top of the module, under a name from the `NameGenerator`, and only if
+/// it's used. Copies share the helpers in use.
+#[derive(Clone, Copy)]
+pub struct Helpers<'ast> {
+    arena: &'ast Arena,
+    names: NameGenerator<'ast>,
+
+    /// Helpers in use with their names, in the order they were requested.
+    used: &'ast GrowableList<'ast, (Helper, &'ast str)>,
+}
+
+impl<'ast> Helpers<'ast> {
+    #[inline]
+    pub fn new(arena: &'ast Arena, names: NameGenerator<'ast>) -> Self {
+        Helpers {
+            arena,
+            names,
+            used: arena.alloc(GrowableList::new()),
+        }
+    }
+
+    /// Name of the helper, declaring it if it isn't in use yet.
+    pub fn get(&self, helper: Helper) -> &'ast str {
+        if let Some(&(_, name)) = self.used.as_list().iter().find(|&&(other, _)| other == helper) {
+            return name;
+        }
+
+        let name = self.names.generate(helper.hint());
+
+        self.used.push(self.arena, (helper, name));
+
+        name
+    }
+
+    /// Insert the declarations of the helpers in use at the top of the
+    /// module, after the directive prologue, in the order they were
+    /// requested. Should be called once, after all transforms are done.
+    pub fn emit(&self, module: &mut Module<'ast>) {
+        let builder = Builder::new(self.arena);
+        let mut statements = Vec::new();
+
+        for &(helper, name) in self.used.as_list().iter() {
+            let source = helper.source(name);
+            let helper = parse_in(self.arena, &source).expect("Helpers are valid JavaScript");
+
+            statements.extend(helper.body().iter().cloned());
+        }
+
+        if !statements.is_empty() {
+            module.set_body(insert_after_directives(&builder, &statements, module.body()));
+        }
+    }
+}
@@ -1,27 +1,54 @@
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
+#[cfg(test)]
+extern crate ratel_codegen;
 extern crate ratel;
 extern crate ratel_visitor;
+extern crate ratel_semantic;
 extern crate toolshed;
 
 use toolshed::Arena;
 use toolshed::list::ListBuilder;
 use ratel::ast::{Loc, Node, NodeList};
 use ratel::Module;
-use ratel_visitor::{ScopeKind, Visitable};
+use ratel_visitor::Visitable;
 
 pub mod es2015;
 // pub mod es2016;
 
 pub mod scope;
+pub mod names;
+pub mod helpers;
+pub mod error;
+
+use self::names::NameGenerator;
+use self::helpers::Helpers;
+
+pub use self::error::Error;
+
+/// Settings for the transforms. Loose transforms produce smaller and faster
+/// output that assumes well-behaved input.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Options {
+    /// Concatenate template literals with `+`, assuming their values convert
+    /// to strings the same way as with `String.prototype.concat`.
+    pub loose_template: bool,
 
-use self::scope::Scope;
+    /// Keep parameters with defaults, which changes the `length` of the
+    /// function, and only replace `undefined` values.
+    pub loose_parameters: bool,
+
+    /// Index `for-of` loops, assuming they only go over arrays.
+    pub loose_for_of: bool,
+}
 
 #[derive(Copy, Clone)]
 pub struct TransformerCtxt<'ast> {
     pub arena: &'ast Arena,
-    pub scope: &'ast Scope<'ast>,
+    pub options: Options,
+    pub names: NameGenerator<'ast>,
+    pub helpers: Helpers<'ast>,
 }
 
 impl<'ast> TransformerCtxt<'ast> {
@@ -61,7 +88,7 @@ impl<'ast> TransformerCtxt<'ast> {
     }
 
     #[inline]
-    pub fn swap<T, I>(&self, ptr: Node<'ast, T>, item: I) where
+    pub fn swap<T, I>(&self, ptr: &Node<'ast, T>, item: I) where
         T: Copy + 'ast,
         I: Into<T>,
     {
@@ -75,13 +102,39 @@ impl<'ast> TransformerCtxt<'ast> {
     }
 }
 
-pub fn transform<'ast>(module: &'ast mut Module<'ast>) {
+/// Lower the module to ES5 with the default options.
+#[inline]
+pub fn transform<'ast>(module: &mut Module<'ast>) -> Result<(), Vec<Error>> {
+    transform_with(module, Options::default())
+}
+
+/// Lower the module to ES5. Nothing is transformed if the module has
+/// errors that can be found ahead of time.
+pub fn transform_with<'ast>(module: &mut Module<'ast>, options: Options) -> Result<(), Vec<Error>> {
+    let semantic = ratel_semantic::analyze(module);
+    let errors = es2015::check(&semantic);
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
     let arena = module.arena();
+    let root = arena.alloc(*module);
 
+    let names = NameGenerator::new(arena, &semantic);
     let ctx = TransformerCtxt {
         arena,
-        scope: arena.alloc(Scope::new(ScopeKind::Function, None)),
+        options,
+        names,
+        helpers: Helpers::new(arena, names),
     };
 
-    module.visit_with(&mut es2015::PresetES2015::new(ctx));
+    root.visit_with(&mut es2015::preset(ctx, semantic));
+
+    // The root copy shares the body with the module, so only the
+    // declarations at the top of the module need to be set on it
+    ctx.names.emit(module);
+    ctx.helpers.emit(module);
+
+    Ok(())
 }
@@ -0,0 +1,291 @@
+use std::ptr;
+
+use ratel::Module;
+use ratel::ast::{Loc, Block, Statement, StatementNode, StatementList, ExpressionNode, Expression, BlockNode, DeclarationKind};
+use ratel::build::Builder;
+use ratel_visitor::{Path, NodeRef};
+use toolshed::Arena;
+use toolshed::list::GrowableList;
+use toolshed::set::Set;
+use ratel_semantic::Semantic;
+
+/// Where the `var` declaration of a generated name is placed.
+#[derive(Clone, Copy, Debug)]
+pub enum Target<'ast> {
+    /// Top of the module.
+    Module,
+
+    /// Top of the body of a function.
+    Function(&'ast BlockNode<'ast, Statement<'ast>>),
+}
+
+impl<'ast> Target<'ast> {
+    /// Closest function enclosing the current node of the `Path`, skipping
+    /// arrow functions since they can have an expression for a body and share
+    /// `this` and `arguments` with the function they are in.
+    pub fn of(path: &Path<'ast>) -> Self {
+        for node in path.ancestors() {
+            let body = match node {
+                NodeRef::Statement(node) => match node.item {
+                    Statement::Function(ref function) => &function.body,
+                    _ => continue,
+                },
+                NodeRef::Expression(node) => match node.item {
+                    Expression::Function(ref function) => &function.body,
+                    _ => continue,
+                },
+                NodeRef::Method(node) => &node.body,
+                _ => continue,
+            };
+
+            return Target::Function(body);
+        }
+
+        Target::Module
+    }
+
+}
+
+/// Targets are the same if they are the same function body, by identity.
+impl<'ast> PartialEq for Target<'ast> {
+    #[inline]
+    fn eq(&self, other: &Target<'ast>) -> bool {
+        match (*self, *other) {
+            (Target::Module, Target::Module) => true,
+            (Target::Function(a), Target::Function(b)) => ptr::eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// Produces identifiers for temporaries introduced by transforms, such as
+/// `_this` or `_ref`, that don't collide with any name used in the module.
+/// Declarations of the names are collected and only inserted into the AST
+/// by `NameGenerator::emit`, once the transforms are done. Copies share the
+/// names and declarations.
+#[derive(Clone, Copy)]
+pub struct NameGenerator<'ast> {
+    arena: &'ast Arena,
+
+    /// Names of all bindings and references in the module.
+    used: &'ast Set<'ast, &'ast str>,
+
+    /// All names produced so far.
+    generated: &'ast Set<'ast, &'ast str>,
+
+    /// Names to declare with `var`, in the order they were produced.
+    declarations: &'ast GrowableList<'ast, Declaration<'ast>>,
+}
+
+#[derive(Clone, Copy)]
+struct Declaration<'ast> {
+    target: Target<'ast>,
+    name: &'ast str,
+    init: Option<ExpressionNode<'ast>>,
+}
+
+impl<'ast> NameGenerator<'ast> {
+    pub fn new(arena: &'ast Arena, semantic: &Semantic<'ast>) -> Self {
+        let used = arena.alloc(Set::new());
+
+        for binding in semantic.bindings() {
+            used.insert(arena, binding.name);
+        }
+        for reference in semantic.references() {
+            used.insert(arena, reference.name);
+        }
+
+        NameGenerator {
+            arena,
+            used,
+            generated: arena.alloc(Set::new()),
+            declarations: arena.alloc(GrowableList::new()),
+        }
+    }
+
+    /// Produce a fresh name based on the hint, `_hint`, `_hint2`, `_hint3`
+    /// and so on. The name isn't declared anywhere.
+    pub fn generate(&self, hint: &str) -> &'ast str {
+        let base: String = hint
+            .trim_start_matches('_')
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .chars()
+            .filter(|&c| c == '_' || c == '$' || c.is_alphanumeric())
+            .collect();
+
+        let base = if base.is_empty() { "ref".to_string() } else { base };
+
+        let mut name = self.arena.alloc_string(format!("_{}", base));
+        let mut counter = 1;
+
+        while self.is_taken(name) {
+            counter += 1;
+            name = self.arena.alloc_string(format!("_{}{}", base, counter));
+        }
+
+        self.generated.insert(self.arena, name);
+
+        name
+    }
+
+    /// Produce a fresh name and declare it with `var` at the target.
+    #[inline]
+    pub fn declare(&self, target: Target<'ast>, hint: &str) -> &'ast str {
+        self.declare_init(target, hint, None)
+    }
+
+    /// Produce a fresh name and declare it with `var` at the target,
+    /// initialized to the given expression.
+    pub fn declare_init(&self, target: Target<'ast>, hint: &str, init: Option<ExpressionNode<'ast>>) -> &'ast str {
+        let name = self.generate(hint);
+
+        self.declarations.push(self.arena, Declaration { target, name, init });
+
+        name
+    }
+
+    /// Whether the name is used or declared anywhere in the module,
+    /// or has already been produced.
+    pub fn is_taken(&self, name: &'ast str) -> bool {
+        self.generated.contains(name) || self.used.contains(name)
+    }
+
+    /// Insert the `var` declarations at the top of their targets, after the
+    /// directive prologue, a single declaration per target, with declarators
+    /// in the order the names were produced. Should be called once, after all
+    /// transforms are done.
+    pub fn emit(&self, module: &mut Module<'ast>) {
+        let builder = Builder::new(self.arena);
+        let declarations = self.declarations.as_list();
+        let mut done: Vec<Target<'ast>> = Vec::new();
+
+        for declaration in declarations.iter() {
+            let target = declaration.target;
+
+            if done.contains(&target) {
+                continue;
+            }
+
+            done.push(target);
+
+            let declarators = declarations
+                .iter()
+                .filter(|other| other.target == target)
+                .map(|other| builder.declarator(builder.pattern(other.name), other.init));
+
+            let statement = builder.declaration_statement(DeclarationKind::Var, builder.list(declarators));
+
+            match target {
+                Target::Module => {
+                    module.set_body(insert_after_directives(&builder, &[statement], module.body()));
+                },
+                Target::Function(body) => {
+                    body.set(self.arena.alloc(Loc::new(body.start, body.end, Block {
+                        body: insert_after_directives(&builder, &[statement], body.body),
+                    })));
+                },
+            }
+        }
+    }
+}
+
+/// Build a new list with the statements inserted into `body` after its
+/// directive prologue, such as `"use strict";`, which only takes effect
+/// at the very top of a module or function.
+pub fn insert_after_directives<'ast>(builder: &Builder<'ast>, statements: &[StatementNode<'ast>], body: StatementList<'ast>) -> StatementList<'ast> {
+    let mut list: Vec<_> = body.iter().cloned().collect();
+    let directives = list.iter().take_while(|statement| statement.item.is_directive()).count();
+
+    list.splice(directives..directives, statements.iter().cloned());
+
+    builder.list(list)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::parse_in;
+    use ratel_visitor::{Visitable, Visitor, VisitAction};
+    use ratel_codegen::codegen;
+
+    #[test]
+    fn should_generate_fresh_names() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "var _this = this; function foo(_ref) { _ref2; }").unwrap();
+        let names = NameGenerator::new(&arena, &ratel_semantic::analyze(&module));
+
+        assert_eq!(names.generate("this"), "_this2");
+        assert_eq!(names.generate("_this"), "_this3");
+        assert_eq!(names.generate("ref"), "_ref3");
+        assert_eq!(names.generate("foo.bar"), "_foobar");
+        assert_eq!(names.generate(""), "_ref4");
+        assert_eq!(names.is_taken("foo"), true);
+        assert_eq!(names.is_taken("_foobar"), true);
+        assert_eq!(names.is_taken("bar"), false);
+    }
+
+    struct DeclareThis<'ast> {
+        names: NameGenerator<'ast>,
+    }
+
+    impl<'ast> Visitor<'ast> for DeclareThis<'ast> {
+        fn on_this_expression(&mut self, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+            let this = Builder::new(self.names.arena).this();
+            let name = self.names.declare_init(Target::of(path), "this", Some(this));
+
+            node.set(self.names.arena.alloc(Loc::new(node.start, node.end, Expression::Identifier(name))));
+
+            VisitAction::Continue
+        }
+    }
+
+    #[test]
+    fn should_declare_names_at_the_top_of_functions_and_module() {
+        let arena = Arena::new();
+        let mut module = parse_in(&arena, r#"
+            foo(this);
+            function bar(_this) {
+                return () => this;
+            }
+            var baz = { qux() { return [this, this]; } };
+        "#).unwrap();
+
+        let root = arena.alloc(module);
+        let names = NameGenerator::new(&arena, &ratel_semantic::analyze(&module));
+
+        root.visit_with(&mut DeclareThis { names });
+        names.emit(&mut module);
+
+        assert_eq!(
+            codegen(&module, true),
+            "var _this2=this;foo(_this2);function bar(_this){var _this3=this;return ()=>_this3;}\
+             var baz={qux(){var _this4=this,_this5=this;return [_this4,_this5];}};"
+        );
+    }
+
+    #[test]
+    fn should_declare_names_after_directives() {
+        let arena = Arena::new();
+        let mut module = parse_in(&arena, r#"
+            "use strict";
+            foo(this);
+            function bar() {
+                "use strict";
+                'use asm';
+                return this;
+            }
+        "#).unwrap();
+
+        let root = arena.alloc(module);
+        let names = NameGenerator::new(&arena, &ratel_semantic::analyze(&module));
+
+        root.visit_with(&mut DeclareThis { names });
+        names.emit(&mut module);
+
+        assert_eq!(
+            codegen(&module, true),
+            "\"use strict\";var _this=this;foo(_this);\
+             function bar(){\"use strict\";'use asm';var _this2=this;return _this2;}"
+        );
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt::{self, Debug};
 
 use ratel::Module;
 use ratel::ast::{Identifier, ExpressionNode};
-use ratel_visitor::{Visitable, ScopeKind, Visitor};
+use ratel_visitor::{Visitable, ScopeKind, Visitor, VisitAction, Path};
 use toolshed::{Arena, CopyCell};
 use toolshed::list::GrowableList;
 use toolshed::map::BloomMap;
@@ -61,11 +61,6 @@ impl<'ast> Scope<'ast> {
     pub fn as_usize(&'ast self) -> usize {
         self as *const Scope as usize
     }
-
-    #[inline]
-    pub unsafe fn from_usize(ptr: usize) -> &'ast Self {
-        &*(ptr as *const Scope)
-    }
 }
 
 /// Need to manually implement Debug to avoid circular reference on `parent`
@@ -111,14 +106,14 @@ impl<'ast> ScopeAnalyzer<'ast> {
 
 impl<'ast> Visitor<'ast> for ScopeAnalyzer<'ast> {
     #[inline]
-    fn on_enter_scope(&mut self, kind: ScopeKind) {
+    fn on_enter_scope(&mut self, kind: ScopeKind, _: &Path<'ast>) {
         self.current.set(
             self.arena.alloc(Scope::new(kind, Some(self.current.get())))
         );
     }
 
     #[inline]
-    fn on_leave_scope(&mut self) {
+    fn on_leave_scope(&mut self, _: &Path<'ast>) {
         let popped = self.current.get();
 
         self.current.set(popped.parent.unwrap());
@@ -126,29 +121,31 @@ impl<'ast> Visitor<'ast> for ScopeAnalyzer<'ast> {
     }
 
     #[inline]
-    fn on_reference_use(&mut self, ident: &Identifier<'ast>) {
+    fn on_reference_use(&mut self, ident: &Identifier<'ast>, _: &Path<'ast>) {
         self.current.get().used_refs.insert(self.arena, *ident, ());
     }
 
     #[inline]
-    fn on_reference_declaration(&mut self, ident: &Identifier<'ast>) {
+    fn on_reference_declaration(&mut self, ident: &Identifier<'ast>, _: &Path<'ast>) {
         self.current.get().declared_refs.insert(self.arena, *ident, ());
     }
 
     #[inline]
-    fn on_this_expression(&mut self, _: &ExpressionNode<'ast>) {
+    fn on_this_expression(&mut self, _: &ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
         self.current.get().used_this.set(true);
+        VisitAction::Continue
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use ratel::parse;
+    use ratel::{parse_in, Arena};
 
     #[test]
     fn scope_analysis() {
-        let module = parse("function test_function(bar) { doge; { moon; } return 10; }").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "function test_function(bar) { doge; { moon; } return 10; }").unwrap();
         let root = analyze(&module);
 
         assert_eq!(root.parent, None);
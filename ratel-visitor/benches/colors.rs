@@ -103,8 +103,8 @@ impl<'ast> Visitor<'ast> for DummyStaticVisitor {}
 
 #[bench]
 fn empty_traverse(b: &mut Bencher) {
-    let module = ratel::parse(SOURCE).expect("Must parse");
-    let arena = module.arena();
+    let arena = ratel::Arena::new();
+    let module = ratel::parse_in(&arena, SOURCE).expect("Must parse");
     let offset = unsafe { arena.offset() };
 
     b.iter(|| {
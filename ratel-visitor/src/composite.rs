@@ -0,0 +1,679 @@
+use ratel::ast::expression::*;
+use ratel::ast::statement::*;
+use ratel::ast::{ExpressionNode, StatementList, StatementNode};
+use ratel::ast::{Identifier, Literal, Node, Pattern, PropertyNode};
+use ratel::ast::{Function, EmptyName, ClassMember};
+
+use std::fmt;
+
+use {Visitor, VisitAction, ScopeKind, Path};
+
+/// A `Visitor` that can be run as a member of a `Composite`.
+pub trait Pass<'ast>: Visitor<'ast> {
+    /// Name other passes refer to in their ordering constraints.
+    fn name(&self) -> &'static str;
+
+    /// Disabled passes are left out of the `Composite` altogether.
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    /// Names of passes whose hooks must be called before the hooks of this one.
+    /// Passes that aren't part of the `Composite` are ignored.
+    fn after(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Names of passes whose hooks must be called after the hooks of this one.
+    /// Passes that aren't part of the `Composite` are ignored.
+    fn before(&self) -> &[&'static str] {
+        &[]
+    }
+}
+
+/// Error returned by `Composite::new` when the ordering constraints of the
+/// passes can't be satisfied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderError {
+    /// Names of the passes that are part of the cycle.
+    pub passes: Vec<&'static str>,
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cyclic ordering constraints between passes: {}", self.passes.join(", "))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Active,
+    /// Returned `SkipChildren` from the entry hook of the node at this depth
+    /// of the `Path`, hears nothing until the exit hook of that node.
+    Skipping(usize),
+    /// Returned `Stop` within this many scopes. Only hears the `on_leave_scope`
+    /// calls for those, so its scope state stays balanced.
+    Stopped(usize),
+}
+
+struct Member<'a, 'ast> {
+    pass: Box<dyn Pass<'ast> + 'a>,
+    state: State,
+}
+
+/// Runs a number of passes in a single traversal, calling every hook on each
+/// enabled pass in turn.
+///
+/// `VisitAction`s are tracked per pass: a pass that skips the children of a
+/// node, or stops, doesn't affect the others. The traversal itself only skips
+/// or stops once every pass has asked for it. A pass returning `Revisit` from
+/// an entry hook should have replaced the node, so the remaining passes don't
+/// see the stale node and are called with the replacement instead. Passes
+/// before it have entered the node already and aren't called again, so that
+/// every pass enters and exits a node once; their exit hooks are called with
+/// the replacement.
+pub struct Composite<'a, 'ast> {
+    members: Vec<Member<'a, 'ast>>,
+    scopes: usize,
+
+    /// Depth of the node being revisited after an entry hook replaced it,
+    /// and the index of the pass to resume entering it from.
+    resume: Option<(usize, usize)>,
+}
+
+impl<'a, 'ast> Composite<'a, 'ast> {
+    /// Drop the disabled passes, and order the rest by their constraints.
+    /// Passes that aren't constrained keep the order they are given in.
+    pub fn new(passes: Vec<Box<dyn Pass<'ast> + 'a>>) -> Result<Self, OrderError> {
+        let mut passes: Vec<_> = passes.into_iter().filter(|pass| pass.enabled()).map(Some).collect();
+
+        // `edges[i]` lists the passes that must come after the pass at `i`
+        let mut edges = vec![Vec::new(); passes.len()];
+        let mut incoming = vec![0; passes.len()];
+
+        {
+            let index_of = |name: &str| passes.iter().position(|pass| {
+                pass.as_ref().is_some_and(|pass| pass.name() == name)
+            });
+
+            for (index, pass) in passes.iter().enumerate() {
+                let pass = pass.as_ref().unwrap();
+
+                for &name in pass.after() {
+                    if let Some(other) = index_of(name) {
+                        edges[other].push(index);
+                        incoming[index] += 1;
+                    }
+                }
+
+                for &name in pass.before() {
+                    if let Some(other) = index_of(name) {
+                        edges[index].push(other);
+                        incoming[other] += 1;
+                    }
+                }
+            }
+        }
+
+        let mut members = Vec::with_capacity(passes.len());
+
+        while members.len() < passes.len() {
+            let next = (0..passes.len()).find(|&index| {
+                incoming[index] == 0 && passes[index].is_some()
+            });
+
+            let index = match next {
+                Some(index) => index,
+                None => return Err(OrderError {
+                    passes: passes.iter().filter_map(|pass| pass.as_ref().map(|pass| pass.name())).collect(),
+                }),
+            };
+
+            for &other in &edges[index] {
+                incoming[other] -= 1;
+            }
+
+            members.push(Member {
+                pass: passes[index].take().unwrap(),
+                state: State::Active,
+            });
+        }
+
+        Ok(Composite {
+            members,
+            scopes: 0,
+            resume: None,
+        })
+    }
+
+    /// Names of the enabled passes, in the order their hooks are called.
+    pub fn passes(&self) -> Vec<&'static str> {
+        self.members.iter().map(|member| member.pass.name()).collect()
+    }
+
+    fn enter<F>(&mut self, path: &Path<'ast>, mut hook: F) -> VisitAction
+    where
+        F: FnMut(&mut dyn Pass<'ast>) -> VisitAction,
+    {
+        let depth = path.depth();
+
+        let start = match self.resume.take() {
+            Some((resumed, index)) if resumed == depth => index,
+            _ => 0,
+        };
+
+        // Passes entered before the revisit walk the children of the replacement
+        let mut walk = self.members[..start].iter().any(|member| member.state == State::Active);
+
+        for index in start..self.members.len() {
+            if self.members[index].state != State::Active {
+                continue;
+            }
+
+            match hook(&mut *self.members[index].pass) {
+                VisitAction::Continue => walk = true,
+                VisitAction::SkipChildren => self.members[index].state = State::Skipping(depth),
+                VisitAction::Stop => self.members[index].state = State::Stopped(self.scopes),
+                VisitAction::Revisit => {
+                    // Skipping the children of the replaced node is moot
+                    for member in &mut self.members[..index] {
+                        if member.state == State::Skipping(depth) {
+                            member.state = State::Active;
+                        }
+                    }
+
+                    self.resume = Some((depth, index));
+
+                    return VisitAction::Revisit;
+                }
+            }
+        }
+
+        self.action(walk)
+    }
+
+    fn exit<F>(&mut self, path: &Path<'ast>, mut hook: F) -> VisitAction
+    where
+        F: FnMut(&mut dyn Pass<'ast>) -> VisitAction,
+    {
+        let depth = path.depth();
+        let mut revisit = false;
+
+        for member in &mut self.members {
+            match member.state {
+                State::Active => {},
+                State::Skipping(skipped) if skipped == depth => member.state = State::Active,
+                _ => continue,
+            }
+
+            match hook(&mut *member.pass) {
+                VisitAction::Stop => member.state = State::Stopped(self.scopes),
+                VisitAction::Revisit => revisit = true,
+                _ => {},
+            }
+        }
+
+        if revisit {
+            return VisitAction::Revisit;
+        }
+
+        self.action(true)
+    }
+
+    fn action(&self, walk: bool) -> VisitAction {
+        let stopped = self.members.iter().all(|member| matches!(member.state, State::Stopped(_)));
+
+        if stopped {
+            VisitAction::Stop
+        } else if walk {
+            VisitAction::Continue
+        } else {
+            VisitAction::SkipChildren
+        }
+    }
+
+    fn each<F>(&mut self, mut hook: F)
+    where
+        F: FnMut(&mut dyn Pass<'ast>),
+    {
+        for member in &mut self.members {
+            if member.state == State::Active {
+                hook(&mut *member.pass);
+            }
+        }
+    }
+}
+
+macro_rules! enter_hooks {
+    ($( fn $name:ident(&mut self, $( $arg:ident: $type:ty ),*); )*) => ($(
+        #[inline]
+        fn $name(&mut self, $( $arg: $type, )* path: &Path<'ast>) -> VisitAction {
+            self.enter(path, |pass| pass.$name($( $arg, )* path))
+        }
+    )*)
+}
+
+macro_rules! exit_hooks {
+    ($( fn $name:ident(&mut self, $( $arg:ident: $type:ty ),*); )*) => ($(
+        #[inline]
+        fn $name(&mut self, $( $arg: $type, )* path: &Path<'ast>) -> VisitAction {
+            self.exit(path, |pass| pass.$name($( $arg, )* path))
+        }
+    )*)
+}
+
+impl<'a, 'ast> Visitor<'ast> for Composite<'a, 'ast> {
+    #[inline]
+    fn on_statement_list(&mut self, body: StatementList<'ast>, path: &Path<'ast>) {
+        self.each(|pass| pass.on_statement_list(body, path));
+    }
+
+    #[inline]
+    fn on_enter_scope(&mut self, kind: ScopeKind, path: &Path<'ast>) {
+        self.scopes += 1;
+        self.each(|pass| pass.on_enter_scope(kind, path));
+    }
+
+    #[inline]
+    fn on_leave_scope(&mut self, path: &Path<'ast>) {
+        let scopes = self.scopes;
+
+        for member in &mut self.members {
+            match member.state {
+                State::Active => {},
+                State::Stopped(stopped) if scopes <= stopped => {},
+                _ => continue,
+            }
+
+            member.pass.on_leave_scope(path);
+        }
+
+        self.scopes -= 1;
+    }
+
+    #[inline]
+    fn on_reference_use(&mut self, ident: &Identifier<'ast>, path: &Path<'ast>) {
+        self.each(|pass| pass.on_reference_use(ident, path));
+    }
+
+    #[inline]
+    fn on_reference_declaration(&mut self, ident: &Identifier<'ast>, path: &Path<'ast>) {
+        self.each(|pass| pass.on_reference_declaration(ident, path));
+    }
+
+    enter_hooks! {
+        fn on_this_expression(&mut self, node: &'ast ExpressionNode<'ast>);
+        fn on_super_expression(&mut self, node: &'ast ExpressionNode<'ast>);
+        fn on_identifier_expression(&mut self, item: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_literal_expression(&mut self, item: &Literal<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_sequence_expression(&mut self, item: &SequenceExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_array_expression(&mut self, item: &ArrayExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_member_expression(&mut self, item: &MemberExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_computed_member_expression(&mut self, item: &ComputedMemberExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_meta_property(&mut self, item: &MetaPropertyExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_call_expression(&mut self, item: &CallExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_binary_expression(&mut self, item: &BinaryExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_prefix_expression(&mut self, item: &PrefixExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_postfix_expression(&mut self, item: &PostfixExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_conditional_expression(&mut self, item: &ConditionalExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_template_literal(&mut self, item: &TemplateLiteral<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_tagged_template_expression(&mut self, item: &TaggedTemplateExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_spread_expression(&mut self, item: &SpreadExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_arrow_expression(&mut self, item: &ArrowExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_object_expression(&mut self, item: &ObjectExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_function_expression(&mut self, item: &FunctionExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_class_expression(&mut self, item: &ClassExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_expression_statement(&mut self, item: &'ast ExpressionNode<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_declaration_statement(&mut self, item: &DeclarationStatement, node: &'ast StatementNode<'ast>);
+        fn on_return_statement(&mut self, item: &ReturnStatement, node: &'ast StatementNode<'ast>);
+        fn on_break_statement(&mut self, item: &BreakStatement, node: &'ast StatementNode<'ast>);
+        fn on_continue_statement(&mut self, item: &ContinueStatement, node: &'ast StatementNode<'ast>);
+        fn on_throw_statement(&mut self, item: &ThrowStatement, node: &'ast StatementNode<'ast>);
+        fn on_if_statement(&mut self, item: &IfStatement, node: &'ast StatementNode<'ast>);
+        fn on_while_statement(&mut self, item: &WhileStatement, node: &'ast StatementNode<'ast>);
+        fn on_do_statement(&mut self, item: &DoStatement, node: &'ast StatementNode<'ast>);
+        fn on_for_statement(&mut self, item: &ForStatement, node: &'ast StatementNode<'ast>);
+        fn on_for_in_statement(&mut self, item: &ForInStatement, node: &'ast StatementNode<'ast>);
+        fn on_for_of_statement(&mut self, item: &ForOfStatement, node: &'ast StatementNode<'ast>);
+        fn on_try_statement(&mut self, item: &TryStatement, node: &'ast StatementNode<'ast>);
+        fn on_block_statement(&mut self, item: &BlockStatement<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_labeled_statement(&mut self, item: &LabeledStatement, node: &'ast StatementNode<'ast>);
+        fn on_switch_statement(&mut self, item: &SwitchStatement, node: &'ast StatementNode<'ast>);
+        fn on_function_statement(&mut self, item: &FunctionStatement<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_class_statement(&mut self, item:&ClassStatement<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_import_declaration_statement(&mut self, item:&ImportDeclaration<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_pattern(&mut self, item: &Pattern<'ast>, node: &'ast Node<'ast, Pattern<'ast>>);
+        fn on_property(&mut self, item: &Property<'ast>, node: &'ast PropertyNode<'ast>);
+        fn on_property_key(&mut self, item: &PropertyKey<'ast>, node: &'ast Node<'ast, PropertyKey<'ast>>);
+        fn on_method(&mut self, item: &Function<'ast, EmptyName>, node: &'ast Node<'ast, Function<'ast, EmptyName>>);
+        fn on_class_member(&mut self, item: &ClassMember<'ast>, node: &'ast Node<'ast, ClassMember<'ast>>);
+        fn on_declarator(&mut self, item: &Declarator<'ast>, node: &'ast Node<'ast, Declarator<'ast>>);
+        fn on_for_init(&mut self, item: &ForInit<'ast>, node: &'ast Node<'ast, ForInit<'ast>>);
+        fn on_catch_clause(&mut self, item: &CatchClause<'ast>, node: &'ast Node<'ast, CatchClause<'ast>>);
+        fn on_switch_case(&mut self, item: &SwitchCase<'ast>, node: &'ast Node<'ast, SwitchCase<'ast>>);
+        fn on_block(&mut self, item: &BlockStatement<'ast>, node: &'ast Node<'ast, BlockStatement<'ast>>);
+        fn on_import_specifier(&mut self, item: &ForImportSpecifier<'ast>, node: &'ast Node<'ast, ForImportSpecifier<'ast>>);
+        fn on_template_quasi(&mut self, item: &'ast str, node: &'ast Node<'ast, &'ast str>);
+    }
+
+    exit_hooks! {
+        fn on_this_expression_exit(&mut self, node: &'ast ExpressionNode<'ast>);
+        fn on_super_expression_exit(&mut self, node: &'ast ExpressionNode<'ast>);
+        fn on_identifier_expression_exit(&mut self, item: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_literal_expression_exit(&mut self, item: &Literal<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_sequence_expression_exit(&mut self, item: &SequenceExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_array_expression_exit(&mut self, item: &ArrayExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_member_expression_exit(&mut self, item: &MemberExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_computed_member_expression_exit(&mut self, item: &ComputedMemberExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_meta_property_exit(&mut self, item: &MetaPropertyExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_call_expression_exit(&mut self, item: &CallExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_binary_expression_exit(&mut self, item: &BinaryExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_prefix_expression_exit(&mut self, item: &PrefixExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_postfix_expression_exit(&mut self, item: &PostfixExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_conditional_expression_exit(&mut self, item: &ConditionalExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_template_literal_exit(&mut self, item: &TemplateLiteral<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_tagged_template_expression_exit(&mut self, item: &TaggedTemplateExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_spread_expression_exit(&mut self, item: &SpreadExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_arrow_expression_exit(&mut self, item: &ArrowExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_object_expression_exit(&mut self, item: &ObjectExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_function_expression_exit(&mut self, item: &FunctionExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_class_expression_exit(&mut self, item: &ClassExpression<'ast>, node: &'ast ExpressionNode<'ast>);
+        fn on_expression_statement_exit(&mut self, item: &'ast ExpressionNode<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_declaration_statement_exit(&mut self, item: &DeclarationStatement, node: &'ast StatementNode<'ast>);
+        fn on_return_statement_exit(&mut self, item: &ReturnStatement, node: &'ast StatementNode<'ast>);
+        fn on_break_statement_exit(&mut self, item: &BreakStatement, node: &'ast StatementNode<'ast>);
+        fn on_continue_statement_exit(&mut self, item: &ContinueStatement, node: &'ast StatementNode<'ast>);
+        fn on_throw_statement_exit(&mut self, item: &ThrowStatement, node: &'ast StatementNode<'ast>);
+        fn on_if_statement_exit(&mut self, item: &IfStatement, node: &'ast StatementNode<'ast>);
+        fn on_while_statement_exit(&mut self, item: &WhileStatement, node: &'ast StatementNode<'ast>);
+        fn on_do_statement_exit(&mut self, item: &DoStatement, node: &'ast StatementNode<'ast>);
+        fn on_for_statement_exit(&mut self, item: &ForStatement, node: &'ast StatementNode<'ast>);
+        fn on_for_in_statement_exit(&mut self, item: &ForInStatement, node: &'ast StatementNode<'ast>);
+        fn on_for_of_statement_exit(&mut self, item: &ForOfStatement, node: &'ast StatementNode<'ast>);
+        fn on_try_statement_exit(&mut self, item: &TryStatement, node: &'ast StatementNode<'ast>);
+        fn on_block_statement_exit(&mut self, item: &BlockStatement<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_labeled_statement_exit(&mut self, item: &LabeledStatement, node: &'ast StatementNode<'ast>);
+        fn on_switch_statement_exit(&mut self, item: &SwitchStatement, node: &'ast StatementNode<'ast>);
+        fn on_function_statement_exit(&mut self, item: &FunctionStatement<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_class_statement_exit(&mut self, item:&ClassStatement<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_import_declaration_statement_exit(&mut self, item:&ImportDeclaration<'ast>, node: &'ast StatementNode<'ast>);
+        fn on_pattern_exit(&mut self, item: &Pattern<'ast>, node: &'ast Node<'ast, Pattern<'ast>>);
+        fn on_property_exit(&mut self, item: &Property<'ast>, node: &'ast PropertyNode<'ast>);
+        fn on_property_key_exit(&mut self, item: &PropertyKey<'ast>, node: &'ast Node<'ast, PropertyKey<'ast>>);
+        fn on_method_exit(&mut self, item: &Function<'ast, EmptyName>, node: &'ast Node<'ast, Function<'ast, EmptyName>>);
+        fn on_class_member_exit(&mut self, item: &ClassMember<'ast>, node: &'ast Node<'ast, ClassMember<'ast>>);
+        fn on_declarator_exit(&mut self, item: &Declarator<'ast>, node: &'ast Node<'ast, Declarator<'ast>>);
+        fn on_for_init_exit(&mut self, item: &ForInit<'ast>, node: &'ast Node<'ast, ForInit<'ast>>);
+        fn on_catch_clause_exit(&mut self, item: &CatchClause<'ast>, node: &'ast Node<'ast, CatchClause<'ast>>);
+        fn on_switch_case_exit(&mut self, item: &SwitchCase<'ast>, node: &'ast Node<'ast, SwitchCase<'ast>>);
+        fn on_block_exit(&mut self, item: &BlockStatement<'ast>, node: &'ast Node<'ast, BlockStatement<'ast>>);
+        fn on_import_specifier_exit(&mut self, item: &ForImportSpecifier<'ast>, node: &'ast Node<'ast, ForImportSpecifier<'ast>>);
+        fn on_template_quasi_exit(&mut self, item: &'ast str, node: &'ast Node<'ast, &'ast str>);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::{parse_in, Arena};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use Visitable;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    struct LogPass {
+        name: &'static str,
+        log: Log,
+        enabled: bool,
+        after: Vec<&'static str>,
+        before: Vec<&'static str>,
+        // Action returned when entering a call expression
+        on_call: VisitAction,
+        scopes: i32,
+    }
+
+    impl LogPass {
+        fn new(name: &'static str, log: &Log) -> Self {
+            LogPass {
+                name,
+                log: log.clone(),
+                enabled: true,
+                after: Vec::new(),
+                before: Vec::new(),
+                on_call: VisitAction::Continue,
+                scopes: 0,
+            }
+        }
+
+        fn record(&mut self, event: &str) -> VisitAction {
+            self.log.borrow_mut().push(format!("{} {}", self.name, event));
+            VisitAction::Continue
+        }
+    }
+
+    impl<'ast> Visitor<'ast> for LogPass {
+        fn on_enter_scope(&mut self, _: ScopeKind, _: &Path<'ast>) {
+            self.scopes += 1;
+        }
+
+        fn on_leave_scope(&mut self, _: &Path<'ast>) {
+            self.scopes -= 1;
+            self.log.borrow_mut().push(format!("{} scopes {}", self.name, self.scopes));
+        }
+
+        fn on_identifier_expression(&mut self, item: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record(item)
+        }
+
+        fn on_call_expression(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("call");
+            self.on_call
+        }
+
+        fn on_call_expression_exit(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("call exit")
+        }
+    }
+
+    impl<'ast> Pass<'ast> for LogPass {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn enabled(&self) -> bool {
+            self.enabled
+        }
+
+        fn after(&self) -> &[&'static str] {
+            &self.after
+        }
+
+        fn before(&self) -> &[&'static str] {
+            &self.before
+        }
+    }
+
+    fn run(source: &str, passes: Vec<LogPass>) -> Vec<String> {
+        let log = passes[0].log.clone();
+        let arena = Arena::new();
+        let module = parse_in(&arena, source).unwrap();
+        let passes = passes.into_iter().map(|pass| Box::new(pass) as Box<dyn Pass>).collect();
+        let mut composite = Composite::new(passes).unwrap();
+
+        module.visit_with(&mut composite);
+
+        let log = log.borrow().clone();
+        log
+    }
+
+    #[test]
+    fn should_order_passes() {
+        let log = Log::default();
+        let mut a = LogPass::new("a", &log);
+        let mut b = LogPass::new("b", &log);
+        let mut c = LogPass::new("c", &log);
+        let mut d = LogPass::new("d", &log);
+
+        a.after.push("c");
+        b.before.push("a");
+        c.after.push("missing");
+        d.enabled = false;
+        d.before.push("c");
+
+        let passes: Vec<Box<dyn Pass>> = vec![Box::new(a), Box::new(b), Box::new(c), Box::new(d)];
+        let composite = Composite::new(passes).unwrap();
+
+        assert_eq!(composite.passes(), vec!["b", "c", "a"]);
+    }
+
+    #[test]
+    fn should_reject_cyclic_constraints() {
+        let log = Log::default();
+        let mut a = LogPass::new("a", &log);
+        let mut b = LogPass::new("b", &log);
+        let c = LogPass::new("c", &log);
+
+        a.after.push("b");
+        b.after.push("a");
+
+        let passes: Vec<Box<dyn Pass>> = vec![Box::new(a), Box::new(b), Box::new(c)];
+
+        assert_eq!(Composite::new(passes).err(), Some(OrderError {
+            passes: vec!["a", "b"],
+        }));
+    }
+
+    #[test]
+    fn should_call_every_pass_in_order() {
+        let log = Log::default();
+        let a = LogPass::new("a", &log);
+        let b = LogPass::new("b", &log);
+
+        assert_eq!(run("a(b);", vec![a, b]), vec![
+            "a call", "b call",
+            "a a", "b a",
+            "a b", "b b",
+            "a call exit", "b call exit",
+        ]);
+    }
+
+    #[test]
+    fn should_skip_children_per_pass() {
+        let log = Log::default();
+        let mut a = LogPass::new("a", &log);
+        let b = LogPass::new("b", &log);
+
+        a.on_call = VisitAction::SkipChildren;
+
+        assert_eq!(run("a(b); c;", vec![a, b]), vec![
+            "a call", "b call",
+            "b a",
+            "b b",
+            "a call exit", "b call exit",
+            "a c", "b c",
+        ]);
+    }
+
+    #[test]
+    fn should_stop_per_pass() {
+        let log = Log::default();
+        let mut a = LogPass::new("a", &log);
+        let b = LogPass::new("b", &log);
+
+        a.on_call = VisitAction::Stop;
+
+        assert_eq!(run("function f() { a(b); } c;", vec![a, b]), vec![
+            "a call", "b call",
+            "b a",
+            "b b",
+            "b call exit",
+            "a scopes 0", "b scopes 0",
+            "b c",
+        ]);
+    }
+
+    /// Keeps a stack of the expressions it's in.
+    struct StackPass {
+        stack: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl<'ast> Visitor<'ast> for StackPass {
+        fn on_identifier_expression(&mut self, _: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.stack.borrow_mut().push("identifier");
+            VisitAction::Continue
+        }
+
+        fn on_identifier_expression_exit(&mut self, _: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.stack.borrow_mut().pop().expect("Unbalanced exit");
+            VisitAction::Continue
+        }
+
+        fn on_call_expression(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.stack.borrow_mut().push("call");
+            VisitAction::Continue
+        }
+
+        fn on_call_expression_exit(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.stack.borrow_mut().pop().expect("Unbalanced exit");
+            VisitAction::Continue
+        }
+    }
+
+    impl<'ast> Pass<'ast> for StackPass {
+        fn name(&self) -> &'static str {
+            "stack"
+        }
+    }
+
+    /// Replaces calls with their first argument.
+    struct UnwrapPass {
+        log: Log,
+    }
+
+    impl<'ast> Visitor<'ast> for UnwrapPass {
+        fn on_identifier_expression(&mut self, item: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.log.borrow_mut().push(item.to_string());
+            VisitAction::Continue
+        }
+
+        fn on_call_expression(&mut self, item: &CallExpression<'ast>, node: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            match item.arguments.first_element() {
+                Some(&(mut argument)) => {
+                    node.set(*argument.get_mut());
+                    VisitAction::Revisit
+                },
+                None => VisitAction::Continue,
+            }
+        }
+    }
+
+    impl<'ast> Pass<'ast> for UnwrapPass {
+        fn name(&self) -> &'static str {
+            "unwrap"
+        }
+
+        fn after(&self) -> &[&'static str] {
+            &["stack"]
+        }
+    }
+
+    #[test]
+    fn should_enter_once_before_revisiting_pass() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "f(g(x)); y;").unwrap();
+        let stack = Rc::new(RefCell::new(Vec::new()));
+        let log = Log::default();
+
+        let passes: Vec<Box<dyn Pass>> = vec![
+            Box::new(UnwrapPass { log: log.clone() }),
+            Box::new(StackPass { stack: stack.clone() }),
+        ];
+        let mut composite = Composite::new(passes).unwrap();
+
+        module.visit_with(&mut composite);
+
+        assert_eq!(*log.borrow(), vec!["x", "y"]);
+        assert!(stack.borrow().is_empty());
+    }
+}
@@ -1,124 +1,147 @@
-use ratel::ast::{Identifier, Expression, ExpressionNode, StatementNode, Literal};
+use ratel::ast::{Node, Identifier, Expression, ExpressionNode, StatementNode, Literal};
 use ratel::ast::expression::*;
 
-use {Visitor, Visitable, ParentNode};
+use {current, Path, NodeRef, Slot, Visitor, Visitable, VisitAction};
 
 
 impl<'ast> Visitable<'ast> for ExpressionNode<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         use self::Expression::*;
 
-        match self.item {
-            Void => {
-                // Void doesn't have children, we return early to avoid calling pop_parent
-                return;
-            },
-            This(_) => {
-                visitor.on_this_expression(&self);
-                return;
-            },
-            Identifier(ref ident) => {
-                visitor.on_identifier_expression(ident, self);
-                visitor.push_parent(ParentNode::from(self));
-                ident.visit_with(visitor);
-            },
-            Literal(ref literal) => {
-                visitor.on_literal_expression(literal, self);
-                return;
-            },
-            Sequence(ref sequence) => {
-                visitor.on_sequence_expression(sequence, self);
-                visitor.push_parent(ParentNode::from(self));
-                sequence.visit_with(visitor);
-            },
-            Array(ref array) => {
-                visitor.on_array_expression(array, self);
-                visitor.push_parent(ParentNode::from(self));
-                array.visit_with(visitor);
-            },
-            Member(ref member) => {
-                visitor.on_member_expression(member, self);
-                visitor.push_parent(ParentNode::from(self));
-                member.visit_with(visitor);
-            },
-            ComputedMember(ref computed) => {
-                visitor.on_computed_member_expression(computed, self);
-                visitor.push_parent(ParentNode::from(self));
-                computed.visit_with(visitor);
-            },
-            MetaProperty(ref property) => {
-                visitor.on_meta_property(property, self);
-                visitor.push_parent(ParentNode::from(self));
-                property.visit_with(visitor);
-            },
-            Call(ref call) => {
-                visitor.on_call_expression(call, self);
-                visitor.push_parent(ParentNode::from(self));
-                call.visit_with(visitor);
-            },
-            Binary(ref binary) => {
-                visitor.on_binary_expression(binary, self);
-                visitor.push_parent(ParentNode::from(self));
-                binary.visit_with(visitor);
-            },
-            Prefix(ref prefix) => {
-                visitor.on_prefix_expression(prefix, self);
-                visitor.push_parent(ParentNode::from(self));
-                prefix.visit_with(visitor);
-            },
-            Postfix(ref postfix) => {
-                visitor.on_postfix_expression(postfix, self);
-                visitor.push_parent(ParentNode::from(self));
-                postfix.visit_with(visitor);
-            },
-            Conditional(ref conditional) => {
-                visitor.on_conditional_expression(conditional, self);
-                visitor.push_parent(ParentNode::from(self));
-                conditional.visit_with(visitor);
-            },
-            Template(ref template) => {
-                visitor.on_template_literal(template, self);
-                visitor.push_parent(ParentNode::from(self));
-                template.visit_with(visitor);
-            },
-            TaggedTemplate(ref tagged) => {
-                visitor.on_tagged_template_expression(tagged, self);
-                visitor.push_parent(ParentNode::from(self));
-                tagged.visit_with(visitor);
-            },
-            Spread(ref spread) => {
-                visitor.on_spread_expression(spread, self);
-                visitor.push_parent(ParentNode::from(self));
-                spread.visit_with(visitor);
-            },
-            Arrow(ref arrow) => {
-                visitor.on_arrow_expression(arrow, self);
-                visitor.push_parent(ParentNode::from(self));
-                arrow.visit_with(visitor);
-            },
-            Object(ref object) => {
-                visitor.on_object_expression(object, self);
-                visitor.push_parent(ParentNode::from(self));
-                object.visit_with(visitor);
-            },
-            Function(ref function) => {
-                visitor.on_function_expression(function, self);
-                visitor.push_parent(ParentNode::from(self));
-                function.visit_with(visitor);
-            },
-            Class(ref class) => {
-                visitor.on_class_expression(class, self);
-                visitor.push_parent(ParentNode::from(self));
-                class.visit_with(visitor);
+        loop {
+            match current(self).item {
+                Void => return VisitAction::Continue,
+                This(_) => walk_node!(
+                    path, self,
+                    visitor.on_this_expression(self, path),
+                    visitor.on_this_expression_exit(self, path)
+                ),
+                Super(_) => walk_node!(
+                    path, self,
+                    visitor.on_super_expression(self, path),
+                    visitor.on_super_expression_exit(self, path)
+                ),
+                Identifier(ref ident) => walk_node!(
+                    path, self,
+                    visitor.on_identifier_expression(ident, self, path),
+                    ident.visit_with_path(visitor, path),
+                    visitor.on_identifier_expression_exit(ident, self, path)
+                ),
+                Literal(ref literal) => walk_node!(
+                    path, self,
+                    visitor.on_literal_expression(literal, self, path),
+                    visitor.on_literal_expression_exit(literal, self, path)
+                ),
+                Sequence(ref sequence) => walk_node!(
+                    path, self,
+                    visitor.on_sequence_expression(sequence, self, path),
+                    sequence.visit_with_path(visitor, path),
+                    visitor.on_sequence_expression_exit(sequence, self, path)
+                ),
+                Array(ref array) => walk_node!(
+                    path, self,
+                    visitor.on_array_expression(array, self, path),
+                    array.visit_with_path(visitor, path),
+                    visitor.on_array_expression_exit(array, self, path)
+                ),
+                Member(ref member) => walk_node!(
+                    path, self,
+                    visitor.on_member_expression(member, self, path),
+                    member.visit_with_path(visitor, path),
+                    visitor.on_member_expression_exit(member, self, path)
+                ),
+                ComputedMember(ref computed) => walk_node!(
+                    path, self,
+                    visitor.on_computed_member_expression(computed, self, path),
+                    computed.visit_with_path(visitor, path),
+                    visitor.on_computed_member_expression_exit(computed, self, path)
+                ),
+                MetaProperty(ref property) => walk_node!(
+                    path, self,
+                    visitor.on_meta_property(property, self, path),
+                    property.visit_with_path(visitor, path),
+                    visitor.on_meta_property_exit(property, self, path)
+                ),
+                Call(ref call) => walk_node!(
+                    path, self,
+                    visitor.on_call_expression(call, self, path),
+                    call.visit_with_path(visitor, path),
+                    visitor.on_call_expression_exit(call, self, path)
+                ),
+                Binary(ref binary) => walk_node!(
+                    path, self,
+                    visitor.on_binary_expression(binary, self, path),
+                    binary.visit_with_path(visitor, path),
+                    visitor.on_binary_expression_exit(binary, self, path)
+                ),
+                Prefix(ref prefix) => walk_node!(
+                    path, self,
+                    visitor.on_prefix_expression(prefix, self, path),
+                    prefix.visit_with_path(visitor, path),
+                    visitor.on_prefix_expression_exit(prefix, self, path)
+                ),
+                Postfix(ref postfix) => walk_node!(
+                    path, self,
+                    visitor.on_postfix_expression(postfix, self, path),
+                    postfix.visit_with_path(visitor, path),
+                    visitor.on_postfix_expression_exit(postfix, self, path)
+                ),
+                Conditional(ref conditional) => walk_node!(
+                    path, self,
+                    visitor.on_conditional_expression(conditional, self, path),
+                    conditional.visit_with_path(visitor, path),
+                    visitor.on_conditional_expression_exit(conditional, self, path)
+                ),
+                Template(ref template) => walk_node!(
+                    path, self,
+                    visitor.on_template_literal(template, self, path),
+                    template.visit_with_path(visitor, path),
+                    visitor.on_template_literal_exit(template, self, path)
+                ),
+                TaggedTemplate(ref tagged) => walk_node!(
+                    path, self,
+                    visitor.on_tagged_template_expression(tagged, self, path),
+                    tagged.visit_with_path(visitor, path),
+                    visitor.on_tagged_template_expression_exit(tagged, self, path)
+                ),
+                Spread(ref spread) => walk_node!(
+                    path, self,
+                    visitor.on_spread_expression(spread, self, path),
+                    spread.visit_with_path(visitor, path),
+                    visitor.on_spread_expression_exit(spread, self, path)
+                ),
+                Arrow(ref arrow) => walk_node!(
+                    path, self,
+                    visitor.on_arrow_expression(arrow, self, path),
+                    arrow.visit_with_path(visitor, path),
+                    visitor.on_arrow_expression_exit(arrow, self, path)
+                ),
+                Object(ref object) => walk_node!(
+                    path, self,
+                    visitor.on_object_expression(object, self, path),
+                    object.visit_with_path(visitor, path),
+                    visitor.on_object_expression_exit(object, self, path)
+                ),
+                Function(ref function) => walk_node!(
+                    path, self,
+                    visitor.on_function_expression(function, self, path),
+                    function.visit_with_path(visitor, path),
+                    visitor.on_function_expression_exit(function, self, path)
+                ),
+                Class(ref class) => walk_node!(
+                    path, self,
+                    visitor.on_class_expression(class, self, path),
+                    class.visit_with_path(visitor, path),
+                    visitor.on_class_expression_exit(class, self, path)
+                ),
             }
         }
-        visitor.pop_parent();
     }
 }
 
@@ -126,15 +149,27 @@ impl<'ast> Visitable<'ast> for ThisExpression {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V: Visitor<'ast>>(&self, _: &mut V) {}
+    fn visit_with_path<V: Visitor<'ast>>(&self, _: &mut V, _: &mut Path<'ast>) -> VisitAction {
+        VisitAction::Continue
+    }
+}
+
+impl<'ast> Visitable<'ast> for SuperExpression {
+    type Parent = ExpressionNode<'ast>;
+
+    #[inline]
+    fn visit_with_path<V: Visitor<'ast>>(&self, _: &mut V, _: &mut Path<'ast>) -> VisitAction {
+        VisitAction::Continue
+    }
 }
 
 impl<'ast> Visitable<'ast> for Identifier<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V: Visitor<'ast>>(&self, visitor: &mut V) {
-        visitor.on_reference_use(self);
+    fn visit_with_path<V: Visitor<'ast>>(&self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction {
+        visitor.on_reference_use(self, path);
+        VisitAction::Continue
     }
 }
 
@@ -142,18 +177,21 @@ impl<'ast> Visitable<'ast> for Literal<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V: Visitor<'ast>>(&self, _: &mut V) {}
+    fn visit_with_path<V: Visitor<'ast>>(&self, _: &mut V, _: &mut Path<'ast>) -> VisitAction {
+        VisitAction::Continue
+    }
 }
 
 impl<'ast> Visitable<'ast> for SequenceExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.body.visit_with(visitor);
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -161,11 +199,12 @@ impl<'ast> Visitable<'ast> for ArrayExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.body.visit_with(visitor);
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -173,11 +212,12 @@ impl<'ast> Visitable<'ast> for MemberExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.object.visit_with(visitor);
+        path.field("object");
+        self.object.visit_with_path(visitor, path)
     }
 }
 
@@ -185,12 +225,14 @@ impl<'ast> Visitable<'ast> for ComputedMemberExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.object.visit_with(visitor);
-        self.property.visit_with(visitor);
+        path.field("object");
+        try_visit!(self.object.visit_with_path(visitor, path));
+        path.field("property");
+        self.property.visit_with_path(visitor, path)
     }
 }
 
@@ -198,12 +240,14 @@ impl<'ast> Visitable<'ast> for MetaPropertyExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.meta.visit_with(visitor);
-        self.property.visit_with(visitor);
+        path.field("meta");
+        try_visit!(self.meta.visit_with_path(visitor, path));
+        path.field("property");
+        self.property.visit_with_path(visitor, path)
     }
 }
 
@@ -211,12 +255,14 @@ impl<'ast> Visitable<'ast> for CallExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.callee.visit_with(visitor);
-        self.arguments.visit_with(visitor);
+        path.field("callee");
+        try_visit!(self.callee.visit_with_path(visitor, path));
+        path.field("arguments");
+        self.arguments.visit_with_path(visitor, path)
     }
 }
 
@@ -224,12 +270,14 @@ impl<'ast> Visitable<'ast> for BinaryExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.left.visit_with(visitor);
-        self.right.visit_with(visitor);
+        path.field("left");
+        try_visit!(self.left.visit_with_path(visitor, path));
+        path.field("right");
+        self.right.visit_with_path(visitor, path)
     }
 }
 
@@ -237,11 +285,12 @@ impl<'ast> Visitable<'ast> for PrefixExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.operand.visit_with(visitor);
+        path.field("operand");
+        self.operand.visit_with_path(visitor, path)
     }
 }
 
@@ -249,11 +298,12 @@ impl<'ast> Visitable<'ast> for PostfixExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.operand.visit_with(visitor);
+        path.field("operand");
+        self.operand.visit_with_path(visitor, path)
     }
 }
 
@@ -261,13 +311,16 @@ impl<'ast> Visitable<'ast> for ConditionalExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.test.visit_with(visitor);
-        self.consequent.visit_with(visitor);
-        self.alternate.visit_with(visitor);
+        path.field("test");
+        try_visit!(self.test.visit_with_path(visitor, path));
+        path.field("consequent");
+        try_visit!(self.consequent.visit_with_path(visitor, path));
+        path.field("alternate");
+        self.alternate.visit_with_path(visitor, path)
     }
 }
 
@@ -275,11 +328,39 @@ impl<'ast> Visitable<'ast> for TemplateLiteral<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.expressions.visit_with(visitor);
+        let mut expressions = self.expressions.iter();
+
+        // Quasis and expressions alternate, starting and ending with a quasi
+        for (index, quasi) in self.quasis.iter().enumerate() {
+            path.set_slot(Some(Slot::new("quasis").at(index)));
+            try_visit!(visit_quasi(quasi, visitor, path));
+
+            if let Some(expression) = expressions.next() {
+                path.set_slot(Some(Slot::new("expressions").at(index)));
+                try_visit!(expression.visit_with_path(visitor, path));
+            }
+        }
+        VisitAction::Continue
+    }
+}
+
+#[inline]
+fn visit_quasi<'ast, V>(node: &'ast Node<'ast, &'ast str>, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
+where
+    V: Visitor<'ast>,
+{
+    loop {
+        let item = current(node).item;
+
+        walk_node!(
+            path, NodeRef::TemplateQuasi(node),
+            visitor.on_template_quasi(item, node, path),
+            visitor.on_template_quasi_exit(item, node, path)
+        )
     }
 }
 
@@ -287,12 +368,14 @@ impl<'ast> Visitable<'ast> for TaggedTemplateExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.tag.visit_with(visitor);
-        self.quasi.visit_with(visitor);
+        path.field("tag");
+        try_visit!(self.tag.visit_with_path(visitor, path));
+        path.field("quasi");
+        self.quasi.visit_with_path(visitor, path)
     }
 }
 
@@ -300,11 +383,12 @@ impl<'ast> Visitable<'ast> for SpreadExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.argument.visit_with(visitor);
+        path.field("argument");
+        self.argument.visit_with_path(visitor, path)
     }
 }
 
@@ -312,13 +396,13 @@ impl<'ast> Visitable<'ast> for ArrowBody<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         match *self {
-            ArrowBody::Expression(ref expression) => expression.visit_with(visitor),
-            ArrowBody::Block(ref block)           => block.body.visit_with(visitor),
+            ArrowBody::Expression(ref expression) => expression.visit_with_path(visitor, path),
+            ArrowBody::Block(ref block)           => block.body.visit_with_path(visitor, path),
         }
     }
 }
@@ -327,12 +411,14 @@ impl<'ast> Visitable<'ast> for ArrowExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.params.visit_with(visitor);
-        self.body.visit_with(visitor);
+        path.field("params");
+        try_visit!(self.params.visit_with_path(visitor, path));
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -340,10 +426,11 @@ impl<'ast> Visitable<'ast> for ObjectExpression<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.body.visit_with(visitor);
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
@@ -0,0 +1,564 @@
+//! Rewriting the AST by folding.
+//!
+//! Unlike a `Visitor`, which can only replace nodes in place with `Node::set`,
+//! a `Fold` takes each node and returns its replacement, rebuilding the parents
+//! on the way up. Statements fold into a list, so they can just as well be
+//! removed or replaced with many statements.
+//!
+//! Every method of `Fold` defaults to the function of the same name in this
+//! module, which folds the children of the node. Overriding methods can call
+//! it to keep folding the children of the nodes they don't replace.
+
+use ratel::ast::{Node, Loc, NodeList, Block, BlockNode, Pattern, Property, PropertyKey};
+use ratel::ast::{ExpressionNode, ExpressionList, Statement, StatementNode, StatementList};
+use ratel::ast::{Declarator, Function, Class, ClassMember, Name};
+use ratel::ast::expression::*;
+use ratel::ast::statement::*;
+use ratel::{Arena, Module};
+
+/// Rewrites the AST, allocating the rebuilt nodes on its `Arena`.
+pub trait Fold<'ast>: Sized {
+    /// `Arena` on which the rebuilt nodes are allocated.
+    fn arena(&self) -> &'ast Arena;
+
+    /// Fold the body of the module and put the result back.
+    fn fold_module(&mut self, module: &mut Module<'ast>) {
+        let body = self.fold_statement_list(module.body());
+
+        module.set_body(body);
+    }
+
+    fn fold_statement_list(&mut self, list: StatementList<'ast>) -> StatementList<'ast> {
+        fold_statement_list(self, list)
+    }
+
+    /// Return an empty list to remove the statement, or many statements to
+    /// splice them in its place. Where the grammar only allows a single
+    /// statement, such as the body of a loop, no statements become an
+    /// `EmptyStatement` and many statements are wrapped in a block.
+    fn fold_statement(&mut self, node: StatementNode<'ast>) -> StatementList<'ast> {
+        fold_statement(self, node)
+    }
+
+    fn fold_expression(&mut self, node: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        fold_expression(self, node)
+    }
+
+    fn fold_expression_list(&mut self, list: ExpressionList<'ast>) -> ExpressionList<'ast> {
+        fold_expression_list(self, list)
+    }
+
+    fn fold_pattern(&mut self, node: Node<'ast, Pattern<'ast>>) -> Node<'ast, Pattern<'ast>> {
+        fold_pattern(self, node)
+    }
+
+    fn fold_property(&mut self, node: Node<'ast, Property<'ast>>) -> Node<'ast, Property<'ast>> {
+        fold_property(self, node)
+    }
+
+    fn fold_property_key(&mut self, node: Node<'ast, PropertyKey<'ast>>) -> Node<'ast, PropertyKey<'ast>> {
+        fold_property_key(self, node)
+    }
+
+    fn fold_class_member(&mut self, node: Node<'ast, ClassMember<'ast>>) -> Node<'ast, ClassMember<'ast>> {
+        fold_class_member(self, node)
+    }
+
+    fn fold_declarator(&mut self, node: Node<'ast, Declarator<'ast>>) -> Node<'ast, Declarator<'ast>> {
+        fold_declarator(self, node)
+    }
+
+    fn fold_for_init(&mut self, node: Node<'ast, ForInit<'ast>>) -> Node<'ast, ForInit<'ast>> {
+        fold_for_init(self, node)
+    }
+
+    fn fold_catch_clause(&mut self, node: Node<'ast, CatchClause<'ast>>) -> Node<'ast, CatchClause<'ast>> {
+        fold_catch_clause(self, node)
+    }
+
+    fn fold_switch_case(&mut self, node: Node<'ast, SwitchCase<'ast>>) -> Node<'ast, SwitchCase<'ast>> {
+        fold_switch_case(self, node)
+    }
+
+    fn fold_block(&mut self, node: BlockNode<'ast, Statement<'ast>>) -> BlockNode<'ast, Statement<'ast>> {
+        fold_block(self, node)
+    }
+
+    fn fold_function<N: Name<'ast>>(&mut self, function: Function<'ast, N>) -> Function<'ast, N> {
+        fold_function(self, function)
+    }
+
+    fn fold_class<N: Name<'ast>>(&mut self, class: Class<'ast, N>) -> Class<'ast, N> {
+        fold_class(self, class)
+    }
+
+    fn fold_template_literal(&mut self, template: TemplateLiteral<'ast>) -> TemplateLiteral<'ast> {
+        fold_template_literal(self, template)
+    }
+}
+
+/// Allocate the rebuilt item with the span of the original node.
+#[inline]
+fn alloc_as<'ast, S, T: Copy>(arena: &'ast Arena, source: &Loc<S>, item: T) -> Node<'ast, T> {
+    Node::new(arena.alloc(Loc::new(source.start, source.end, item)))
+}
+
+#[inline]
+fn fold_list<'ast, T, F>(arena: &'ast Arena, list: NodeList<'ast, T>, fold: F) -> NodeList<'ast, T>
+where
+    T: 'ast + Copy,
+    F: FnMut(Node<'ast, T>) -> Node<'ast, T>,
+{
+    NodeList::from_iter(arena, list.iter().cloned().map(fold))
+}
+
+#[inline]
+fn fold_patterns<'ast, F: Fold<'ast>>(folder: &mut F, list: NodeList<'ast, Pattern<'ast>>) -> NodeList<'ast, Pattern<'ast>> {
+    fold_list(folder.arena(), list, |pattern| folder.fold_pattern(pattern))
+}
+
+/// Fold a statement in a position that only allows a single statement.
+fn fold_body<'ast, F: Fold<'ast>>(folder: &mut F, node: StatementNode<'ast>) -> StatementNode<'ast> {
+    let body = folder.fold_statement(node);
+
+    if let Some(statement) = body.only_element() {
+        return *statement;
+    }
+
+    let item = if body.is_empty() {
+        Statement::Empty
+    } else {
+        Statement::Block(Block { body })
+    };
+
+    alloc_as(folder.arena(), &node, item)
+}
+
+#[inline]
+fn fold_declaration<'ast, F: Fold<'ast>>(folder: &mut F, declaration: DeclarationStatement<'ast>) -> DeclarationStatement<'ast> {
+    DeclarationStatement {
+        kind: declaration.kind,
+        declarators: fold_list(folder.arena(), declaration.declarators, |declarator| folder.fold_declarator(declarator)),
+    }
+}
+
+pub fn fold_statement_list<'ast, F: Fold<'ast>>(folder: &mut F, list: StatementList<'ast>) -> StatementList<'ast> {
+    let arena = folder.arena();
+
+    NodeList::from_iter(arena, list.iter().flat_map(|statement| folder.fold_statement(*statement).iter().cloned()))
+}
+
+pub fn fold_statement<'ast, F: Fold<'ast>>(folder: &mut F, node: StatementNode<'ast>) -> StatementList<'ast> {
+    use ratel::ast::Statement::*;
+
+    let arena = folder.arena();
+
+    let item = match node.item {
+        Empty | Debugger | Break(_) | Continue(_) | Import(_) => return NodeList::from(arena, node),
+        Expression(expression) => Expression(folder.fold_expression(expression)),
+        Declaration(declaration) => Declaration(fold_declaration(folder, declaration)),
+        Return(ReturnStatement { value }) => Return(ReturnStatement {
+            value: value.map(|value| folder.fold_expression(value)),
+        }),
+        Throw(ThrowStatement { value }) => Throw(ThrowStatement {
+            value: folder.fold_expression(value),
+        }),
+        If(IfStatement { test, consequent, alternate }) => If(IfStatement {
+            test: folder.fold_expression(test),
+            consequent: fold_body(folder, consequent),
+            alternate: alternate.map(|alternate| fold_body(folder, alternate)),
+        }),
+        While(WhileStatement { test, body }) => While(WhileStatement {
+            test: folder.fold_expression(test),
+            body: fold_body(folder, body),
+        }),
+        Do(DoStatement { body, test }) => Do(DoStatement {
+            body: fold_body(folder, body),
+            test: folder.fold_expression(test),
+        }),
+        For(ForStatement { init, test, update, body }) => For(ForStatement {
+            init: init.map(|init| folder.fold_for_init(init)),
+            test: test.map(|test| folder.fold_expression(test)),
+            update: update.map(|update| folder.fold_expression(update)),
+            body: fold_body(folder, body),
+        }),
+        ForIn(ForInStatement { left, right, body }) => ForIn(ForInStatement {
+            left: folder.fold_for_init(left),
+            right: folder.fold_expression(right),
+            body: fold_body(folder, body),
+        }),
+        ForOf(ForOfStatement { left, right, body }) => ForOf(ForOfStatement {
+            left: folder.fold_for_init(left),
+            right: folder.fold_expression(right),
+            body: fold_body(folder, body),
+        }),
+        Try(TryStatement { block, handler, finalizer }) => Try(TryStatement {
+            block: folder.fold_block(block),
+            handler: handler.map(|handler| folder.fold_catch_clause(handler)),
+            finalizer: finalizer.map(|finalizer| folder.fold_block(finalizer)),
+        }),
+        Block(block) => Block(ratel::ast::Block {
+            body: folder.fold_statement_list(block.body),
+        }),
+        Labeled(LabeledStatement { label, body }) => Labeled(LabeledStatement {
+            label,
+            body: fold_body(folder, body),
+        }),
+        Function(function) => Function(folder.fold_function(function)),
+        Class(class) => Class(folder.fold_class(class)),
+        Switch(SwitchStatement { discriminant, cases }) => Switch(SwitchStatement {
+            discriminant: folder.fold_expression(discriminant),
+            cases: alloc_as(arena, &cases, ratel::ast::Block {
+                body: fold_list(arena, cases.body, |case| folder.fold_switch_case(case)),
+            }),
+        }),
+    };
+
+    NodeList::from(arena, alloc_as(arena, &node, item))
+}
+
+pub fn fold_expression<'ast, F: Fold<'ast>>(folder: &mut F, node: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+    use ratel::ast::Expression::*;
+
+    let arena = folder.arena();
+
+    let item = match node.item {
+        Void | This(_) | Super(_) | Identifier(_) | Literal(_) | MetaProperty(_) => return node,
+        Sequence(SequenceExpression { body }) => Sequence(SequenceExpression {
+            body: folder.fold_expression_list(body),
+        }),
+        Array(ArrayExpression { body }) => Array(ArrayExpression {
+            body: folder.fold_expression_list(body),
+        }),
+        Member(MemberExpression { object, property }) => Member(MemberExpression {
+            object: folder.fold_expression(object),
+            property,
+        }),
+        ComputedMember(ComputedMemberExpression { object, property }) => ComputedMember(ComputedMemberExpression {
+            object: folder.fold_expression(object),
+            property: folder.fold_expression(property),
+        }),
+        Call(CallExpression { callee, arguments }) => Call(CallExpression {
+            callee: folder.fold_expression(callee),
+            arguments: folder.fold_expression_list(arguments),
+        }),
+        Binary(BinaryExpression { operator, left, right }) => Binary(BinaryExpression {
+            operator,
+            left: folder.fold_expression(left),
+            right: folder.fold_expression(right),
+        }),
+        Prefix(PrefixExpression { operator, operand }) => Prefix(PrefixExpression {
+            operator,
+            operand: folder.fold_expression(operand),
+        }),
+        Postfix(PostfixExpression { operator, operand }) => Postfix(PostfixExpression {
+            operator,
+            operand: folder.fold_expression(operand),
+        }),
+        Conditional(ConditionalExpression { test, consequent, alternate }) => Conditional(ConditionalExpression {
+            test: folder.fold_expression(test),
+            consequent: folder.fold_expression(consequent),
+            alternate: folder.fold_expression(alternate),
+        }),
+        Template(template) => Template(folder.fold_template_literal(template)),
+        TaggedTemplate(TaggedTemplateExpression { tag, quasi }) => TaggedTemplate(TaggedTemplateExpression {
+            tag: folder.fold_expression(tag),
+            quasi: alloc_as(arena, &quasi, folder.fold_template_literal(quasi.item)),
+        }),
+        Spread(SpreadExpression { argument }) => Spread(SpreadExpression {
+            argument: folder.fold_expression(argument),
+        }),
+        Arrow(ArrowExpression { params, body }) => Arrow(ArrowExpression {
+            params: fold_patterns(folder, params),
+            body: match body {
+                ArrowBody::Expression(expression) => ArrowBody::Expression(folder.fold_expression(expression)),
+                ArrowBody::Block(block) => ArrowBody::Block(folder.fold_block(block)),
+            },
+        }),
+        Object(ObjectExpression { body }) => Object(ObjectExpression {
+            body: fold_list(arena, body, |property| folder.fold_property(property)),
+        }),
+        Function(function) => Function(folder.fold_function(function)),
+        Class(class) => Class(folder.fold_class(class)),
+    };
+
+    alloc_as(arena, &node, item)
+}
+
+pub fn fold_expression_list<'ast, F: Fold<'ast>>(folder: &mut F, list: ExpressionList<'ast>) -> ExpressionList<'ast> {
+    fold_list(folder.arena(), list, |expression| folder.fold_expression(expression))
+}
+
+pub fn fold_pattern<'ast, F: Fold<'ast>>(folder: &mut F, node: Node<'ast, Pattern<'ast>>) -> Node<'ast, Pattern<'ast>> {
+    use ratel::ast::Pattern::*;
+
+    let arena = folder.arena();
+
+    let item = match node.item {
+        Void | Identifier(_) | RestElement { .. } => return node,
+        ObjectPattern { properties } => ObjectPattern {
+            properties: fold_list(arena, properties, |property| folder.fold_property(property)),
+        },
+        ArrayPattern { elements } => ArrayPattern {
+            elements: fold_patterns(folder, elements),
+        },
+        AssignmentPattern { left, right } => AssignmentPattern {
+            left: folder.fold_pattern(left),
+            right: folder.fold_expression(right),
+        },
+    };
+
+    alloc_as(arena, &node, item)
+}
+
+pub fn fold_property<'ast, F: Fold<'ast>>(folder: &mut F, node: Node<'ast, Property<'ast>>) -> Node<'ast, Property<'ast>> {
+    use ratel::ast::Property::*;
+
+    let arena = folder.arena();
+
+    let item = match node.item {
+        Shorthand(_) => return node,
+        Literal { key, value } => Literal {
+            key: folder.fold_property_key(key),
+            value: folder.fold_expression(value),
+        },
+        Method { key, value } => Method {
+            key: folder.fold_property_key(key),
+            value: alloc_as(arena, &value, folder.fold_function(value.item)),
+        },
+        Spread { argument } => Spread {
+            argument: folder.fold_expression(argument),
+        },
+    };
+
+    alloc_as(arena, &node, item)
+}
+
+pub fn fold_property_key<'ast, F: Fold<'ast>>(folder: &mut F, node: Node<'ast, PropertyKey<'ast>>) -> Node<'ast, PropertyKey<'ast>> {
+    match node.item {
+        PropertyKey::Computed(expression) => {
+            let expression = folder.fold_expression(expression);
+
+            alloc_as(folder.arena(), &node, PropertyKey::Computed(expression))
+        },
+        _ => node,
+    }
+}
+
+pub fn fold_class_member<'ast, F: Fold<'ast>>(folder: &mut F, node: Node<'ast, ClassMember<'ast>>) -> Node<'ast, ClassMember<'ast>> {
+    use ratel::ast::ClassMember::*;
+
+    let arena = folder.arena();
+
+    let item = match node.item {
+        Error => return node,
+        Method { is_static, key, kind, value } => Method {
+            is_static,
+            key: folder.fold_property_key(key),
+            kind,
+            value: alloc_as(arena, &value, folder.fold_function(value.item)),
+        },
+        Literal { is_static, key, value } => Literal {
+            is_static,
+            key: folder.fold_property_key(key),
+            value: folder.fold_expression(value),
+        },
+    };
+
+    alloc_as(arena, &node, item)
+}
+
+pub fn fold_declarator<'ast, F: Fold<'ast>>(folder: &mut F, node: Node<'ast, Declarator<'ast>>) -> Node<'ast, Declarator<'ast>> {
+    let item = Declarator {
+        id: folder.fold_pattern(node.id),
+        init: node.init.map(|init| folder.fold_expression(init)),
+    };
+
+    alloc_as(folder.arena(), &node, item)
+}
+
+pub fn fold_for_init<'ast, F: Fold<'ast>>(folder: &mut F, node: Node<'ast, ForInit<'ast>>) -> Node<'ast, ForInit<'ast>> {
+    let item = match node.item {
+        ForInit::Declaration(declaration) => ForInit::Declaration(fold_declaration(folder, declaration)),
+        ForInit::Expression(expression) => ForInit::Expression(folder.fold_expression(expression)),
+    };
+
+    alloc_as(folder.arena(), &node, item)
+}
+
+pub fn fold_catch_clause<'ast, F: Fold<'ast>>(folder: &mut F, node: Node<'ast, CatchClause<'ast>>) -> Node<'ast, CatchClause<'ast>> {
+    let item = CatchClause {
+        param: folder.fold_pattern(node.param),
+        body: folder.fold_block(node.body),
+    };
+
+    alloc_as(folder.arena(), &node, item)
+}
+
+pub fn fold_switch_case<'ast, F: Fold<'ast>>(folder: &mut F, node: Node<'ast, SwitchCase<'ast>>) -> Node<'ast, SwitchCase<'ast>> {
+    let item = SwitchCase {
+        test: node.test.map(|test| folder.fold_expression(test)),
+        consequent: folder.fold_statement_list(node.consequent),
+    };
+
+    alloc_as(folder.arena(), &node, item)
+}
+
+pub fn fold_block<'ast, F: Fold<'ast>>(folder: &mut F, node: BlockNode<'ast, Statement<'ast>>) -> BlockNode<'ast, Statement<'ast>> {
+    let item = Block {
+        body: folder.fold_statement_list(node.body),
+    };
+
+    alloc_as(folder.arena(), &node, item)
+}
+
+pub fn fold_function<'ast, F: Fold<'ast>, N: Name<'ast>>(folder: &mut F, function: Function<'ast, N>) -> Function<'ast, N> {
+    Function {
+        name: function.name,
+        generator: function.generator,
+        params: fold_patterns(folder, function.params),
+        body: folder.fold_block(function.body),
+    }
+}
+
+pub fn fold_class<'ast, F: Fold<'ast>, N: Name<'ast>>(folder: &mut F, class: Class<'ast, N>) -> Class<'ast, N> {
+    let arena = folder.arena();
+
+    Class {
+        name: class.name,
+        extends: class.extends.map(|extends| folder.fold_expression(extends)),
+        body: alloc_as(arena, &class.body, Block {
+            body: fold_list(arena, class.body.body, |member| folder.fold_class_member(member)),
+        }),
+    }
+}
+
+pub fn fold_template_literal<'ast, F: Fold<'ast>>(folder: &mut F, template: TemplateLiteral<'ast>) -> TemplateLiteral<'ast> {
+    TemplateLiteral {
+        expressions: folder.fold_expression_list(template.expressions),
+        quasis: template.quasis,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratel::ast::Expression;
+    use ratel::parse_in;
+
+    struct Identity<'ast> {
+        arena: &'ast Arena,
+    }
+
+    impl<'ast> Fold<'ast> for Identity<'ast> {
+        fn arena(&self) -> &'ast Arena {
+            self.arena
+        }
+    }
+
+    /// Removes `x;`, splits sequences in expression statements into
+    /// separate statements and renames `a` to `b`.
+    struct Rewrite<'ast> {
+        arena: &'ast Arena,
+    }
+
+    impl<'ast> Fold<'ast> for Rewrite<'ast> {
+        fn arena(&self) -> &'ast Arena {
+            self.arena
+        }
+
+        fn fold_statement(&mut self, node: StatementNode<'ast>) -> StatementList<'ast> {
+            match node.item {
+                Statement::Expression(expression) => match expression.item {
+                    Expression::Identifier("x") => NodeList::empty(),
+                    Expression::Sequence(SequenceExpression { body }) => {
+                        NodeList::from_iter(self.arena, body.iter().map(|expression| {
+                            let expression = self.fold_expression(*expression);
+
+                            alloc_as(self.arena, &expression, Statement::Expression(expression))
+                        }))
+                    },
+                    _ => fold_statement(self, node),
+                },
+                _ => fold_statement(self, node),
+            }
+        }
+
+        fn fold_expression(&mut self, node: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+            match node.item {
+                Expression::Identifier("a") => alloc_as(self.arena, &node, Expression::Identifier("b")),
+                _ => fold_expression(self, node),
+            }
+        }
+    }
+
+    fn assert_folds<'ast, F: Fold<'ast>>(folder: &mut F, source: &str, expected: &str) {
+        let arena = folder.arena();
+        let mut module = parse_in(arena, source).unwrap();
+        let expected = parse_in(arena, expected).unwrap();
+
+        folder.fold_module(&mut module);
+
+        assert_eq!(module.body(), expected.body());
+    }
+
+    #[test]
+    fn should_rebuild_unchanged() {
+        let arena = Arena::new();
+        let source = r#"
+            class Foo extends Bar { constructor() { this.baz = [1, ...qux]; } static get x() {} }
+            function foo(a, { b, c: [d] }, e = 1, ...f) { return `${a}-${b}` + tag`${c}`; }
+            for (let i = 0; i < 10; i++) { label: switch (i) { case 1: break label; default: i--; } }
+            for (var key in obj) if (key) continue; else debugger;
+            try { throw new Error('x'); } catch (err) {} finally {}
+            var obj = { a, [b]: c, d() {} }, re = /ab+c/g, fn = (x) => x * 2;
+            do { x = !x ? y : z; } while (x);
+        "#;
+
+        let mut module = parse_in(&arena, source).unwrap();
+        let body = module.body();
+
+        Identity { arena: &arena }.fold_module(&mut module);
+
+        assert_eq!(module.body(), body);
+        assert_eq!(module.body(), parse_in(&arena, source).unwrap().body());
+    }
+
+    #[test]
+    fn should_not_alias_rebuilt_nodes() {
+        let arena = Arena::new();
+        let mut module = parse_in(&arena, "foo(a);").unwrap();
+        let body = module.body();
+
+        Rewrite { arena: &arena }.fold_module(&mut module);
+
+        assert_eq!(body, parse_in(&arena, "foo(a);").unwrap().body());
+        assert_eq!(module.body(), parse_in(&arena, "foo(b);").unwrap().body());
+    }
+
+    #[test]
+    fn should_remove_and_splice_statements() {
+        let arena = Arena::new();
+
+        assert_folds(&mut Rewrite { arena: &arena }, "x; a, c; d;", "b; c; d;");
+        assert_folds(&mut Rewrite { arena: &arena }, "function f() { x; return a; }", "function f() { return b; }");
+        assert_folds(&mut Rewrite { arena: &arena }, "switch (a) { case a: x; c, d; }", "switch (b) { case b: c; d; }");
+    }
+
+    #[test]
+    fn should_fit_statements_in_single_statement_positions() {
+        let arena = Arena::new();
+
+        assert_folds(&mut Rewrite { arena: &arena }, "if (a) c, d; else a;", "if (b) { c; d; } else b;");
+        assert_folds(&mut Rewrite { arena: &arena }, "while (a) a, c;", "while (b) { b; c; }");
+
+        let mut module = parse_in(&arena, "if (a) x;").unwrap();
+
+        Rewrite { arena: &arena }.fold_module(&mut module);
+
+        match module.body().only_element().unwrap().item {
+            Statement::If(IfStatement { consequent, .. }) => assert_eq!(consequent.item, Statement::Empty),
+            _ => panic!("Expected an if statement"),
+        }
+    }
+}
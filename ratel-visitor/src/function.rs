@@ -1,38 +1,43 @@
 use ratel::ast::{Function, Class, ClassMember, Name, EmptyName, OptionalName, MandatoryName};
 use ratel::ast::{Node, ExpressionNode, StatementNode};
 
-use {Visitable, Visitor, ScopeKind, NoParent};
+use {current, Path, Visitable, Visitor, VisitAction, ScopeKind, NoParent};
 
 
 impl<'ast> Visitable<'ast> for EmptyName {
     type Parent = NoParent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, _: &mut V)
+    fn visit_with_path<V>(&'ast self, _: &mut V, _: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
-    {}
+    {
+        VisitAction::Continue
+    }
 }
 
 impl<'ast> Visitable<'ast> for OptionalName<'ast> {
     type Parent = ExpressionNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, _: &mut V)
+    fn visit_with_path<V>(&'ast self, _: &mut V, _: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
-    {}
+    {
+        VisitAction::Continue
+    }
 }
 
 impl<'ast> Visitable<'ast> for MandatoryName<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        visitor.on_reference_declaration(&(self.0).item);
+        visitor.on_reference_declaration(&(self.0).item, path);
+        VisitAction::Continue
     }
 }
 
@@ -43,18 +48,25 @@ where
     type Parent = N::Parent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.name.visit_with(visitor);
+        try_visit!(self.name.visit_with_path(visitor, path));
 
         // Call visit on the StatementList instead of BlockNode since we
         // need to make sure that function parameters end up inside the block
-        visitor.on_enter_scope(ScopeKind::Function);
-        self.params.visit_with(visitor);
-        self.body.body.visit_with(visitor);
-        visitor.on_leave_scope();
+        visitor.on_enter_scope(ScopeKind::Function, path);
+        path.field("params");
+        let action = match self.params.visit_with_path(visitor, path) {
+            VisitAction::Stop => VisitAction::Stop,
+            _ => {
+                path.field("body");
+                self.body.body.visit_with_path(visitor, path)
+            },
+        };
+        visitor.on_leave_scope(path);
+        action
     }
 }
 
@@ -62,7 +74,7 @@ impl<'ast> Visitable<'ast> for ClassMember<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
@@ -75,21 +87,30 @@ impl<'ast> Visitable<'ast> for ClassMember<'ast> {
                 ref value,
                 ..
             } => {
-                key.visit_with(visitor);
-                value.visit_with(visitor);
+                path.field("key");
+                try_visit!(key.visit_with_path(visitor, path));
+                path.field("value");
+                value.visit_with_path(visitor, path)
             },
             Literal {
                 ref key,
                 ref value,
                 ..
             } => {
-                key.visit_with(visitor);
-                value.visit_with(visitor);
+                path.field("key");
+                try_visit!(key.visit_with_path(visitor, path));
+                path.field("value");
+                value.visit_with_path(visitor, path)
             },
         }
     }
 }
 
+visitable_nodes! {
+    Function<'ast, EmptyName> => on_method, on_method_exit;
+    ClassMember<'ast> => on_class_member, on_class_member_exit;
+}
+
 impl<'ast, N> Visitable<'ast> for Class<'ast, N>
 where
     N: Visitable<'ast> + Name<'ast>,
@@ -97,12 +118,14 @@ where
     type Parent = N::Parent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.name.visit_with(visitor);
-        self.extends.visit_with(visitor);
-        self.body.body.visit_with(visitor);
+        try_visit!(self.name.visit_with_path(visitor, path));
+        path.field("extends");
+        try_visit!(self.extends.visit_with_path(visitor, path));
+        path.field("body");
+        self.body.body.visit_with_path(visitor, path)
     }
 }
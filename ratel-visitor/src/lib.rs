@@ -6,113 +6,287 @@ extern crate ratel;
 use ratel::ast::expression::*;
 use ratel::ast::statement::*;
 use ratel::ast::{ExpressionList, ExpressionNode, StatementList, StatementNode};
-use ratel::ast::{Identifier, Literal, Node, NodeList, Pattern};
+use ratel::ast::{Identifier, Literal, Loc, Node, NodeList, Pattern, PropertyNode};
+use ratel::ast::{Function, EmptyName, ClassMember};
 
 use ratel::Module;
 
+use std::ptr;
+
+/// Bail out of the current `visit_with` if the traversal has been stopped.
+macro_rules! try_visit {
+    ($action:expr) => {
+        if $action == $crate::VisitAction::Stop {
+            return $crate::VisitAction::Stop;
+        }
+    };
+}
+
+/// Visit a single variant of an `ExpressionNode` or `StatementNode`: push the
+/// node on the `Path`, call the entry hook, walk the children, then call the
+/// exit hook. Must be used inside a `loop`, which `Revisit` restarts.
+macro_rules! walk_node {
+    ($path:ident, $node:expr, $enter:expr, $children:expr, $exit:expr) => {{
+        $path.push($crate::NodeRef::from($node));
+
+        let action = match $enter {
+            $crate::VisitAction::Continue => $children,
+            $crate::VisitAction::SkipChildren => $crate::VisitAction::Continue,
+            action => action,
+        };
+        let action = match action {
+            $crate::VisitAction::Continue => $exit,
+            action => action,
+        };
+
+        $path.pop();
+
+        match action {
+            $crate::VisitAction::Stop => return $crate::VisitAction::Stop,
+            $crate::VisitAction::Revisit => continue,
+            _ => return $crate::VisitAction::Continue,
+        }
+    }};
+    ($path:ident, $node:expr, $enter:expr, $exit:expr) => {
+        walk_node!($path, $node, $enter, $crate::VisitAction::Continue, $exit)
+    };
+}
+
+/// Implement `Visitable` for `Node`s of types other than expressions and
+/// statements, calling the entry and exit hooks around the item.
+macro_rules! visitable_nodes {
+    ($( $type:ty => $enter:ident, $exit:ident; )*) => ($(
+        impl<'ast> Visitable<'ast> for Node<'ast, $type> {
+            type Parent = NoParent;
+
+            #[inline]
+            fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
+            where
+                V: Visitor<'ast>,
+            {
+                loop {
+                    let item = &current(self).item;
+
+                    walk_node!(
+                        path, self,
+                        visitor.$enter(item, self, path),
+                        item.visit_with_path(visitor, path),
+                        visitor.$exit(item, self, path)
+                    )
+                }
+            }
+        }
+    )*)
+}
+
+/// Read the value currently held by a `Node`. `Node::set` writes through a
+/// shared reference, so a regular read could be hoisted out of the loop that
+/// handles `VisitAction::Revisit`, never seeing the replacement.
+#[inline]
+fn current<'ast, T: 'ast>(node: &Node<'ast, T>) -> &'ast Loc<T> {
+    let mut node = unsafe { ptr::read_volatile(node) };
+    let &mut loc = node.get_mut();
+
+    loc
+}
+
+mod composite;
 mod expression;
 mod function;
+pub mod fold;
+mod path;
 mod statement;
 
+pub use composite::{Composite, Pass, OrderError};
+pub use fold::Fold;
+pub use path::{Path, NodeRef, NodeKind, Slot, Ancestors};
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ScopeKind {
     Function,
     Block,
 }
 
+/// Returned by `Visitor` hooks to control the traversal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VisitAction {
+    /// Visit the children of the node, then carry on.
+    Continue,
+    /// Don't visit the children of the node. The exit hook is still called.
+    /// Same as `Continue` when returned from an exit hook.
+    SkipChildren,
+    /// Abort the whole traversal, no further hooks are called.
+    Stop,
+    /// Visit the node again, from its entry hook. Use after replacing the node
+    /// with `Node::set` to walk the replacement instead of the stale item.
+    /// Returning it without replacing the node will loop forever.
+    Revisit,
+}
+
+impl Default for VisitAction {
+    #[inline]
+    fn default() -> Self {
+        VisitAction::Continue
+    }
+}
+
 // Like Batman!
 pub type NoParent = ();
 
 #[allow(unused_variables)]
 pub trait Visitor<'ast> {
-    /// after visiting a node, add it to the stack of parents
-    fn push_parent(&mut self, node: ParentNode<'ast>) {}
-    /// after visiting a node's children, pop it from the stack of parents
-    fn pop_parent(&mut self) {}
+    // Every hook gets the `Path` from the root to the node being visited.
 
     /// Enters a new statement list (program body, block body, switch case, etc.)
-    fn on_statement_list(&mut self, body: StatementList<'ast>) {}
+    fn on_statement_list(&mut self, body: StatementList<'ast>, path: &Path<'ast>) {}
 
     /// Entered a new scope
-    fn on_enter_scope(&mut self, kind: ScopeKind) {}
+    fn on_enter_scope(&mut self, kind: ScopeKind, path: &Path<'ast>) {}
 
     /// Leave the current scope
-    fn on_leave_scope(&mut self) {}
+    fn on_leave_scope(&mut self, path: &Path<'ast>) {}
 
     /// A reference has been used within the current scope
-    fn on_reference_use(&mut self, ident: &Identifier<'ast>) {}
+    fn on_reference_use(&mut self, ident: &Identifier<'ast>, path: &Path<'ast>) {}
 
     /// A reference has been declared within the current scope
-    fn on_reference_declaration(&mut self, ident: &Identifier<'ast>) {}
+    fn on_reference_declaration(&mut self, ident: &Identifier<'ast>, path: &Path<'ast>) {}
+
+    // Hooks for expressions and statements return a `VisitAction` to control
+    // the traversal, see its variants for details.
 
     // expressions
-    fn on_this_expression(&mut self, node: &'ast ExpressionNode<'ast>) {}
-    fn on_identifier_expression(&mut self, item: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_literal_expression(&mut self, item: &Literal<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_sequence_expression(&mut self, item: &SequenceExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_array_expression(&mut self, item: &ArrayExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_member_expression(&mut self, item: &MemberExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_computed_member_expression(&mut self, item: &ComputedMemberExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_meta_property(&mut self, item: &MetaPropertyExpression<'ast>, node: &ExpressionNode<'ast>) {}
-    fn on_call_expression(&mut self, item: &CallExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_binary_expression(&mut self, item: &BinaryExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_prefix_expression(&mut self, item: &PrefixExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_postfix_expression(&mut self, item: &PostfixExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_conditional_expression(&mut self, item: &ConditionalExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_template_literal(&mut self, item: &TemplateLiteral<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_tagged_template_expression(&mut self, item: &TaggedTemplateExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_spread_expression(&mut self, item: &SpreadExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_arrow_expression(&mut self, item: &ArrowExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_object_expression(&mut self, item: &ObjectExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_function_expression(&mut self, item: &FunctionExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
-    fn on_class_expression(&mut self, item: &ClassExpression<'ast>, node: &'ast ExpressionNode<'ast>) {}
+    fn on_this_expression(&mut self, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_super_expression(&mut self, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_identifier_expression(&mut self, item: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_literal_expression(&mut self, item: &Literal<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_sequence_expression(&mut self, item: &SequenceExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_array_expression(&mut self, item: &ArrayExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_member_expression(&mut self, item: &MemberExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_computed_member_expression(&mut self, item: &ComputedMemberExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_meta_property(&mut self, item: &MetaPropertyExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_call_expression(&mut self, item: &CallExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_binary_expression(&mut self, item: &BinaryExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_prefix_expression(&mut self, item: &PrefixExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_postfix_expression(&mut self, item: &PostfixExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_conditional_expression(&mut self, item: &ConditionalExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_template_literal(&mut self, item: &TemplateLiteral<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_tagged_template_expression(&mut self, item: &TaggedTemplateExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_spread_expression(&mut self, item: &SpreadExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_arrow_expression(&mut self, item: &ArrowExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_object_expression(&mut self, item: &ObjectExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_function_expression(&mut self, item: &FunctionExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_class_expression(&mut self, item: &ClassExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
 
     // statements
-    fn on_expression_statement(&mut self, item: &'ast ExpressionNode<'ast>, node: &'ast StatementNode<'ast>) {}
-    fn on_declaration_statement(&mut self, item: &DeclarationStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_return_statement(&mut self, item: &ReturnStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_break_statement(&mut self, item: &BreakStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_continue_statement(&mut self, item: &ContinueStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_throw_statement(&mut self, item: &ThrowStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_if_statement(&mut self, item: &IfStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_while_statement(&mut self, item: &WhileStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_do_statement(&mut self, item: &DoStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_for_statement(&mut self, item: &ForStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_for_in_statement(&mut self, item: &ForInStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_for_of_statement(&mut self, item: &ForOfStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_try_statement(&mut self, item: &TryStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_block_statement(&mut self, item: &BlockStatement<'ast>, node: &'ast StatementNode<'ast>) {}
-    fn on_labeled_statement(&mut self, item: &LabeledStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_switch_statement(&mut self, item: &SwitchStatement, node: &'ast StatementNode<'ast>) {}
-    fn on_function_statement(&mut self, item: &FunctionStatement<'ast>, node: &'ast StatementNode<'ast>) {}
-    fn on_class_statement(&mut self, item:&ClassStatement<'ast>, node: &'ast StatementNode<'ast>) {}
-    fn on_import_declaration_statement(&mut self, item:&ImportDeclaration<'ast>, node: &'ast StatementNode<'ast>) {}
+    fn on_expression_statement(&mut self, item: &'ast ExpressionNode<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_declaration_statement(&mut self, item: &DeclarationStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_return_statement(&mut self, item: &ReturnStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_break_statement(&mut self, item: &BreakStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_continue_statement(&mut self, item: &ContinueStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_throw_statement(&mut self, item: &ThrowStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_if_statement(&mut self, item: &IfStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_while_statement(&mut self, item: &WhileStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_do_statement(&mut self, item: &DoStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_for_statement(&mut self, item: &ForStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_for_in_statement(&mut self, item: &ForInStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_for_of_statement(&mut self, item: &ForOfStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_try_statement(&mut self, item: &TryStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_block_statement(&mut self, item: &BlockStatement<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_labeled_statement(&mut self, item: &LabeledStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_switch_statement(&mut self, item: &SwitchStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_function_statement(&mut self, item: &FunctionStatement<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_class_statement(&mut self, item:&ClassStatement<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_import_declaration_statement(&mut self, item:&ImportDeclaration<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+
+    // other nodes
+    fn on_pattern(&mut self, item: &Pattern<'ast>, node: &'ast Node<'ast, Pattern<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_property(&mut self, item: &Property<'ast>, node: &'ast PropertyNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_property_key(&mut self, item: &PropertyKey<'ast>, node: &'ast Node<'ast, PropertyKey<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_method(&mut self, item: &Function<'ast, EmptyName>, node: &'ast Node<'ast, Function<'ast, EmptyName>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_class_member(&mut self, item: &ClassMember<'ast>, node: &'ast Node<'ast, ClassMember<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_declarator(&mut self, item: &Declarator<'ast>, node: &'ast Node<'ast, Declarator<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_for_init(&mut self, item: &ForInit<'ast>, node: &'ast Node<'ast, ForInit<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_catch_clause(&mut self, item: &CatchClause<'ast>, node: &'ast Node<'ast, CatchClause<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_switch_case(&mut self, item: &SwitchCase<'ast>, node: &'ast Node<'ast, SwitchCase<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_block(&mut self, item: &BlockStatement<'ast>, node: &'ast Node<'ast, BlockStatement<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_import_specifier(&mut self, item: &ForImportSpecifier<'ast>, node: &'ast Node<'ast, ForImportSpecifier<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_template_quasi(&mut self, item: &'ast str, node: &'ast Node<'ast, &'ast str>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+
+    // expressions, after their children have been visited
+    fn on_this_expression_exit(&mut self, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_super_expression_exit(&mut self, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_identifier_expression_exit(&mut self, item: &Identifier<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_literal_expression_exit(&mut self, item: &Literal<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_sequence_expression_exit(&mut self, item: &SequenceExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_array_expression_exit(&mut self, item: &ArrayExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_member_expression_exit(&mut self, item: &MemberExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_computed_member_expression_exit(&mut self, item: &ComputedMemberExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_meta_property_exit(&mut self, item: &MetaPropertyExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_call_expression_exit(&mut self, item: &CallExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_binary_expression_exit(&mut self, item: &BinaryExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_prefix_expression_exit(&mut self, item: &PrefixExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_postfix_expression_exit(&mut self, item: &PostfixExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_conditional_expression_exit(&mut self, item: &ConditionalExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_template_literal_exit(&mut self, item: &TemplateLiteral<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_tagged_template_expression_exit(&mut self, item: &TaggedTemplateExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_spread_expression_exit(&mut self, item: &SpreadExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_arrow_expression_exit(&mut self, item: &ArrowExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_object_expression_exit(&mut self, item: &ObjectExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_function_expression_exit(&mut self, item: &FunctionExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_class_expression_exit(&mut self, item: &ClassExpression<'ast>, node: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+
+    // statements, after their children have been visited
+    fn on_expression_statement_exit(&mut self, item: &'ast ExpressionNode<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_declaration_statement_exit(&mut self, item: &DeclarationStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_return_statement_exit(&mut self, item: &ReturnStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_break_statement_exit(&mut self, item: &BreakStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_continue_statement_exit(&mut self, item: &ContinueStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_throw_statement_exit(&mut self, item: &ThrowStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_if_statement_exit(&mut self, item: &IfStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_while_statement_exit(&mut self, item: &WhileStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_do_statement_exit(&mut self, item: &DoStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_for_statement_exit(&mut self, item: &ForStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_for_in_statement_exit(&mut self, item: &ForInStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_for_of_statement_exit(&mut self, item: &ForOfStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_try_statement_exit(&mut self, item: &TryStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_block_statement_exit(&mut self, item: &BlockStatement<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_labeled_statement_exit(&mut self, item: &LabeledStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_switch_statement_exit(&mut self, item: &SwitchStatement, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_function_statement_exit(&mut self, item: &FunctionStatement<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_class_statement_exit(&mut self, item:&ClassStatement<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_import_declaration_statement_exit(&mut self, item:&ImportDeclaration<'ast>, node: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+
+    // other nodes, after their children have been visited
+    fn on_pattern_exit(&mut self, item: &Pattern<'ast>, node: &'ast Node<'ast, Pattern<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_property_exit(&mut self, item: &Property<'ast>, node: &'ast PropertyNode<'ast>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_property_key_exit(&mut self, item: &PropertyKey<'ast>, node: &'ast Node<'ast, PropertyKey<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_method_exit(&mut self, item: &Function<'ast, EmptyName>, node: &'ast Node<'ast, Function<'ast, EmptyName>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_class_member_exit(&mut self, item: &ClassMember<'ast>, node: &'ast Node<'ast, ClassMember<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_declarator_exit(&mut self, item: &Declarator<'ast>, node: &'ast Node<'ast, Declarator<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_for_init_exit(&mut self, item: &ForInit<'ast>, node: &'ast Node<'ast, ForInit<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_catch_clause_exit(&mut self, item: &CatchClause<'ast>, node: &'ast Node<'ast, CatchClause<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_switch_case_exit(&mut self, item: &SwitchCase<'ast>, node: &'ast Node<'ast, SwitchCase<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_block_exit(&mut self, item: &BlockStatement<'ast>, node: &'ast Node<'ast, BlockStatement<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_import_specifier_exit(&mut self, item: &ForImportSpecifier<'ast>, node: &'ast Node<'ast, ForImportSpecifier<'ast>>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
+    fn on_template_quasi_exit(&mut self, item: &'ast str, node: &'ast Node<'ast, &'ast str>, path: &Path<'ast>) -> VisitAction { VisitAction::Continue }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum ParentNode<'ast> {
-    Statement(&'ast StatementNode<'ast>),
-    Expression(&'ast ExpressionNode<'ast>),
-}
-
-impl<'ast> From<&'ast StatementNode<'ast>> for ParentNode<'ast> {
-    #[inline]
-    fn from(node: &'ast StatementNode<'ast>) -> ParentNode<'ast> {
-        ParentNode::Statement(node)
-    }
-}
+pub trait Visitable<'ast>: 'ast {
+    type Parent;
 
-impl<'ast> From<&'ast ExpressionNode<'ast>> for ParentNode<'ast> {
+    /// Walk the node as the root of a new `Path`.
     #[inline]
-    fn from(node: &'ast ExpressionNode<'ast>) -> ParentNode<'ast> {
-        ParentNode::Expression(node)
+    fn visit_with<V>(&'ast self, visitor: &mut V) -> VisitAction
+    where
+        V: Visitor<'ast>,
+    {
+        self.visit_with_path(visitor, &mut Path::new())
     }
-}
-
-pub trait Visitable<'ast>: 'ast {
-    type Parent;
 
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    /// Walk the node, keeping the `Path` up to date. Returns `VisitAction::Stop`
+    /// if the traversal has been stopped, or `VisitAction::Continue` otherwise.
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>;
 }
@@ -121,14 +295,16 @@ impl<'ast> Visitable<'ast> for Module<'ast> {
     type Parent = NoParent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         let body = self.body();
-        for item in body {
-            item.visit_with(visitor);
+        for (index, item) in body.iter().enumerate() {
+            path.set_slot(Some(Slot::new("body").at(index)));
+            try_visit!(item.visit_with_path(visitor, path));
         }
+        VisitAction::Continue
     }
 }
 
@@ -136,28 +312,36 @@ impl<'ast> Visitable<'ast> for Pattern<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         match *self {
-            Pattern::Void => {}
-            Pattern::Identifier(ref ident) => visitor.on_reference_declaration(ident),
+            Pattern::Void => VisitAction::Continue,
+            Pattern::Identifier(ref ident) => {
+                visitor.on_reference_declaration(ident, path);
+                VisitAction::Continue
+            }
             Pattern::ObjectPattern { ref properties } => {
-                properties.visit_with(visitor);
+                path.field("properties");
+                properties.visit_with_path(visitor, path)
             }
             Pattern::ArrayPattern { ref elements } => {
-                elements.visit_with(visitor);
+                path.field("elements");
+                elements.visit_with_path(visitor, path)
             }
             Pattern::RestElement { ref argument } => {
-                argument.visit_with(visitor);
+                path.field("argument");
+                argument.visit_with_path(visitor, path)
             }
             Pattern::AssignmentPattern {
                 ref left,
                 ref right,
             } => {
-                left.visit_with(visitor);
-                right.visit_with(visitor);
+                path.field("left");
+                try_visit!(left.visit_with_path(visitor, path));
+                path.field("right");
+                right.visit_with_path(visitor, path)
             }
         }
     }
@@ -167,13 +351,16 @@ impl<'ast> Visitable<'ast> for PropertyKey<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         match *self {
-            PropertyKey::Computed(ref expression) => expression.visit_with(visitor),
-            PropertyKey::Literal(_) | PropertyKey::Binary(_) => {}
+            PropertyKey::Computed(ref expression) => {
+                path.field("expression");
+                expression.visit_with_path(visitor, path)
+            },
+            PropertyKey::Literal(_) | PropertyKey::Binary(_) => VisitAction::Continue,
         }
     }
 }
@@ -182,22 +369,30 @@ impl<'ast> Visitable<'ast> for Property<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         match *self {
-            Property::Shorthand(ref ident) => visitor.on_reference_use(ident),
+            Property::Shorthand(ref ident) => {
+                visitor.on_reference_use(ident, path);
+                VisitAction::Continue
+            }
             Property::Literal { ref key, ref value } => {
-                key.visit_with(visitor);
-                value.visit_with(visitor);
+                path.field("key");
+                try_visit!(key.visit_with_path(visitor, path));
+                path.field("value");
+                value.visit_with_path(visitor, path)
             }
             Property::Method { ref key, ref value } => {
-                key.visit_with(visitor);
-                value.visit_with(visitor);
+                path.field("key");
+                try_visit!(key.visit_with_path(visitor, path));
+                path.field("value");
+                value.visit_with_path(visitor, path)
             },
             Property::Spread { ref argument } => {
-                argument.visit_with(visitor);
+                path.field("argument");
+                argument.visit_with_path(visitor, path)
             }
         }
     }
@@ -210,47 +405,43 @@ where
     type Parent = T::Parent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        if let Some(ref visitable) = *self {
-            visitable.visit_with(visitor);
+        match *self {
+            Some(ref visitable) => visitable.visit_with_path(visitor, path),
+            None => VisitAction::Continue,
         }
     }
 }
 
-// Requiring that `Parent = Node<'ast, T>` means that we avoid having
-// a default implementation for (Expression|Statement)(Node|List)
-impl<'ast, T> Visitable<'ast> for Node<'ast, T>
-where
-    T: Visitable<'ast, Parent = Node<'ast, T>>,
-{
-    type Parent = NoParent;
-
-    #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
-    where
-        V: Visitor<'ast>,
-    {
-        self.item.visit_with(visitor);
-    }
+visitable_nodes! {
+    Pattern<'ast> => on_pattern, on_pattern_exit;
+    Property<'ast> => on_property, on_property_exit;
+    PropertyKey<'ast> => on_property_key, on_property_key_exit;
 }
 
+// Requiring that `Parent = Node<'ast, T>` means that we avoid having
+// a default implementation for (Expression|Statement)List
 impl<'ast, T> Visitable<'ast> for NodeList<'ast, T>
 where
     T: Visitable<'ast, Parent = Node<'ast, T>>,
+    Node<'ast, T>: Visitable<'ast>,
 {
     type Parent = NoParent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        for item in self {
-            item.visit_with(visitor);
+        let slot = path.take_slot();
+        for (index, item) in self.iter().enumerate() {
+            path.set_slot(slot.map(|slot| slot.at(index)));
+            try_visit!(item.visit_with_path(visitor, path));
         }
+        VisitAction::Continue
     }
 }
 
@@ -258,13 +449,16 @@ impl<'ast> Visitable<'ast> for ExpressionList<'ast> {
     type Parent = NoParent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        for node in self.iter() {
-            node.visit_with(visitor);
+        let slot = path.take_slot();
+        for (index, node) in self.iter().enumerate() {
+            path.set_slot(slot.map(|slot| slot.at(index)));
+            try_visit!(node.visit_with_path(visitor, path));
         }
+        VisitAction::Continue
     }
 }
 
@@ -272,22 +466,26 @@ impl<'ast> Visitable<'ast> for StatementList<'ast> {
     type Parent = NoParent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        visitor.on_statement_list(*self);
-        for node in self.iter() {
-            node.visit_with(visitor);
+        visitor.on_statement_list(*self, path);
+        let slot = path.take_slot();
+        for (index, node) in self.iter().enumerate() {
+            path.set_slot(slot.map(|slot| slot.at(index)));
+            try_visit!(node.visit_with_path(visitor, path));
         }
+        VisitAction::Continue
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use ratel::parse;
+    use ratel::{parse_in, Arena};
     use ScopeKind::*;
+    use ratel::ast::Function;
 
     struct ScopeTest<'ast> {
         depth: i32,
@@ -310,28 +508,29 @@ mod test {
     }
 
     impl<'ast> Visitor<'ast> for ScopeTest<'ast> {
-        fn on_enter_scope(&mut self, kind: ScopeKind) {
+        fn on_enter_scope(&mut self, kind: ScopeKind, _: &Path<'ast>) {
             self.scopes.push(kind);
             self.depth += 1;
             self.max_depth = self.max_depth.max(self.depth);
         }
 
-        fn on_leave_scope(&mut self) {
+        fn on_leave_scope(&mut self, _: &Path<'ast>) {
             self.depth -= 1;
         }
 
-        fn on_reference_use(&mut self, ident: &Identifier<'ast>) {
+        fn on_reference_use(&mut self, ident: &Identifier<'ast>, _: &Path<'ast>) {
             self.used_vars.push((*ident, self.depth));
         }
 
-        fn on_reference_declaration(&mut self, ident: &Identifier<'ast>) {
+        fn on_reference_declaration(&mut self, ident: &Identifier<'ast>, _: &Path<'ast>) {
             self.declared_vars.push((*ident, self.depth));
         }
     }
 
     #[test]
     fn keeps_track_of_blocks() {
-        let module = parse("{{{}}}").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "{{{}}}").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -345,7 +544,8 @@ mod test {
 
     #[test]
     fn keeps_track_of_declarations() {
-        let module = parse("let foo; const bar = 42, doge;").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "let foo; const bar = 42, doge;").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -359,7 +559,8 @@ mod test {
 
     #[test]
     fn keeps_track_of_declarations_at_the_correct_depth() {
-        let module = parse("let foo; { let foo; { let foo; }}").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "let foo; { let foo; { let foo; }}").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -373,7 +574,8 @@ mod test {
 
     #[test]
     fn keeps_track_of_uses() {
-        let module = parse("doge = to + the + moon").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "doge = to + the + moon").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -390,7 +592,8 @@ mod test {
 
     #[test]
     fn keeps_track_of_uses_at_the_correct_depth() {
-        let module = parse("doge; { to; { the; { moon; }}}").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "doge; { to; { the; { moon; }}}").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -407,7 +610,8 @@ mod test {
 
     #[test]
     fn function_and_class_are_declarations() {
-        let module = parse("function foo() {} class Bar {}").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "function foo() {} class Bar {}").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -421,7 +625,8 @@ mod test {
 
     #[test]
     fn function_and_class_expressions_are_not_declarations() {
-        let module = parse("(function foo() {}); (class Bar {});").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "(function foo() {}); (class Bar {});").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -435,7 +640,8 @@ mod test {
 
     #[test]
     fn empty_class_has_no_scope() {
-        let module = parse("class Doge {}").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "class Doge {}").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -449,7 +655,8 @@ mod test {
 
     #[test]
     fn functions_and_object_methods_are_scopes() {
-        let module = parse(
+        let arena = Arena::new();
+        let module = parse_in(&arena, 
             r"
             function doge() {
                 foo;
@@ -476,7 +683,8 @@ mod test {
 
     #[test]
     fn object_property_shorthand_is_a_use() {
-        let module = parse("const doge = { to, the, moon };").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "const doge = { to, the, moon };").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -490,7 +698,8 @@ mod test {
 
     #[test]
     fn function_params_are_declarations() {
-        let module = parse("function doge(to, the) { const moon; }").unwrap();
+        let arena = Arena::new();
+        let module = parse_in(&arena, "function doge(to, the) { const moon; }").unwrap();
         let mut visitor = ScopeTest::new();
 
         module.visit_with(&mut visitor);
@@ -505,41 +714,417 @@ mod test {
         );
     }
 
-    struct ParentsTest<'ast> {
-        count: u32,
-        parents: Vec<ParentNode<'ast>>,
+    /// Records the path of every identifier, as slots and kinds from the root.
+    struct PathTest {
+        paths: Vec<(String, Vec<String>, Vec<NodeKind>)>,
+        statement_position: Vec<NodeKind>,
+    }
+
+    impl PathTest {
+        fn new() -> Self {
+            PathTest {
+                paths: Vec::new(),
+                statement_position: Vec::new(),
+            }
+        }
+
+        fn record(&mut self, ident: &str, path: &Path) {
+            let mut kinds: Vec<NodeKind> = path.node().into_iter().chain(path.ancestors()).map(|node| node.kind()).collect();
+            kinds.reverse();
+
+            self.paths.push((ident.to_string(), path.slot().into_iter().map(|slot| slot.to_string()).collect(), kinds));
+        }
+    }
+
+    impl<'ast> Visitor<'ast> for PathTest {
+        fn on_reference_use(&mut self, ident: &Identifier<'ast>, path: &Path<'ast>) {
+            self.record(ident, path);
+        }
+
+        fn on_reference_declaration(&mut self, ident: &Identifier<'ast>, path: &Path<'ast>) {
+            self.record(ident, path);
+        }
+
+        fn on_call_expression(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+            if path.is_statement_position() {
+                self.statement_position.push(NodeKind::CallExpression);
+            }
+            VisitAction::Continue
+        }
+
+        fn on_identifier_expression(&mut self, _: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+            if path.is_statement_position() {
+                self.statement_position.push(NodeKind::IdentifierExpression);
+            }
+            VisitAction::Continue
+        }
+    }
+
+    #[test]
+    fn should_track_slots_in_path() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "foo; bar(a, b, c);").unwrap();
+        let mut visitor = PathTest::new();
+
+        module.visit_with(&mut visitor);
+
+        assert_eq!(visitor.paths[0], ("foo".to_string(), vec!["expression".to_string()], vec![
+            NodeKind::ExpressionStatement,
+            NodeKind::IdentifierExpression,
+        ]));
+        assert_eq!(visitor.paths[1], ("bar".to_string(), vec!["callee".to_string()], vec![
+            NodeKind::ExpressionStatement,
+            NodeKind::CallExpression,
+            NodeKind::IdentifierExpression,
+        ]));
+        assert_eq!(visitor.paths[4].0, "c");
+        assert_eq!(visitor.paths[4].1, vec!["arguments[2]"]);
+        assert_eq!(visitor.statement_position, vec![NodeKind::IdentifierExpression, NodeKind::CallExpression]);
+    }
+
+    #[test]
+    fn should_track_patterns_in_path() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "{ var [a, b] = c; }").unwrap();
+        let mut visitor = PathTest::new();
+
+        module.visit_with(&mut visitor);
+
+        assert_eq!(visitor.paths[1], ("b".to_string(), vec!["elements[1]".to_string()], vec![
+            NodeKind::BlockStatement,
+            NodeKind::DeclarationStatement,
+            NodeKind::Declarator,
+            NodeKind::Pattern,
+            NodeKind::Pattern,
+        ]));
+    }
+
+    /// Checks ancestors of the first identifier expression.
+    struct AncestorTest<'ast> {
+        function: Option<NodeRef<'ast>>,
+        statement: Option<NodeRef<'ast>>,
+        slot: Option<Slot>,
+        depth: usize,
+    }
+
+    impl<'ast> Visitor<'ast> for AncestorTest<'ast> {
+        fn on_identifier_expression(&mut self, _: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, path: &Path<'ast>) -> VisitAction {
+            self.function = path.find_ancestor(NodeKind::FunctionStatement);
+            self.statement = path.find_ancestor(NodeKind::ReturnStatement);
+            self.slot = path.slot();
+            self.depth = path.depth();
+            VisitAction::Stop
+        }
+
+        fn on_return_statement(&mut self, _: &ReturnStatement, _: &'ast StatementNode<'ast>, path: &Path<'ast>) -> VisitAction {
+            assert_eq!(path.is_statement_list_item(), true);
+            assert_eq!(path.slot().unwrap().to_string(), "body[1]");
+            VisitAction::Continue
+        }
+    }
+
+    #[test]
+    fn should_find_ancestors() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "function f() { 1; return x; }").unwrap();
+        let mut visitor = AncestorTest {
+            function: None,
+            statement: None,
+            slot: None,
+            depth: 0,
+        };
+
+        module.visit_with(&mut visitor);
+
+        let function = visitor.function.unwrap().as_statement().unwrap();
+
+        assert_eq!(function as *const _, module.body().first_element().unwrap() as *const _);
+        assert_eq!(visitor.statement.unwrap().kind(), NodeKind::ReturnStatement);
+        assert_eq!(visitor.slot.unwrap().to_string(), "value");
+        assert_eq!(visitor.depth, 3);
+    }
+
+    struct OrderTest {
+        events: Vec<&'static str>,
+    }
+
+    impl OrderTest {
+        fn record(&mut self, event: &'static str) -> VisitAction {
+            self.events.push(event);
+            VisitAction::Continue
+        }
+    }
+
+    impl<'ast> Visitor<'ast> for OrderTest {
+        fn on_expression_statement(&mut self, _: &'ast ExpressionNode<'ast>, _: &'ast StatementNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("enter statement")
+        }
+
+        fn on_expression_statement_exit(&mut self, _: &'ast ExpressionNode<'ast>, _: &'ast StatementNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("exit statement")
+        }
+
+        fn on_binary_expression(&mut self, _: &BinaryExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("enter binary")
+        }
+
+        fn on_binary_expression_exit(&mut self, _: &BinaryExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("exit binary")
+        }
+
+        fn on_call_expression(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("enter call")
+        }
+
+        fn on_call_expression_exit(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("exit call")
+        }
+
+        fn on_identifier_expression_exit(&mut self, _: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.record("exit identifier")
+        }
+    }
+
+    #[test]
+    fn should_call_exit_hooks_after_children() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "a + b(c);").unwrap();
+        let mut visitor = OrderTest { events: Vec::new() };
+
+        module.visit_with(&mut visitor);
+
+        assert_eq!(visitor.events, vec![
+            "enter statement",
+            "enter binary",
+            "exit identifier",
+            "enter call",
+            "exit identifier",
+            "exit identifier",
+            "exit call",
+            "exit binary",
+            "exit statement",
+        ]);
+    }
+
+    /// Counts identifiers, returning a fixed action from `on_call_expression`.
+    struct ActionTest {
+        action: VisitAction,
+        identifiers: Vec<String>,
+        calls_exited: usize,
+        depth: i32,
+    }
+
+    impl ActionTest {
+        fn new(action: VisitAction) -> Self {
+            ActionTest {
+                action,
+                identifiers: Vec::new(),
+                calls_exited: 0,
+                depth: 0,
+            }
+        }
+    }
+
+    impl<'ast> Visitor<'ast> for ActionTest {
+        fn on_enter_scope(&mut self, _: ScopeKind, _: &Path<'ast>) {
+            self.depth += 1;
+        }
+
+        fn on_leave_scope(&mut self, _: &Path<'ast>) {
+            self.depth -= 1;
+        }
+
+        fn on_identifier_expression(&mut self, item: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.identifiers.push(item.to_string());
+            VisitAction::Continue
+        }
+
+        fn on_call_expression(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.action
+        }
+
+        fn on_call_expression_exit(&mut self, _: &CallExpression<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.calls_exited += 1;
+            VisitAction::Continue
+        }
+    }
+
+    #[test]
+    fn should_skip_children() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "a; b(c); d;").unwrap();
+        let mut visitor = ActionTest::new(VisitAction::SkipChildren);
+
+        assert_eq!(module.visit_with(&mut visitor), VisitAction::Continue);
+        assert_eq!(visitor.identifiers, vec!["a", "d"]);
+        assert_eq!(visitor.calls_exited, 1);
+    }
+
+    #[test]
+    fn should_stop() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "a; function f() { b(c); } d;").unwrap();
+        let mut visitor = ActionTest::new(VisitAction::Stop);
+
+        assert_eq!(module.visit_with(&mut visitor), VisitAction::Stop);
+        assert_eq!(visitor.identifiers, vec!["a"]);
+        assert_eq!(visitor.calls_exited, 0);
+        assert_eq!(visitor.depth, 0);
     }
 
-   impl<'ast> ParentsTest<'ast> {
-        fn new() -> ParentsTest<'ast> {
-            ParentsTest {
-                count: 0,
-                parents: Vec::new(),
+    /// Replaces the first call with its first argument.
+    struct ReplaceTest {
+        identifiers: Vec<String>,
+    }
+
+    impl<'ast> Visitor<'ast> for ReplaceTest {
+        fn on_identifier_expression(&mut self, item: &Identifier<'ast>, _: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            self.identifiers.push(item.to_string());
+            VisitAction::Continue
+        }
+
+        fn on_call_expression(&mut self, item: &CallExpression<'ast>, node: &'ast ExpressionNode<'ast>, _: &Path<'ast>) -> VisitAction {
+            match item.arguments.first_element() {
+                Some(&(mut argument)) => {
+                    node.set(*argument.get_mut());
+                    VisitAction::Revisit
+                },
+                None => VisitAction::Continue,
             }
         }
     }
 
-    impl<'ast> Visitor<'ast> for ParentsTest<'ast> {
-        fn push_parent(&mut self, node: ParentNode<'ast>) {
-            self.count += 1;
-            self.parents.push(node);
+    #[test]
+    fn should_revisit_replaced_nodes() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "a(b(c));").unwrap();
+        let mut visitor = ReplaceTest { identifiers: Vec::new() };
+
+        module.visit_with(&mut visitor);
+
+        assert_eq!(visitor.identifiers, vec!["c"]);
+    }
+
+    /// Records the hooks called for nodes other than expressions and statements.
+    struct NodesTest {
+        events: Vec<String>,
+    }
+
+    impl NodesTest {
+        fn record(&mut self, event: &str, path: &Path) -> VisitAction {
+            self.events.push(format!("{} {}", event, path.slot().unwrap()));
+            VisitAction::Continue
+        }
+    }
+
+    impl<'ast> Visitor<'ast> for NodesTest {
+        fn on_pattern(&mut self, _: &Pattern<'ast>, _: &'ast Node<'ast, Pattern<'ast>>, path: &Path<'ast>) -> VisitAction {
+            self.record("pattern", path)
+        }
+
+        fn on_property(&mut self, _: &Property<'ast>, _: &'ast PropertyNode<'ast>, path: &Path<'ast>) -> VisitAction {
+            self.record("property", path)
+        }
+
+        fn on_property_key(&mut self, _: &PropertyKey<'ast>, _: &'ast Node<'ast, PropertyKey<'ast>>, path: &Path<'ast>) -> VisitAction {
+            self.record("key", path)
+        }
+
+        fn on_method(&mut self, _: &Function<'ast, EmptyName>, _: &'ast Node<'ast, Function<'ast, EmptyName>>, path: &Path<'ast>) -> VisitAction {
+            self.record("method", path)
         }
 
-        fn pop_parent(&mut self) {
-            assert_eq!(self.parents.pop().is_some(), true);
+        fn on_class_member(&mut self, _: &ClassMember<'ast>, _: &'ast Node<'ast, ClassMember<'ast>>, path: &Path<'ast>) -> VisitAction {
+            self.record("member", path)
+        }
+
+        fn on_declarator(&mut self, _: &Declarator<'ast>, _: &'ast Node<'ast, Declarator<'ast>>, path: &Path<'ast>) -> VisitAction {
+            self.record("declarator", path)
+        }
+
+        fn on_for_init(&mut self, _: &ForInit<'ast>, _: &'ast Node<'ast, ForInit<'ast>>, path: &Path<'ast>) -> VisitAction {
+            self.record("init", path)
+        }
+
+        fn on_catch_clause(&mut self, _: &CatchClause<'ast>, _: &'ast Node<'ast, CatchClause<'ast>>, path: &Path<'ast>) -> VisitAction {
+            self.record("catch", path)
+        }
+
+        fn on_switch_case(&mut self, _: &SwitchCase<'ast>, _: &'ast Node<'ast, SwitchCase<'ast>>, path: &Path<'ast>) -> VisitAction {
+            self.record("case", path)
+        }
+
+        fn on_block(&mut self, _: &BlockStatement<'ast>, _: &'ast Node<'ast, BlockStatement<'ast>>, path: &Path<'ast>) -> VisitAction {
+            self.record("block", path)
+        }
+
+        fn on_template_quasi(&mut self, item: &'ast str, _: &'ast Node<'ast, &'ast str>, path: &Path<'ast>) -> VisitAction {
+            self.record(item, path)
+        }
+    }
+
+    #[test]
+    fn should_visit_other_nodes() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, r#"
+            var { a, b: c } = `x${ d }y`, [[e]] = f;
+            for (g in h) {}
+            try {} catch (i) {}
+            switch (j) { case 1: break; }
+            class I { m() {} }
+        "#).unwrap();
+        let mut visitor = NodesTest { events: Vec::new() };
+
+        module.visit_with(&mut visitor);
+
+        assert_eq!(visitor.events, vec![
+            "declarator declarators[0]",
+            "pattern id",
+            "property properties[0]",
+            "property properties[1]",
+            "key key",
+            "x quasis[0]",
+            "y quasis[1]",
+            "declarator declarators[1]",
+            "pattern id",
+            "pattern elements[0]",
+            "pattern elements[0]",
+            "init left",
+            "block block",
+            "catch handler",
+            "pattern param",
+            "block body",
+            "case cases[0]",
+            "member body[0]",
+            "key key",
+            "method value",
+        ]);
+    }
+
+    /// Renames every identifier pattern to `renamed`.
+    struct RenameTest<'ast> {
+        arena: &'ast Arena,
+    }
+
+    impl<'ast> Visitor<'ast> for RenameTest<'ast> {
+        fn on_pattern(&mut self, item: &Pattern<'ast>, node: &'ast Node<'ast, Pattern<'ast>>, _: &Path<'ast>) -> VisitAction {
+            if let Pattern::Identifier(_) = *item {
+                node.set(self.arena.alloc(Loc::new(node.start, node.end, Pattern::Identifier("renamed"))));
+            }
+            VisitAction::Continue
         }
     }
 
     #[test]
-    fn should_track_1_parent_for_every_statement_or_expression_node() {
-        let module = parse("{{{1+2}}}").unwrap();
-        let mut visitor = ParentsTest::new();
+    fn should_replace_other_nodes() {
+        let arena = Arena::new();
+        let module = parse_in(&arena, "var [a, b] = c;").unwrap();
+        let mut visitor = RenameTest { arena: &arena };
+        let mut scopes = ScopeTest::new();
 
         module.visit_with(&mut visitor);
+        module.visit_with(&mut scopes);
 
-        // 3 BlockStatements + 1 ExpressionStatement + 1 BinaryExpression = 5 Parents tracked
-        // The 2 Literals don't have children, therefore they aren't tracked
-        assert_eq!(visitor.count, 5);
-        assert_eq!(visitor.parents.len(), 0);
+        assert_eq!(scopes.declared_vars, vec![("renamed", 0), ("renamed", 0)]);
     }
 }
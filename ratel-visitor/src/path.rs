@@ -0,0 +1,359 @@
+use ratel::ast::{Node, Expression, ExpressionNode, Statement, StatementNode};
+use ratel::ast::{Pattern, Property, PropertyKey, ClassMember, Function, EmptyName};
+use ratel::ast::statement::*;
+
+use std::fmt;
+use std::iter::Rev;
+use std::slice;
+
+/// Reference to a node that can appear on a `Path`.
+#[derive(Debug, Clone, Copy)]
+pub enum NodeRef<'ast> {
+    Statement(&'ast StatementNode<'ast>),
+    Expression(&'ast ExpressionNode<'ast>),
+    Pattern(&'ast Node<'ast, Pattern<'ast>>),
+    Property(&'ast Node<'ast, Property<'ast>>),
+    PropertyKey(&'ast Node<'ast, PropertyKey<'ast>>),
+    ClassMember(&'ast Node<'ast, ClassMember<'ast>>),
+    Declarator(&'ast Node<'ast, Declarator<'ast>>),
+    ForInit(&'ast Node<'ast, ForInit<'ast>>),
+    CatchClause(&'ast Node<'ast, CatchClause<'ast>>),
+    SwitchCase(&'ast Node<'ast, SwitchCase<'ast>>),
+    Block(&'ast Node<'ast, BlockStatement<'ast>>),
+    ImportSpecifier(&'ast Node<'ast, ForImportSpecifier<'ast>>),
+    Method(&'ast Node<'ast, Function<'ast, EmptyName>>),
+    TemplateQuasi(&'ast Node<'ast, &'ast str>),
+}
+
+macro_rules! impl_from {
+    ($( $type:ty => $variant:ident ),*) => ($(
+        impl<'ast> From<&'ast Node<'ast, $type>> for NodeRef<'ast> {
+            #[inline]
+            fn from(node: &'ast Node<'ast, $type>) -> NodeRef<'ast> {
+                NodeRef::$variant(node)
+            }
+        }
+    )*)
+}
+
+impl_from! {
+    Statement<'ast> => Statement,
+    Expression<'ast> => Expression,
+    Pattern<'ast> => Pattern,
+    Property<'ast> => Property,
+    PropertyKey<'ast> => PropertyKey,
+    ClassMember<'ast> => ClassMember,
+    Declarator<'ast> => Declarator,
+    ForInit<'ast> => ForInit,
+    CatchClause<'ast> => CatchClause,
+    SwitchCase<'ast> => SwitchCase,
+    BlockStatement<'ast> => Block,
+    ForImportSpecifier<'ast> => ImportSpecifier,
+    Function<'ast, EmptyName> => Method
+}
+
+impl<'ast> NodeRef<'ast> {
+    /// Kind of the node, looking at the variant for expressions and statements.
+    pub fn kind(&self) -> NodeKind {
+        match *self {
+            NodeRef::Statement(node) => match node.item {
+                Statement::Empty          => NodeKind::EmptyStatement,
+                Statement::Debugger       => NodeKind::DebuggerStatement,
+                Statement::Expression(_)  => NodeKind::ExpressionStatement,
+                Statement::Declaration(_) => NodeKind::DeclarationStatement,
+                Statement::Return(_)      => NodeKind::ReturnStatement,
+                Statement::Break(_)       => NodeKind::BreakStatement,
+                Statement::Continue(_)    => NodeKind::ContinueStatement,
+                Statement::Throw(_)       => NodeKind::ThrowStatement,
+                Statement::If(_)          => NodeKind::IfStatement,
+                Statement::While(_)       => NodeKind::WhileStatement,
+                Statement::Do(_)          => NodeKind::DoStatement,
+                Statement::For(_)         => NodeKind::ForStatement,
+                Statement::ForIn(_)       => NodeKind::ForInStatement,
+                Statement::ForOf(_)       => NodeKind::ForOfStatement,
+                Statement::Try(_)         => NodeKind::TryStatement,
+                Statement::Block(_)       => NodeKind::BlockStatement,
+                Statement::Labeled(_)     => NodeKind::LabeledStatement,
+                Statement::Function(_)    => NodeKind::FunctionStatement,
+                Statement::Class(_)       => NodeKind::ClassStatement,
+                Statement::Switch(_)      => NodeKind::SwitchStatement,
+                Statement::Import(_)      => NodeKind::ImportDeclaration,
+            },
+            NodeRef::Expression(node) => match node.item {
+                Expression::Void              => NodeKind::VoidExpression,
+                Expression::This(_)           => NodeKind::ThisExpression,
+                Expression::Super(_)          => NodeKind::SuperExpression,
+                Expression::Identifier(_)     => NodeKind::IdentifierExpression,
+                Expression::Literal(_)        => NodeKind::LiteralExpression,
+                Expression::Sequence(_)       => NodeKind::SequenceExpression,
+                Expression::Array(_)          => NodeKind::ArrayExpression,
+                Expression::Member(_)         => NodeKind::MemberExpression,
+                Expression::ComputedMember(_) => NodeKind::ComputedMemberExpression,
+                Expression::MetaProperty(_)   => NodeKind::MetaProperty,
+                Expression::Call(_)           => NodeKind::CallExpression,
+                Expression::Binary(_)         => NodeKind::BinaryExpression,
+                Expression::Prefix(_)         => NodeKind::PrefixExpression,
+                Expression::Postfix(_)        => NodeKind::PostfixExpression,
+                Expression::Conditional(_)    => NodeKind::ConditionalExpression,
+                Expression::Template(_)       => NodeKind::TemplateLiteral,
+                Expression::TaggedTemplate(_) => NodeKind::TaggedTemplateExpression,
+                Expression::Spread(_)         => NodeKind::SpreadExpression,
+                Expression::Arrow(_)          => NodeKind::ArrowExpression,
+                Expression::Object(_)         => NodeKind::ObjectExpression,
+                Expression::Function(_)       => NodeKind::FunctionExpression,
+                Expression::Class(_)          => NodeKind::ClassExpression,
+            },
+            NodeRef::Pattern(_)         => NodeKind::Pattern,
+            NodeRef::Property(_)        => NodeKind::Property,
+            NodeRef::PropertyKey(_)     => NodeKind::PropertyKey,
+            NodeRef::ClassMember(_)     => NodeKind::ClassMember,
+            NodeRef::Declarator(_)      => NodeKind::Declarator,
+            NodeRef::ForInit(_)         => NodeKind::ForInit,
+            NodeRef::CatchClause(_)     => NodeKind::CatchClause,
+            NodeRef::SwitchCase(_)      => NodeKind::SwitchCase,
+            NodeRef::Block(_)           => NodeKind::Block,
+            NodeRef::ImportSpecifier(_) => NodeKind::ImportSpecifier,
+            NodeRef::Method(_)          => NodeKind::Method,
+            NodeRef::TemplateQuasi(_)   => NodeKind::TemplateQuasi,
+        }
+    }
+
+    /// Get the `StatementNode`, if this is a statement.
+    #[inline]
+    pub fn as_statement(&self) -> Option<&'ast StatementNode<'ast>> {
+        match *self {
+            NodeRef::Statement(node) => Some(node),
+            _                        => None,
+        }
+    }
+
+    /// Get the `ExpressionNode`, if this is an expression.
+    #[inline]
+    pub fn as_expression(&self) -> Option<&'ast ExpressionNode<'ast>> {
+        match *self {
+            NodeRef::Expression(node) => Some(node),
+            _                         => None,
+        }
+    }
+}
+
+/// Kind of a `NodeRef`. Expressions and statements get a kind per variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    // statements
+    EmptyStatement,
+    DebuggerStatement,
+    ExpressionStatement,
+    DeclarationStatement,
+    ReturnStatement,
+    BreakStatement,
+    ContinueStatement,
+    ThrowStatement,
+    IfStatement,
+    WhileStatement,
+    DoStatement,
+    ForStatement,
+    ForInStatement,
+    ForOfStatement,
+    TryStatement,
+    BlockStatement,
+    LabeledStatement,
+    FunctionStatement,
+    ClassStatement,
+    SwitchStatement,
+    ImportDeclaration,
+
+    // expressions
+    VoidExpression,
+    ThisExpression,
+    SuperExpression,
+    IdentifierExpression,
+    LiteralExpression,
+    SequenceExpression,
+    ArrayExpression,
+    MemberExpression,
+    ComputedMemberExpression,
+    MetaProperty,
+    CallExpression,
+    BinaryExpression,
+    PrefixExpression,
+    PostfixExpression,
+    ConditionalExpression,
+    TemplateLiteral,
+    TaggedTemplateExpression,
+    SpreadExpression,
+    ArrowExpression,
+    ObjectExpression,
+    FunctionExpression,
+    ClassExpression,
+
+    // other nodes
+    Pattern,
+    Property,
+    PropertyKey,
+    ClassMember,
+    Declarator,
+    ForInit,
+    CatchClause,
+    SwitchCase,
+    Block,
+    ImportSpecifier,
+    Method,
+    TemplateQuasi,
+}
+
+/// Field of the parent a node is stored in, with the index for lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    pub field: &'static str,
+    pub index: Option<usize>,
+}
+
+impl Slot {
+    #[inline]
+    pub fn new(field: &'static str) -> Self {
+        Slot {
+            field,
+            index: None,
+        }
+    }
+
+    /// Same field, at the given index of a list.
+    #[inline]
+    pub fn at(self, index: usize) -> Self {
+        Slot {
+            field: self.field,
+            index: Some(index),
+        }
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{}[{}]", self.field, index),
+            None        => f.write_str(self.field),
+        }
+    }
+}
+
+/// Nodes between the root and the node currently being visited, kept up to
+/// date by the `Visitable` implementations and passed to every `Visitor` hook.
+#[derive(Debug, Default)]
+pub struct Path<'ast> {
+    stack: Vec<(NodeRef<'ast>, Option<Slot>)>,
+    slot: Option<Slot>,
+}
+
+impl<'ast> Path<'ast> {
+    /// Create an empty `Path`, for visiting from the root.
+    #[inline]
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    /// The node currently being visited.
+    #[inline]
+    pub fn node(&self) -> Option<NodeRef<'ast>> {
+        self.stack.last().map(|&(node, _)| node)
+    }
+
+    /// The slot of the current node in its parent. Statements at the root
+    /// of the module have the `body[n]` slot, without a parent.
+    #[inline]
+    pub fn slot(&self) -> Option<Slot> {
+        self.stack.last().and_then(|&(_, slot)| slot)
+    }
+
+    /// Parent of the current node.
+    #[inline]
+    pub fn parent(&self) -> Option<NodeRef<'ast>> {
+        self.ancestors().next()
+    }
+
+    /// Iterate over the ancestors of the current node, closest first.
+    #[inline]
+    pub fn ancestors(&self) -> Ancestors<'_, 'ast> {
+        let len = self.stack.len().saturating_sub(1);
+
+        Ancestors {
+            iter: self.stack[..len].iter().rev(),
+        }
+    }
+
+    /// Number of nodes on the path, including the current node.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Closest ancestor of the given kind.
+    #[inline]
+    pub fn find_ancestor(&self, kind: NodeKind) -> Option<NodeRef<'ast>> {
+        self.ancestors().find(|node| node.kind() == kind)
+    }
+
+    /// Whether the current node is an expression whose value is discarded,
+    /// because it makes up an expression statement.
+    #[inline]
+    pub fn is_statement_position(&self) -> bool {
+        match (self.node(), self.parent()) {
+            (Some(NodeRef::Expression(_)), Some(parent)) => parent.kind() == NodeKind::ExpressionStatement,
+            _ => false,
+        }
+    }
+
+    /// Whether the current node is a statement directly inside a list of
+    /// statements, as opposed to a body of `if`, `while`, labels etc.
+    #[inline]
+    pub fn is_statement_list_item(&self) -> bool {
+        match (self.node(), self.slot()) {
+            (Some(NodeRef::Statement(_)), Some(slot)) => slot.index.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Set the slot of the next node to be pushed.
+    #[inline]
+    pub(crate) fn field(&mut self, field: &'static str) {
+        self.slot = Some(Slot::new(field));
+    }
+
+    /// Take the slot of the next node to be pushed, used by lists to index it.
+    #[inline]
+    pub(crate) fn take_slot(&mut self) -> Option<Slot> {
+        self.slot.take()
+    }
+
+    #[inline]
+    pub(crate) fn set_slot(&mut self, slot: Option<Slot>) {
+        self.slot = slot;
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, node: NodeRef<'ast>) {
+        let slot = self.slot.take();
+
+        self.stack.push((node, slot));
+    }
+
+    /// Pop the current node, restoring its slot so it can be pushed again.
+    #[inline]
+    pub(crate) fn pop(&mut self) {
+        self.slot = self.stack.pop().and_then(|(_, slot)| slot);
+    }
+}
+
+/// Iterator over the ancestors on a `Path`, see `Path::ancestors`.
+pub struct Ancestors<'path, 'ast: 'path> {
+    iter: Rev<slice::Iter<'path, (NodeRef<'ast>, Option<Slot>)>>,
+}
+
+impl<'path, 'ast> Iterator for Ancestors<'path, 'ast> {
+    type Item = NodeRef<'ast>;
+
+    #[inline]
+    fn next(&mut self) -> Option<NodeRef<'ast>> {
+        self.iter.next().map(|&(node, _)| node)
+    }
+}
@@ -1,123 +1,141 @@
 use ratel::ast::{Node, Statement, StatementNode};
 use ratel::ast::statement::*;
 
-use {Visitor, Visitable, ParentNode, ScopeKind, NoParent};
+use {current, Path, Visitor, Visitable, VisitAction, ScopeKind, NoParent};
 
 
 impl<'ast> Visitable<'ast> for StatementNode<'ast> {
     type Parent = NoParent;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         use self::Statement::*;
 
-        match self.item {
-            Empty => { 
-                // EmptyStatement doesn't have children, we return early to avoid calling pop_parent
-                return;
-            },
-            Debugger => {
-                return;
-            },
-            Expression(ref expression) => {
-                visitor.on_expression_statement(expression, self);
-                visitor.push_parent(ParentNode::from(self));
-                expression.visit_with(visitor);
-            },
-            Declaration(ref declaration) => {
-                visitor.on_declaration_statement(declaration, self);
-                visitor.push_parent(ParentNode::from(self));
-                declaration.visit_with(visitor);
-            },
-            Return(ref return_statement) => {
-                visitor.on_return_statement(return_statement, self);
-                visitor.push_parent(ParentNode::from(self));
-                return_statement.visit_with(visitor);
-            },
-            Break(ref break_statement) => {
-                visitor.on_break_statement(break_statement, self);
-                visitor.push_parent(ParentNode::from(self));
-                break_statement.visit_with(visitor);
-            },
-            Continue(ref continue_statement) => {
-                visitor.on_continue_statement(continue_statement, self);
-                visitor.push_parent(ParentNode::from(self));
-                continue_statement.visit_with(visitor);
-            },
-            Throw(ref throw) => {
-                visitor.on_throw_statement(throw, self);
-                visitor.push_parent(ParentNode::from(self));
-                throw.visit_with(visitor);
-            },
-            If(ref if_statement) => {
-                visitor.on_if_statement(if_statement, self);
-                visitor.push_parent(ParentNode::from(self));
-                if_statement.visit_with(visitor);
-            },
-            While(ref while_statement) => {
-                visitor.on_while_statement(while_statement, self);
-                visitor.push_parent(ParentNode::from(self));
-                while_statement.visit_with(visitor);
-            },
-            Do(ref do_statement) => {
-                visitor.on_do_statement(do_statement, self);
-                visitor.push_parent(ParentNode::from(self));
-                do_statement.visit_with(visitor);
-            },
-            For(ref for_statement) => {
-                visitor.on_for_statement(for_statement, self);
-                visitor.push_parent(ParentNode::from(self));
-                for_statement.visit_with(visitor);
-            },
-            ForIn(ref for_in) => {
-                visitor.on_for_in_statement(for_in, self);
-                visitor.push_parent(ParentNode::from(self));
-                for_in.visit_with(visitor);
-            },
-            ForOf(ref for_of) => {
-                visitor.on_for_of_statement(for_of, self);
-                visitor.push_parent(ParentNode::from(self));
-                for_of.visit_with(visitor);
-            },
-            Try(ref try) => {
-                visitor.on_try_statement(try, self);
-                visitor.push_parent(ParentNode::from(self));
-                try.visit_with(visitor);
-            },
-            Labeled(ref labeled) => {
-                visitor.on_labeled_statement(labeled, self);
-                visitor.push_parent(ParentNode::from(self));
-                labeled.visit_with(visitor);
-            },
-            Block(ref block) => {
-                visitor.on_block_statement(block, self);
-                visitor.push_parent(ParentNode::from(self));
-                block.visit_with(visitor);
-            },
-            Switch(ref switch) => {
-                visitor.on_switch_statement(switch, self);
-                visitor.push_parent(ParentNode::from(self));
-                switch.visit_with(visitor);
-            },
-            Function(ref function) => {
-                visitor.on_function_statement(function, self);
-                visitor.push_parent(ParentNode::from(self));
-                function.visit_with(visitor);
-            },
-            Class(ref class) => {
-                visitor.on_class_statement(class, self);
-                visitor.push_parent(ParentNode::from(self));
-                class.visit_with(visitor);
-            },
-            Import(ref import) => {
-                visitor.on_import_declaration_statement(import, self);
-                import.visit_with(visitor);
+        loop {
+            match current(self).item {
+                Empty | Debugger => return VisitAction::Continue,
+                Expression(ref expression) => walk_node!(
+                    path, self,
+                    visitor.on_expression_statement(expression, self, path),
+                    {
+                        path.field("expression");
+                        expression.visit_with_path(visitor, path)
+                    },
+                    visitor.on_expression_statement_exit(expression, self, path)
+                ),
+                Declaration(ref declaration) => walk_node!(
+                    path, self,
+                    visitor.on_declaration_statement(declaration, self, path),
+                    declaration.visit_with_path(visitor, path),
+                    visitor.on_declaration_statement_exit(declaration, self, path)
+                ),
+                Return(ref return_statement) => walk_node!(
+                    path, self,
+                    visitor.on_return_statement(return_statement, self, path),
+                    return_statement.visit_with_path(visitor, path),
+                    visitor.on_return_statement_exit(return_statement, self, path)
+                ),
+                Break(ref break_statement) => walk_node!(
+                    path, self,
+                    visitor.on_break_statement(break_statement, self, path),
+                    break_statement.visit_with_path(visitor, path),
+                    visitor.on_break_statement_exit(break_statement, self, path)
+                ),
+                Continue(ref continue_statement) => walk_node!(
+                    path, self,
+                    visitor.on_continue_statement(continue_statement, self, path),
+                    continue_statement.visit_with_path(visitor, path),
+                    visitor.on_continue_statement_exit(continue_statement, self, path)
+                ),
+                Throw(ref throw) => walk_node!(
+                    path, self,
+                    visitor.on_throw_statement(throw, self, path),
+                    throw.visit_with_path(visitor, path),
+                    visitor.on_throw_statement_exit(throw, self, path)
+                ),
+                If(ref if_statement) => walk_node!(
+                    path, self,
+                    visitor.on_if_statement(if_statement, self, path),
+                    if_statement.visit_with_path(visitor, path),
+                    visitor.on_if_statement_exit(if_statement, self, path)
+                ),
+                While(ref while_statement) => walk_node!(
+                    path, self,
+                    visitor.on_while_statement(while_statement, self, path),
+                    while_statement.visit_with_path(visitor, path),
+                    visitor.on_while_statement_exit(while_statement, self, path)
+                ),
+                Do(ref do_statement) => walk_node!(
+                    path, self,
+                    visitor.on_do_statement(do_statement, self, path),
+                    do_statement.visit_with_path(visitor, path),
+                    visitor.on_do_statement_exit(do_statement, self, path)
+                ),
+                For(ref for_statement) => walk_node!(
+                    path, self,
+                    visitor.on_for_statement(for_statement, self, path),
+                    for_statement.visit_with_path(visitor, path),
+                    visitor.on_for_statement_exit(for_statement, self, path)
+                ),
+                ForIn(ref for_in) => walk_node!(
+                    path, self,
+                    visitor.on_for_in_statement(for_in, self, path),
+                    for_in.visit_with_path(visitor, path),
+                    visitor.on_for_in_statement_exit(for_in, self, path)
+                ),
+                ForOf(ref for_of) => walk_node!(
+                    path, self,
+                    visitor.on_for_of_statement(for_of, self, path),
+                    for_of.visit_with_path(visitor, path),
+                    visitor.on_for_of_statement_exit(for_of, self, path)
+                ),
+                Try(ref try) => walk_node!(
+                    path, self,
+                    visitor.on_try_statement(try, self, path),
+                    try.visit_with_path(visitor, path),
+                    visitor.on_try_statement_exit(try, self, path)
+                ),
+                Labeled(ref labeled) => walk_node!(
+                    path, self,
+                    visitor.on_labeled_statement(labeled, self, path),
+                    labeled.visit_with_path(visitor, path),
+                    visitor.on_labeled_statement_exit(labeled, self, path)
+                ),
+                Block(ref block) => walk_node!(
+                    path, self,
+                    visitor.on_block_statement(block, self, path),
+                    block.visit_with_path(visitor, path),
+                    visitor.on_block_statement_exit(block, self, path)
+                ),
+                Switch(ref switch) => walk_node!(
+                    path, self,
+                    visitor.on_switch_statement(switch, self, path),
+                    switch.visit_with_path(visitor, path),
+                    visitor.on_switch_statement_exit(switch, self, path)
+                ),
+                Function(ref function) => walk_node!(
+                    path, self,
+                    visitor.on_function_statement(function, self, path),
+                    function.visit_with_path(visitor, path),
+                    visitor.on_function_statement_exit(function, self, path)
+                ),
+                Class(ref class) => walk_node!(
+                    path, self,
+                    visitor.on_class_statement(class, self, path),
+                    class.visit_with_path(visitor, path),
+                    visitor.on_class_statement_exit(class, self, path)
+                ),
+                Import(ref import) => walk_node!(
+                    path, self,
+                    visitor.on_import_declaration_statement(import, self, path),
+                    import.visit_with_path(visitor, path),
+                    visitor.on_import_declaration_statement_exit(import, self, path)
+                ),
             }
         }
-        visitor.pop_parent();
     }
 }
 
@@ -125,38 +143,39 @@ impl<'ast> Visitable<'ast> for BlockStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        visitor.on_enter_scope(ScopeKind::Block);
-        self.body.visit_with(visitor);
-        visitor.on_leave_scope();
+        visitor.on_enter_scope(ScopeKind::Block, path);
+        path.field("body");
+        let action = self.body.visit_with_path(visitor, path);
+        visitor.on_leave_scope(path);
+        action
     }
 }
 
-impl<'ast> Visitable<'ast> for Node<'ast, BlockStatement<'ast>> {
-    type Parent = StatementNode<'ast>;
-
-    #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
-    where
-        V: Visitor<'ast>,
-    {
-        self.item.visit_with(visitor);
-    }
+visitable_nodes! {
+    BlockStatement<'ast> => on_block, on_block_exit;
+    Declarator<'ast> => on_declarator, on_declarator_exit;
+    ForInit<'ast> => on_for_init, on_for_init_exit;
+    CatchClause<'ast> => on_catch_clause, on_catch_clause_exit;
+    SwitchCase<'ast> => on_switch_case, on_switch_case_exit;
+    ForImportSpecifier<'ast> => on_import_specifier, on_import_specifier_exit;
 }
 
 impl<'ast> Visitable<'ast> for Declarator<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.id.visit_with(visitor);
-        self.init.visit_with(visitor);
+        path.field("id");
+        try_visit!(self.id.visit_with_path(visitor, path));
+        path.field("init");
+        self.init.visit_with_path(visitor, path)
     }
 }
 
@@ -164,11 +183,12 @@ impl<'ast> Visitable<'ast> for DeclarationStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.declarators.visit_with(visitor);
+        path.field("declarators");
+        self.declarators.visit_with_path(visitor, path)
     }
 }
 
@@ -176,11 +196,12 @@ impl<'ast> Visitable<'ast> for ReturnStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.value.visit_with(visitor);
+        path.field("value");
+        self.value.visit_with_path(visitor, path)
     }
 }
 
@@ -188,10 +209,11 @@ impl<'ast> Visitable<'ast> for BreakStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V: Visitor<'ast>>(&self, _visitor: &mut V) {
+    fn visit_with_path<V: Visitor<'ast>>(&self, _: &mut V, _: &mut Path<'ast>) -> VisitAction {
         // FIXME:
         // INTENTIONALLY KEPT EMPTY FOR NOW!
         // The identifier here is a label reference, _not_ a variable!
+        VisitAction::Continue
     }
 }
 
@@ -199,10 +221,11 @@ impl<'ast> Visitable<'ast> for ContinueStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V: Visitor<'ast>>(&self, _visitor: &mut V) {
+    fn visit_with_path<V: Visitor<'ast>>(&self, _: &mut V, _: &mut Path<'ast>) -> VisitAction {
         // FIXME:
         // INTENTIONALLY KEPT EMPTY FOR NOW!
         // The identifier here is a label reference, _not_ a variable!
+        VisitAction::Continue
     }
 }
 
@@ -210,11 +233,12 @@ impl<'ast> Visitable<'ast> for ThrowStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.value.visit_with(visitor);
+        path.field("value");
+        self.value.visit_with_path(visitor, path)
     }
 }
 
@@ -222,13 +246,16 @@ impl<'ast> Visitable<'ast> for IfStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.test.visit_with(visitor);
-        self.consequent.visit_with(visitor);
-        self.alternate.visit_with(visitor);
+        path.field("test");
+        try_visit!(self.test.visit_with_path(visitor, path));
+        path.field("consequent");
+        try_visit!(self.consequent.visit_with_path(visitor, path));
+        path.field("alternate");
+        self.alternate.visit_with_path(visitor, path)
     }
 }
 
@@ -236,12 +263,14 @@ impl<'ast> Visitable<'ast> for WhileStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.test.visit_with(visitor);
-        self.body.visit_with(visitor);
+        path.field("test");
+        try_visit!(self.test.visit_with_path(visitor, path));
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -249,12 +278,14 @@ impl<'ast> Visitable<'ast> for DoStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.body.visit_with(visitor);
-        self.test.visit_with(visitor);
+        path.field("body");
+        try_visit!(self.body.visit_with_path(visitor, path));
+        path.field("test");
+        self.test.visit_with_path(visitor, path)
     }
 }
 
@@ -262,13 +293,16 @@ impl<'ast> Visitable<'ast> for ForInit<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         match *self {
-            ForInit::Declaration(ref declaration) => declaration.visit_with(visitor),
-            ForInit::Expression(ref expression) => expression.visit_with(visitor),
+            ForInit::Declaration(ref declaration) => declaration.visit_with_path(visitor, path),
+            ForInit::Expression(ref expression) => {
+                path.field("expression");
+                expression.visit_with_path(visitor, path)
+            },
         }
     }
 }
@@ -277,14 +311,18 @@ impl<'ast> Visitable<'ast> for ForStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.init.visit_with(visitor);
-        self.test.visit_with(visitor);
-        self.update.visit_with(visitor);
-        self.body.visit_with(visitor);
+        path.field("init");
+        try_visit!(self.init.visit_with_path(visitor, path));
+        path.field("test");
+        try_visit!(self.test.visit_with_path(visitor, path));
+        path.field("update");
+        try_visit!(self.update.visit_with_path(visitor, path));
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -292,13 +330,16 @@ impl<'ast> Visitable<'ast> for ForInStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.left.visit_with(visitor);
-        self.right.visit_with(visitor);
-        self.body.visit_with(visitor);
+        path.field("left");
+        try_visit!(self.left.visit_with_path(visitor, path));
+        path.field("right");
+        try_visit!(self.right.visit_with_path(visitor, path));
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -306,13 +347,16 @@ impl<'ast> Visitable<'ast> for ForOfStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.left.visit_with(visitor);
-        self.right.visit_with(visitor);
-        self.body.visit_with(visitor);
+        path.field("left");
+        try_visit!(self.left.visit_with_path(visitor, path));
+        path.field("right");
+        try_visit!(self.right.visit_with_path(visitor, path));
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -320,12 +364,14 @@ impl<'ast> Visitable<'ast> for CatchClause<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.param.visit_with(visitor);
-        self.body.visit_with(visitor);
+        path.field("param");
+        try_visit!(self.param.visit_with_path(visitor, path));
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -333,13 +379,16 @@ impl<'ast> Visitable<'ast> for TryStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.block.visit_with(visitor);
-        self.handler.visit_with(visitor);
-        self.finalizer.visit_with(visitor);
+        path.field("block");
+        try_visit!(self.block.visit_with_path(visitor, path));
+        path.field("handler");
+        try_visit!(self.handler.visit_with_path(visitor, path));
+        path.field("finalizer");
+        self.finalizer.visit_with_path(visitor, path)
     }
 }
 
@@ -347,12 +396,13 @@ impl<'ast> Visitable<'ast> for LabeledStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         // FIXME: newtype for label
-        self.body.visit_with(visitor);
+        path.field("body");
+        self.body.visit_with_path(visitor, path)
     }
 }
 
@@ -360,12 +410,14 @@ impl<'ast> Visitable<'ast> for SwitchCase<'ast> {
     type Parent = Node<'ast, Self>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.test.visit_with(visitor);
-        self.consequent.visit_with(visitor);
+        path.field("test");
+        try_visit!(self.test.visit_with_path(visitor, path));
+        path.field("consequent");
+        self.consequent.visit_with_path(visitor, path)
     }
 }
 
@@ -373,14 +425,17 @@ impl<'ast> Visitable<'ast> for SwitchStatement<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.discriminant.visit_with(visitor);
-        visitor.on_enter_scope(ScopeKind::Block);
-        self.cases.body.visit_with(visitor);
-        visitor.on_leave_scope();
+        path.field("discriminant");
+        try_visit!(self.discriminant.visit_with_path(visitor, path));
+        visitor.on_enter_scope(ScopeKind::Block, path);
+        path.field("cases");
+        let action = self.cases.body.visit_with_path(visitor, path);
+        visitor.on_leave_scope(path);
+        action
     }
 }
 
@@ -388,12 +443,13 @@ impl<'ast> Visitable<'ast> for ImportDeclaration<'ast> {
     type Parent = StatementNode<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.source.visit_with(visitor);
-        self.specifiers.visit_with(visitor);
+        try_visit!(self.source.visit_with_path(visitor, path));
+        path.field("specifiers");
+        self.specifiers.visit_with_path(visitor, path)
     }
 }
 
@@ -401,14 +457,14 @@ impl<'ast> Visitable<'ast> for ForImportSpecifier<'ast> {
     type Parent = Node<'ast, ForImportSpecifier<'ast>>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
         match *self {
-            ForImportSpecifier::ImportSpecifier(ref spec) => spec.visit_with(visitor),
-            ForImportSpecifier::ImportDefaultSpecifier(ref spec) => spec.visit_with(visitor),
-            ForImportSpecifier::ImportNamespaceSpecifier(ref spec) => spec.visit_with(visitor),
+            ForImportSpecifier::ImportSpecifier(ref spec) => spec.visit_with_path(visitor, path),
+            ForImportSpecifier::ImportDefaultSpecifier(ref spec) => spec.visit_with_path(visitor, path),
+            ForImportSpecifier::ImportNamespaceSpecifier(ref spec) => spec.visit_with_path(visitor, path),
         }
     }
 }
@@ -417,12 +473,14 @@ impl<'ast> Visitable<'ast> for ImportSpecifier<'ast> {
     type Parent = ForImportSpecifier<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.local.visit_with(visitor);
-        self.imported.visit_with(visitor);
+        path.field("local");
+        try_visit!(self.local.visit_with_path(visitor, path));
+        path.field("imported");
+        self.imported.visit_with_path(visitor, path)
     }
 }
 
@@ -430,11 +488,12 @@ impl<'ast> Visitable<'ast> for ImportDefaultSpecifier<'ast> {
     type Parent = ForImportSpecifier<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.local.visit_with(visitor);
+        path.field("local");
+        self.local.visit_with_path(visitor, path)
     }
 }
 
@@ -442,10 +501,11 @@ impl<'ast> Visitable<'ast> for ImportNamespaceSpecifier<'ast> {
     type Parent = ForImportSpecifier<'ast>;
 
     #[inline]
-    fn visit_with<V>(&'ast self, visitor: &mut V)
+    fn visit_with_path<V>(&'ast self, visitor: &mut V, path: &mut Path<'ast>) -> VisitAction
     where
         V: Visitor<'ast>,
     {
-        self.local.visit_with(visitor);
+        path.field("local");
+        self.local.visit_with_path(visitor, path)
     }
 }
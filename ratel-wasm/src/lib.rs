@@ -5,6 +5,7 @@ extern crate wasm_bindgen;
 
 use wasm_bindgen::prelude::*;
 
+use ratel::Arena;
 use ratel::error::{Error, ParseError};
 
 fn format_errors(errors: Vec<Error>, source: &str) -> String {
@@ -26,7 +27,9 @@ fn format_errors(errors: Vec<Error>, source: &str) -> String {
 
 #[wasm_bindgen]
 pub fn transform(data: &str, minify: bool) -> String {
-	match ratel::parse(&data) {
+	let arena = Arena::new();
+
+	match ratel::parse_in(&arena, data) {
 		Ok(module) => {
 			ratel_codegen::codegen(&module, minify)
 		},
@@ -36,7 +39,9 @@ pub fn transform(data: &str, minify: bool) -> String {
 
 #[wasm_bindgen(js_name = generateAST)]
 pub fn generate_ast(data: &str, minify: bool) -> String {
-	match ratel::parse(&data) {
+	let arena = Arena::new();
+
+	match ratel::parse_in(&arena, data) {
 		Ok(module) => {
 			if minify {
 		    	format!("{:?}", module.body())
@@ -50,8 +55,28 @@ pub fn generate_ast(data: &str, minify: bool) -> String {
 
 #[wasm_bindgen(js_name = generateASTEstree)]
 pub fn generate_ast_estree(data: &str, minify: bool) -> String {
-	match ratel::parse(&data) {
+	let arena = Arena::new();
+
+	match ratel::parse_in(&arena, data) {
+		Ok(module) => {
+			if minify {
+				serde_json::to_string(&module).unwrap()
+			} else {
+				serde_json::to_string_pretty(&module).unwrap()
+			}
+		},
+		Err(errors) => format_errors(errors, data)
+	}
+}
+
+#[wasm_bindgen(js_name = generateASTBabel)]
+pub fn generate_ast_babel(data: &str, minify: bool) -> String {
+	let arena = Arena::new();
+
+	match ratel::parse_in(&arena, data) {
 		Ok(module) => {
+			let module = module.with_flavor(ratel::AstFlavor::Babel);
+
 			if minify {
 				serde_json::to_string(&module).unwrap()
 			} else {
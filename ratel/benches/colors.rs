@@ -97,7 +97,8 @@ fn parse_to_ast(b: &mut Bencher) {
     b.bytes = SOURCE.len() as u64;
 
     b.iter(|| {
-        let _module = ratel::parse(SOURCE).expect("Must parse");
+        let arena = ratel::Arena::new();
+        let _module = ratel::parse_in(&arena, SOURCE).expect("Must parse");
     });
 }
 
@@ -119,7 +120,8 @@ fn tokenize(b: &mut Bencher) {
 
 #[bench]
 fn serialize_to_json(b: &mut Bencher) {
-    let module = ratel::parse(SOURCE).expect("Must parse");
+    let arena = ratel::Arena::new();
+    let module = ratel::parse_in(&arena, SOURCE).expect("Must parse");
     let output = serde_json::to_string(&module).unwrap();
 
     b.bytes = output.len() as u64;
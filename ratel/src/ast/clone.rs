@@ -0,0 +1,867 @@
+//! Deep cloning of AST subtrees.
+//!
+//! Copying a `Node` only copies the pointer, so both copies share the same
+//! `Loc` and any `Node::set` on one of them changes the other. `DeepClone`
+//! instead re-allocates the whole subtree, including strings, into a target
+//! `Arena`.
+
+use toolshed::Arena;
+use toolshed::list::ListBuilder;
+
+use ast::{Node, Loc, NodeList, Block, Literal, Pattern, Expression, ExpressionNode, Statement, StatementList};
+use ast::{Property, PropertyKey, Declarator, Function, Class, ClassMember, Name};
+use ast::{EmptyName, OptionalName, MandatoryName, OperatorKind};
+use ast::expression::*;
+use ast::statement::*;
+
+/// What to do with spans of cloned nodes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Spans {
+    /// Cloned nodes keep the `start` and `end` of the original.
+    Preserve,
+
+    /// Cloned nodes get an empty `0..0` span, useful when moving
+    /// code to a different source.
+    Strip,
+}
+
+/// Replaces identifiers with other nodes while cloning, such as the
+/// placeholders of templates. Every method returns `None` by default,
+/// cloning the identifier as it is.
+pub trait CloneHook<'ast> {
+    /// Name in place of an identifier in a position that only allows
+    /// a name, such as a property key, a function name or a label.
+    #[inline]
+    fn name(&self, _ident: &str) -> Option<&'ast str> {
+        None
+    }
+
+    /// Expression in place of an identifier expression. Property keys
+    /// replaced by an expression become computed, `{ [key]: value }`.
+    #[inline]
+    fn expression(&self, _ident: &str) -> Option<ExpressionNode<'ast>> {
+        None
+    }
+
+    /// Pattern in place of an identifier pattern, or of an identifier
+    /// expression, which gets the pattern as an expression.
+    #[inline]
+    fn pattern(&self, _ident: &str) -> Option<Node<'ast, Pattern<'ast>>> {
+        None
+    }
+
+    /// Statements in place of an `ident;` statement, spliced into
+    /// the surrounding list, or wrapped in a block elsewhere.
+    #[inline]
+    fn statements(&self, _ident: &str) -> Option<StatementList<'ast>> {
+        None
+    }
+}
+
+/// Context for `DeepClone`, holding the target `Arena`.
+#[derive(Clone, Copy)]
+pub struct Cloner<'ast> {
+    arena: &'ast Arena,
+    spans: Spans,
+    hook: Option<&'ast dyn CloneHook<'ast>>,
+}
+
+impl<'ast> Cloner<'ast> {
+    /// Create a new `Cloner` allocating on the given `Arena`.
+    #[inline]
+    pub fn new(arena: &'ast Arena, spans: Spans) -> Self {
+        Cloner {
+            arena,
+            spans,
+            hook: None,
+        }
+    }
+
+    /// Create a new `Cloner` replacing identifiers with the `CloneHook`.
+    #[inline]
+    pub fn with_hook(arena: &'ast Arena, spans: Spans, hook: &'ast dyn CloneHook<'ast>) -> Self {
+        Cloner {
+            arena,
+            spans,
+            hook: Some(hook),
+        }
+    }
+
+    /// Get the `Arena` this cloner allocates on.
+    #[inline]
+    pub fn arena(&self) -> &'ast Arena {
+        self.arena
+    }
+
+    /// Get the span mode of this cloner.
+    #[inline]
+    pub fn spans(&self) -> Spans {
+        self.spans
+    }
+
+    /// Allocate a node cloned from `source`, with its span unless stripped.
+    #[inline]
+    fn alloc_as<S, T: Copy>(&self, source: &Loc<S>, item: T) -> Node<'ast, T> {
+        let (start, end) = match self.spans {
+            Spans::Preserve => (source.start, source.end),
+            Spans::Strip    => (0, 0),
+        };
+
+        Node::new(self.arena.alloc(Loc::new(start, end, item)))
+    }
+
+    /// Allocate a synthesized node.
+    #[inline]
+    fn alloc<T: Copy>(&self, item: T) -> Node<'ast, T> {
+        Node::new(self.arena.alloc(Loc::new(0, 0, item)))
+    }
+
+    #[inline]
+    fn str(&self, value: &str) -> &'ast str {
+        self.arena.alloc_str(value)
+    }
+
+    /// Nodes from the hook are cloned as well, so that using the same
+    /// node twice doesn't alias nodes.
+    #[inline]
+    fn substituted<T: DeepClone<'ast>>(&self, node: &T) -> T::Output {
+        node.deep_clone_with(&Cloner::new(self.arena, Spans::Preserve))
+    }
+
+    #[inline]
+    fn hook_expression(&self, ident: &str) -> Option<ExpressionNode<'ast>> {
+        self.hook.and_then(|hook| hook.expression(ident))
+    }
+
+    #[inline]
+    fn hook_pattern(&self, ident: &str) -> Option<Node<'ast, Pattern<'ast>>> {
+        self.hook.and_then(|hook| hook.pattern(ident))
+    }
+
+    /// Patterns nested in object patterns are stored as expressions,
+    /// same as the parser does.
+    fn pattern_as_expression(&self, pattern: Node<'ast, Pattern<'ast>>) -> Node<'ast, Expression<'ast>> {
+        let expression = match pattern.item {
+            Pattern::Void => Expression::Void,
+            Pattern::Identifier(name) => Expression::Identifier(name),
+            Pattern::ObjectPattern { properties } => Expression::Object(ObjectExpression {
+                body: properties,
+            }),
+            Pattern::ArrayPattern { elements } => Expression::Array(ArrayExpression {
+                body: NodeList::from_iter(self.arena, elements.iter().map(|element| self.pattern_as_expression(*element))),
+            }),
+            Pattern::RestElement { argument } => Expression::Spread(SpreadExpression {
+                argument: self.alloc_as(&argument, Expression::Identifier(argument.item)),
+            }),
+            Pattern::AssignmentPattern { left, right } => Expression::Binary(BinaryExpression {
+                operator: OperatorKind::Assign,
+                left: self.pattern_as_expression(left),
+                right,
+            }),
+        };
+
+        self.alloc_as(&pattern, expression)
+    }
+
+    /// Clone an identifier in a position that only allows a name.
+    #[inline]
+    fn name(&self, ident: &str) -> &'ast str {
+        match self.hook.and_then(|hook| hook.name(ident)) {
+            Some(name) => name,
+            None       => self.str(ident),
+        }
+    }
+}
+
+/// Recursively clone an AST subtree into another `Arena`.
+pub trait DeepClone<'ast> {
+    type Output: 'ast + Copy;
+
+    /// Clone into the `Arena`, preserving spans.
+    #[inline]
+    fn deep_clone(&self, arena: &'ast Arena) -> Self::Output {
+        self.deep_clone_with(&Cloner::new(arena, Spans::Preserve))
+    }
+
+    /// Clone using the given `Cloner`.
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output;
+
+    /// Clone a node, types that can be template placeholders override
+    /// this to return substituted nodes.
+    #[doc(hidden)]
+    #[inline]
+    fn deep_clone_node(loc: &Loc<Self>, ctx: &Cloner<'ast>) -> Node<'ast, Self::Output> where
+        Self: Sized,
+    {
+        ctx.alloc_as(loc, loc.item.deep_clone_with(ctx))
+    }
+
+    /// Clone a list of nodes, statements override this to splice
+    /// substituted lists.
+    #[doc(hidden)]
+    #[inline]
+    fn deep_clone_list<'t>(list: NodeList<'t, Self>, ctx: &Cloner<'ast>) -> NodeList<'ast, Self::Output> where
+        Self: 't + Sized,
+    {
+        NodeList::from_iter(ctx.arena, list.iter().map(|node| Self::deep_clone_node(node, ctx)))
+    }
+}
+
+impl<'t, 'ast, T> DeepClone<'ast> for Node<'t, T> where
+    T: 't + DeepClone<'ast>,
+{
+    type Output = Node<'ast, T::Output>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        T::deep_clone_node(self, ctx)
+    }
+}
+
+impl<'t, 'ast, T> DeepClone<'ast> for NodeList<'t, T> where
+    T: 't + Copy + DeepClone<'ast>,
+{
+    type Output = NodeList<'ast, T::Output>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        T::deep_clone_list(*self, ctx)
+    }
+}
+
+impl<'ast, T> DeepClone<'ast> for Option<T> where
+    T: DeepClone<'ast>,
+{
+    type Output = Option<T::Output>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        self.as_ref().map(|item| item.deep_clone_with(ctx))
+    }
+}
+
+/// Identifiers in name-only positions.
+impl<'ast> DeepClone<'ast> for &str {
+    type Output = &'ast str;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        ctx.name(self)
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for Literal<'t> {
+    type Output = Literal<'ast>;
+
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        use self::Literal::*;
+
+        match *self {
+            Undefined   => Undefined,
+            Null        => Null,
+            True        => True,
+            False       => False,
+            Number(raw) => Number(ctx.str(raw)),
+            Binary(raw) => Binary(ctx.str(raw)),
+            String(raw) => String(ctx.str(raw)),
+            RegEx(raw)  => RegEx(ctx.str(raw)),
+        }
+    }
+}
+
+impl<'t, 'ast, T> DeepClone<'ast> for Block<'t, T> where
+    T: 't + Copy + DeepClone<'ast>,
+{
+    type Output = Block<'ast, T::Output>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        Block {
+            body: self.body.deep_clone_with(ctx),
+        }
+    }
+}
+
+impl<'ast> DeepClone<'ast> for EmptyName {
+    type Output = EmptyName;
+
+    #[inline]
+    fn deep_clone_with(&self, _: &Cloner<'ast>) -> Self::Output {
+        EmptyName
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for OptionalName<'t> {
+    type Output = OptionalName<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        OptionalName(self.0.deep_clone_with(ctx))
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for MandatoryName<'t> {
+    type Output = MandatoryName<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        MandatoryName(self.0.deep_clone_with(ctx))
+    }
+}
+
+impl<'t, 'ast, N> DeepClone<'ast> for Function<'t, N> where
+    N: Name<'t> + DeepClone<'ast>,
+    N::Output: Name<'ast>,
+{
+    type Output = Function<'ast, N::Output>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        Function {
+            name: self.name.deep_clone_with(ctx),
+            generator: self.generator,
+            params: self.params.deep_clone_with(ctx),
+            body: self.body.deep_clone_with(ctx),
+        }
+    }
+}
+
+impl<'t, 'ast, N> DeepClone<'ast> for Class<'t, N> where
+    N: Name<'t> + DeepClone<'ast>,
+    N::Output: Name<'ast>,
+{
+    type Output = Class<'ast, N::Output>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        Class {
+            name: self.name.deep_clone_with(ctx),
+            extends: self.extends.deep_clone_with(ctx),
+            body: self.body.deep_clone_with(ctx),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for ClassMember<'t> {
+    type Output = ClassMember<'ast>;
+
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        use self::ClassMember::*;
+
+        match *self {
+            Error => Error,
+            Method { is_static, key, kind, value } => Method {
+                is_static,
+                key: key.deep_clone_with(ctx),
+                kind,
+                value: value.deep_clone_with(ctx),
+            },
+            Literal { is_static, key, value } => Literal {
+                is_static,
+                key: key.deep_clone_with(ctx),
+                value: value.deep_clone_with(ctx),
+            },
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for PropertyKey<'t> {
+    type Output = PropertyKey<'ast>;
+
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        use self::PropertyKey::*;
+
+        match *self {
+            Computed(expression) => Computed(expression.deep_clone_with(ctx)),
+            Literal(label) => match ctx.hook_expression(label) {
+                Some(expression) => Computed(ctx.substituted(&expression)),
+                None => Literal(ctx.name(label)),
+            },
+            Binary(raw) => Binary(ctx.str(raw)),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for Property<'t> {
+    type Output = Property<'ast>;
+
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        use self::Property::*;
+
+        match *self {
+            Shorthand(name) => match ctx.hook_expression(name) {
+                Some(expression) => match expression.item {
+                    Expression::Identifier(_) => Shorthand(ctx.name(name)),
+                    _ => panic!("Shorthand property {} can only be replaced by an identifier", name),
+                },
+                None => Shorthand(ctx.name(name)),
+            },
+            Literal { key, value } => Literal {
+                key: key.deep_clone_with(ctx),
+                value: value.deep_clone_with(ctx),
+            },
+            Method { key, value } => Method {
+                key: key.deep_clone_with(ctx),
+                value: value.deep_clone_with(ctx),
+            },
+            Spread { argument } => Spread {
+                argument: argument.deep_clone_with(ctx),
+            },
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for Pattern<'t> {
+    type Output = Pattern<'ast>;
+
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        use self::Pattern::*;
+
+        match *self {
+            Void => Void,
+            Identifier(name) => Identifier(ctx.name(name)),
+            ObjectPattern { properties } => ObjectPattern {
+                properties: properties.deep_clone_with(ctx),
+            },
+            ArrayPattern { elements } => ArrayPattern {
+                elements: elements.deep_clone_with(ctx),
+            },
+            RestElement { argument } => RestElement {
+                argument: argument.deep_clone_with(ctx),
+            },
+            AssignmentPattern { left, right } => AssignmentPattern {
+                left: left.deep_clone_with(ctx),
+                right: right.deep_clone_with(ctx),
+            },
+        }
+    }
+
+    #[inline]
+    fn deep_clone_node(loc: &Loc<Self>, ctx: &Cloner<'ast>) -> Node<'ast, Self::Output> {
+        if let Pattern::Identifier(name) = loc.item {
+            if let Some(pattern) = ctx.hook_pattern(name) {
+                return ctx.substituted(&pattern);
+            }
+        }
+
+        ctx.alloc_as(loc, loc.item.deep_clone_with(ctx))
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for TemplateLiteral<'t> {
+    type Output = TemplateLiteral<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        TemplateLiteral {
+            expressions: self.expressions.deep_clone_with(ctx),
+            // Quasis are never placeholders, copy them verbatim
+            quasis: NodeList::from_iter(ctx.arena, self.quasis.iter().map(|quasi| ctx.alloc_as(quasi, ctx.str(quasi.item)))),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for ArrowBody<'t> {
+    type Output = ArrowBody<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        match *self {
+            ArrowBody::Expression(expression) => ArrowBody::Expression(expression.deep_clone_with(ctx)),
+            ArrowBody::Block(block) => ArrowBody::Block(block.deep_clone_with(ctx)),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for Expression<'t> {
+    type Output = Expression<'ast>;
+
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        use self::Expression::*;
+
+        match *self {
+            Void => Void,
+            This(_) => This(ThisExpression),
+            Super(_) => Super(SuperExpression),
+            Identifier(name) => Identifier(ctx.name(name)),
+            Literal(literal) => Literal(literal.deep_clone_with(ctx)),
+            Sequence(SequenceExpression { body }) => Sequence(SequenceExpression {
+                body: body.deep_clone_with(ctx),
+            }),
+            Array(ArrayExpression { body }) => Array(ArrayExpression {
+                body: body.deep_clone_with(ctx),
+            }),
+            Member(MemberExpression { object, property }) => Member(MemberExpression {
+                object: object.deep_clone_with(ctx),
+                property: property.deep_clone_with(ctx),
+            }),
+            ComputedMember(ComputedMemberExpression { object, property }) => ComputedMember(ComputedMemberExpression {
+                object: object.deep_clone_with(ctx),
+                property: property.deep_clone_with(ctx),
+            }),
+            MetaProperty(MetaPropertyExpression { meta, property }) => MetaProperty(MetaPropertyExpression {
+                meta: meta.deep_clone_with(ctx),
+                property: property.deep_clone_with(ctx),
+            }),
+            Call(CallExpression { callee, arguments }) => Call(CallExpression {
+                callee: callee.deep_clone_with(ctx),
+                arguments: arguments.deep_clone_with(ctx),
+            }),
+            Binary(BinaryExpression { operator, left, right }) => Binary(BinaryExpression {
+                operator,
+                left: left.deep_clone_with(ctx),
+                right: right.deep_clone_with(ctx),
+            }),
+            Prefix(PrefixExpression { operator, operand }) => Prefix(PrefixExpression {
+                operator,
+                operand: operand.deep_clone_with(ctx),
+            }),
+            Postfix(PostfixExpression { operator, operand }) => Postfix(PostfixExpression {
+                operator,
+                operand: operand.deep_clone_with(ctx),
+            }),
+            Conditional(ConditionalExpression { test, consequent, alternate }) => Conditional(ConditionalExpression {
+                test: test.deep_clone_with(ctx),
+                consequent: consequent.deep_clone_with(ctx),
+                alternate: alternate.deep_clone_with(ctx),
+            }),
+            Template(template) => Template(template.deep_clone_with(ctx)),
+            TaggedTemplate(TaggedTemplateExpression { tag, quasi }) => TaggedTemplate(TaggedTemplateExpression {
+                tag: tag.deep_clone_with(ctx),
+                quasi: quasi.deep_clone_with(ctx),
+            }),
+            Spread(SpreadExpression { argument }) => Spread(SpreadExpression {
+                argument: argument.deep_clone_with(ctx),
+            }),
+            Arrow(ArrowExpression { params, body }) => Arrow(ArrowExpression {
+                params: params.deep_clone_with(ctx),
+                body: body.deep_clone_with(ctx),
+            }),
+            Object(ObjectExpression { body }) => Object(ObjectExpression {
+                body: body.deep_clone_with(ctx),
+            }),
+            Function(function) => Function(function.deep_clone_with(ctx)),
+            Class(class) => Class(class.deep_clone_with(ctx)),
+        }
+    }
+
+    #[inline]
+    fn deep_clone_node(loc: &Loc<Self>, ctx: &Cloner<'ast>) -> Node<'ast, Self::Output> {
+        if let Expression::Identifier(name) = loc.item {
+            if let Some(expression) = ctx.hook_expression(name) {
+                return ctx.substituted(&expression);
+            }
+
+            if let Some(pattern) = ctx.hook_pattern(name) {
+                return ctx.pattern_as_expression(ctx.substituted(&pattern));
+            }
+        }
+
+        ctx.alloc_as(loc, loc.item.deep_clone_with(ctx))
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for Declarator<'t> {
+    type Output = Declarator<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        Declarator {
+            id: self.id.deep_clone_with(ctx),
+            init: self.init.deep_clone_with(ctx),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for DeclarationStatement<'t> {
+    type Output = DeclarationStatement<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        DeclarationStatement {
+            kind: self.kind,
+            declarators: self.declarators.deep_clone_with(ctx),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for ForInit<'t> {
+    type Output = ForInit<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        match *self {
+            ForInit::Declaration(declaration) => ForInit::Declaration(declaration.deep_clone_with(ctx)),
+            ForInit::Expression(expression) => ForInit::Expression(expression.deep_clone_with(ctx)),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for CatchClause<'t> {
+    type Output = CatchClause<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        CatchClause {
+            param: self.param.deep_clone_with(ctx),
+            body: self.body.deep_clone_with(ctx),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for SwitchCase<'t> {
+    type Output = SwitchCase<'ast>;
+
+    #[inline]
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        SwitchCase {
+            test: self.test.deep_clone_with(ctx),
+            consequent: self.consequent.deep_clone_with(ctx),
+        }
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for ForImportSpecifier<'t> {
+    type Output = ForImportSpecifier<'ast>;
+
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        use self::ForImportSpecifier::*;
+
+        match *self {
+            ImportSpecifier(::ast::statement::ImportSpecifier { imported, local }) => {
+                ImportSpecifier(::ast::statement::ImportSpecifier {
+                    imported: imported.deep_clone_with(ctx),
+                    local: local.deep_clone_with(ctx),
+                })
+            },
+            ImportDefaultSpecifier(::ast::statement::ImportDefaultSpecifier { local }) => {
+                ImportDefaultSpecifier(::ast::statement::ImportDefaultSpecifier {
+                    local: local.deep_clone_with(ctx),
+                })
+            },
+            ImportNamespaceSpecifier(::ast::statement::ImportNamespaceSpecifier { local }) => {
+                ImportNamespaceSpecifier(::ast::statement::ImportNamespaceSpecifier {
+                    local: local.deep_clone_with(ctx),
+                })
+            },
+        }
+    }
+}
+
+/// Statements from the hook in place of an `ident;` statement.
+#[inline]
+fn hook_statements<'ast>(statement: &Statement, ctx: &Cloner<'ast>) -> Option<StatementList<'ast>> {
+    let hook = ctx.hook?;
+
+    match *statement {
+        Statement::Expression(expression) => match expression.item {
+            Expression::Identifier(name) => hook.statements(name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl<'t, 'ast> DeepClone<'ast> for Statement<'t> {
+    type Output = Statement<'ast>;
+
+    fn deep_clone_with(&self, ctx: &Cloner<'ast>) -> Self::Output {
+        use self::Statement::*;
+
+        match *self {
+            Empty => Empty,
+            Debugger => Debugger,
+            Expression(expression) => Expression(expression.deep_clone_with(ctx)),
+            Declaration(declaration) => Declaration(declaration.deep_clone_with(ctx)),
+            Return(ReturnStatement { value }) => Return(ReturnStatement {
+                value: value.deep_clone_with(ctx),
+            }),
+            Break(BreakStatement { label }) => Break(BreakStatement {
+                label: label.deep_clone_with(ctx),
+            }),
+            Continue(ContinueStatement { label }) => Continue(ContinueStatement {
+                label: label.deep_clone_with(ctx),
+            }),
+            Throw(ThrowStatement { value }) => Throw(ThrowStatement {
+                value: value.deep_clone_with(ctx),
+            }),
+            If(IfStatement { test, consequent, alternate }) => If(IfStatement {
+                test: test.deep_clone_with(ctx),
+                consequent: consequent.deep_clone_with(ctx),
+                alternate: alternate.deep_clone_with(ctx),
+            }),
+            While(WhileStatement { test, body }) => While(WhileStatement {
+                test: test.deep_clone_with(ctx),
+                body: body.deep_clone_with(ctx),
+            }),
+            Do(DoStatement { body, test }) => Do(DoStatement {
+                body: body.deep_clone_with(ctx),
+                test: test.deep_clone_with(ctx),
+            }),
+            For(ForStatement { init, test, update, body }) => For(ForStatement {
+                init: init.deep_clone_with(ctx),
+                test: test.deep_clone_with(ctx),
+                update: update.deep_clone_with(ctx),
+                body: body.deep_clone_with(ctx),
+            }),
+            ForIn(ForInStatement { left, right, body }) => ForIn(ForInStatement {
+                left: left.deep_clone_with(ctx),
+                right: right.deep_clone_with(ctx),
+                body: body.deep_clone_with(ctx),
+            }),
+            ForOf(ForOfStatement { left, right, body }) => ForOf(ForOfStatement {
+                left: left.deep_clone_with(ctx),
+                right: right.deep_clone_with(ctx),
+                body: body.deep_clone_with(ctx),
+            }),
+            Try(TryStatement { block, handler, finalizer }) => Try(TryStatement {
+                block: block.deep_clone_with(ctx),
+                handler: handler.deep_clone_with(ctx),
+                finalizer: finalizer.deep_clone_with(ctx),
+            }),
+            Block(block) => Block(block.deep_clone_with(ctx)),
+            Labeled(LabeledStatement { label, body }) => Labeled(LabeledStatement {
+                label: label.deep_clone_with(ctx),
+                body: body.deep_clone_with(ctx),
+            }),
+            Function(function) => Function(function.deep_clone_with(ctx)),
+            Class(class) => Class(class.deep_clone_with(ctx)),
+            Switch(SwitchStatement { discriminant, cases }) => Switch(SwitchStatement {
+                discriminant: discriminant.deep_clone_with(ctx),
+                cases: cases.deep_clone_with(ctx),
+            }),
+            Import(ImportDeclaration { specifiers, source }) => Import(ImportDeclaration {
+                specifiers: specifiers.deep_clone_with(ctx),
+                source: ctx.str(source),
+            }),
+        }
+    }
+
+    fn deep_clone_node(loc: &Loc<Self>, ctx: &Cloner<'ast>) -> Node<'ast, Self::Output> {
+        match hook_statements(&loc.item, ctx) {
+            Some(body) => match body.only_element() {
+                Some(statement) => ctx.substituted(statement),
+                None => ctx.alloc(Statement::Block(Block { body: ctx.substituted(&body) })),
+            },
+            None => ctx.alloc_as(loc, loc.item.deep_clone_with(ctx)),
+        }
+    }
+
+    fn deep_clone_list<'t2>(list: NodeList<'t2, Self>, ctx: &Cloner<'ast>) -> StatementList<'ast> where
+        Self: 't2,
+    {
+        let mut builder: Option<ListBuilder<'ast, Node<'ast, Statement<'ast>>>> = None;
+
+        {
+            let mut push = |statement| match builder {
+                Some(ref builder) => builder.push(ctx.arena, statement),
+                None => builder = Some(ListBuilder::new(ctx.arena, statement)),
+            };
+
+            for statement in list.iter() {
+                match hook_statements(&statement.item, ctx) {
+                    Some(body) => {
+                        for statement in body.iter() {
+                            push(ctx.substituted(statement));
+                        }
+                    },
+                    None => push(Self::deep_clone_node(statement, ctx)),
+                }
+            }
+        }
+
+        match builder {
+            Some(builder) => builder.as_list(),
+            None => NodeList::empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse;
+
+    #[test]
+    fn deep_clone_program() {
+        let module = parse(r#"
+            import foo from "foo";
+            class Bar extends Baz { constructor() { this.bar = 1; } static baz() {} }
+            function qux(a, b = 1, ...c) { return `${a}-${b}` }
+            var [d, { e, f: g }] = h;
+            for (let i = 0; i < 10; i++) { label: switch (i) { case 1: break label; } }
+            try { throw new Error('x'); } catch (err) {} finally {}
+            var obj = { a, [b]: c, d() {} }, re = /ab+c/g;
+        "#).unwrap();
+
+        let arena = Arena::new();
+        let body = module.body().deep_clone(&arena);
+
+        assert_eq!(body, module.body());
+    }
+
+    #[test]
+    fn deep_clone_does_not_alias() {
+        let module = parse("foo(bar);").unwrap();
+
+        let arena = Arena::new();
+        let body = module.body().deep_clone(&arena);
+
+        match body.only_element().unwrap().item {
+            Statement::Expression(ref expression) => {
+                expression.set(arena.alloc(Loc::new(0, 0, Expression::This(ThisExpression))));
+            },
+            _ => panic!("Expected an expression statement"),
+        }
+
+        assert_eq!(module.body(), parse("foo(bar);").unwrap().body());
+        assert_eq!(body, parse("this;").unwrap().body());
+    }
+
+    #[test]
+    fn deep_clone_outlives_source() {
+        let arena = Arena::new();
+
+        let body = {
+            let module = parse("let foo = 'bar';").unwrap();
+
+            module.body().deep_clone(&arena)
+        };
+
+        assert_eq!(body, parse("let foo = 'bar';").unwrap().body());
+    }
+
+    #[test]
+    fn deep_clone_with_hook() {
+        struct Rename;
+
+        impl<'ast> CloneHook<'ast> for Rename {
+            fn name(&self, ident: &str) -> Option<&'ast str> {
+                match ident {
+                    "foo" => Some("bar"),
+                    _     => None,
+                }
+            }
+        }
+
+        let module = parse("function foo(foo) { return foo.foo + baz; }").unwrap();
+        let arena = Arena::new();
+        let body = module.body().deep_clone_with(&Cloner::with_hook(&arena, Spans::Strip, &Rename));
+
+        assert_eq!(body, parse("function bar(bar) { return bar.bar + baz; }").unwrap().body());
+    }
+
+    #[test]
+    fn deep_clone_spans() {
+        let module = parse("  foo;").unwrap();
+        let arena = Arena::new();
+
+        let statement = module.body().only_element().unwrap();
+
+        let preserved = statement.deep_clone(&arena);
+        let stripped = statement.deep_clone_with(&Cloner::new(&arena, Spans::Strip));
+
+        assert_eq!((preserved.start, preserved.end), (statement.start, statement.end));
+        assert_eq!((stripped.start, stripped.end), (0, 0));
+    }
+}
@@ -0,0 +1,777 @@
+//! Structural comparison and hashing of AST subtrees.
+//!
+//! `PartialEq` on AST types ignores spans, so two nodes parsed from
+//! differently formatted sources compare equal. The functions in this
+//! module make that choice explicit, and provide a stable hash that is
+//! consistent with either flavor of equality.
+
+use std::hash::Hasher;
+
+use ast::{Node, Loc, NodeList, Block, Literal, Pattern, Expression, Statement};
+use ast::{Property, PropertyKey, Declarator, Function, Class, ClassMember, Name};
+use ast::{EmptyName, OptionalName, MandatoryName};
+use ast::expression::*;
+use ast::statement::*;
+
+/// A `Hasher` that is also told about spans of nodes.
+pub trait StructureHasher: Hasher {
+    /// Called with the span of every node, before its contents.
+    fn write_span(&mut self, start: u32, end: u32);
+}
+
+/// Feed the structure of an AST subtree into a `StructureHasher`.
+///
+/// The structure written is unambiguous: two subtrees write the same
+/// bytes if, and only if, they are equal.
+pub trait Structure {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H);
+}
+
+/// Compare two AST subtrees, ignoring spans. Same as `==`.
+#[inline]
+pub fn ast_eq_ignoring_spans<T: PartialEq>(left: &T, right: &T) -> bool {
+    left == right
+}
+
+/// Compare two AST subtrees, including spans of every node.
+pub fn ast_eq_with_spans<T: Structure>(left: &T, right: &T) -> bool {
+    let mut left_bytes = Recorder(Vec::new());
+    let mut right_bytes = Recorder(Vec::new());
+
+    left.write_structure(&mut left_bytes);
+    right.write_structure(&mut right_bytes);
+
+    left_bytes.0 == right_bytes.0
+}
+
+/// Hash an AST subtree, ignoring spans. Consistent with `ast_eq_ignoring_spans`.
+///
+/// The hash doesn't depend on the platform, process or the version of
+/// the Rust compiler, so it can be stored, eg. in snapshot tests.
+#[inline]
+pub fn structural_hash<T: Structure>(node: &T) -> u64 {
+    let mut state = StableHasher::new(false);
+
+    node.write_structure(&mut state);
+
+    state.finish()
+}
+
+/// Hash an AST subtree, including spans. Consistent with `ast_eq_with_spans`.
+#[inline]
+pub fn structural_hash_with_spans<T: Structure>(node: &T) -> u64 {
+    let mut state = StableHasher::new(true);
+
+    node.write_structure(&mut state);
+
+    state.finish()
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// 64-bit FNV-1a, writing all integers as little endian.
+struct StableHasher {
+    hash: u64,
+    spans: bool,
+}
+
+impl StableHasher {
+    #[inline]
+    fn new(spans: bool) -> Self {
+        StableHasher {
+            hash: FNV_OFFSET,
+            spans,
+        }
+    }
+}
+
+impl Hasher for StableHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= u64::from(*byte);
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, value: u8) {
+        self.write(&[value]);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Written as 64 bits, so the hash is the same on 32-bit platforms.
+    #[inline]
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    #[inline]
+    fn write_i8(&mut self, value: i8) {
+        self.write_u8(value as u8);
+    }
+
+    #[inline]
+    fn write_i16(&mut self, value: i16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_i32(&mut self, value: i32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_i64(&mut self, value: i64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_i128(&mut self, value: i128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    #[inline]
+    fn write_isize(&mut self, value: isize) {
+        self.write_i64(value as i64);
+    }
+}
+
+impl StructureHasher for StableHasher {
+    #[inline]
+    fn write_span(&mut self, start: u32, end: u32) {
+        if self.spans {
+            self.write_u32(start);
+            self.write_u32(end);
+        }
+    }
+}
+
+/// Records the structure, including spans, for comparison.
+struct Recorder(Vec<u8>);
+
+impl Hasher for Recorder {
+    #[inline]
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+impl StructureHasher for Recorder {
+    #[inline]
+    fn write_span(&mut self, start: u32, end: u32) {
+        self.write_u32(start);
+        self.write_u32(end);
+    }
+}
+
+impl<T: Structure> Structure for Loc<T> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        state.write_span(self.start, self.end);
+        self.item.write_structure(state);
+    }
+}
+
+impl<'ast, T: 'ast + Structure> Structure for Node<'ast, T> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        (**self).write_structure(state);
+    }
+}
+
+impl<'ast, T: 'ast + Structure> Structure for NodeList<'ast, T> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        state.write_u64(self.iter().count() as u64);
+
+        for node in self.iter() {
+            node.write_structure(state);
+        }
+    }
+}
+
+impl<T: Structure> Structure for Option<T> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        match *self {
+            None => state.write_u8(0),
+            Some(ref item) => {
+                state.write_u8(1);
+                item.write_structure(state);
+            },
+        }
+    }
+}
+
+impl Structure for &str {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        state.write_u64(self.len() as u64);
+        state.write(self.as_bytes());
+    }
+}
+
+impl Structure for bool {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        state.write_u8(*self as u8);
+    }
+}
+
+impl<'ast> Structure for Literal<'ast> {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        use self::Literal::*;
+
+        match *self {
+            Undefined   => state.write_u8(0),
+            Null        => state.write_u8(1),
+            True        => state.write_u8(2),
+            False       => state.write_u8(3),
+            Number(raw) => { state.write_u8(4); raw.write_structure(state) },
+            Binary(raw) => { state.write_u8(5); raw.write_structure(state) },
+            String(raw) => { state.write_u8(6); raw.write_structure(state) },
+            RegEx(raw)  => { state.write_u8(7); raw.write_structure(state) },
+        }
+    }
+}
+
+impl<'ast, T: 'ast + Structure> Structure for Block<'ast, T> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.body.write_structure(state);
+    }
+}
+
+impl Structure for EmptyName {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, _: &mut H) {}
+}
+
+impl<'ast> Structure for OptionalName<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.0.write_structure(state);
+    }
+}
+
+impl<'ast> Structure for MandatoryName<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.0.write_structure(state);
+    }
+}
+
+impl<'ast, N: Name<'ast> + Structure> Structure for Function<'ast, N> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.name.write_structure(state);
+        self.generator.write_structure(state);
+        self.params.write_structure(state);
+        self.body.write_structure(state);
+    }
+}
+
+impl<'ast, N: Name<'ast> + Structure> Structure for Class<'ast, N> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.name.write_structure(state);
+        self.extends.write_structure(state);
+        self.body.write_structure(state);
+    }
+}
+
+impl<'ast> Structure for ClassMember<'ast> {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        use self::ClassMember::*;
+
+        match *self {
+            Error => state.write_u8(0),
+            Method { is_static, key, kind, value } => {
+                state.write_u8(1);
+                is_static.write_structure(state);
+                key.write_structure(state);
+                state.write_u8(kind as u8);
+                value.write_structure(state);
+            },
+            Literal { is_static, key, value } => {
+                state.write_u8(2);
+                is_static.write_structure(state);
+                key.write_structure(state);
+                value.write_structure(state);
+            },
+        }
+    }
+}
+
+impl<'ast> Structure for PropertyKey<'ast> {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        use self::PropertyKey::*;
+
+        match *self {
+            Computed(expression) => { state.write_u8(0); expression.write_structure(state) },
+            Literal(label)       => { state.write_u8(1); label.write_structure(state) },
+            Binary(raw)          => { state.write_u8(2); raw.write_structure(state) },
+        }
+    }
+}
+
+impl<'ast> Structure for Property<'ast> {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        use self::Property::*;
+
+        match *self {
+            Shorthand(name) => {
+                state.write_u8(0);
+                name.write_structure(state);
+            },
+            Literal { key, value } => {
+                state.write_u8(1);
+                key.write_structure(state);
+                value.write_structure(state);
+            },
+            Method { key, value } => {
+                state.write_u8(2);
+                key.write_structure(state);
+                value.write_structure(state);
+            },
+            Spread { argument } => {
+                state.write_u8(3);
+                argument.write_structure(state);
+            },
+        }
+    }
+}
+
+impl<'ast> Structure for Pattern<'ast> {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        use self::Pattern::*;
+
+        match *self {
+            Void => state.write_u8(0),
+            Identifier(name) => {
+                state.write_u8(1);
+                name.write_structure(state);
+            },
+            ObjectPattern { properties } => {
+                state.write_u8(2);
+                properties.write_structure(state);
+            },
+            ArrayPattern { elements } => {
+                state.write_u8(3);
+                elements.write_structure(state);
+            },
+            RestElement { argument } => {
+                state.write_u8(4);
+                argument.write_structure(state);
+            },
+            AssignmentPattern { left, right } => {
+                state.write_u8(5);
+                left.write_structure(state);
+                right.write_structure(state);
+            },
+        }
+    }
+}
+
+impl<'ast> Structure for TemplateLiteral<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.expressions.write_structure(state);
+        self.quasis.write_structure(state);
+    }
+}
+
+impl<'ast> Structure for ArrowBody<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        match *self {
+            ArrowBody::Expression(expression) => { state.write_u8(0); expression.write_structure(state) },
+            ArrowBody::Block(block)           => { state.write_u8(1); block.write_structure(state) },
+        }
+    }
+}
+
+impl<'ast> Structure for Expression<'ast> {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        use self::Expression::*;
+
+        match *self {
+            Void => state.write_u8(0),
+            This(_) => state.write_u8(1),
+            Identifier(name) => {
+                state.write_u8(2);
+                name.write_structure(state);
+            },
+            Literal(literal) => {
+                state.write_u8(3);
+                literal.write_structure(state);
+            },
+            Sequence(SequenceExpression { body }) => {
+                state.write_u8(4);
+                body.write_structure(state);
+            },
+            Array(ArrayExpression { body }) => {
+                state.write_u8(5);
+                body.write_structure(state);
+            },
+            Member(MemberExpression { object, property }) => {
+                state.write_u8(6);
+                object.write_structure(state);
+                property.write_structure(state);
+            },
+            ComputedMember(ComputedMemberExpression { object, property }) => {
+                state.write_u8(7);
+                object.write_structure(state);
+                property.write_structure(state);
+            },
+            MetaProperty(MetaPropertyExpression { meta, property }) => {
+                state.write_u8(8);
+                meta.write_structure(state);
+                property.write_structure(state);
+            },
+            Call(CallExpression { callee, arguments }) => {
+                state.write_u8(9);
+                callee.write_structure(state);
+                arguments.write_structure(state);
+            },
+            Binary(BinaryExpression { operator, left, right }) => {
+                state.write_u8(10);
+                state.write_u8(operator as u8);
+                left.write_structure(state);
+                right.write_structure(state);
+            },
+            Prefix(PrefixExpression { operator, operand }) => {
+                state.write_u8(11);
+                state.write_u8(operator as u8);
+                operand.write_structure(state);
+            },
+            Postfix(PostfixExpression { operator, operand }) => {
+                state.write_u8(12);
+                state.write_u8(operator as u8);
+                operand.write_structure(state);
+            },
+            Conditional(ConditionalExpression { test, consequent, alternate }) => {
+                state.write_u8(13);
+                test.write_structure(state);
+                consequent.write_structure(state);
+                alternate.write_structure(state);
+            },
+            Template(template) => {
+                state.write_u8(14);
+                template.write_structure(state);
+            },
+            TaggedTemplate(TaggedTemplateExpression { tag, quasi }) => {
+                state.write_u8(15);
+                tag.write_structure(state);
+                quasi.write_structure(state);
+            },
+            Spread(SpreadExpression { argument }) => {
+                state.write_u8(16);
+                argument.write_structure(state);
+            },
+            Arrow(ArrowExpression { params, body }) => {
+                state.write_u8(17);
+                params.write_structure(state);
+                body.write_structure(state);
+            },
+            Object(ObjectExpression { body }) => {
+                state.write_u8(18);
+                body.write_structure(state);
+            },
+            Function(function) => {
+                state.write_u8(19);
+                function.write_structure(state);
+            },
+            Class(class) => {
+                state.write_u8(20);
+                class.write_structure(state);
+            },
+            Super(_) => state.write_u8(21),
+        }
+    }
+}
+
+impl<'ast> Structure for Declarator<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.id.write_structure(state);
+        self.init.write_structure(state);
+    }
+}
+
+impl<'ast> Structure for DeclarationStatement<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        state.write_u8(self.kind as u8);
+        self.declarators.write_structure(state);
+    }
+}
+
+impl<'ast> Structure for ForInit<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        match *self {
+            ForInit::Declaration(declaration) => { state.write_u8(0); declaration.write_structure(state) },
+            ForInit::Expression(expression)   => { state.write_u8(1); expression.write_structure(state) },
+        }
+    }
+}
+
+impl<'ast> Structure for CatchClause<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.param.write_structure(state);
+        self.body.write_structure(state);
+    }
+}
+
+impl<'ast> Structure for SwitchCase<'ast> {
+    #[inline]
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        self.test.write_structure(state);
+        self.consequent.write_structure(state);
+    }
+}
+
+impl<'ast> Structure for ForImportSpecifier<'ast> {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        use self::ForImportSpecifier::*;
+
+        match *self {
+            ImportSpecifier(::ast::statement::ImportSpecifier { imported, local }) => {
+                state.write_u8(0);
+                imported.write_structure(state);
+                local.write_structure(state);
+            },
+            ImportDefaultSpecifier(::ast::statement::ImportDefaultSpecifier { local }) => {
+                state.write_u8(1);
+                local.write_structure(state);
+            },
+            ImportNamespaceSpecifier(::ast::statement::ImportNamespaceSpecifier { local }) => {
+                state.write_u8(2);
+                local.write_structure(state);
+            },
+        }
+    }
+}
+
+impl<'ast> Structure for Statement<'ast> {
+    fn write_structure<H: StructureHasher>(&self, state: &mut H) {
+        use self::Statement::*;
+
+        match *self {
+            Empty => state.write_u8(0),
+            Debugger => state.write_u8(1),
+            Expression(expression) => {
+                state.write_u8(2);
+                expression.write_structure(state);
+            },
+            Declaration(declaration) => {
+                state.write_u8(3);
+                declaration.write_structure(state);
+            },
+            Return(ReturnStatement { value }) => {
+                state.write_u8(4);
+                value.write_structure(state);
+            },
+            Break(BreakStatement { label }) => {
+                state.write_u8(5);
+                label.write_structure(state);
+            },
+            Continue(ContinueStatement { label }) => {
+                state.write_u8(6);
+                label.write_structure(state);
+            },
+            Throw(ThrowStatement { value }) => {
+                state.write_u8(7);
+                value.write_structure(state);
+            },
+            If(IfStatement { test, consequent, alternate }) => {
+                state.write_u8(8);
+                test.write_structure(state);
+                consequent.write_structure(state);
+                alternate.write_structure(state);
+            },
+            While(WhileStatement { test, body }) => {
+                state.write_u8(9);
+                test.write_structure(state);
+                body.write_structure(state);
+            },
+            Do(DoStatement { body, test }) => {
+                state.write_u8(10);
+                body.write_structure(state);
+                test.write_structure(state);
+            },
+            For(ForStatement { init, test, update, body }) => {
+                state.write_u8(11);
+                init.write_structure(state);
+                test.write_structure(state);
+                update.write_structure(state);
+                body.write_structure(state);
+            },
+            ForIn(ForInStatement { left, right, body }) => {
+                state.write_u8(12);
+                left.write_structure(state);
+                right.write_structure(state);
+                body.write_structure(state);
+            },
+            ForOf(ForOfStatement { left, right, body }) => {
+                state.write_u8(13);
+                left.write_structure(state);
+                right.write_structure(state);
+                body.write_structure(state);
+            },
+            Try(TryStatement { block, handler, finalizer }) => {
+                state.write_u8(14);
+                block.write_structure(state);
+                handler.write_structure(state);
+                finalizer.write_structure(state);
+            },
+            Block(block) => {
+                state.write_u8(15);
+                block.write_structure(state);
+            },
+            Labeled(LabeledStatement { label, body }) => {
+                state.write_u8(16);
+                label.write_structure(state);
+                body.write_structure(state);
+            },
+            Function(function) => {
+                state.write_u8(17);
+                function.write_structure(state);
+            },
+            Class(class) => {
+                state.write_u8(18);
+                class.write_structure(state);
+            },
+            Switch(SwitchStatement { discriminant, cases }) => {
+                state.write_u8(19);
+                discriminant.write_structure(state);
+                cases.write_structure(state);
+            },
+            Import(ImportDeclaration { specifiers, source }) => {
+                state.write_u8(20);
+                specifiers.write_structure(state);
+                source.write_structure(state);
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse;
+
+    #[test]
+    fn eq_ignoring_spans() {
+        let left = parse("foo(bar, 1 + 2);").unwrap();
+        let right = parse("foo( bar,1+2 )").unwrap();
+
+        assert!(ast_eq_ignoring_spans(&left.body(), &right.body()));
+        assert!(!ast_eq_with_spans(&left.body(), &right.body()));
+        assert_eq!(structural_hash(&left.body()), structural_hash(&right.body()));
+        assert_ne!(structural_hash_with_spans(&left.body()), structural_hash_with_spans(&right.body()));
+    }
+
+    #[test]
+    fn eq_with_spans() {
+        let left = parse("function foo(a) { return a ? [a] : { a }; }").unwrap();
+        let right = parse("function foo(a) { return a ? [a] : { a }; }").unwrap();
+
+        assert!(ast_eq_with_spans(&left.body(), &right.body()));
+        assert_eq!(structural_hash_with_spans(&left.body()), structural_hash_with_spans(&right.body()));
+    }
+
+    #[test]
+    fn structural_difference() {
+        let sources = [
+            "a + b;", "a - b;", "b + a;", "a + b + c;", "(a, b);", "[a, b];",
+            "a.b;", "a[b];", "'a';", "\"a\";", "a;", "`a`;", "let a;", "var a;",
+        ];
+
+        let hashes: Vec<u64> = sources.iter().map(|source| structural_hash(&parse(*source).unwrap().body())).collect();
+
+        for (index, hash) in hashes.iter().enumerate() {
+            assert!(hashes[index + 1..].iter().all(|other| other != hash), "Collision for {}", sources[index]);
+        }
+    }
+
+    #[test]
+    fn stable_hash() {
+        let module = parse("a;").unwrap();
+
+        // Expression statement (2) -> identifier (2), "a"
+        let mut expected = StableHasher::new(false);
+        expected.write_u64(1);
+        expected.write_u8(2);
+        expected.write_u8(2);
+        expected.write_u64(1);
+        expected.write(b"a");
+
+        assert_eq!(structural_hash(&module.body()), expected.finish());
+        assert_eq!(structural_hash(&module.body()), 0x677b_095f_97d7_9190);
+    }
+
+    #[test]
+    fn stable_hash_integers() {
+        let mut hasher = StableHasher::new(false);
+        hasher.write_u8(0x01);
+        hasher.write_u16(0x0203);
+        hasher.write_u32(0x0405_0607);
+        hasher.write_u64(0x0809_0a0b_0c0d_0e0f);
+        hasher.write_u128(1);
+        hasher.write_usize(2);
+        hasher.write_i8(-1);
+        hasher.write_i16(-2);
+        hasher.write_i32(-3);
+        hasher.write_i64(-4);
+        hasher.write_i128(-5);
+        hasher.write_isize(-6);
+
+        // Same bytes written one by one, little endian
+        let mut expected = StableHasher::new(false);
+        expected.write(&[0x01, 0x03, 0x02, 0x07, 0x06, 0x05, 0x04]);
+        expected.write(&[0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08]);
+        expected.write(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        expected.write(&[2, 0, 0, 0, 0, 0, 0, 0]);
+        expected.write(&[0xff, 0xfe, 0xff, 0xfd, 0xff, 0xff, 0xff]);
+        expected.write(&[0xfc, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        expected.write(&[0xfb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        expected.write(&[0xfa, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+        assert_eq!(hasher.finish(), expected.finish());
+        assert_eq!(hasher.finish(), 0x612c_ced2_01b1_c450);
+    }
+}
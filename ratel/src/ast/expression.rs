@@ -29,6 +29,11 @@ pub enum Property<'ast> {
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct ThisExpression;
 
+/// `super` as the callee of a call in a constructor, or the object of
+/// a member expression in a method.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SuperExpression;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct SequenceExpression<'ast> {
     pub body: ExpressionList<'ast>
@@ -95,6 +100,106 @@ pub struct TemplateLiteral<'ast> {
     pub quasis: NodeList<'ast, &'ast str>,
 }
 
+/// Value of a quasi of a template literal from its raw source, with the
+/// escape sequences resolved and line terminators normalized to `\n`.
+/// `None` if it has an invalid escape sequence, which is only allowed in
+/// tagged templates. Lone surrogates are replaced by U+FFFD.
+pub fn cook(raw: &str) -> Option<String> {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+
+    // High surrogate of a `\uXXXX` pair, waiting for the low one
+    let mut high: Option<u32> = None;
+
+    while let Some(ch) = chars.next() {
+        let code = match ch {
+            '\\' => match chars.next()? {
+                'b' => 0x08,
+                'f' => 0x0C,
+                'n' => 0x0A,
+                'r' => 0x0D,
+                't' => 0x09,
+                'v' => 0x0B,
+                '0' if !chars.peek().is_some_and(char::is_ascii_digit) => 0,
+                '0'..='9' => return None,
+                'x' => hex(&mut chars, 2)?,
+                'u' if chars.peek() == Some(&'{') => {
+                    chars.next();
+
+                    let mut code: u32 = 0;
+                    let mut digits = 0;
+
+                    loop {
+                        match chars.next()? {
+                            '}' if digits > 0 => break,
+                            ch => code = code.checked_mul(16)? + ch.to_digit(16)?,
+                        }
+
+                        digits += 1;
+
+                        if code > 0x10FFFF {
+                            return None;
+                        }
+                    }
+
+                    code
+                },
+                'u' => hex(&mut chars, 4)?,
+                // Line continuations
+                '\r' => {
+                    if chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+
+                    continue;
+                },
+                '\n' | '\u{2028}' | '\u{2029}' => continue,
+                ch => ch as u32,
+            },
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+
+                0x0A
+            },
+            ch => ch as u32,
+        };
+
+        if let Some(first) = high.take() {
+            if let 0xDC00..=0xDFFF = code {
+                value.extend(char::from_u32(0x10000 + ((first - 0xD800) << 10) + (code - 0xDC00)));
+
+                continue;
+            }
+
+            value.push('\u{FFFD}');
+        }
+
+        match code {
+            0xD800..=0xDBFF => high = Some(code),
+            _ => value.push(char::from_u32(code).unwrap_or('\u{FFFD}')),
+        }
+    }
+
+    if high.is_some() {
+        value.push('\u{FFFD}');
+    }
+
+    Some(value)
+}
+
+/// Reads a number of hex digits.
+fn hex<I: Iterator<Item = char>>(chars: &mut I, digits: usize) -> Option<u32> {
+    let mut code = 0;
+
+    for _ in 0..digits {
+        code = code * 16 + chars.next()?.to_digit(16)?;
+    }
+
+    Some(code)
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct TaggedTemplateExpression<'ast> {
     pub tag: ExpressionNode<'ast>,
@@ -130,6 +235,7 @@ pub type ClassExpression<'ast> = Class<'ast, OptionalName<'ast>>;
 pub enum Expression<'ast> {
     Void,
     This(ThisExpression),
+    Super(SuperExpression),
     Identifier(Identifier<'ast>),
     Literal(Literal<'ast>),
     Sequence(SequenceExpression<'ast>),
@@ -164,6 +270,7 @@ macro_rules! impl_from {
 
 impl_from! {
     ThisExpression => This,
+    SuperExpression => Super,
     Identifier<'ast> => Identifier,
     Literal<'ast> => Literal,
     SequenceExpression<'ast> => Sequence,
@@ -208,6 +315,9 @@ impl<'ast> Expression<'ast> {
         }
     }
 
+    /// Whether the expression can be written as a statement without
+    /// parentheses, which isn't the case if it starts with `{`, `function`
+    /// or `class`, such as `(function () {})();`.
     #[inline]
     pub fn is_allowed_as_bare_statement(&self) -> bool {
         use self::Expression::*;
@@ -216,7 +326,20 @@ impl<'ast> Expression<'ast> {
             Object(_)   |
             Function(_) |
             Class(_)    => false,
-            _           => true,
+
+            Sequence(SequenceExpression { body }) => match body.first_element() {
+                Some(first) => first.is_allowed_as_bare_statement(),
+                None        => true,
+            },
+            Member(MemberExpression { object, .. })                 |
+            ComputedMember(ComputedMemberExpression { object, .. }) => object.is_allowed_as_bare_statement(),
+            Call(CallExpression { callee, .. })                     => callee.is_allowed_as_bare_statement(),
+            TaggedTemplate(TaggedTemplateExpression { tag, .. })    => tag.is_allowed_as_bare_statement(),
+            Binary(BinaryExpression { left, .. })                   => left.is_allowed_as_bare_statement(),
+            Postfix(PostfixExpression { operand, .. })              => operand.is_allowed_as_bare_statement(),
+            Conditional(ConditionalExpression { test, .. })         => test.is_allowed_as_bare_statement(),
+
+            _ => true,
         }
     }
 
@@ -235,3 +358,23 @@ impl<'ast> Expression<'ast> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cook_template_quasis() {
+        assert_eq!(cook("foo"), Some("foo".into()));
+        assert_eq!(cook("\\`\\$\\\\\\'"), Some("`$\\'".into()));
+        assert_eq!(cook("\\n\\t\\0\\x41\\u0042\\u{43}"), Some("\n\t\u{0}ABC".into()));
+        assert_eq!(cook("\\u{1F600}\\uD83D\\uDE00"), Some("\u{1F600}\u{1F600}".into()));
+        assert_eq!(cook("\\uD83Da"), Some("\u{FFFD}a".into()));
+        assert_eq!(cook("a\r\nb\rc\\\r\nd"), Some("a\nb\ncd".into()));
+        assert_eq!(cook("\\01"), None);
+        assert_eq!(cook("\\1"), None);
+        assert_eq!(cook("\\xg"), None);
+        assert_eq!(cook("\\u{110000}"), None);
+        assert_eq!(cook("\\u{}"), None);
+    }
+}
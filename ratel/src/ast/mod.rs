@@ -4,6 +4,8 @@ mod operator;
 // mod types;
 mod function;
 mod literal;
+mod clone;
+mod compare;
 pub mod node;
 pub mod expression;
 pub mod statement;
@@ -20,6 +22,9 @@ pub use ast::statement::{Statement, Declarator, BlockStatement};
 pub use ast::function::{Function, Class, ClassMember, Method, MethodKind};
 pub use ast::function::{Name, EmptyName, OptionalName, MandatoryName};
 pub use ast::literal::Literal;
+pub use ast::clone::{DeepClone, Cloner, CloneHook, Spans};
+pub use ast::compare::{Structure, StructureHasher, ast_eq_ignoring_spans, ast_eq_with_spans};
+pub use ast::compare::{structural_hash, structural_hash_with_spans};
 
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -1,5 +1,6 @@
 use ast::{Node, NodeList, DeclarationKind, Function, Class, MandatoryName, IdentifierNode};
 use ast::{ExpressionNode, StatementNode, StatementList, Block, BlockNode, Pattern};
+use ast::{Expression, Literal};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Declarator<'ast> {
@@ -247,4 +248,17 @@ impl<'ast> Statement<'ast> {
             _                   => false,
         }
     }
+
+    /// Whether this is a string literal expression statement, which makes
+    /// a directive such as `"use strict";` at the top of a program or
+    /// function body.
+    #[inline]
+    pub fn is_directive(&self) -> bool {
+        match *self {
+            Statement::Expression(expression) => {
+                matches!(expression.item, Expression::Literal(Literal::String(_)))
+            },
+            _ => false,
+        }
+    }
 }
@@ -2,9 +2,10 @@ use serde::ser::{Serialize, Serializer, SerializeStruct};
 use ast::{Expression, Loc, OperatorKind};
 use ast::expression::*;
 use astgen::SerializeInLoc;
+use astgen::statement::FunctionBody;
 
 #[inline]
-fn expression_type(operator: OperatorKind, prefix: bool) -> &'static str {
+pub fn expression_type(operator: OperatorKind, prefix: bool) -> &'static str {
     use self::OperatorKind::*;
 
     match operator {
@@ -119,7 +120,7 @@ impl<'ast> Serialize for Loc<ArrowBody<'ast>> {
         use self::ArrowBody::*;
         match self.item {
             Expression(ref expression) => serializer.serialize_some(expression),
-            Block(ref expression) => serializer.serialize_some(expression),
+            Block(ref block) => serializer.serialize_some(&FunctionBody(block)),
         }
     }
 }
@@ -129,7 +130,15 @@ impl<'ast> SerializeInLoc for ArrowExpression<'ast> {
     where
         S: Serializer,
     {
-        self.in_loc(serializer, "ArrowFunctionExpression", 2, |state| {
+        let expression = match self.body {
+            ArrowBody::Expression(_) => true,
+            ArrowBody::Block(_)      => false,
+        };
+
+        self.in_loc(serializer, "ArrowFunctionExpression", 5, |state| {
+            state.serialize_field("id", &())?;
+            state.serialize_field("generator", &false)?;
+            state.serialize_field("expression", &expression)?;
             state.serialize_field("params", &self.params)?;
             state.serialize_field("body", &Loc::new(0, 0, self.body))
         })
@@ -270,6 +279,7 @@ impl<'ast> SerializeInLoc for Expression<'ast> {
         match *self {
             Void => unreachable!(),
             This(_) => self.in_loc(serializer, "ThisExpression", 0, |_| Ok(())),
+            Super(_) => self.in_loc(serializer, "Super", 0, |_| Ok(())),
             Identifier(ref ident) => {
                 self.in_loc(serializer, "Identifier", 1, |state| {
                     state.serialize_field("name", ident)
@@ -303,6 +313,7 @@ mod test {
     fn test_void_expression() {
         expect_parse!("[1,]", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -330,6 +341,7 @@ mod test {
 
         expect_parse!("[1,,]", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -357,6 +369,7 @@ mod test {
 
         expect_parse!("[,1,]", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -388,6 +401,7 @@ mod test {
     fn test_this_expression() {
         expect_parse!("this;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -409,6 +423,7 @@ mod test {
     fn test_identifier_expression() {
         expect_parse!("foo;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -431,6 +446,7 @@ mod test {
     fn test_literal_expression() {
         expect_parse!("'foo';", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -441,6 +457,7 @@ mod test {
                         "start": 0,
                         "end": 5
                     },
+                    "directive": "foo",
                     "start": 0,
                     "end": 5,
                 }
@@ -454,6 +471,7 @@ mod test {
     fn test_array_expression() {
         expect_parse!("[true, 0, 'foo', bar];", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -504,6 +522,7 @@ mod test {
     fn test_sequence_expression() {
         expect_parse!("true, false;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -526,14 +545,14 @@ mod test {
                             },
                         ],
                         "start": 0,
-                        "end": 12
+                        "end": 11
                     },
                     "start": 0,
-                    "end": 12
+                    "end": 11
                 }
               ],
               "start": 0,
-              "end": 12,
+              "end": 11,
         });
     }
 
@@ -541,6 +560,7 @@ mod test {
     fn test_binary_expression() {
         expect_parse!("a > 0;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -573,6 +593,7 @@ mod test {
 
         expect_parse!("new Foo();", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -598,6 +619,7 @@ mod test {
 
         expect_parse!("new Foo(0, true);", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -638,6 +660,7 @@ mod test {
 
         expect_parse!("new 'foo';", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -667,6 +690,7 @@ mod test {
     fn test_prefix_expression () {
         expect_parse!("++i", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -693,6 +717,7 @@ mod test {
 
         expect_parse!("+0", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -723,6 +748,7 @@ mod test {
     fn test_postfix_expression () {
         expect_parse!("i++;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -752,6 +778,7 @@ mod test {
     fn test_object_expression () {
         expect_parse!("const a = {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -785,6 +812,7 @@ mod test {
 
         expect_parse!("const a = { foo: 'bar' }", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -841,6 +869,7 @@ mod test {
 
         expect_parse!("const a = { [foo]: 'bar' }", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -897,6 +926,7 @@ mod test {
 
         expect_parse!("const a = { get (a) {} }", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -970,6 +1000,7 @@ mod test {
     fn test_meta_property_expression() {
         expect_parse!("function Handler () { new.target; }", {
           "type": "Program",
+          "sourceType": "module",
           "body": [
             {
               "type": "FunctionDeclaration",
@@ -997,7 +1028,7 @@ mod test {
                       "property": {
                         "type": "Identifier",
                         "name": "target",
-                        "start": 25,
+                        "start": 26,
                         "end": 32,
                       },
                       "start": 22,
@@ -1023,6 +1054,7 @@ mod test {
     fn test_member_expression () {
         expect_parse!("foo.bar", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -1037,7 +1069,7 @@ mod test {
                         "property": {
                             "type": "Identifier",
                             "name": "bar",
-                            "start": 7,
+                            "start": 4,
                             "end": 7
                         },
                         "computed": false,
@@ -1057,6 +1089,7 @@ mod test {
     fn test_computed_member_expression () {
         expect_parse!("foo[bar]", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -1091,6 +1124,7 @@ mod test {
     fn test_spread_expression () {
         expect_parse!("function foo (a, ...opts) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "FunctionDeclaration",
@@ -1136,6 +1170,7 @@ mod test {
 
         expect_parse!("[head, ...iter, tail]", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -1182,6 +1217,7 @@ mod test {
     fn test_call_expression () {
         expect_parse!("foo()", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -1194,7 +1230,7 @@ mod test {
                             "end": 3
                         },
                         "arguments": [],
-                        "start": 3,
+                        "start": 0,
                         "end": 5
                     },
                     "start": 0,
@@ -1210,6 +1246,7 @@ mod test {
     fn test_condititional_expression () {
         expect_parse!("a ? true : false", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -1251,11 +1288,15 @@ mod test {
     fn test_arrow_function_expression () {
         expect_parse!("(b) => b", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
                     "expression": {
                         "type": "ArrowFunctionExpression",
+                        "id": null,
+                        "generator": false,
+                        "expression": true,
                         "params": [
                             {
                                 "type": "Identifier",
@@ -1270,23 +1311,27 @@ mod test {
                             "start": 7,
                             "end": 8
                         },
-                        "start": 1,
+                        "start": 0,
                         "end": 8
                     },
-                    "start": 1,
+                    "start": 0,
                     "end": 8
                 }
               ],
-              "start": 1,
+              "start": 0,
               "end": 8,
         });
         expect_parse!("(b) => {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
                     "expression": {
                         "type": "ArrowFunctionExpression",
+                        "id": null,
+                        "generator": false,
+                        "expression": false,
                         "params": [
                             {
                                 "type": "Identifier",
@@ -1301,23 +1346,27 @@ mod test {
                             "start": 7,
                             "end": 9
                         },
-                        "start": 1,
+                        "start": 0,
                         "end": 9
                     },
-                    "start": 1,
+                    "start": 0,
                     "end": 9
                 }
               ],
-              "start": 1,
+              "start": 0,
               "end": 9,
         });
         expect_parse!("() => {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
                     "expression": {
                         "type": "ArrowFunctionExpression",
+                        "id": null,
+                        "generator": false,
+                        "expression": false,
                         "params": [],
                         "body": {
                             "type": "BlockStatement",
@@ -1337,11 +1386,15 @@ mod test {
         });
         expect_parse!("n => n", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
                     "expression": {
                         "type": "ArrowFunctionExpression",
+                        "id": null,
+                        "generator": false,
+                        "expression": true,
                         "params": [
                             {
                                 "type": "Identifier",
@@ -1372,6 +1425,7 @@ mod test {
     fn test_function_expression () {
         expect_parse!("function foo () {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "FunctionDeclaration",
@@ -1399,6 +1453,7 @@ mod test {
 
         expect_parse!("function foo (a, b = 2) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "FunctionDeclaration",
@@ -1454,6 +1509,7 @@ mod test {
     fn test_class_expression() {
         expect_parse!("class Foo {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ClassDeclaration",
@@ -1480,6 +1536,7 @@ mod test {
 
         expect_parse!("class Foo extends Bar {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ClassDeclaration",
@@ -1511,6 +1568,7 @@ mod test {
 
         expect_parse!(r"class Foo { bar() {} }", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ClassDeclaration",
@@ -1566,6 +1624,7 @@ mod test {
 
         expect_parse!("class Foo { static bar() {} }", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ClassDeclaration",
@@ -1,5 +1,7 @@
 use serde::ser::{Serialize, Serializer, SerializeStruct};
 use astgen::SerializeInLoc;
+use astgen::statement::FunctionBody;
+use astgen::value::is_computed;
 use ast::{Function, Class, Name, MandatoryName, OptionalName, EmptyName, ClassMember, Block};
 use ast::MethodKind;
 
@@ -31,7 +33,7 @@ impl<'ast> SerializeInLoc for ClassMember<'ast> {
                 self.in_loc(serializer, "MethodDefinition", 5, |state| {
                     state.serialize_field("kind", &kind)?;
                     state.serialize_field("static", &is_static)?;
-                    state.serialize_field("computed", &false)?;
+                    state.serialize_field("computed", &is_computed(&key))?;
                     state.serialize_field("key", &*key)?;
                     state.serialize_field("value", &value)
                 })
@@ -124,7 +126,7 @@ where
             state.serialize_field("generator", &self.generator)?;
             state.serialize_field("id", &self.name)?;
             state.serialize_field("params", &self.params)?;
-            state.serialize_field("body", &self.body)
+            state.serialize_field("body", &FunctionBody(&self.body))
         })
     }
 }
\ No newline at end of file
@@ -7,7 +7,14 @@ mod value;
 
 use serde::ser::{Serialize, Serializer, SerializeStruct};
 use ast::{Loc, Node};
-use module::Module;
+use module::{Module, OwnedModule, AstFlavor, FlavoredModule};
+use babel::Babel;
+
+pub(crate) use self::expression::expression_type;
+pub(crate) use self::value::{NumericValue, TemplateQuasis, parse_regex};
+pub(crate) use self::function::NameType;
+
+use self::statement::Prologue;
 
 pub trait SerializeInLoc {
     #[inline]
@@ -67,21 +74,59 @@ impl<'ast> Serialize for Module<'ast> {
         }
 
         let name = "Program";
-        let mut state = serializer.serialize_struct(name, 4)?;
+        let mut state = serializer.serialize_struct(name, 5)?;
         state.serialize_field("type", &name)?;
-        state.serialize_field("body", &body)?;
+        state.serialize_field("sourceType", "module")?;
+        state.serialize_field("body", &Prologue(body))?;
         state.serialize_field("start", &start)?;
         state.serialize_field("end", &end)?;
         state.end()
     }
 }
 
+impl Serialize for OwnedModule {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        self.module().serialize(serializer)
+    }
+}
+
+impl<'m, 'ast> Serialize for FlavoredModule<'m, 'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        match self.flavor {
+            AstFlavor::ESTree => self.module.serialize(serializer),
+            AstFlavor::Babel  => Babel(self.module).serialize(serializer),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use parser::parse;
+    use module::AstFlavor;
+    use serde_json::to_value;
+
+    #[test]
+    fn test_flavored_estree_matches_default() {
+        let module = parse("foo(42);").unwrap();
+
+        assert_eq!(
+            to_value(&module.module().with_flavor(AstFlavor::ESTree)).unwrap(),
+            to_value(&module).unwrap()
+        );
+    }
+
     #[test]
     fn test_generate_ast_empty() {
         expect_parse!("", {
             "type": "Program",
+            "sourceType": "module",
             "body": [],
             "start": 0,
             "end": 0,
@@ -91,6 +136,7 @@ mod test {
     fn test_generate_ast_expression() {
         expect_parse!("this;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
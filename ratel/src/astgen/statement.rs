@@ -1,7 +1,77 @@
 use ast::statement::*;
-use ast::{Block, DeclarationKind, Declarator, Loc, Statement};
+use ast::{Block, DeclarationKind, Declarator, Expression, Literal, Loc, Statement, StatementList};
 use astgen::SerializeInLoc;
-use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+/// Statements of a program or a function body, the leading string literals
+/// of which form the directive prologue.
+pub struct Prologue<'ast>(pub StatementList<'ast>);
+
+impl<'ast> Serialize for Prologue<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(None)?;
+        let mut prologue = true;
+
+        for statement in self.0.iter() {
+            prologue = prologue && statement.item.is_directive();
+
+            if prologue {
+                seq.serialize_element(&Directive(statement))?;
+            } else {
+                seq.serialize_element(statement)?;
+            }
+        }
+
+        seq.end()
+    }
+}
+
+/// An `ExpressionStatement` in the directive prologue, which carries the
+/// raw directive without its quotes.
+struct Directive<'a, 'ast: 'a>(&'a Loc<Statement<'ast>>);
+
+impl<'a, 'ast> Serialize for Directive<'a, 'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (expression, raw) = match self.0.item {
+            Statement::Expression(expression) => match expression.item {
+                Expression::Literal(Literal::String(raw)) => (expression, raw),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        let mut state = serializer.serialize_struct("ExpressionStatement", 5)?;
+        state.serialize_field("type", "ExpressionStatement")?;
+        state.serialize_field("expression", &*expression)?;
+        state.serialize_field("directive", &raw[1..raw.len() - 1])?;
+        state.serialize_field("start", &self.0.start)?;
+        state.serialize_field("end", &self.0.end)?;
+        state.end()
+    }
+}
+
+/// A `BlockStatement` that is the body of a function.
+pub struct FunctionBody<'a, 'ast: 'a>(pub &'a Loc<BlockStatement<'ast>>);
+
+impl<'a, 'ast> Serialize for FunctionBody<'a, 'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("BlockStatement", 4)?;
+        state.serialize_field("type", "BlockStatement")?;
+        state.serialize_field("body", &Prologue(self.0.body))?;
+        state.serialize_field("start", &self.0.start)?;
+        state.serialize_field("end", &self.0.end)?;
+        state.end()
+    }
+}
 
 // TODO: DRY with BlockStatement
 impl<'ast> Serialize for Loc<Block<'ast, SwitchCase<'ast>>> {
@@ -310,6 +380,7 @@ mod test {
     fn test_statement_empty() {
         expect_parse!(";", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "EmptyStatement",
@@ -326,6 +397,7 @@ mod test {
     fn test_statement_expression_statement() {
         expect_parse!("foo;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -348,6 +420,7 @@ mod test {
     fn test_declaration_statement() {
         expect_parse!("var a", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -376,6 +449,7 @@ mod test {
 
         expect_parse!("let a", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -404,6 +478,7 @@ mod test {
 
         expect_parse!("const a", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -432,6 +507,7 @@ mod test {
 
         expect_parse!("const a = 2", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -466,6 +542,7 @@ mod test {
 
         expect_parse!("const [a] = [2]", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "VariableDeclaration",
@@ -518,6 +595,7 @@ mod test {
     fn test_statement_return_statement() {
         expect_parse!("return;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ReturnStatement",
@@ -532,6 +610,7 @@ mod test {
 
         expect_parse!("return foo;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ReturnStatement",
@@ -554,6 +633,7 @@ mod test {
     fn test_statement_break_statement() {
         expect_parse!("break;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "BreakStatement",
@@ -568,6 +648,7 @@ mod test {
 
         expect_parse!("break foo;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "BreakStatement",
@@ -590,6 +671,7 @@ mod test {
     fn test_statement_continue_statement() {
         expect_parse!("continue;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ContinueStatement",
@@ -604,6 +686,7 @@ mod test {
 
         expect_parse!("continue foo;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ContinueStatement",
@@ -626,6 +709,7 @@ mod test {
     fn test_statement_throw_statement() {
         expect_parse!("throw foo;", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ThrowStatement",
@@ -648,6 +732,7 @@ mod test {
     fn test_statement_if_statement() {
         expect_parse!("if (true) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "IfStatement",
@@ -675,6 +760,7 @@ mod test {
 
         expect_parse!("if (true) {} else {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "IfStatement",
@@ -710,6 +796,7 @@ mod test {
     fn test_while_statement() {
         expect_parse!("while (false) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "WhileStatement",
@@ -739,6 +826,7 @@ mod test {
     fn test_do_statement() {
         expect_parse!("do {} while (false)", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "DoWhileStatement",
@@ -768,6 +856,7 @@ mod test {
     fn test_for_statement() {
         expect_parse!("for (;;) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ForStatement",
@@ -790,6 +879,7 @@ mod test {
 
         expect_parse!("for (i; i < 10; i++) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ForStatement",
@@ -847,6 +937,7 @@ mod test {
 
         expect_parse!("for (key in {}) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ForInStatement",
@@ -878,6 +969,7 @@ mod test {
 
         expect_parse!("for (key of {}) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ForOfStatement",
@@ -912,6 +1004,7 @@ mod test {
     fn test_try_statement() {
         expect_parse!("try {} catch (e) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "TryStatement",
@@ -952,6 +1045,7 @@ mod test {
     fn test_block_statement() {
         expect_parse!("{2}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "BlockStatement",
@@ -982,6 +1076,7 @@ mod test {
     fn test_function_statement() {
         expect_parse!("function foo () {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "FunctionDeclaration",
@@ -1009,6 +1104,7 @@ mod test {
 
         expect_parse!("function* foo () {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "FunctionDeclaration",
@@ -1036,6 +1132,7 @@ mod test {
 
         expect_parse!("function foo (a, value = true) {}", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "FunctionDeclaration",
@@ -1091,6 +1188,7 @@ mod test {
     fn test_import_statement() {
         expect_parse!("import defaultName, * as namespace from 'module-name'", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ImportDeclaration",
@@ -1128,6 +1226,7 @@ mod test {
         });
         expect_parse!("import {a, b as c} from 'module-name'", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ImportDeclaration",
@@ -1,5 +1,5 @@
-use ast::expression::{PropertyKey, TaggedTemplateExpression, TemplateLiteral};
-use ast::{Expression, Literal, Loc, NodeList, Pattern, Property};
+use ast::expression::{PropertyKey, TaggedTemplateExpression, TemplateLiteral, cook};
+use ast::{Expression, Literal, Loc, Node, NodeList, Pattern, Property};
 use astgen::SerializeInLoc;
 use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
 
@@ -30,6 +30,66 @@ pub fn is_float(value: &str) -> bool {
     value.bytes().any(|c| c == b'.')
 }
 
+/// Value of a numeric literal, parsed from its raw representation.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NumericValue {
+    Integer(i64),
+    Float(f64),
+}
+
+impl NumericValue {
+    pub fn parse(number: &str) -> NumericValue {
+        if number.len() > 2 {
+            let radix = match &number[0..2] {
+                "0x" | "0X" => 16,
+                "0o" | "0O" => 8,
+                "0b" | "0B" => 2,
+                _           => 10,
+            };
+
+            if radix != 10 {
+                let digits = &number[2..];
+
+                if let Ok(value) = i64::from_str_radix(digits, radix) {
+                    return NumericValue::Integer(value);
+                }
+
+                // Too large for `i64`, rounded like the decimal path does
+                let value = u128::from_str_radix(digits, radix)
+                    .map(|value| value as f64)
+                    .unwrap_or_else(|_| {
+                        digits.chars()
+                            .filter_map(|digit| digit.to_digit(radix))
+                            .fold(0.0, |value, digit| value * radix as f64 + digit as f64)
+                    });
+
+                return NumericValue::Float(value);
+            }
+        }
+
+        if !is_float(number) {
+            if let Ok(value) = number.parse() {
+                return NumericValue::Integer(value);
+            }
+        }
+
+        // Floats, exponents and integers too large for `i64`
+        NumericValue::Float(number.parse().expect("Invalid number"))
+    }
+}
+
+impl Serialize for NumericValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            NumericValue::Integer(value) => serializer.serialize_i64(value),
+            NumericValue::Float(value) => serializer.serialize_f64(value),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TemplateElement<'ast> {
     pub tail: bool,
@@ -39,11 +99,11 @@ pub struct TemplateElement<'ast> {
 #[derive(Debug, Serialize)]
 pub struct TemplateElementValue<'ast> {
     pub raw: &'ast str,
-    pub cooked: &'ast str,
+    pub cooked: Option<String>,
 }
 
 #[derive(Debug)]
-pub struct TemplateQuasis<'ast>(NodeList<'ast, &'ast str>);
+pub struct TemplateQuasis<'ast>(pub NodeList<'ast, &'ast str>);
 
 impl<'ast> Serialize for TemplateQuasis<'ast> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -54,9 +114,13 @@ impl<'ast> Serialize for TemplateQuasis<'ast> {
         let mut quasis = (self.0).iter().peekable();
 
         while let Some(q) = quasis.next() {
+            // The quasi location includes the delimiters around it, the
+            // element itself only spans the raw text.
+            let start = q.start + 1;
+
             seq.serialize_element(&Loc::new(
-                q.start + 1,
-                q.end - 1,
+                start,
+                start + q.item.len() as u32,
                 TemplateElement {
                     tail: quasis.peek().is_none(),
                     value: q.item,
@@ -103,47 +167,92 @@ impl<'ast> SerializeInLoc for TaggedTemplateExpression<'ast> {
     }
 }
 
-impl<'ast> SerializeInLoc for Property<'ast> {
+#[inline]
+pub fn is_computed(key: &Loc<PropertyKey>) -> bool {
+    matches!(key.item, PropertyKey::Computed(_))
+}
+
+/// Fields of an ESTree `Property`.
+struct PropertyFields<K, V> {
+    key: K,
+    value: V,
+    method: bool,
+    shorthand: bool,
+    computed: bool,
+}
+
+impl<K: Serialize, V: Serialize> SerializeInLoc for PropertyFields<K, V> {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "Property", 6, |state| {
+            state.serialize_field("key", &self.key)?;
+            state.serialize_field("method", &self.method)?;
+            state.serialize_field("shorthand", &self.shorthand)?;
+            state.serialize_field("computed", &self.computed)?;
+            state.serialize_field("value", &self.value)?;
+            state.serialize_field("kind", &"init")
+        })
+    }
+}
+
+impl<'ast> Serialize for Loc<Property<'ast>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         use self::Property::*;
-        match *self {
-            Shorthand(value) => {
-                Expression::Identifier(value).serialize(serializer)
-            }
-            Literal { key, value } => {
-                let computed = if let PropertyKey::Computed(_) = key.item {
-                    true
-                } else {
-                    false
-                };
-
-                self.in_loc(serializer, "Property", 6, |state| {
-                    state.serialize_field("key", &*key)?;
-                    state.serialize_field("method", &false)?;
-                    state.serialize_field("shorthand", &false)?;
-                    state.serialize_field("computed", &computed)?;
-                    state.serialize_field("value", &value)?;
-                    state.serialize_field("kind", &"init")
-                })
+
+        match self.item {
+            Shorthand(name) => {
+                // The key and the value are the same identifier
+                let ident = Loc::new(self.start, self.end, Expression::Identifier(name));
+
+                Loc::new(self.start, self.end, PropertyFields {
+                    key: &ident,
+                    value: &ident,
+                    method: false,
+                    shorthand: true,
+                    computed: false,
+                }).serialize(serializer)
+            },
+            Literal { key, value } => Loc::new(self.start, self.end, PropertyFields {
+                key: &*key,
+                value,
+                method: false,
+                shorthand: false,
+                computed: is_computed(&key),
+            }).serialize(serializer),
+            Method { key, value } => Loc::new(self.start, self.end, PropertyFields {
+                key: &*key,
+                value,
+                method: true,
+                shorthand: false,
+                computed: is_computed(&key),
+            }).serialize(serializer),
+            Spread { argument } => {
+                let mut state = serializer.serialize_struct("SpreadProperty", 4)?;
+                state.serialize_field("type", "SpreadProperty")?;
+                state.serialize_field("argument", &argument)?;
+                state.serialize_field("start", &self.start)?;
+                state.serialize_field("end", &self.end)?;
+                state.end()
             }
-            Method { key, value } => self.in_loc(serializer, "Property", 6, |state| {
-                state.serialize_field("key", &*key)?;
-                state.serialize_field("method", &true)?;
-                state.serialize_field("shorthand", &false)?;
-                state.serialize_field("computed", &false)?;
-                state.serialize_field("value", &value)?;
-                state.serialize_field("kind", &"init")
-            }),
-            Spread { argument } => self.in_loc(serializer, "SpreadProperty", 1, |state| {
-                state.serialize_field("argument", &argument)
-            })
         }
     }
 }
 
+impl<'ast> Serialize for Node<'ast, Property<'ast>> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Loc::<Property>::serialize(self, serializer)
+    }
+}
+
 impl<'ast> Serialize for Loc<PropertyKey<'ast>> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -151,19 +260,17 @@ impl<'ast> Serialize for Loc<PropertyKey<'ast>> {
     {
         use self::PropertyKey::*;
 
-        match self.item {
-            Computed(expr) => serializer.serialize_some(&*expr),
-            Literal(value) => serializer.serialize_some(&Loc::new(
-                self.start,
-                self.end,
-                Expression::Identifier(value),
-            )),
-            Binary(value) => serializer.serialize_some(&Loc::new(
-                self.start,
-                self.end,
-                Expression::Identifier(value),
-            )),
-        }
+        let key = match self.item {
+            Computed(expr) => return serializer.serialize_some(&*expr),
+            Literal(raw) => match raw.as_bytes()[0] {
+                b'"' | b'\'' => Expression::Literal(::ast::Literal::String(raw)),
+                b'0'..=b'9' | b'.' => Expression::Literal(::ast::Literal::Number(raw)),
+                _ => Expression::Identifier(raw),
+            },
+            Binary(raw) => Expression::Literal(::ast::Literal::Binary(raw)),
+        };
+
+        serializer.serialize_some(&Loc::new(self.start, self.end, key))
     }
 }
 
@@ -197,38 +304,8 @@ impl<'ast> SerializeInLoc for Literal<'ast> {
                     state.serialize_field("value", &false)?;
                     state.serialize_field("raw", &"false")
                 },
-                Number(number) => {
-                    if number.len() > 2 {
-                        let prefix = &number[0..2];
-                        let is_hexdecimal = prefix == "0x" || prefix == "0X";
-                        let is_octal = prefix == "0o" || prefix == "0O";
-                        if is_hexdecimal || is_octal {
-                            let value = unsafe { number.get_unchecked(2..number.len()) };
-                            let radix = if is_hexdecimal { 16 } else { 8 };
-                            state.serialize_field(
-                                "value",
-                                &i32::from_str_radix(value, radix).expect("Invalid number"),
-                            )?;
-                            return state.serialize_field("raw", &number)
-                        }
-                    }
-
-                    if is_float(number) {
-                        let value: f64 = number.parse().expect("Invalid number");
-                        state.serialize_field("value", &value)?;
-                        return state.serialize_field("raw", &number)
-                    }
-
-                    let value: i64 = number.parse().expect("Invalid number");
-                    state.serialize_field("value", &value)?;
-                    state.serialize_field("raw", &number)
-                }
-                Binary(number) => {
-                    let value = unsafe { number.get_unchecked(2..number.len()) };
-                    state.serialize_field(
-                        "value",
-                        &i32::from_str_radix(value, 2).expect("Invalid number"),
-                    )?;
+                Number(number) | Binary(number) => {
+                    state.serialize_field("value", &NumericValue::parse(number))?;
                     state.serialize_field("raw", &number)
                 }
                 String(value) => {
@@ -237,6 +314,8 @@ impl<'ast> SerializeInLoc for Literal<'ast> {
                     state.serialize_field("raw", &value)
                 },
                 RegEx(value) => {
+                    // There is no JSON representation of a `RegExp`
+                    state.serialize_field("value", &())?;
                     state.serialize_field("regex", &parse_regex(value))?;
                     state.serialize_field("raw", &value)
                 },
@@ -256,7 +335,7 @@ impl<'ast> SerializeInLoc for Pattern<'ast> {
             Void => unreachable!(),
             Identifier(a) => Expression::Identifier(a).serialize(serializer),
             ObjectPattern { properties } => self.in_loc(serializer, "ObjectPattern", 1, |state| {
-                state.serialize_field("keys", &properties)
+                state.serialize_field("properties", &properties)
             }),
             ArrayPattern { elements } => self.in_loc(serializer, "ArrayPattern", 1, |state| {
                 state.serialize_field("elements", &elements)
@@ -283,7 +362,7 @@ impl<'ast> SerializeInLoc for TemplateElement<'ast> {
             state.serialize_field("tail", &self.tail)?;
             let value = TemplateElementValue {
                 raw: self.value,
-                cooked: self.value,
+                cooked: cook(self.value),
             };
             state.serialize_field("value", &value)
         })
@@ -325,6 +404,7 @@ mod test {
     fn test_value_undefined() {
         expect_parse!("undefined", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -347,6 +427,7 @@ mod test {
     fn test_value_null() {
         expect_parse!("null", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -370,6 +451,7 @@ mod test {
     fn test_value_true() {
         expect_parse!("true", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -393,6 +475,7 @@ mod test {
     fn test_value_false() {
         expect_parse!("false", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -416,6 +499,7 @@ mod test {
     fn test_value_number() {
         expect_parse!("0", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -436,6 +520,7 @@ mod test {
 
         expect_parse!("255", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -456,6 +541,7 @@ mod test {
 
         expect_parse!("0.234", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -476,6 +562,7 @@ mod test {
 
         expect_parse!("0xff", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -496,6 +583,7 @@ mod test {
 
         expect_parse!("0b1111", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -516,6 +604,7 @@ mod test {
 
         expect_parse!("0o1111", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -535,10 +624,97 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_value_number_beyond_i32() {
+        expect_parse!("0xFFFFFFFF", {
+            "type": "Program",
+            "sourceType": "module",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "Literal",
+                        "value": 4294967295i64,
+                        "raw": "0xFFFFFFFF",
+                        "start": 0,
+                        "end": 10
+                    },
+                    "start": 0,
+                    "end": 10,
+                }
+            ],
+            "start": 0,
+            "end": 10,
+        });
+
+        expect_parse!("0o77777777777", {
+            "type": "Program",
+            "sourceType": "module",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "Literal",
+                        "value": 8589934591i64,
+                        "raw": "0o77777777777",
+                        "start": 0,
+                        "end": 13
+                    },
+                    "start": 0,
+                    "end": 13,
+                }
+            ],
+            "start": 0,
+            "end": 13,
+        });
+    }
+
+    #[test]
+    fn test_value_number_beyond_i64() {
+        expect_parse!("x = 0xFFFFFFFFFFFFFFFFFF;", {
+            "type": "Program",
+            "sourceType": "module",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "AssignmentExpression",
+                        "operator": "=",
+                        "left": {
+                            "type": "Identifier",
+                            "name": "x",
+                            "start": 0,
+                            "end": 1
+                        },
+                        "right": {
+                            "type": "Literal",
+                            "value": 4722366482869645213696.0,
+                            "raw": "0xFFFFFFFFFFFFFFFFFF",
+                            "start": 4,
+                            "end": 24
+                        },
+                        "start": 0,
+                        "end": 24
+                    },
+                    "start": 0,
+                    "end": 24,
+                }
+            ],
+            "start": 0,
+            "end": 24,
+        });
+
+        assert_eq!(
+            NumericValue::parse(&format!("0x{}", "F".repeat(40))),
+            NumericValue::Float(16f64.powi(40))
+        );
+    }
+
     #[test]
     fn test_value_string() {
         expect_parse!("'foo'", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -549,6 +725,7 @@ mod test {
                         "start": 0,
                         "end": 5
                     },
+                    "directive": "foo",
                     "start": 0,
                     "end": 5,
                 }
@@ -562,11 +739,13 @@ mod test {
     fn test_regex() {
         expect_parse!(r#"/^\b\w+/m"#, {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
                     "expression": {
                         "type": "Literal",
+                        "value": null,
                         "regex": {
                             "pattern": r#"^\b\w+"#,
                             "flags": "m"
@@ -588,6 +767,7 @@ mod test {
     fn test_template() {
         expect_parse!("``", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -617,8 +797,84 @@ mod test {
             "end": 2,
         });
 
+        expect_parse!(r"`a\u{62}`", {
+            "type": "Program",
+            "sourceType": "module",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "TemplateLiteral",
+                        "quasis": [
+                            {
+                                "type": "TemplateElement",
+                                "tail": true,
+                                "value": {
+                                    "raw": "a\\u{62}",
+                                    "cooked": "ab",
+                                },
+                                "start": 1,
+                                "end": 8
+                            }
+                        ],
+                        "expressions": [],
+                        "start": 0,
+                        "end": 9
+                    },
+                    "start": 0,
+                    "end": 9,
+                }
+            ],
+            "start": 0,
+            "end": 9,
+        });
+
+        expect_parse!(r"foo`\01`", {
+            "type": "Program",
+            "sourceType": "module",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "TaggedTemplateExpression",
+                        "tag": {
+                            "type": "Identifier",
+                            "name": "foo",
+                            "start": 0,
+                            "end": 3
+                        },
+                        "quasi": {
+                            "type": "TemplateLiteral",
+                            "quasis": [
+                                {
+                                    "type": "TemplateElement",
+                                    "tail": true,
+                                    "value": {
+                                        "raw": "\\01",
+                                        "cooked": null,
+                                    },
+                                    "start": 4,
+                                    "end": 7
+                                }
+                            ],
+                            "expressions": [],
+                            "start": 3,
+                            "end": 8
+                        },
+                        "start": 0,
+                        "end": 8
+                    },
+                    "start": 0,
+                    "end": 8,
+                }
+            ],
+            "start": 0,
+            "end": 8,
+        });
+
         expect_parse!("foo``", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -661,6 +917,7 @@ mod test {
 
         expect_parse!("``", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -692,6 +949,7 @@ mod test {
 
         expect_parse!("`foo${bar}baz`", {
             "type": "Program",
+            "sourceType": "module",
             "body": [
                 {
                     "type": "ExpressionStatement",
@@ -706,7 +964,7 @@ mod test {
                                     "cooked": "foo",
                                 },
                                 "start": 1,
-                                "end": 5
+                                "end": 4
                             },
                             {
                                 "type": "TemplateElement",
@@ -744,6 +1002,7 @@ mod test {
     fn test_pattern() {
         expect_parse!("let {a, b} = { a: 1, b: 2 };", {
             "type":"Program",
+            "sourceType":"module",
             "body":[
                 {
                     "type":"VariableDeclaration",
@@ -753,16 +1012,46 @@ mod test {
                             "type":"VariableDeclarator",
                             "id":{
                                 "type":"ObjectPattern",
-                                "keys":[
+                                "properties":[
                                     {
-                                        "type":"Identifier",
-                                        "name":"a",
+                                        "type":"Property",
+                                        "key":{
+                                            "type":"Identifier",
+                                            "name":"a",
+                                            "start":5,
+                                            "end":6
+                                        },
+                                        "method":false,
+                                        "shorthand":true,
+                                        "computed":false,
+                                        "value":{
+                                            "type":"Identifier",
+                                            "name":"a",
+                                            "start":5,
+                                            "end":6
+                                        },
+                                        "kind":"init",
                                         "start":5,
                                         "end":6
                                     },
                                     {
-                                        "type":"Identifier",
-                                        "name":"b",
+                                        "type":"Property",
+                                        "key":{
+                                            "type":"Identifier",
+                                            "name":"b",
+                                            "start":8,
+                                            "end":9
+                                        },
+                                        "method":false,
+                                        "shorthand":true,
+                                        "computed":false,
+                                        "value":{
+                                            "type":"Identifier",
+                                            "name":"b",
+                                            "start":8,
+                                            "end":9
+                                        },
+                                        "kind":"init",
                                         "start":8,
                                         "end":9
                                     }
@@ -834,6 +1123,7 @@ mod test {
         });
         expect_parse!("let [a, b] = [1, 2];", {
             "type":"Program",
+            "sourceType":"module",
             "body":[
                 {
                     "type":"VariableDeclaration",
@@ -0,0 +1,437 @@
+use serde::ser::{Serialize, Serializer, SerializeStruct};
+use ast::{Expression, Loc, OperatorKind};
+use ast::expression::*;
+use astgen::expression_type;
+use babel::{Babel, SerializeBabel, BlockBody};
+
+impl<'ast> SerializeBabel for SpreadExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "SpreadElement", 1, |state| {
+            state.serialize_field("argument", &Babel(&self.argument))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for MemberExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "MemberExpression", 3, |state| {
+            state.serialize_field("object", &Babel(&self.object))?;
+            state.serialize_field("property", &Babel(&self.property))?;
+            state.serialize_field("computed", &false)
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ComputedMemberExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "MemberExpression", 3, |state| {
+            state.serialize_field("object", &Babel(&self.object))?;
+            state.serialize_field("property", &Babel(&self.property))?;
+            state.serialize_field("computed", &true)
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for MetaPropertyExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "MetaProperty", 2, |state| {
+            state.serialize_field("meta", &Babel(&self.meta))?;
+            state.serialize_field("property", &Babel(&self.property))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for CallExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "CallExpression", 2, |state| {
+            state.serialize_field("callee", &Babel(&self.callee))?;
+            state.serialize_field("arguments", &Babel(&self.arguments))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ConditionalExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ConditionalExpression", 3, |state| {
+            state.serialize_field("test", &Babel(&self.test))?;
+            state.serialize_field("consequent", &Babel(&self.consequent))?;
+            state.serialize_field("alternate", &Babel(&self.alternate))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ArrowExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ArrowFunctionExpression", 5, |state| {
+            state.serialize_field("id", &())?;
+            state.serialize_field("generator", &false)?;
+            state.serialize_field("async", &false)?;
+            state.serialize_field("params", &Babel(&self.params))?;
+
+            match self.body {
+                ArrowBody::Expression(expression) => state.serialize_field("body", &Babel(&expression)),
+                ArrowBody::Block(block)           => state.serialize_field("body", &ArrowBlock(&block)),
+            }
+        })
+    }
+}
+
+/// Block body of an arrow function, which may have a directive prologue.
+struct ArrowBlock<'a, 'ast: 'a>(&'a Loc<::ast::BlockStatement<'ast>>);
+
+impl<'a, 'ast> Serialize for ArrowBlock<'a, 'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let body = BlockBody {
+            body: self.0.body,
+            directives: true,
+        };
+
+        let mut state = serializer.serialize_struct("BlockStatement", 5)?;
+        state.serialize_field("type", "BlockStatement")?;
+        body.serialize_fields(&mut state)?;
+        state.serialize_field("start", &self.0.start)?;
+        state.serialize_field("end", &self.0.end)?;
+        state.end()
+    }
+}
+
+impl<'ast> SerializeBabel for SequenceExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "SequenceExpression", 1, |state| {
+            state.serialize_field("expressions", &Babel(&self.body))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for PrefixExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        let prefix = true;
+        let expr_type = expression_type(self.operator, prefix);
+
+        if let OperatorKind::New = self.operator {
+            match self.operand.item {
+                Expression::Call(CallExpression { ref callee, ref arguments }) => {
+                    self.in_loc(serializer, expr_type, 2, |state| {
+                        state.serialize_field("callee", &Babel(callee))?;
+                        state.serialize_field("arguments", &Babel(arguments))
+                    })
+                },
+                _ => {
+                    self.in_loc(serializer, expr_type, 2, |state| {
+                        // 0 byte array, will be optimized away
+                        let arguments: [(); 0] = [];
+                        state.serialize_field("callee", &Babel(&self.operand))?;
+                        state.serialize_field("arguments", &arguments)
+                    })
+                },
+            }
+        } else {
+            self.in_loc(serializer, expr_type, 3, |state| {
+                state.serialize_field("operator", &self.operator)?;
+                state.serialize_field("argument", &Babel(&self.operand))?;
+                state.serialize_field("prefix", &prefix)
+            })
+        }
+    }
+}
+
+impl<'ast> SerializeBabel for PostfixExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        let prefix = false;
+        let expr_type = expression_type(self.operator, prefix);
+        self.in_loc(serializer, expr_type, 3, |state| {
+            state.serialize_field("operator", &self.operator)?;
+            state.serialize_field("argument", &Babel(&self.operand))?;
+            state.serialize_field("prefix", &prefix)
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ObjectExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ObjectExpression", 1, |state| {
+            state.serialize_field("properties", &Babel(&self.body))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ArrayExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ArrayExpression", 1, |state| {
+            let mut elements = self.body.iter()
+                                        .map(|loc| if loc.item != Expression::Void { Some(Babel(&**loc)) } else { None })
+                                        .collect::<Vec<_>>();
+
+            // Trailing holes are not part of the array
+            while let Some(&None) = elements.last() {
+                elements.pop();
+            }
+
+            state.serialize_field("elements", &elements)
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for BinaryExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        let prefix = false;
+        self.in_loc(serializer, expression_type(self.operator, prefix), 3, |state| {
+            state.serialize_field("operator", &self.operator)?;
+            state.serialize_field("left", &Babel(&self.left))?;
+            state.serialize_field("right", &Babel(&self.right))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for Expression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::Expression::*;
+
+        match *self {
+            Void => unreachable!(),
+            This(_) => self.in_loc(serializer, "ThisExpression", 0, |_| Ok(())),
+            Super(_) => self.in_loc(serializer, "Super", 0, |_| Ok(())),
+            Identifier(ref ident)          => SerializeBabel::serialize(ident, serializer),
+            Literal(ref value)             => value.serialize(serializer),
+            Array(ref value)               => value.serialize(serializer),
+            Sequence(ref expression)       => expression.serialize(serializer),
+            Binary(ref expression)         => expression.serialize(serializer),
+            Prefix(ref expression)         => expression.serialize(serializer),
+            Postfix(ref expression)        => expression.serialize(serializer),
+            Object(ref expression)         => expression.serialize(serializer),
+            Template(ref expression)       => expression.serialize(serializer),
+            TaggedTemplate(ref expression) => expression.serialize(serializer),
+            Spread(ref expression)         => expression.serialize(serializer),
+            Member(ref expression)         => expression.serialize(serializer),
+            ComputedMember(ref expression) => expression.serialize(serializer),
+            MetaProperty(ref expression)   => expression.serialize(serializer),
+            Call(ref expression)           => expression.serialize(serializer),
+            Conditional(ref expression)    => expression.serialize(serializer),
+            Arrow(ref expression)          => expression.serialize(serializer),
+            Function(ref expression)       => expression.serialize(serializer),
+            Class(ref expression)          => expression.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_new_expression() {
+        expect_parse_babel!("new Foo(1);", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "NewExpression",
+                            "callee": {
+                                "type": "Identifier",
+                                "name": "Foo",
+                                "start": 4,
+                                "end": 7,
+                            },
+                            "arguments": [
+                                {
+                                    "type": "NumericLiteral",
+                                    "value": 1,
+                                    "extra": {
+                                        "rawValue": 1,
+                                        "raw": "1",
+                                    },
+                                    "start": 8,
+                                    "end": 9,
+                                }
+                            ],
+                            "start": 0,
+                            "end": 11,
+                        },
+                        "start": 0,
+                        "end": 11,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 11,
+            },
+            "start": 0,
+            "end": 11,
+        });
+    }
+
+    #[test]
+    fn test_arrow_expression() {
+        expect_parse_babel!("(a) => a;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "ArrowFunctionExpression",
+                            "id": null,
+                            "generator": false,
+                            "async": false,
+                            "params": [
+                                {
+                                    "type": "Identifier",
+                                    "name": "a",
+                                    "start": 1,
+                                    "end": 2,
+                                }
+                            ],
+                            "body": {
+                                "type": "Identifier",
+                                "name": "a",
+                                "start": 7,
+                                "end": 8,
+                            },
+                            "start": 0,
+                            "end": 8,
+                        },
+                        "start": 0,
+                        "end": 8,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 8,
+            },
+            "start": 0,
+            "end": 8,
+        });
+    }
+
+    #[test]
+    fn test_object_expression() {
+        expect_parse_babel!("({ 'a': 1, b() {} });", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "ObjectExpression",
+                            "properties": [
+                                {
+                                    "type": "ObjectProperty",
+                                    "method": false,
+                                    "key": {
+                                        "type": "StringLiteral",
+                                        "value": "a",
+                                        "extra": {
+                                            "rawValue": "a",
+                                            "raw": "'a'",
+                                        },
+                                        "start": 3,
+                                        "end": 6,
+                                    },
+                                    "computed": false,
+                                    "shorthand": false,
+                                    "value": {
+                                        "type": "NumericLiteral",
+                                        "value": 1,
+                                        "extra": {
+                                            "rawValue": 1,
+                                            "raw": "1",
+                                        },
+                                        "start": 8,
+                                        "end": 9,
+                                    },
+                                    "start": 3,
+                                    "end": 9,
+                                },
+                                {
+                                    "type": "ObjectMethod",
+                                    "method": true,
+                                    "key": {
+                                        "type": "Identifier",
+                                        "name": "b",
+                                        "start": 11,
+                                        "end": 12,
+                                    },
+                                    "computed": false,
+                                    "kind": "method",
+                                    "id": null,
+                                    "generator": false,
+                                    "async": false,
+                                    "params": [],
+                                    "body": {
+                                        "type": "BlockStatement",
+                                        "body": [],
+                                        "directives": [],
+                                        "start": 15,
+                                        "end": 17,
+                                    },
+                                    "start": 11,
+                                    "end": 17,
+                                }
+                            ],
+                            "start": 1,
+                            "end": 19,
+                        },
+                        "start": 1,
+                        "end": 19,
+                    }
+                ],
+                "directives": [],
+                "start": 1,
+                "end": 19,
+            },
+            "start": 1,
+            "end": 19,
+        });
+    }
+}
@@ -0,0 +1,266 @@
+use serde::ser::{Serialize, Serializer, SerializeStruct};
+use ast::{Function, Class, MandatoryName, OptionalName, EmptyName, ClassMember, Block};
+use ast::{BlockNode, Statement};
+use astgen::NameType;
+use babel::{Babel, SerializeBabel, BlockBody};
+use babel::value::is_computed;
+
+/// Names of functions and classes, serialized as the `id` field.
+pub trait BabelName<'ast>: NameType<'ast> {
+    fn serialize_id<S>(&self, state: &mut S) -> Result<(), S::Error>
+    where
+        S: SerializeStruct;
+}
+
+impl<'ast> BabelName<'ast> for EmptyName {
+    fn serialize_id<S>(&self, state: &mut S) -> Result<(), S::Error>
+    where
+        S: SerializeStruct,
+    {
+        state.serialize_field("id", &())
+    }
+}
+
+impl<'ast> BabelName<'ast> for OptionalName<'ast> {
+    fn serialize_id<S>(&self, state: &mut S) -> Result<(), S::Error>
+    where
+        S: SerializeStruct,
+    {
+        state.serialize_field("id", &Babel(&self.0))
+    }
+}
+
+impl<'ast> BabelName<'ast> for MandatoryName<'ast> {
+    fn serialize_id<S>(&self, state: &mut S) -> Result<(), S::Error>
+    where
+        S: SerializeStruct,
+    {
+        state.serialize_field("id", &Babel(&self.0))
+    }
+}
+
+/// Function bodies carry their own directive prologue.
+struct FunctionBody<'ast>(BlockNode<'ast, Statement<'ast>>);
+
+impl<'ast> Serialize for FunctionBody<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let body = BlockBody {
+            body: self.0.body,
+            directives: true,
+        };
+
+        let mut state = serializer.serialize_struct("BlockStatement", 5)?;
+        state.serialize_field("type", "BlockStatement")?;
+        body.serialize_fields(&mut state)?;
+        state.serialize_field("start", &self.0.start)?;
+        state.serialize_field("end", &self.0.end)?;
+        state.end()
+    }
+}
+
+/// Fields shared by function declarations, expressions and methods.
+pub fn serialize_function_fields<'ast, N, S>(function: &Function<'ast, N>, state: &mut S) -> Result<(), S::Error>
+where
+    N: BabelName<'ast>,
+    S: SerializeStruct,
+{
+    function.name.serialize_id(state)?;
+    state.serialize_field("generator", &function.generator)?;
+    state.serialize_field("async", &false)?;
+    state.serialize_field("params", &Babel(&function.params))?;
+    state.serialize_field("body", &FunctionBody(function.body))
+}
+
+impl<'ast> SerializeBabel for ClassMember<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::ClassMember::*;
+
+        match *self {
+            Error => panic!("Module contains errors"),
+            Method { is_static, key, kind, value } => {
+                self.in_loc(serializer, "ClassMethod", 9, |state| {
+                    state.serialize_field("kind", &kind)?;
+                    state.serialize_field("static", &is_static)?;
+                    state.serialize_field("computed", &is_computed(&key))?;
+                    state.serialize_field("key", &Babel(&*key))?;
+                    serialize_function_fields(&value.item, state)
+                })
+            },
+            Literal { is_static, key, value } => {
+                self.in_loc(serializer, "ClassProperty", 4, |state| {
+                    state.serialize_field("static", &is_static)?;
+                    state.serialize_field("computed", &is_computed(&key))?;
+                    state.serialize_field("key", &Babel(&*key))?;
+                    state.serialize_field("value", &Babel(&value))
+                })
+            }
+        }
+    }
+}
+
+impl<'ast> SerializeBabel for Block<'ast, ClassMember<'ast>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ClassBody", 1, |state| {
+            state.serialize_field("body", &Babel(&self.body))
+        })
+    }
+}
+
+impl<'ast, N> SerializeBabel for Class<'ast, N>
+where
+    N: BabelName<'ast>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, N::IN_CLASS, 3, |state| {
+            self.name.serialize_id(state)?;
+            state.serialize_field("superClass", &Babel(&self.extends))?;
+            state.serialize_field("body", &Babel(&self.body))
+        })
+    }
+}
+
+impl<'ast, N> SerializeBabel for Function<'ast, N>
+where
+    N: BabelName<'ast>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, N::IN_FUNCTION, 5, |state| {
+            serialize_function_fields(self, state)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_function_declaration() {
+        expect_parse_babel!("function foo() { 'use strict'; }", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "FunctionDeclaration",
+                        "id": {
+                            "type": "Identifier",
+                            "name": "foo",
+                            "start": 9,
+                            "end": 12,
+                        },
+                        "generator": false,
+                        "async": false,
+                        "params": [],
+                        "body": {
+                            "type": "BlockStatement",
+                            "body": [],
+                            "directives": [
+                                {
+                                    "type": "Directive",
+                                    "value": {
+                                        "type": "DirectiveLiteral",
+                                        "value": "use strict",
+                                        "extra": {
+                                            "rawValue": "use strict",
+                                            "raw": "'use strict'",
+                                        },
+                                        "start": 17,
+                                        "end": 29,
+                                    },
+                                    "start": 17,
+                                    "end": 29,
+                                }
+                            ],
+                            "start": 15,
+                            "end": 32,
+                        },
+                        "start": 0,
+                        "end": 32,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 32,
+            },
+            "start": 0,
+            "end": 32,
+        });
+    }
+
+    #[test]
+    fn test_class_expression() {
+        expect_parse_babel!("(class { static bar() {} });", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "ClassExpression",
+                            "id": null,
+                            "superClass": null,
+                            "body": {
+                                "type": "ClassBody",
+                                "body": [
+                                    {
+                                        "type": "ClassMethod",
+                                        "kind": "method",
+                                        "static": true,
+                                        "computed": false,
+                                        "key": {
+                                            "type": "Identifier",
+                                            "name": "bar",
+                                            "start": 16,
+                                            "end": 19,
+                                        },
+                                        "id": null,
+                                        "generator": false,
+                                        "async": false,
+                                        "params": [],
+                                        "body": {
+                                            "type": "BlockStatement",
+                                            "body": [],
+                                            "directives": [],
+                                            "start": 22,
+                                            "end": 24,
+                                        },
+                                        "start": 9,
+                                        "end": 24,
+                                    }
+                                ],
+                                "start": 7,
+                                "end": 26,
+                            },
+                            "start": 1,
+                            "end": 26,
+                        },
+                        "start": 1,
+                        "end": 26,
+                    }
+                ],
+                "directives": [],
+                "start": 1,
+                "end": 26,
+            },
+            "start": 1,
+            "end": 26,
+        });
+    }
+}
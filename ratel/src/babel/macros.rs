@@ -0,0 +1,17 @@
+/// Parses the given input string into an AST and compares its
+/// Babel flavored serialization with the given JSON input.
+
+#[cfg(test)]
+#[macro_export]
+macro_rules! expect_parse_babel {
+    ($expr:expr, $expected:tt) => {{
+        use $crate::parser::parse;
+        use $crate::module::AstFlavor;
+        use $crate::serde_json::to_value;
+
+        let module = parse($expr).unwrap();
+        let result = to_value(&module.module().with_flavor(AstFlavor::Babel)).unwrap();
+        let expected = json!($expected);
+        assert_eq!(result, expected);
+    }};
+}
@@ -0,0 +1,362 @@
+#[macro_use]
+mod macros;
+mod statement;
+mod expression;
+mod function;
+mod value;
+
+use serde::ser::{Serialize, Serializer, SerializeStruct, SerializeSeq};
+use ast::{Loc, Node, NodeList, Statement, StatementList, Expression, Literal};
+use module::Module;
+
+/// Wrapper that serializes the wrapped AST in the Babel flavor,
+/// as opposed to ESTree produced by `Serialize` on the AST itself.
+pub struct Babel<'a, T: 'a + ?Sized>(pub &'a T);
+
+/// Starts a node struct of the given type, the caller is expected
+/// to add `start` and `end` before ending it.
+#[inline]
+fn in_loc<S, F>(serializer: S, name: &'static str, length: usize, build: F) -> Result<S::SerializeStruct, S::Error>
+where
+    S: Serializer,
+    F: FnOnce(&mut S::SerializeStruct) -> Result<(), S::Error>
+{
+    let mut state = serializer.serialize_struct(name, length + 3)?;
+    state.serialize_field("type", name)?;
+    build(&mut state).map(move |_| state)
+}
+
+pub trait SerializeBabel {
+    #[inline]
+    fn in_loc<S, F>(&self, serializer: S, name: &'static str, length: usize, build: F) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+        F: FnOnce(&mut S::SerializeStruct) -> Result<(), S::Error>
+    {
+        in_loc(serializer, name, length, build)
+    }
+
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where S: Serializer;
+}
+
+impl<'a, T: SerializeBabel> Serialize for Babel<'a, Loc<T>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut state = self.0.item.serialize(serializer)?;
+        state.serialize_field("start", &self.0.start)?;
+        state.serialize_field("end", &self.0.end)?;
+        state.end()
+    }
+}
+
+impl<'a, 'ast, T: 'ast> Serialize for Babel<'a, Node<'ast, T>>
+where
+    for<'b> Babel<'b, Loc<T>>: Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        Babel(&**self.0).serialize(serializer)
+    }
+}
+
+impl<'a, 'ast, T: 'ast> Serialize for Babel<'a, NodeList<'ast, T>>
+where
+    for<'b> Babel<'b, Loc<T>>: Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut seq = serializer.serialize_seq(None)?;
+
+        for node in self.0.iter() {
+            seq.serialize_element(&Babel(&**node))?;
+        }
+
+        seq.end()
+    }
+}
+
+impl<'a, T> Serialize for Babel<'a, Option<T>>
+where
+    for<'b> Babel<'b, T>: Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        match *self.0 {
+            Some(ref value) => serializer.serialize_some(&Babel(value)),
+            None            => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Babel lifts the directive prologue (`"use strict";` and friends) of
+/// programs and function bodies out of the statement list.
+#[inline]
+fn split_directives<'ast>(body: StatementList<'ast>) -> (Directives<'ast>, StatementList<'ast>) {
+    let mut rest = body;
+    let mut count = 0;
+
+    while let Some(statement) = rest.first_element() {
+        if !statement.item.is_directive() {
+            break;
+        }
+
+        rest.shift_ref();
+        count += 1;
+    }
+
+    (Directives { body, count }, rest)
+}
+
+struct Directives<'ast> {
+    body: StatementList<'ast>,
+    count: usize,
+}
+
+impl<'ast> Serialize for Directives<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut seq = serializer.serialize_seq(Some(self.count))?;
+
+        for statement in self.body.iter().take(self.count) {
+            if let Statement::Expression(expression) = statement.item {
+                if let Expression::Literal(Literal::String(raw)) = expression.item {
+                    seq.serialize_element(&Loc::new(statement.start, statement.end, Directive {
+                        value: Loc::new(expression.start, expression.end, raw),
+                    }))?;
+                }
+            }
+        }
+
+        seq.end()
+    }
+}
+
+struct Directive<'ast> {
+    value: Loc<&'ast str>,
+}
+
+impl<'ast> Serialize for Loc<Directive<'ast>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let raw = self.item.value.item;
+        let value = &raw[1..raw.len() - 1];
+
+        let mut state = serializer.serialize_struct("Directive", 4)?;
+        state.serialize_field("type", "Directive")?;
+        state.serialize_field("value", &Loc::new(self.item.value.start, self.item.value.end, DirectiveLiteral {
+            value,
+            raw,
+        }))?;
+        state.serialize_field("start", &self.start)?;
+        state.serialize_field("end", &self.end)?;
+        state.end()
+    }
+}
+
+struct DirectiveLiteral<'ast> {
+    value: &'ast str,
+    raw: &'ast str,
+}
+
+impl<'ast> Serialize for Loc<DirectiveLiteral<'ast>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut state = serializer.serialize_struct("DirectiveLiteral", 5)?;
+        state.serialize_field("type", "DirectiveLiteral")?;
+        state.serialize_field("value", self.item.value)?;
+        state.serialize_field("extra", &value::Extra {
+            raw_value: self.item.value,
+            raw: self.item.raw,
+        })?;
+        state.serialize_field("start", &self.start)?;
+        state.serialize_field("end", &self.end)?;
+        state.end()
+    }
+}
+
+/// Body of a `BlockStatement`, with or without the directive prologue.
+struct BlockBody<'ast> {
+    body: StatementList<'ast>,
+    directives: bool,
+}
+
+impl<'ast> BlockBody<'ast> {
+    fn serialize_fields<S>(&self, state: &mut S) -> Result<(), S::Error>
+    where
+        S: SerializeStruct
+    {
+        if self.directives {
+            let (directives, body) = split_directives(self.body);
+
+            state.serialize_field("body", &Babel(&body))?;
+            state.serialize_field("directives", &directives)
+        } else {
+            // 0 byte array, will be optimized away
+            let directives: [(); 0] = [];
+
+            state.serialize_field("body", &Babel(&self.body))?;
+            state.serialize_field("directives", &directives)
+        }
+    }
+}
+
+struct Program<'ast> {
+    body: StatementList<'ast>,
+}
+
+impl<'ast> Serialize for Loc<Program<'ast>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let body = BlockBody {
+            body: self.item.body,
+            directives: true,
+        };
+
+        let mut state = serializer.serialize_struct("Program", 6)?;
+        state.serialize_field("type", "Program")?;
+        state.serialize_field("sourceType", "module")?;
+        body.serialize_fields(&mut state)?;
+        state.serialize_field("start", &self.start)?;
+        state.serialize_field("end", &self.end)?;
+        state.end()
+    }
+}
+
+impl<'a, 'ast> Serialize for Babel<'a, Module<'ast>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let body = self.0.body();
+
+        let mut start = 0;
+        let mut end = 0;
+        let mut iter = body.iter();
+
+        if let Some(node) = iter.next() {
+            start = node.start;
+            end = node.end;
+        }
+
+        if let Some(node) = iter.last() {
+            end = node.end;
+        }
+
+        let mut state = serializer.serialize_struct("File", 4)?;
+        state.serialize_field("type", "File")?;
+        state.serialize_field("program", &Loc::new(start, end, Program { body }))?;
+        state.serialize_field("start", &start)?;
+        state.serialize_field("end", &end)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_generate_ast_empty() {
+        expect_parse_babel!("", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [],
+                "directives": [],
+                "start": 0,
+                "end": 0,
+            },
+            "start": 0,
+            "end": 0,
+        });
+    }
+
+    #[test]
+    fn test_generate_ast_expression() {
+        expect_parse_babel!("this;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "ThisExpression",
+                            "start": 0,
+                            "end": 4,
+                        },
+                        "start": 0,
+                        "end": 4,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 4,
+            },
+            "start": 0,
+            "end": 4,
+        });
+    }
+
+    #[test]
+    fn test_generate_ast_directives() {
+        expect_parse_babel!("'use strict'; foo", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "Identifier",
+                            "name": "foo",
+                            "start": 14,
+                            "end": 17,
+                        },
+                        "start": 14,
+                        "end": 17,
+                    }
+                ],
+                "directives": [
+                    {
+                        "type": "Directive",
+                        "value": {
+                            "type": "DirectiveLiteral",
+                            "value": "use strict",
+                            "extra": {
+                                "rawValue": "use strict",
+                                "raw": "'use strict'",
+                            },
+                            "start": 0,
+                            "end": 12,
+                        },
+                        "start": 0,
+                        "end": 12,
+                    }
+                ],
+                "start": 0,
+                "end": 17,
+            },
+            "start": 0,
+            "end": 17,
+        });
+    }
+}
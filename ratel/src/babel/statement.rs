@@ -0,0 +1,423 @@
+use ast::statement::*;
+use ast::{Block, Declarator, Loc, Statement};
+use babel::{Babel, SerializeBabel, BlockBody, in_loc};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+impl<'a, 'ast> Serialize for Babel<'a, Loc<Block<'ast, SwitchCase<'ast>>>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Babel(&self.0.body).serialize(serializer)
+    }
+}
+
+impl<'ast> SerializeBabel for DeclarationStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "VariableDeclaration", 2, |state| {
+            state.serialize_field("kind", &self.kind)?;
+            state.serialize_field("declarations", &Babel(&self.declarators))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for Declarator<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "VariableDeclarator", 2, |state| {
+            state.serialize_field("id", &Babel(&self.id))?;
+            state.serialize_field("init", &Babel(&self.init))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ForInit<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            ForInit::Declaration(ref declaration) => declaration.serialize(serializer),
+            ForInit::Expression(ref expression) => expression.item.serialize(serializer),
+        }
+    }
+}
+
+impl<'ast> SerializeBabel for TryStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "TryStatement", 3, |state| {
+            state.serialize_field("block", &Babel(&self.block))?;
+            state.serialize_field("handler", &Babel(&self.handler))?;
+            state.serialize_field("finalizer", &Babel(&self.finalizer))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for CatchClause<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "CatchClause", 2, |state| {
+            state.serialize_field("param", &Babel(&self.param))?;
+            state.serialize_field("body", &Babel(&self.body))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for BlockStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "BlockStatement", 2, |state| {
+            BlockBody { body: self.body, directives: false }.serialize_fields(state)
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for SwitchStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "SwitchStatement", 2, |state| {
+            state.serialize_field("discriminant", &Babel(&self.discriminant))?;
+            state.serialize_field("cases", &Babel(&*self.cases))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for SwitchCase<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "SwitchCase", 2, |state| {
+            state.serialize_field("test", &Babel(&self.test))?;
+            state.serialize_field("consequent", &Babel(&self.consequent))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ForStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ForStatement", 4, |state| {
+            state.serialize_field("init", &Babel(&self.init))?;
+            state.serialize_field("test", &Babel(&self.test))?;
+            state.serialize_field("update", &Babel(&self.update))?;
+            state.serialize_field("body", &Babel(&self.body))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ForInStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ForInStatement", 3, |state| {
+            state.serialize_field("left", &Babel(&self.left))?;
+            state.serialize_field("right", &Babel(&self.right))?;
+            state.serialize_field("body", &Babel(&self.body))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ForOfStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ForOfStatement", 4, |state| {
+            state.serialize_field("await", &false)?;
+            state.serialize_field("left", &Babel(&self.left))?;
+            state.serialize_field("right", &Babel(&self.right))?;
+            state.serialize_field("body", &Babel(&self.body))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for IfStatement<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "IfStatement", 3, |state| {
+            state.serialize_field("test", &Babel(&self.test))?;
+            state.serialize_field("consequent", &Babel(&self.consequent))?;
+            state.serialize_field("alternate", &Babel(&self.alternate))
+        })
+    }
+}
+
+/// Import source, the AST only keeps the unquoted value.
+struct ImportSource<'ast>(&'ast str);
+
+impl<'ast> Serialize for ImportSource<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("StringLiteral", 2)?;
+        state.serialize_field("type", "StringLiteral")?;
+        state.serialize_field("value", self.0)?;
+        state.end()
+    }
+}
+
+impl<'ast> SerializeBabel for ImportDeclaration<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "ImportDeclaration", 2, |state| {
+            state.serialize_field("specifiers", &Babel(&self.specifiers))?;
+            state.serialize_field("source", &ImportSource(self.source))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for ForImportSpecifier<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            ForImportSpecifier::ImportNamespaceSpecifier(ref value) => {
+                self.in_loc(serializer, "ImportNamespaceSpecifier", 1, |state| {
+                    state.serialize_field("local", &Babel(&value.local))
+                })
+            }
+            ForImportSpecifier::ImportDefaultSpecifier(ref value) => {
+                self.in_loc(serializer, "ImportDefaultSpecifier", 1, |state| {
+                    state.serialize_field("local", &Babel(&value.local))
+                })
+            }
+            ForImportSpecifier::ImportSpecifier(ref value) => {
+                self.in_loc(serializer, "ImportSpecifier", 2, |state| {
+                    state.serialize_field("imported", &Babel(&value.imported))?;
+                    state.serialize_field("local", &Babel(&value.local))
+                })
+            }
+        }
+    }
+}
+
+/// Serializes a statement without its location, labeled statements
+/// need the location to place the label identifier.
+fn serialize_statement<'ast, S>(loc: &Loc<Statement<'ast>>, serializer: S) -> Result<S::SerializeStruct, S::Error>
+where
+    S: Serializer,
+{
+    use self::Statement::*;
+
+    match loc.item {
+        Empty => in_loc(serializer, "EmptyStatement", 0, |_| Ok(())),
+        Debugger => in_loc(serializer, "DebuggerStatement", 0, |_| Ok(())),
+        Expression(ref expression) => {
+            in_loc(serializer, "ExpressionStatement", 1, |state| {
+                state.serialize_field("expression", &Babel(expression))
+            })
+        }
+        Declaration(declaration) => declaration.serialize(serializer),
+        Return(ReturnStatement { ref value }) => {
+            in_loc(serializer, "ReturnStatement", 1, |state| {
+                state.serialize_field("argument", &Babel(value))
+            })
+        }
+        Break(BreakStatement { ref label }) => {
+            in_loc(serializer, "BreakStatement", 1, |state| {
+                state.serialize_field("label", &Babel(label))
+            })
+        }
+        Continue(ContinueStatement { ref label }) => {
+            in_loc(serializer, "ContinueStatement", 1, |state| {
+                state.serialize_field("label", &Babel(label))
+            })
+        }
+        Throw(ThrowStatement { ref value }) => {
+            in_loc(serializer, "ThrowStatement", 1, |state| {
+                state.serialize_field("argument", &Babel(value))
+            })
+        }
+        If(statement) => statement.serialize(serializer),
+        While(WhileStatement { ref test, ref body }) => {
+            in_loc(serializer, "WhileStatement", 2, |state| {
+                state.serialize_field("test", &Babel(test))?;
+                state.serialize_field("body", &Babel(body))
+            })
+        }
+        Do(DoStatement { ref body, ref test }) => {
+            in_loc(serializer, "DoWhileStatement", 2, |state| {
+                state.serialize_field("body", &Babel(body))?;
+                state.serialize_field("test", &Babel(test))
+            })
+        }
+        For(statement) => statement.serialize(serializer),
+        ForIn(statement) => statement.serialize(serializer),
+        ForOf(statement) => statement.serialize(serializer),
+        Try(statement) => statement.serialize(serializer),
+        Block(statement) => statement.serialize(serializer),
+        Labeled(LabeledStatement { label, ref body }) => {
+            let label = Loc::new(loc.start, loc.start + label.len() as u32, label);
+
+            in_loc(serializer, "LabeledStatement", 2, |state| {
+                state.serialize_field("label", &Babel(&label))?;
+                state.serialize_field("body", &Babel(body))
+            })
+        }
+        Function(statement) => statement.serialize(serializer),
+        Class(statement) => statement.serialize(serializer),
+        Switch(statement) => statement.serialize(serializer),
+        Import(statement) => statement.serialize(serializer),
+    }
+}
+
+impl<'a, 'ast> Serialize for Babel<'a, Loc<Statement<'ast>>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serialize_statement(self.0, serializer)?;
+        state.serialize_field("start", &self.0.start)?;
+        state.serialize_field("end", &self.0.end)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_statement_block() {
+        expect_parse_babel!("{ 'foo'; }", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "BlockStatement",
+                        "body": [
+                            {
+                                "type": "ExpressionStatement",
+                                "expression": {
+                                    "type": "StringLiteral",
+                                    "value": "foo",
+                                    "extra": {
+                                        "rawValue": "foo",
+                                        "raw": "'foo'",
+                                    },
+                                    "start": 2,
+                                    "end": 7,
+                                },
+                                "start": 2,
+                                "end": 7,
+                            }
+                        ],
+                        "directives": [],
+                        "start": 0,
+                        "end": 10,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 10,
+            },
+            "start": 0,
+            "end": 10,
+        });
+    }
+
+    #[test]
+    fn test_statement_labeled() {
+        expect_parse_babel!("foo: bar;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "LabeledStatement",
+                        "label": {
+                            "type": "Identifier",
+                            "name": "foo",
+                            "start": 0,
+                            "end": 3,
+                        },
+                        "body": {
+                            "type": "ExpressionStatement",
+                            "expression": {
+                                "type": "Identifier",
+                                "name": "bar",
+                                "start": 5,
+                                "end": 8,
+                            },
+                            "start": 5,
+                            "end": 8,
+                        },
+                        "start": 0,
+                        "end": 8,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 8,
+            },
+            "start": 0,
+            "end": 8,
+        });
+    }
+
+    #[test]
+    fn test_statement_for_of() {
+        expect_parse_babel!("for (x of y);", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ForOfStatement",
+                        "await": false,
+                        "left": {
+                            "type": "Identifier",
+                            "name": "x",
+                            "start": 5,
+                            "end": 6,
+                        },
+                        "right": {
+                            "type": "Identifier",
+                            "name": "y",
+                            "start": 10,
+                            "end": 11,
+                        },
+                        "body": {
+                            "type": "EmptyStatement",
+                            "start": 12,
+                            "end": 13,
+                        },
+                        "start": 0,
+                        "end": 13,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 13,
+            },
+            "start": 0,
+            "end": 13,
+        });
+    }
+}
@@ -0,0 +1,716 @@
+use ast::expression::{PropertyKey, TaggedTemplateExpression, TemplateLiteral};
+use ast::{Expression, Literal, Loc, NodeList, Pattern, Property};
+use astgen::{NumericValue, TemplateQuasis, parse_regex};
+use babel::{Babel, SerializeBabel, in_loc};
+use babel::function::serialize_function_fields;
+use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+/// The `extra` object Babel attaches to literals.
+#[derive(Debug, Serialize)]
+pub struct Extra<'ast, T> {
+    #[serde(rename = "rawValue")]
+    pub raw_value: T,
+    pub raw: &'ast str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegExExtra<'ast> {
+    pub raw: &'ast str,
+}
+
+#[inline]
+fn string_value(raw: &str) -> &str {
+    &raw[1..raw.len() - 1]
+}
+
+/// Babel uses a different node type for every kind of literal.
+#[inline]
+fn serialize_literal<S>(literal: &Literal, serializer: S) -> Result<S::SerializeStruct, S::Error>
+where
+    S: Serializer,
+{
+    use self::Literal::*;
+
+    match *literal {
+        Undefined => {
+            let mut state = serializer.serialize_struct("Identifier", 4)?;
+            state.serialize_field("type", "Identifier")?;
+            state.serialize_field("name", "undefined")?;
+            Ok(state)
+        },
+        Null => {
+            let mut state = serializer.serialize_struct("NullLiteral", 3)?;
+            state.serialize_field("type", "NullLiteral")?;
+            Ok(state)
+        },
+        True | False => {
+            let mut state = serializer.serialize_struct("BooleanLiteral", 4)?;
+            state.serialize_field("type", "BooleanLiteral")?;
+            state.serialize_field("value", &(*literal == True))?;
+            Ok(state)
+        },
+        Number(raw) | Binary(raw) => {
+            let value = NumericValue::parse(raw);
+
+            let mut state = serializer.serialize_struct("NumericLiteral", 5)?;
+            state.serialize_field("type", "NumericLiteral")?;
+            state.serialize_field("value", &value)?;
+            state.serialize_field("extra", &Extra { raw_value: value, raw })?;
+            Ok(state)
+        },
+        String(raw) => {
+            let value = string_value(raw);
+
+            let mut state = serializer.serialize_struct("StringLiteral", 5)?;
+            state.serialize_field("type", "StringLiteral")?;
+            state.serialize_field("value", value)?;
+            state.serialize_field("extra", &Extra { raw_value: value, raw })?;
+            Ok(state)
+        },
+        RegEx(raw) => {
+            let regex = parse_regex(raw);
+
+            let mut state = serializer.serialize_struct("RegExpLiteral", 6)?;
+            state.serialize_field("type", "RegExpLiteral")?;
+            state.serialize_field("pattern", regex.pattern)?;
+            state.serialize_field("flags", regex.flags)?;
+            state.serialize_field("extra", &RegExExtra { raw })?;
+            Ok(state)
+        },
+    }
+}
+
+impl<'ast> SerializeBabel for Literal<'ast> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_literal(self, serializer)
+    }
+}
+
+impl SerializeBabel for &str {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "Identifier", 1, |state| {
+            state.serialize_field("name", *self)
+        })
+    }
+}
+
+impl<'a, 'ast> Serialize for Babel<'a, Loc<PropertyKey<'ast>>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::PropertyKey::*;
+
+        let loc = self.0;
+
+        let mut state = match loc.item {
+            Computed(expression) => return Babel(&*expression).serialize(serializer),
+            Literal(raw) => match raw.as_bytes()[0] {
+                b'"' | b'\'' => serialize_literal(&::ast::Literal::String(raw), serializer)?,
+                b'0'..=b'9' | b'.' => serialize_literal(&::ast::Literal::Number(raw), serializer)?,
+                _ => SerializeBabel::serialize(&raw, serializer)?,
+            },
+            Binary(raw) => serialize_literal(&::ast::Literal::Binary(raw), serializer)?,
+        };
+
+        state.serialize_field("start", &loc.start)?;
+        state.serialize_field("end", &loc.end)?;
+        state.end()
+    }
+}
+
+#[inline]
+pub fn is_computed(key: &Loc<PropertyKey>) -> bool {
+    matches!(key.item, PropertyKey::Computed(_))
+}
+
+impl<'a, 'ast> Serialize for Babel<'a, Loc<Property<'ast>>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::Property::*;
+
+        let loc = self.0;
+
+        let mut state = match loc.item {
+            Shorthand(name) => {
+                let ident = Loc::new(loc.start, loc.end, name);
+
+                in_loc(serializer, "ObjectProperty", 5, |state| {
+                    state.serialize_field("method", &false)?;
+                    state.serialize_field("key", &Babel(&ident))?;
+                    state.serialize_field("computed", &false)?;
+                    state.serialize_field("shorthand", &true)?;
+                    state.serialize_field("value", &Babel(&ident))
+                })?
+            },
+            Literal { key, value } => {
+                in_loc(serializer, "ObjectProperty", 5, |state| {
+                    state.serialize_field("method", &false)?;
+                    state.serialize_field("key", &Babel(&*key))?;
+                    state.serialize_field("computed", &is_computed(&key))?;
+                    state.serialize_field("shorthand", &false)?;
+                    state.serialize_field("value", &Babel(&value))
+                })?
+            },
+            Method { key, value } => {
+                in_loc(serializer, "ObjectMethod", 9, |state| {
+                    state.serialize_field("method", &true)?;
+                    state.serialize_field("key", &Babel(&*key))?;
+                    state.serialize_field("computed", &is_computed(&key))?;
+                    state.serialize_field("kind", "method")?;
+                    serialize_function_fields(&value.item, state)
+                })?
+            },
+            Spread { argument } => {
+                in_loc(serializer, "SpreadElement", 1, |state| {
+                    state.serialize_field("argument", &Babel(&argument))
+                })?
+            },
+        };
+
+        state.serialize_field("start", &loc.start)?;
+        state.serialize_field("end", &loc.end)?;
+        state.end()
+    }
+}
+
+/// Properties of an `ObjectPattern`, spread properties are
+/// turned into `RestElement`s in pattern position.
+struct PatternProperties<'ast>(NodeList<'ast, Property<'ast>>);
+
+impl<'ast> Serialize for PatternProperties<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(None)?;
+
+        for property in self.0.iter() {
+            match property.item {
+                Property::Spread { argument } => {
+                    seq.serialize_element(&Babel(&Loc::new(property.start, property.end, RestProperty {
+                        argument: &argument,
+                    })))?;
+                },
+                _ => seq.serialize_element(&Babel(&**property))?,
+            }
+        }
+
+        seq.end()
+    }
+}
+
+struct RestProperty<'a, 'ast: 'a> {
+    argument: &'a Loc<Expression<'ast>>,
+}
+
+impl<'a, 'ast> SerializeBabel for RestProperty<'a, 'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "RestElement", 1, |state| {
+            state.serialize_field("argument", &Babel(self.argument))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for Pattern<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        use self::Pattern::*;
+
+        match *self {
+            Void => unreachable!(),
+            Identifier(name) => SerializeBabel::serialize(&name, serializer),
+            ObjectPattern { properties } => self.in_loc(serializer, "ObjectPattern", 1, |state| {
+                state.serialize_field("properties", &PatternProperties(properties))
+            }),
+            ArrayPattern { elements } => self.in_loc(serializer, "ArrayPattern", 1, |state| {
+                state.serialize_field("elements", &PatternElements(elements))
+            }),
+            AssignmentPattern { left, right } => {
+                self.in_loc(serializer, "AssignmentPattern", 2, |state| {
+                    state.serialize_field("left", &Babel(&left))?;
+                    state.serialize_field("right", &Babel(&right))
+                })
+            }
+            RestElement { argument } => self.in_loc(serializer, "RestElement", 1, |state| {
+                state.serialize_field("argument", &Babel(&argument))
+            }),
+        }
+    }
+}
+
+/// Elements of an `ArrayPattern`, holes are serialized as `null`.
+struct PatternElements<'ast>(NodeList<'ast, Pattern<'ast>>);
+
+impl<'ast> Serialize for PatternElements<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(None)?;
+
+        for element in self.0.iter() {
+            match element.item {
+                Pattern::Void => seq.serialize_element(&())?,
+                _             => seq.serialize_element(&Babel(&**element))?,
+            }
+        }
+
+        seq.end()
+    }
+}
+
+impl<'ast> SerializeBabel for TemplateLiteral<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "TemplateLiteral", 2, |state| {
+            state.serialize_field("expressions", &Babel(&self.expressions))?;
+            state.serialize_field("quasis", &TemplateQuasis(self.quasis))
+        })
+    }
+}
+
+impl<'ast> SerializeBabel for TaggedTemplateExpression<'ast> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
+    where
+        S: Serializer,
+    {
+        self.in_loc(serializer, "TaggedTemplateExpression", 2, |state| {
+            state.serialize_field("tag", &Babel(&self.tag))?;
+            state.serialize_field("quasi", &Babel(&self.quasi))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_value_undefined() {
+        expect_parse_babel!("undefined", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "Identifier",
+                            "name": "undefined",
+                            "start": 0,
+                            "end": 9,
+                        },
+                        "start": 0,
+                        "end": 9,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 9,
+            },
+            "start": 0,
+            "end": 9,
+        });
+    }
+
+    #[test]
+    fn test_value_null() {
+        expect_parse_babel!("null;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "NullLiteral",
+                            "start": 0,
+                            "end": 4,
+                        },
+                        "start": 0,
+                        "end": 4,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 4,
+            },
+            "start": 0,
+            "end": 4,
+        });
+    }
+
+    #[test]
+    fn test_value_boolean() {
+        expect_parse_babel!("false;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "BooleanLiteral",
+                            "value": false,
+                            "start": 0,
+                            "end": 5,
+                        },
+                        "start": 0,
+                        "end": 5,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 5,
+            },
+            "start": 0,
+            "end": 5,
+        });
+    }
+
+    #[test]
+    fn test_value_number() {
+        expect_parse_babel!("0xff;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "NumericLiteral",
+                            "value": 255,
+                            "extra": {
+                                "rawValue": 255,
+                                "raw": "0xff",
+                            },
+                            "start": 0,
+                            "end": 4,
+                        },
+                        "start": 0,
+                        "end": 4,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 4,
+            },
+            "start": 0,
+            "end": 4,
+        });
+
+        expect_parse_babel!("0.5;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "NumericLiteral",
+                            "value": 0.5,
+                            "extra": {
+                                "rawValue": 0.5,
+                                "raw": "0.5",
+                            },
+                            "start": 0,
+                            "end": 3,
+                        },
+                        "start": 0,
+                        "end": 3,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 3,
+            },
+            "start": 0,
+            "end": 3,
+        });
+    }
+
+    #[test]
+    fn test_value_number_beyond_i64() {
+        expect_parse_babel!("x = 0xFFFFFFFFFFFFFFFFFF;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "AssignmentExpression",
+                            "operator": "=",
+                            "left": {
+                                "type": "Identifier",
+                                "name": "x",
+                                "start": 0,
+                                "end": 1,
+                            },
+                            "right": {
+                                "type": "NumericLiteral",
+                                "value": 4722366482869645213696.0,
+                                "extra": {
+                                    "rawValue": 4722366482869645213696.0,
+                                    "raw": "0xFFFFFFFFFFFFFFFFFF",
+                                },
+                                "start": 4,
+                                "end": 24,
+                            },
+                            "start": 0,
+                            "end": 24,
+                        },
+                        "start": 0,
+                        "end": 24,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 24,
+            },
+            "start": 0,
+            "end": 24,
+        });
+    }
+
+    #[test]
+    fn test_value_string() {
+        // Not the first statement, otherwise it would be a directive
+        expect_parse_babel!("foo; 'bar';", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "Identifier",
+                            "name": "foo",
+                            "start": 0,
+                            "end": 3,
+                        },
+                        "start": 0,
+                        "end": 3,
+                    },
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "StringLiteral",
+                            "value": "bar",
+                            "extra": {
+                                "rawValue": "bar",
+                                "raw": "'bar'",
+                            },
+                            "start": 5,
+                            "end": 10,
+                        },
+                        "start": 5,
+                        "end": 10,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 10,
+            },
+            "start": 0,
+            "end": 10,
+        });
+    }
+
+    #[test]
+    fn test_value_regex() {
+        expect_parse_babel!("/foo/gi;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "RegExpLiteral",
+                            "pattern": "foo",
+                            "flags": "gi",
+                            "extra": {
+                                "raw": "/foo/gi",
+                            },
+                            "start": 0,
+                            "end": 7,
+                        },
+                        "start": 0,
+                        "end": 7,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 7,
+            },
+            "start": 0,
+            "end": 7,
+        });
+    }
+
+    #[test]
+    fn test_template() {
+        expect_parse_babel!("`foo${bar}`;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "ExpressionStatement",
+                        "expression": {
+                            "type": "TemplateLiteral",
+                            "expressions": [
+                                {
+                                    "type": "Identifier",
+                                    "name": "bar",
+                                    "start": 6,
+                                    "end": 9,
+                                }
+                            ],
+                            "quasis": [
+                                {
+                                    "type": "TemplateElement",
+                                    "value": {
+                                        "raw": "foo",
+                                        "cooked": "foo",
+                                    },
+                                    "tail": false,
+                                    "start": 1,
+                                    "end": 4,
+                                },
+                                {
+                                    "type": "TemplateElement",
+                                    "value": {
+                                        "raw": "",
+                                        "cooked": "",
+                                    },
+                                    "tail": true,
+                                    "start": 10,
+                                    "end": 10,
+                                }
+                            ],
+                            "start": 0,
+                            "end": 11,
+                        },
+                        "start": 0,
+                        "end": 11,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 11,
+            },
+            "start": 0,
+            "end": 11,
+        });
+    }
+
+    #[test]
+    fn test_object_pattern() {
+        expect_parse_babel!("const {a, b: c} = d;", {
+            "type": "File",
+            "program": {
+                "type": "Program",
+                "sourceType": "module",
+                "body": [
+                    {
+                        "type": "VariableDeclaration",
+                        "kind": "const",
+                        "declarations": [
+                            {
+                                "type": "VariableDeclarator",
+                                "id": {
+                                    "type": "ObjectPattern",
+                                    "properties": [
+                                        {
+                                            "type": "ObjectProperty",
+                                            "method": false,
+                                            "key": {
+                                                "type": "Identifier",
+                                                "name": "a",
+                                                "start": 7,
+                                                "end": 8,
+                                            },
+                                            "computed": false,
+                                            "shorthand": true,
+                                            "value": {
+                                                "type": "Identifier",
+                                                "name": "a",
+                                                "start": 7,
+                                                "end": 8,
+                                            },
+                                            "start": 7,
+                                            "end": 8,
+                                        },
+                                        {
+                                            "type": "ObjectProperty",
+                                            "method": false,
+                                            "key": {
+                                                "type": "Identifier",
+                                                "name": "b",
+                                                "start": 10,
+                                                "end": 11,
+                                            },
+                                            "computed": false,
+                                            "shorthand": false,
+                                            "value": {
+                                                "type": "Identifier",
+                                                "name": "c",
+                                                "start": 13,
+                                                "end": 14,
+                                            },
+                                            "start": 10,
+                                            "end": 14,
+                                        }
+                                    ],
+                                    "start": 6,
+                                    "end": 15,
+                                },
+                                "init": {
+                                    "type": "Identifier",
+                                    "name": "d",
+                                    "start": 18,
+                                    "end": 19,
+                                },
+                                "start": 6,
+                                "end": 19,
+                            }
+                        ],
+                        "start": 0,
+                        "end": 20,
+                    }
+                ],
+                "directives": [],
+                "start": 0,
+                "end": 20,
+            },
+            "start": 0,
+            "end": 20,
+        });
+    }
+}
@@ -0,0 +1,749 @@
+//! Typed constructors for AST nodes.
+//!
+//! All nodes built here are allocated on the `Arena` the `Builder` wraps
+//! and have an empty (`0..0`) span, unless created with `Builder::node_at`.
+
+use toolshed::Arena;
+use toolshed::list::ListBuilder;
+
+use ast::{Node, Loc, NodeList, Block, BlockNode, Literal, OperatorKind, Pattern};
+use ast::{Expression, ExpressionNode, ExpressionList, Statement, StatementNode, StatementList};
+use ast::{IdentifierNode, Property, PropertyKey, DeclarationKind, Declarator};
+use ast::{Function, Class, ClassMember, MethodKind, Name, EmptyName, OptionalName, MandatoryName};
+use ast::expression::*;
+use ast::statement::*;
+
+/// Allocates AST nodes on an `Arena`.
+#[derive(Clone, Copy)]
+pub struct Builder<'ast> {
+    arena: &'ast Arena,
+}
+
+impl<'ast> Builder<'ast> {
+    /// Create a new `Builder` allocating on the given `Arena`.
+    #[inline]
+    pub fn new(arena: &'ast Arena) -> Self {
+        Builder { arena }
+    }
+
+    /// Get the `Arena` this builder allocates on.
+    #[inline]
+    pub fn arena(&self) -> &'ast Arena {
+        self.arena
+    }
+
+    /// Allocate any node with an empty span.
+    #[inline]
+    pub fn node<T, I>(&self, item: I) -> Node<'ast, T> where
+        T: 'ast + Copy,
+        I: Into<T>,
+    {
+        self.node_at(0, 0, item)
+    }
+
+    /// Allocate any node at the given span.
+    #[inline]
+    pub fn node_at<T, I>(&self, start: u32, end: u32, item: I) -> Node<'ast, T> where
+        T: 'ast + Copy,
+        I: Into<T>,
+    {
+        Node::new(self.arena.alloc(Loc::new(start, end, item.into())))
+    }
+
+    /// Copy a string onto the arena.
+    #[inline]
+    pub fn str(&self, value: &str) -> &'ast str {
+        self.arena.alloc_str(value)
+    }
+
+    // Lists
+
+    /// Build a list of nodes from an iterator.
+    #[inline]
+    pub fn list<T, I>(&self, nodes: I) -> NodeList<'ast, T> where
+        T: 'ast + Copy,
+        I: IntoIterator<Item = Node<'ast, T>>,
+    {
+        NodeList::from_iter(self.arena, nodes)
+    }
+
+    /// Build a list containing a single node.
+    #[inline]
+    pub fn list_from<T>(&self, node: Node<'ast, T>) -> NodeList<'ast, T> where
+        T: 'ast + Copy,
+    {
+        NodeList::from(self.arena, node)
+    }
+
+    /// Start a list that can be pushed to, starting with the given node.
+    #[inline]
+    pub fn list_builder<T>(&self, first: Node<'ast, T>) -> ListBuilder<'ast, Node<'ast, T>> where
+        T: 'ast + Copy,
+    {
+        ListBuilder::new(self.arena, first)
+    }
+
+    /// Build a new list with `node` prepended to `list`. The original
+    /// list is left intact.
+    pub fn prepend<T>(&self, node: Node<'ast, T>, list: NodeList<'ast, T>) -> NodeList<'ast, T> where
+        T: 'ast + Copy,
+    {
+        let builder = ListBuilder::new(self.arena, node);
+
+        for node in list.iter() {
+            builder.push(self.arena, *node);
+        }
+
+        builder.as_list()
+    }
+
+    /// Build a new list with `node` appended to `list`. The original
+    /// list is left intact.
+    pub fn append<T>(&self, list: NodeList<'ast, T>, node: Node<'ast, T>) -> NodeList<'ast, T> where
+        T: 'ast + Copy,
+    {
+        let mut iter = list.iter();
+
+        let builder = match iter.next() {
+            Some(first) => ListBuilder::new(self.arena, *first),
+            None        => return NodeList::from(self.arena, node),
+        };
+
+        for node in iter {
+            builder.push(self.arena, *node);
+        }
+
+        builder.push(self.arena, node);
+        builder.as_list()
+    }
+
+    /// Build a new list containing all nodes of `first` followed
+    /// by all nodes of `second`.
+    pub fn concat<T>(&self, first: NodeList<'ast, T>, second: NodeList<'ast, T>) -> NodeList<'ast, T> where
+        T: 'ast + Copy,
+    {
+        self.list(first.iter().chain(second.iter()).cloned())
+    }
+
+    /// Wrap a list in a `Block` node.
+    #[inline]
+    pub fn block<T>(&self, body: NodeList<'ast, T>) -> BlockNode<'ast, T> where
+        T: 'ast + Copy,
+    {
+        self.node(Block { body })
+    }
+
+    // Names
+
+    #[inline]
+    pub fn identifier(&self, name: &'ast str) -> IdentifierNode<'ast> {
+        self.node(name)
+    }
+
+    #[inline]
+    pub fn mandatory_name(&self, name: &'ast str) -> MandatoryName<'ast> {
+        MandatoryName(self.identifier(name))
+    }
+
+    #[inline]
+    pub fn optional_name(&self, name: Option<&'ast str>) -> OptionalName<'ast> {
+        OptionalName(name.map(|name| self.identifier(name)))
+    }
+
+    // Expressions
+
+    #[inline]
+    pub fn this(&self) -> ExpressionNode<'ast> {
+        self.node(ThisExpression)
+    }
+
+    #[inline]
+    pub fn ident(&self, name: &'ast str) -> ExpressionNode<'ast> {
+        self.node(Expression::Identifier(name))
+    }
+
+    #[inline]
+    pub fn literal(&self, literal: Literal<'ast>) -> ExpressionNode<'ast> {
+        self.node(literal)
+    }
+
+    #[inline]
+    pub fn undefined(&self) -> ExpressionNode<'ast> {
+        self.literal(Literal::Undefined)
+    }
+
+    #[inline]
+    pub fn null(&self) -> ExpressionNode<'ast> {
+        self.literal(Literal::Null)
+    }
+
+    #[inline]
+    pub fn boolean(&self, value: bool) -> ExpressionNode<'ast> {
+        self.literal(if value { Literal::True } else { Literal::False })
+    }
+
+    /// Numeric literal from its source representation, such as `"42"` or `"0xFF"`.
+    #[inline]
+    pub fn number(&self, raw: &'ast str) -> ExpressionNode<'ast> {
+        self.literal(Literal::Number(raw))
+    }
+
+    /// Numeric literal from an integer.
+    #[inline]
+    pub fn integer(&self, value: u64) -> ExpressionNode<'ast> {
+        self.number(self.arena.alloc_string(value.to_string()))
+    }
+
+    /// String literal from its unquoted, unescaped value. The value will be
+    /// wrapped in double quotes and escaped as needed.
+    pub fn string(&self, value: &str) -> ExpressionNode<'ast> {
+        self.literal(Literal::String(self.quote(value)))
+    }
+
+    /// Regular expression literal from its source representation, such as `"/foo/g"`.
+    #[inline]
+    pub fn regex(&self, raw: &'ast str) -> ExpressionNode<'ast> {
+        self.literal(Literal::RegEx(raw))
+    }
+
+    fn quote(&self, value: &str) -> &'ast str {
+        let mut raw = String::with_capacity(value.len() + 2);
+
+        raw.push('"');
+
+        for ch in value.chars() {
+            match ch {
+                '"'  => raw.push_str("\\\""),
+                '\\' => raw.push_str("\\\\"),
+                '\n' => raw.push_str("\\n"),
+                '\r' => raw.push_str("\\r"),
+                '\t' => raw.push_str("\\t"),
+                '\u{2028}' => raw.push_str("\\u2028"),
+                '\u{2029}' => raw.push_str("\\u2029"),
+                _    => raw.push(ch),
+            }
+        }
+
+        raw.push('"');
+
+        self.arena.alloc_string(raw)
+    }
+
+    #[inline]
+    pub fn sequence(&self, body: ExpressionList<'ast>) -> ExpressionNode<'ast> {
+        self.node(SequenceExpression { body })
+    }
+
+    /// Array expression, use `Builder::hole` for elisions.
+    #[inline]
+    pub fn array(&self, body: ExpressionList<'ast>) -> ExpressionNode<'ast> {
+        self.node(ArrayExpression { body })
+    }
+
+    /// Elided element of an array expression.
+    #[inline]
+    pub fn hole(&self) -> ExpressionNode<'ast> {
+        self.node(Expression::Void)
+    }
+
+    #[inline]
+    pub fn member(&self, object: ExpressionNode<'ast>, property: &'ast str) -> ExpressionNode<'ast> {
+        self.node(MemberExpression {
+            object,
+            property: self.identifier(property),
+        })
+    }
+
+    #[inline]
+    pub fn computed_member(&self, object: ExpressionNode<'ast>, property: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.node(ComputedMemberExpression { object, property })
+    }
+
+    /// Meta property, such as `new.target`.
+    #[inline]
+    pub fn meta_property(&self, meta: &'ast str, property: &'ast str) -> ExpressionNode<'ast> {
+        self.node(MetaPropertyExpression {
+            meta: self.identifier(meta),
+            property: self.identifier(property),
+        })
+    }
+
+    #[inline]
+    pub fn call(&self, callee: ExpressionNode<'ast>, arguments: ExpressionList<'ast>) -> ExpressionNode<'ast> {
+        self.node(CallExpression { callee, arguments })
+    }
+
+    /// `new callee(arguments)`
+    #[inline]
+    pub fn new_expression(&self, callee: ExpressionNode<'ast>, arguments: ExpressionList<'ast>) -> ExpressionNode<'ast> {
+        self.prefix(OperatorKind::New, self.call(callee, arguments))
+    }
+
+    #[inline]
+    pub fn binary(&self, operator: OperatorKind, left: ExpressionNode<'ast>, right: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.node(BinaryExpression { operator, left, right })
+    }
+
+    /// `left = right`
+    #[inline]
+    pub fn assign(&self, left: ExpressionNode<'ast>, right: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.binary(OperatorKind::Assign, left, right)
+    }
+
+    #[inline]
+    pub fn prefix(&self, operator: OperatorKind, operand: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.node(PrefixExpression { operator, operand })
+    }
+
+    #[inline]
+    pub fn postfix(&self, operator: OperatorKind, operand: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.node(PostfixExpression { operator, operand })
+    }
+
+    #[inline]
+    pub fn conditional(&self, test: ExpressionNode<'ast>, consequent: ExpressionNode<'ast>, alternate: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.node(ConditionalExpression { test, consequent, alternate })
+    }
+
+    /// Template literal, `quasis` must have exactly one more element than `expressions`.
+    #[inline]
+    pub fn template(&self, expressions: ExpressionList<'ast>, quasis: NodeList<'ast, &'ast str>) -> Node<'ast, TemplateLiteral<'ast>> {
+        self.node(TemplateLiteral { expressions, quasis })
+    }
+
+    #[inline]
+    pub fn template_expression(&self, expressions: ExpressionList<'ast>, quasis: NodeList<'ast, &'ast str>) -> ExpressionNode<'ast> {
+        self.node(TemplateLiteral { expressions, quasis })
+    }
+
+    #[inline]
+    pub fn tagged_template(&self, tag: ExpressionNode<'ast>, quasi: Node<'ast, TemplateLiteral<'ast>>) -> ExpressionNode<'ast> {
+        self.node(TaggedTemplateExpression { tag, quasi })
+    }
+
+    #[inline]
+    pub fn spread(&self, argument: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.node(SpreadExpression { argument })
+    }
+
+    /// Arrow function with an expression body, `(params) => body`.
+    #[inline]
+    pub fn arrow(&self, params: NodeList<'ast, Pattern<'ast>>, body: ExpressionNode<'ast>) -> ExpressionNode<'ast> {
+        self.node(ArrowExpression {
+            params,
+            body: ArrowBody::Expression(body),
+        })
+    }
+
+    /// Arrow function with a block body, `(params) => { body }`.
+    #[inline]
+    pub fn arrow_block(&self, params: NodeList<'ast, Pattern<'ast>>, body: StatementList<'ast>) -> ExpressionNode<'ast> {
+        self.node(ArrowExpression {
+            params,
+            body: ArrowBody::Block(self.block(body)),
+        })
+    }
+
+    #[inline]
+    pub fn object(&self, body: NodeList<'ast, Property<'ast>>) -> ExpressionNode<'ast> {
+        self.node(ObjectExpression { body })
+    }
+
+    #[inline]
+    pub fn function_expression(&self, name: Option<&'ast str>, params: NodeList<'ast, Pattern<'ast>>, body: StatementList<'ast>) -> ExpressionNode<'ast> {
+        self.node(self.function(self.optional_name(name), params, body))
+    }
+
+    #[inline]
+    pub fn class_expression(&self, name: Option<&'ast str>, extends: Option<ExpressionNode<'ast>>, body: NodeList<'ast, ClassMember<'ast>>) -> ExpressionNode<'ast> {
+        self.node(self.class(self.optional_name(name), extends, body))
+    }
+
+    // Functions and classes
+
+    /// Function with any kind of name, not allocated.
+    #[inline]
+    pub fn function<N>(&self, name: N, params: NodeList<'ast, Pattern<'ast>>, body: StatementList<'ast>) -> Function<'ast, N> where
+        N: Name<'ast>,
+    {
+        Function {
+            name,
+            generator: false,
+            params,
+            body: self.block(body),
+        }
+    }
+
+    /// Function used as a value of methods.
+    #[inline]
+    pub fn method(&self, params: NodeList<'ast, Pattern<'ast>>, body: StatementList<'ast>) -> Node<'ast, Function<'ast, EmptyName>> {
+        self.node(self.function(EmptyName, params, body))
+    }
+
+    /// Class with any kind of name, not allocated.
+    #[inline]
+    pub fn class<N>(&self, name: N, extends: Option<ExpressionNode<'ast>>, body: NodeList<'ast, ClassMember<'ast>>) -> Class<'ast, N> where
+        N: Name<'ast>,
+    {
+        Class {
+            name,
+            extends,
+            body: self.block(body),
+        }
+    }
+
+    // Properties
+
+    /// Property key for a plain identifier, `foo` in `{ foo: bar }`.
+    #[inline]
+    pub fn key(&self, name: &'ast str) -> Node<'ast, PropertyKey<'ast>> {
+        self.node(PropertyKey::Literal(name))
+    }
+
+    /// Property key for a string, `"foo"` in `{ "foo": bar }`.
+    #[inline]
+    pub fn string_key(&self, value: &str) -> Node<'ast, PropertyKey<'ast>> {
+        self.node(PropertyKey::Literal(self.quote(value)))
+    }
+
+    /// Computed property key, `[foo]` in `{ [foo]: bar }`.
+    #[inline]
+    pub fn computed_key(&self, expression: ExpressionNode<'ast>) -> Node<'ast, PropertyKey<'ast>> {
+        self.node(PropertyKey::Computed(expression))
+    }
+
+    #[inline]
+    pub fn property(&self, key: Node<'ast, PropertyKey<'ast>>, value: ExpressionNode<'ast>) -> Node<'ast, Property<'ast>> {
+        self.node(Property::Literal { key, value })
+    }
+
+    #[inline]
+    pub fn shorthand_property(&self, name: &'ast str) -> Node<'ast, Property<'ast>> {
+        self.node(Property::Shorthand(name))
+    }
+
+    #[inline]
+    pub fn method_property(&self, key: Node<'ast, PropertyKey<'ast>>, value: Node<'ast, Function<'ast, EmptyName>>) -> Node<'ast, Property<'ast>> {
+        self.node(Property::Method { key, value })
+    }
+
+    #[inline]
+    pub fn spread_property(&self, argument: ExpressionNode<'ast>) -> Node<'ast, Property<'ast>> {
+        self.node(Property::Spread { argument })
+    }
+
+    // Class members
+
+    #[inline]
+    pub fn class_method(&self, is_static: bool, kind: MethodKind, key: Node<'ast, PropertyKey<'ast>>, value: Node<'ast, Function<'ast, EmptyName>>) -> Node<'ast, ClassMember<'ast>> {
+        self.node(ClassMember::Method { is_static, key, kind, value })
+    }
+
+    #[inline]
+    pub fn class_property(&self, is_static: bool, key: Node<'ast, PropertyKey<'ast>>, value: ExpressionNode<'ast>) -> Node<'ast, ClassMember<'ast>> {
+        self.node(ClassMember::Literal { is_static, key, value })
+    }
+
+    // Patterns
+
+    #[inline]
+    pub fn pattern(&self, name: &'ast str) -> Node<'ast, Pattern<'ast>> {
+        self.node(Pattern::Identifier(name))
+    }
+
+    #[inline]
+    pub fn object_pattern(&self, properties: NodeList<'ast, Property<'ast>>) -> Node<'ast, Pattern<'ast>> {
+        self.node(Pattern::ObjectPattern { properties })
+    }
+
+    /// Array pattern, use `Builder::pattern_hole` for elisions.
+    #[inline]
+    pub fn array_pattern(&self, elements: NodeList<'ast, Pattern<'ast>>) -> Node<'ast, Pattern<'ast>> {
+        self.node(Pattern::ArrayPattern { elements })
+    }
+
+    /// Elided element of an array pattern.
+    #[inline]
+    pub fn pattern_hole(&self) -> Node<'ast, Pattern<'ast>> {
+        self.node(Pattern::Void)
+    }
+
+    #[inline]
+    pub fn rest_element(&self, name: &'ast str) -> Node<'ast, Pattern<'ast>> {
+        self.node(Pattern::RestElement {
+            argument: self.identifier(name),
+        })
+    }
+
+    #[inline]
+    pub fn assignment_pattern(&self, left: Node<'ast, Pattern<'ast>>, right: ExpressionNode<'ast>) -> Node<'ast, Pattern<'ast>> {
+        self.node(Pattern::AssignmentPattern { left, right })
+    }
+
+    // Statements
+
+    #[inline]
+    pub fn empty_statement(&self) -> StatementNode<'ast> {
+        self.node(Statement::Empty)
+    }
+
+    #[inline]
+    pub fn debugger_statement(&self) -> StatementNode<'ast> {
+        self.node(Statement::Debugger)
+    }
+
+    #[inline]
+    pub fn expression_statement(&self, expression: ExpressionNode<'ast>) -> StatementNode<'ast> {
+        self.node(expression)
+    }
+
+    #[inline]
+    pub fn declarator(&self, id: Node<'ast, Pattern<'ast>>, init: Option<ExpressionNode<'ast>>) -> Node<'ast, Declarator<'ast>> {
+        self.node(Declarator { id, init })
+    }
+
+    /// Declaration statement, not allocated so it can be used as `ForInit`.
+    #[inline]
+    pub fn declaration(&self, kind: DeclarationKind, declarators: NodeList<'ast, Declarator<'ast>>) -> DeclarationStatement<'ast> {
+        DeclarationStatement { kind, declarators }
+    }
+
+    #[inline]
+    pub fn declaration_statement(&self, kind: DeclarationKind, declarators: NodeList<'ast, Declarator<'ast>>) -> StatementNode<'ast> {
+        self.node(self.declaration(kind, declarators))
+    }
+
+    /// `var name = init;`, with a single declarator.
+    #[inline]
+    pub fn var(&self, name: &'ast str, init: Option<ExpressionNode<'ast>>) -> StatementNode<'ast> {
+        let declarator = self.declarator(self.pattern(name), init);
+
+        self.declaration_statement(DeclarationKind::Var, self.list_from(declarator))
+    }
+
+    #[inline]
+    pub fn return_statement(&self, value: Option<ExpressionNode<'ast>>) -> StatementNode<'ast> {
+        self.node(ReturnStatement { value })
+    }
+
+    #[inline]
+    pub fn break_statement(&self, label: Option<&'ast str>) -> StatementNode<'ast> {
+        self.node(BreakStatement {
+            label: label.map(|label| self.identifier(label)),
+        })
+    }
+
+    #[inline]
+    pub fn continue_statement(&self, label: Option<&'ast str>) -> StatementNode<'ast> {
+        self.node(ContinueStatement {
+            label: label.map(|label| self.identifier(label)),
+        })
+    }
+
+    #[inline]
+    pub fn throw_statement(&self, value: ExpressionNode<'ast>) -> StatementNode<'ast> {
+        self.node(ThrowStatement { value })
+    }
+
+    #[inline]
+    pub fn if_statement(&self, test: ExpressionNode<'ast>, consequent: StatementNode<'ast>, alternate: Option<StatementNode<'ast>>) -> StatementNode<'ast> {
+        self.node(IfStatement { test, consequent, alternate })
+    }
+
+    #[inline]
+    pub fn while_statement(&self, test: ExpressionNode<'ast>, body: StatementNode<'ast>) -> StatementNode<'ast> {
+        self.node(WhileStatement { test, body })
+    }
+
+    #[inline]
+    pub fn do_statement(&self, body: StatementNode<'ast>, test: ExpressionNode<'ast>) -> StatementNode<'ast> {
+        self.node(DoStatement { body, test })
+    }
+
+    #[inline]
+    pub fn for_init<I>(&self, init: I) -> Node<'ast, ForInit<'ast>> where
+        I: Into<ForInit<'ast>>,
+    {
+        self.node(init)
+    }
+
+    #[inline]
+    pub fn for_statement(
+        &self,
+        init: Option<Node<'ast, ForInit<'ast>>>,
+        test: Option<ExpressionNode<'ast>>,
+        update: Option<ExpressionNode<'ast>>,
+        body: StatementNode<'ast>
+    ) -> StatementNode<'ast> {
+        self.node(ForStatement { init, test, update, body })
+    }
+
+    #[inline]
+    pub fn for_in_statement(&self, left: Node<'ast, ForInit<'ast>>, right: ExpressionNode<'ast>, body: StatementNode<'ast>) -> StatementNode<'ast> {
+        self.node(ForInStatement { left, right, body })
+    }
+
+    #[inline]
+    pub fn for_of_statement(&self, left: Node<'ast, ForInit<'ast>>, right: ExpressionNode<'ast>, body: StatementNode<'ast>) -> StatementNode<'ast> {
+        self.node(ForOfStatement { left, right, body })
+    }
+
+    #[inline]
+    pub fn catch_clause(&self, param: Node<'ast, Pattern<'ast>>, body: StatementList<'ast>) -> Node<'ast, CatchClause<'ast>> {
+        self.node(CatchClause {
+            param,
+            body: self.block(body),
+        })
+    }
+
+    #[inline]
+    pub fn try_statement(
+        &self,
+        block: StatementList<'ast>,
+        handler: Option<Node<'ast, CatchClause<'ast>>>,
+        finalizer: Option<StatementList<'ast>>
+    ) -> StatementNode<'ast> {
+        self.node(TryStatement {
+            block: self.block(block),
+            handler,
+            finalizer: finalizer.map(|body| self.block(body)),
+        })
+    }
+
+    #[inline]
+    pub fn block_statement(&self, body: StatementList<'ast>) -> StatementNode<'ast> {
+        self.node(Block { body })
+    }
+
+    #[inline]
+    pub fn labeled_statement(&self, label: &'ast str, body: StatementNode<'ast>) -> StatementNode<'ast> {
+        self.node(LabeledStatement { label, body })
+    }
+
+    #[inline]
+    pub fn function_declaration(&self, name: &'ast str, params: NodeList<'ast, Pattern<'ast>>, body: StatementList<'ast>) -> StatementNode<'ast> {
+        self.node(self.function(self.mandatory_name(name), params, body))
+    }
+
+    #[inline]
+    pub fn class_declaration(&self, name: &'ast str, extends: Option<ExpressionNode<'ast>>, body: NodeList<'ast, ClassMember<'ast>>) -> StatementNode<'ast> {
+        self.node(self.class(self.mandatory_name(name), extends, body))
+    }
+
+    #[inline]
+    pub fn switch_case(&self, test: Option<ExpressionNode<'ast>>, consequent: StatementList<'ast>) -> Node<'ast, SwitchCase<'ast>> {
+        self.node(SwitchCase { test, consequent })
+    }
+
+    #[inline]
+    pub fn switch_statement(&self, discriminant: ExpressionNode<'ast>, cases: NodeList<'ast, SwitchCase<'ast>>) -> StatementNode<'ast> {
+        self.node(SwitchStatement {
+            discriminant,
+            cases: self.block(cases),
+        })
+    }
+
+    /// `import { imported as local } from "source";` specifier.
+    #[inline]
+    pub fn import_specifier(&self, imported: &'ast str, local: &'ast str) -> Node<'ast, ForImportSpecifier<'ast>> {
+        self.node(ImportSpecifier {
+            imported: self.identifier(imported),
+            local: self.identifier(local),
+        })
+    }
+
+    /// `import local from "source";` specifier.
+    #[inline]
+    pub fn import_default_specifier(&self, local: &'ast str) -> Node<'ast, ForImportSpecifier<'ast>> {
+        self.node(ImportDefaultSpecifier {
+            local: self.identifier(local),
+        })
+    }
+
+    /// `import * as local from "source";` specifier.
+    #[inline]
+    pub fn import_namespace_specifier(&self, local: &'ast str) -> Node<'ast, ForImportSpecifier<'ast>> {
+        self.node(ImportNamespaceSpecifier {
+            local: self.identifier(local),
+        })
+    }
+
+    /// Import declaration, `source` is the unquoted module path.
+    #[inline]
+    pub fn import_declaration(&self, specifiers: NodeList<'ast, ForImportSpecifier<'ast>>, source: &'ast str) -> StatementNode<'ast> {
+        self.node(ImportDeclaration { specifiers, source })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse;
+
+    #[test]
+    fn build_call() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let expected = parse(r#"foo.bar(1, "baz\n", this);"#).unwrap();
+
+        let arguments = b.list(vec![b.integer(1), b.string("baz\n"), b.this()]);
+        let call = b.call(b.member(b.ident("foo"), "bar"), arguments);
+        let body = b.list_from(b.expression_statement(call));
+
+        assert_eq!(body, expected.body());
+    }
+
+    #[test]
+    fn build_function() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let expected = parse("function foo(a, b = 2) { var c = a; return c; }").unwrap();
+
+        let params = b.list(vec![
+            b.pattern("a"),
+            b.assignment_pattern(b.pattern("b"), b.integer(2)),
+        ]);
+        let body = b.list(vec![
+            b.var("c", Some(b.ident("a"))),
+            b.return_statement(Some(b.ident("c"))),
+        ]);
+        let function = b.function_declaration("foo", params, body);
+
+        assert_eq!(b.list_from(function), expected.body());
+    }
+
+    #[test]
+    fn build_class() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let expected = parse("class Foo extends Bar { constructor() {} static baz() {} }").unwrap();
+
+        let members = b.list(vec![
+            b.class_method(false, MethodKind::Constructor, b.key("constructor"), b.method(NodeList::empty(), NodeList::empty())),
+            b.class_method(true, MethodKind::Method, b.key("baz"), b.method(NodeList::empty(), NodeList::empty())),
+        ]);
+        let class = b.class_declaration("Foo", Some(b.ident("Bar")), members);
+
+        assert_eq!(b.list_from(class), expected.body());
+    }
+
+    #[test]
+    fn list_helpers() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let list = b.list(vec![b.ident("b"), b.ident("c")]);
+        let prepended = b.prepend(b.ident("a"), list);
+        let appended = b.append(list, b.ident("d"));
+        let concatenated = b.concat(prepended, b.list_from(b.ident("d")));
+
+        assert_eq!(list, b.list(vec![b.ident("b"), b.ident("c")]));
+        assert_eq!(prepended, b.list(vec![b.ident("a"), b.ident("b"), b.ident("c")]));
+        assert_eq!(appended, b.list(vec![b.ident("b"), b.ident("c"), b.ident("d")]));
+        assert_eq!(concatenated, b.list(vec![b.ident("a"), b.ident("b"), b.ident("c"), b.ident("d")]));
+        assert_eq!(b.append(NodeList::empty(), b.ident("a")), b.list_from(b.ident("a")));
+    }
+}
@@ -18,10 +18,19 @@ extern crate pretty_assertions;
 pub mod ast;
 pub mod error;
 pub mod lexer;
+pub mod build;
+
+#[macro_use]
+pub mod template;
 
 mod module;
 mod parser;
+mod pool;
 mod astgen;
+mod babel;
 
-pub use parser::parse;
-pub use module::Module;
+pub use parser::{parse, parse_in, parse_in_arena, ParseOptions};
+pub use parser::{parse_expression, parse_statement, parse_pattern, parse_function_body};
+pub use toolshed::Arena;
+pub use module::{Module, OwnedModule, AllocationStats, AstFlavor, FlavoredModule};
+pub use pool::{ArenaPool, PooledArena};
@@ -1,39 +1,201 @@
-use toolshed::list::UnsafeList;
 use toolshed::Arena;
+use toolshed::list::UnsafeList;
 use ast::StatementList;
 
 use std::fmt;
-use std::marker::PhantomData;
 
 
 /// A JavaScript module parsed to an AST.
+///
+/// `Module` borrows the `Arena` on which the AST is allocated, so it can
+/// be copied around freely, but can't outlive the `Arena`. Anything set
+/// on the nodes of the AST must be allocated for the same lifetime.
+#[derive(Clone, Copy)]
 pub struct Module<'ast> {
-    body: UnsafeList,
-    arena: Arena,
-    _phantom: PhantomData<&'ast StatementList<'ast>>
+    body: StatementList<'ast>,
+    arena: &'ast Arena,
+    stats: Option<AllocationStats>,
+}
+
+/// Statistics of allocations made while parsing a `Module`.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct AllocationStats {
+    /// Number of AST nodes.
+    pub nodes: usize,
+
+    /// Bytes taken by AST nodes.
+    pub node_bytes: usize,
+
+    /// Bytes taken by the copy of the source, which all
+    /// identifiers and literals in the AST point to.
+    pub source_bytes: usize,
+}
+
+impl AllocationStats {
+    /// Bytes taken by nodes and source. Links of lists and alignment
+    /// padding are not included, so this is a lower bound of the
+    /// memory used on the `Arena`.
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.node_bytes + self.source_bytes
+    }
 }
 
 impl<'ast> Module<'ast> {
     #[inline]
-    pub(crate) fn new(body: UnsafeList, arena: Arena) -> Self {
+    pub(crate) fn new(body: StatementList<'ast>, arena: &'ast Arena, stats: Option<AllocationStats>) -> Self {
         Module {
             body,
             arena,
-            _phantom: PhantomData,
+            stats,
         }
     }
 
     /// Get the body of the module as a list of statements.
     #[inline]
     pub fn body(&self) -> StatementList<'ast> {
-        unsafe { self.body.into_list() }
+        self.body
+    }
+
+    /// Replace the body of the module, e.g. with the result of a fold.
+    /// The new body must be allocated on the same `Arena`.
+    #[inline]
+    pub fn set_body(&mut self, body: StatementList<'ast>) {
+        self.body = body;
     }
 
     /// Get a reference to the `Arena` on which the AST is allocated.
     #[inline]
-    pub fn arena(&'ast self) -> &'ast Arena {
+    pub fn arena(&self) -> &'ast Arena {
+        self.arena
+    }
+
+    /// Get the `AllocationStats`, if they were requested in `ParseOptions`.
+    #[inline]
+    pub fn stats(&self) -> Option<&AllocationStats> {
+        self.stats.as_ref()
+    }
+
+    /// Wrap the module so that it serializes into the given JSON AST flavor.
+    /// Serializing the `Module` directly always produces ESTree.
+    #[inline]
+    pub fn with_flavor<'m>(&'m self, flavor: AstFlavor) -> FlavoredModule<'m, 'ast> {
+        FlavoredModule {
+            module: self,
+            flavor,
+        }
+    }
+}
+
+/// A module that owns its source and the `Arena` its AST is allocated on,
+/// as returned by `ratel::parse`. It hands out `Module` handles that borrow
+/// from it.
+///
+/// ```
+/// let mut owned = ratel::parse("foo();".to_string()).unwrap();
+///
+/// owned.modify(|module| {
+///     let body = module.body().iter().chain(module.body().iter()).cloned();
+///     let body = ratel::build::Builder::new(module.arena()).list(body);
+///
+///     module.set_body(body);
+/// });
+///
+/// assert_eq!(owned.source(), "foo();");
+/// assert_eq!(owned.body().iter().count(), 2);
+/// ```
+pub struct OwnedModule {
+    source: String,
+    arena: Arena,
+    // Points into `arena`, which keeps its pages in place when moved
+    body: UnsafeList,
+    stats: Option<AllocationStats>,
+}
+
+impl OwnedModule {
+    #[inline]
+    pub(crate) fn new(source: String, arena: Arena, body: UnsafeList, stats: Option<AllocationStats>) -> Self {
+        OwnedModule {
+            source,
+            arena,
+            body,
+            stats,
+        }
+    }
+
+    /// Get the source the module was parsed from.
+    #[inline]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Get a `Module` handle on the AST, borrowing from this module.
+    #[inline]
+    pub fn module(&self) -> Module<'_> {
+        // The body was allocated on `self.arena`, or is `'static`,
+        // see `OwnedModule::modify`
+        let body = unsafe { self.body.into_list() };
+
+        Module::new(body, &self.arena, self.stats)
+    }
+
+    /// Get the body of the module as a list of statements.
+    #[inline]
+    pub fn body(&self) -> StatementList<'_> {
+        self.module().body()
+    }
+
+    /// Get a reference to the `Arena` on which the AST is allocated.
+    #[inline]
+    pub fn arena(&self) -> &Arena {
         &self.arena
     }
+
+    /// Get the `AllocationStats`, if they were requested in `ParseOptions`.
+    #[inline]
+    pub fn stats(&self) -> Option<&AllocationStats> {
+        self.stats.as_ref()
+    }
+
+    /// Run `f` on a `Module` handle, e.g. to transform it, keeping the body
+    /// it ends up with. Since `f` has to work for any `'ast`, a new body can
+    /// only be allocated on the `Arena` of the module.
+    pub fn modify<F, R>(&mut self, f: F) -> R
+    where
+        F: for<'ast> FnOnce(&mut Module<'ast>) -> R,
+    {
+        let mut module = self.module();
+        let result = f(&mut module);
+
+        self.body = module.body().into_unsafe();
+
+        result
+    }
+}
+
+impl fmt::Debug for OwnedModule {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.module().fmt(f)
+    }
+}
+
+/// Flavor of the JSON AST produced when serializing a `Module`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AstFlavor {
+    /// Plain ESTree, as produced by Esprima or Acorn.
+    ESTree,
+
+    /// Babel AST, with `File` and `Program` wrappers, `StringLiteral`,
+    /// `ObjectProperty`, `ClassMethod`, `extra.raw` and friends.
+    Babel,
+}
+
+/// A `Module` paired with the `AstFlavor` it should be serialized as.
+#[derive(Clone, Copy)]
+pub struct FlavoredModule<'m, 'ast: 'm> {
+    pub(crate) module: &'m Module<'ast>,
+    pub(crate) flavor: AstFlavor,
 }
 
 impl<'ast> fmt::Debug for Module<'ast> {
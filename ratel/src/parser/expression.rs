@@ -1,8 +1,9 @@
 use toolshed::list::ListBuilder;
-use parser::{Parser, Parse, BindingPower, ANY, B0, B15};
+use error::Error;
+use parser::{Parser, Parse, SuperUsage, BindingPower, ANY, B0, B15};
 use lexer::Token::*;
 use ast::{Node, NodeList, Expression, ExpressionNode, IdentifierNode, ExpressionList};
-use ast::{Property, PropertyKey, OperatorKind, Literal, Function, Class, StatementNode};
+use ast::{Property, PropertyKey, OperatorKind, Literal, Class, StatementNode};
 use ast::expression::*;
 
 
@@ -29,7 +30,7 @@ static DEF_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
 //  CONST BREAK DO    CASE  ELSE  CATCH EXPRT CLASS EXTND RET   WHILE FINLY
 
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
+    SUPR, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
 //  SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 
     ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
@@ -47,7 +48,7 @@ pub static ARRAY_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, SPRD, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
+    SUPR, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
     ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
     ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
 ];
@@ -60,7 +61,7 @@ pub static CALL_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, SPRD, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, CLAS, ____, ____, ____, ____,
-    ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
+    SUPR, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
     ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
     ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
 ];
@@ -125,6 +126,30 @@ create_handlers! {
         par.alloc_at_loc(start, argument.end, SpreadExpression { argument })
     };
 
+    // `super` is only valid as the object of a member expression within
+    // methods, or as the callee of a call within derived constructors.
+    pub const SUPR = |par| {
+        let (start, end) = par.loc();
+        par.lexer.consume();
+
+        let valid = match par.lexer.token {
+            ParenOpen => par.super_usage == SuperUsage::Call,
+            Accessor | BracketOpen => par.super_usage != SuperUsage::Forbidden,
+            _ => false,
+        };
+
+        if !valid {
+            par.errors.push(Error {
+                token: Super,
+                raw: "super".into(),
+                start: start as usize,
+                end: end as usize,
+            });
+        }
+
+        par.alloc_at_loc(start, end, SuperExpression)
+    };
+
     pub const THIS = |par| {
         let expr = par.alloc_in_loc(ThisExpression);
         par.lexer.consume();
@@ -266,18 +291,26 @@ impl<'ast> Parser<'ast> {
     }
 
     #[inline]
-    pub fn arrow_function_expression(&mut self, params: ExpressionList<'ast>) -> ArrowExpression<'ast> {
+    pub fn arrow_function_expression(&mut self, start: u32, params: ExpressionList<'ast>) -> ExpressionNode<'ast> {
         let params = self.params_from_expressions(params);
 
-        let body = match self.lexer.token {
-            BraceOpen => ArrowBody::Block(self.unchecked_block()),
-            _         => ArrowBody::Expression(self.expression::<B0>()),
+        let (body, end) = match self.lexer.token {
+            BraceOpen => {
+                let block = self.unchecked_block();
+
+                (ArrowBody::Block(block), block.end)
+            },
+            _ => {
+                let expression = self.expression::<B0>();
+
+                (ArrowBody::Expression(expression), expression.end)
+            },
         };
 
-        ArrowExpression {
+        self.alloc_at_loc(start, end, ArrowExpression {
             params,
             body,
-        }
+        })
     }
 
     #[inline]
@@ -320,16 +353,26 @@ impl<'ast> Parser<'ast> {
             ParenClose => {
                 self.lexer.consume();
                 expect!(self, OperatorFatArrow);
-                let expression = self.arrow_function_expression(NodeList::empty());
-                let end = self.lexer.end();
-                self.alloc_at_loc(start, end, expression)
+
+                self.arrow_function_expression(start, NodeList::empty())
             },
             _ => {
                 let expression = self.expression::<ANY>();
 
                 expect!(self, ParenClose);
 
-                expression
+                if self.lexer.token != OperatorFatArrow {
+                    return expression;
+                }
+
+                self.lexer.consume();
+
+                let params = match expression.item {
+                    Expression::Sequence(SequenceExpression { body }) => body,
+                    _ => NodeList::from(self.arena, expression)
+                };
+
+                self.arrow_function_expression(start, params)
             }
         }
     }
@@ -364,7 +407,8 @@ impl<'ast> Parser<'ast> {
             self.error::<()>();
         }
 
-        let property = self.alloc_in_loc(property);
+        let end = self.lexer.end();
+        let property = self.alloc_at_loc(end - property.len() as u32, end, property);
 
         MetaPropertyExpression {
             meta,
@@ -462,7 +506,7 @@ impl<'ast> Parser<'ast> {
                 })
             },
             ParenOpen => {
-                let value = Node::parse(self);
+                let value = self.method(SuperUsage::Property);
 
                 self.alloc_at_loc(start, value.end, Property::Method {
                     key,
@@ -620,7 +664,7 @@ impl<'ast> Parser<'ast> {
     #[inline]
     pub fn function_expression(&mut self) -> ExpressionNode<'ast> {
         let start = self.lexer.start_then_consume();
-        let function = Function::parse(self);
+        let function = self.function(SuperUsage::Forbidden);
 
         self.alloc_at_loc(start, function.body.end, function)
     }
@@ -638,6 +682,7 @@ impl<'ast> Parser<'ast> {
 mod test {
     use super::*;
     use ast::{OperatorKind, Literal, Statement, Function, Pattern, Class};
+    use ast::{ClassMember, MethodKind, EmptyName};
     use ast::expression::*;
     use ast::statement::*;
     use parser::parse;
@@ -923,6 +968,64 @@ mod test {
         assert_expr!(src, expected);
     }
 
+    #[test]
+    fn super_expression() {
+        let src = "class Foo extends Bar { constructor() { super(1); super.foo; } }";
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Class {
+                name: mock.name("Foo"),
+                extends: Some(mock.ptr("Bar")),
+                body: mock.block([
+                    ClassMember::Method {
+                        is_static: false,
+                        key: mock.ptr(PropertyKey::Literal("constructor")),
+                        kind: MethodKind::Constructor,
+                        value: mock.ptr(Function {
+                            name: EmptyName,
+                            generator: false,
+                            params: NodeList::empty(),
+                            body: mock.block([
+                                mock.ptr(CallExpression {
+                                    callee: mock.ptr(SuperExpression),
+                                    arguments: mock.list([
+                                        Literal::Number("1"),
+                                    ]),
+                                }),
+                                mock.ptr(MemberExpression {
+                                    object: mock.ptr(SuperExpression),
+                                    property: mock.ptr("foo"),
+                                }),
+                            ])
+                        })
+                    }
+                ])
+            }
+        ]);
+
+        assert_eq!(parse(src).unwrap().body(), expected);
+    }
+
+    #[test]
+    fn super_expression_in_methods() {
+        assert!(parse("class Foo { foo() { return () => super[foo]; } }").is_ok());
+        assert!(parse("class Foo { foo = super.foo; }").is_ok());
+        assert!(parse("({ foo() { super.foo(); } });").is_ok());
+        assert!(parse("class Foo extends Bar { constructor() { super(); } }").is_ok());
+    }
+
+    #[test]
+    fn invalid_super_expression() {
+        assert!(parse("super.foo;").is_err());
+        assert!(parse("class Foo { foo() { super; } }").is_err());
+        assert!(parse("class Foo { foo() { function bar() { super.foo; } } }").is_err());
+        assert!(parse("class Foo { constructor() { super(); } }").is_err());
+        assert!(parse("class Foo extends Bar { foo() { super(); } }").is_err());
+        assert!(parse("class Foo extends Bar { constructor() { class Baz { constructor() { super(); } } } }").is_err());
+        assert!(parse("({ foo: function () { super.foo; } });").is_err());
+    }
+
     #[test]
     fn meta_property_expression() {
         let src = "new.target";
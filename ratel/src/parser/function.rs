@@ -1,8 +1,10 @@
+use std::mem;
+
 use toolshed::list::ListBuilder;
-use parser::{Parser, Parse, ANY, B0};
+use parser::{Parser, Parse, SuperUsage, ANY, B0};
 use lexer::Token::*;
 use ast::{Node, NodeList, EmptyName, OptionalName, MandatoryName, Name};
-use ast::{MethodKind, Pattern, Function, Class, ClassMember, PropertyKey};
+use ast::{MethodKind, Pattern, Function, Method, Class, ClassMember, PropertyKey};
 
 impl<'ast> Parse<'ast> for EmptyName {
     type Output = Self;
@@ -115,8 +117,8 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
 
         let mut kind = MethodKind::Method;
 
-        let token_start = par.lexer.start();
-        let token_end;
+        let mut token_start = par.lexer.start();
+        let mut token_end;
 
         let key = match par.lexer.token {
             _ if par.lexer.token.is_word() => {
@@ -130,7 +132,8 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
                         _     => return par.error()
                     };
                     label = par.lexer.token_as_str();
-                    par.lexer.consume();
+                    token_start = par.lexer.start();
+                    token_end = par.lexer.end_then_consume();
                 } else if !is_static && label == "constructor" {
                     kind = MethodKind::Constructor;
                 }
@@ -164,7 +167,12 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
         let end;
         let member = match par.lexer.token {
             ParenOpen => {
-                let value = Node::parse(par);
+                let usage = match kind {
+                    MethodKind::Constructor if par.derived_class => SuperUsage::Call,
+                    _ => SuperUsage::Property,
+                };
+
+                let value = par.method(usage);
 
                 end = value.end;
 
@@ -178,8 +186,10 @@ impl<'ast> Parse<'ast> for ClassMember<'ast> {
             OperatorAssign => {
                 par.lexer.consume();
 
+                let outer = mem::replace(&mut par.super_usage, SuperUsage::Property);
                 let expression = par.expression::<B0>();
 
+                par.super_usage = outer;
                 end = expression.end;
 
                 ClassMember::Literal {
@@ -217,15 +227,44 @@ impl<'ast, N> Parse<'ast> for Class<'ast, N> where
             _ => None
         };
 
+        let outer = mem::replace(&mut par.derived_class, super_class.is_some());
+        let body = par.block();
+
+        par.derived_class = outer;
+
         Class {
             name,
             extends: super_class,
-            body: par.block(),
+            body,
         }
     }
 }
 
 impl<'ast> Parser<'ast> {
+    /// Parse a function with `super` usable as given within it. Arrow
+    /// functions are parsed as they are, sharing it with the function
+    /// around them.
+    #[inline]
+    pub fn function<N>(&mut self, usage: SuperUsage) -> Function<'ast, N> where
+        N: Name<'ast> + Parse<'ast, Output = N>,
+    {
+        let outer = mem::replace(&mut self.super_usage, usage);
+        let function = Function::parse(self);
+
+        self.super_usage = outer;
+
+        function
+    }
+
+    /// Parse a method of a class or an object literal.
+    #[inline]
+    pub fn method(&mut self, usage: SuperUsage) -> Node<'ast, Method<'ast>> {
+        let start = self.lexer.start();
+        let function = self.function(usage);
+
+        self.alloc_at_loc(start, function.body.end, function)
+    }
+
     #[inline]
     fn pattern_void(&mut self) -> Node<'ast, Pattern<'ast>> {
         let loc = self.lexer.start();
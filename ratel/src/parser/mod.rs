@@ -9,12 +9,14 @@ mod nested;
 use toolshed::list::ListBuilder;
 use toolshed::Arena;
 use error::Error;
-use module::Module;
+use module::{Module, OwnedModule, AllocationStats};
+
+use std::mem::size_of;
 
 use self::error::ToError;
 use self::nested::*;
 
-use ast::{Loc, Node, Statement, NodeList, Block, BlockNode};
+use ast::{Loc, Node, Statement, StatementNode, StatementList, NodeList, Block, BlockNode};
 use ast::{Expression, ExpressionNode, ExpressionList, IdentifierNode};
 use ast::{OperatorKind, Pattern};
 use ast::expression::BinaryExpression;
@@ -38,6 +40,28 @@ pub struct Parser<'ast> {
 
     /// AST under construction
     body: NodeList<'ast, Statement<'ast>>,
+
+    /// Allocation statistics, if requested
+    stats: Option<AllocationStats>,
+
+    /// What `super` can be used for in the function being parsed
+    super_usage: SuperUsage,
+
+    /// Whether the class being parsed extends another
+    derived_class: bool,
+}
+
+/// What `super` can be used for, depending on the function it is in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SuperUsage {
+    /// Outside of methods.
+    Forbidden,
+
+    /// `super.foo` and `super[foo]`, in methods of classes and objects.
+    Property,
+
+    /// Calls to `super()` as well, in constructors of classes that extend another.
+    Call,
 }
 
 impl<'ast> Parser<'ast> {
@@ -47,6 +71,9 @@ impl<'ast> Parser<'ast> {
             lexer: Lexer::new(arena, source),
             errors: Vec::new(),
             body: NodeList::empty(),
+            stats: None,
+            super_usage: SuperUsage::Forbidden,
+            derived_class: false,
         }
     }
 
@@ -79,6 +106,11 @@ impl<'ast> Parser<'ast> {
     fn alloc<T>(&mut self, val: Loc<T>) -> Node<'ast, T> where
         T: Copy,
     {
+        if let Some(ref mut stats) = self.stats {
+            stats.nodes += 1;
+            stats.node_bytes += size_of::<Loc<T>>();
+        }
+
         Node::new(self.arena.alloc(val))
     }
 
@@ -218,24 +250,139 @@ impl<'ast> Parser<'ast> {
     }
 }
 
-/// Parse the JavaScript source `&str` and produce an Abstract Syntax Tree `Module`.
-pub fn parse<'src, 'ast>(source: &'src str) -> Result<Module<'ast>, Vec<Error>> {
-    let arena = Arena::new();
+/// Options for `parse_in_arena`.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct ParseOptions {
+    /// Collect `AllocationStats`, available via `Module::stats`.
+    pub stats: bool,
+}
 
-    let (body, errors) = {
-        let mut parser = Parser::new(source, &arena);
+/// Parse the JavaScript source `&str` into an Abstract Syntax Tree `Module`
+/// allocated on the given `Arena`.
+///
+/// ```
+/// use ratel::Arena;
+///
+/// let arena = Arena::new();
+/// let module = ratel::parse_in(&arena, "let foo = 42;").unwrap();
+///
+/// assert_eq!(module.body().iter().count(), 1);
+/// ```
+#[inline]
+pub fn parse_in<'ast>(arena: &'ast Arena, source: &str) -> Result<Module<'ast>, Vec<Error>> {
+    parse_in_arena(arena, source, ParseOptions::default())
+}
 
-        parser.parse();
+/// Same as `parse_in`, with `ParseOptions`.
+///
+/// Combined with an `ArenaPool`, memory can be reused between many files,
+/// see `PooledArena::parse`:
+///
+/// ```
+/// use ratel::{ArenaPool, ParseOptions};
+///
+/// let pool = ArenaPool::new();
+/// let options = ParseOptions { stats: true };
+///
+/// for source in &["foo();", "bar();"] {
+///     let arena = pool.get();
+///     let module = arena.parse(source, options).unwrap();
+///
+///     assert_eq!(module.stats().unwrap().nodes, 3);
+/// }
+/// ```
+pub fn parse_in_arena<'ast>(arena: &'ast Arena, source: &str, options: ParseOptions) -> Result<Module<'ast>, Vec<Error>> {
+    let mut parser = Parser::new(source, arena);
+
+    if options.stats {
+        parser.stats = Some(AllocationStats {
+            // Lexer copies the source with a trailing null byte
+            source_bytes: source.len() + 1,
+            ..AllocationStats::default()
+        });
+    }
+
+    parser.parse();
+
+    match parser.errors.len() {
+        0 => Ok(Module::new(parser.body, arena, parser.stats)),
+        _ => Err(parser.errors)
+    }
+}
+
+/// Parse the JavaScript source into a `Module` that owns both the source
+/// and the `Arena` its AST is allocated on.
+///
+/// ```
+/// let module = ratel::parse("let foo = 42;").unwrap();
+///
+/// assert_eq!(module.body().iter().count(), 1);
+/// ```
+pub fn parse<S: Into<String>>(source: S) -> Result<OwnedModule, Vec<Error>> {
+    let source = source.into();
+    let arena = Arena::new();
 
-        (parser.body.into_unsafe(), parser.errors)
+    let (body, stats) = {
+        let module = parse_in(&arena, &source)?;
+
+        (module.body().into_unsafe(), module.stats().cloned())
     };
 
-    match errors.len() {
-        0 => Ok(Module::new(body, arena)),
-        _ => Err(errors)
+    Ok(OwnedModule::new(source, arena, body, stats))
+}
+
+/// Run `parse` on a fresh `Parser`, then make sure nothing is left in the source.
+fn parse_only<'ast, T, F>(arena: &'ast Arena, source: &str, parse: F) -> Result<T, Vec<Error>> where
+    F: FnOnce(&mut Parser<'ast>) -> T,
+{
+    let mut parser = Parser::new(source, arena);
+
+    let result = parse(&mut parser);
+
+    if parser.lexer.token != EndOfProgram {
+        parser.error::<()>();
+    }
+
+    match parser.errors.len() {
+        0 => Ok(result),
+        _ => Err(parser.errors)
     }
 }
 
+/// Parse a single expression, sequences included. Any tokens
+/// following the expression, including a semicolon, are an error.
+///
+/// ```
+/// use ratel::Arena;
+///
+/// let arena = Arena::new();
+///
+/// assert!(ratel::parse_expression(&arena, "a ? b : c").is_ok());
+/// assert!(ratel::parse_expression(&arena, "a b").is_err());
+/// ```
+pub fn parse_expression<'ast>(arena: &'ast Arena, source: &str) -> Result<ExpressionNode<'ast>, Vec<Error>> {
+    parse_only(arena, source, |parser| parser.expression::<ANY>())
+}
+
+/// Parse a single statement. Any tokens following the statement are an error.
+pub fn parse_statement<'ast>(arena: &'ast Arena, source: &str) -> Result<StatementNode<'ast>, Vec<Error>> {
+    parse_only(arena, source, |parser| parser.statement())
+}
+
+/// Parse a single binding pattern, such as `foo`, `[a, b]` or `{ a, b: c }`.
+/// Any tokens following the pattern are an error.
+pub fn parse_pattern<'ast>(arena: &'ast Arena, source: &str) -> Result<Node<'ast, Pattern<'ast>>, Vec<Error>> {
+    parse_only(arena, source, Pattern::parse)
+}
+
+/// Parse the body of a function, as passed to `new Function`.
+pub fn parse_function_body<'ast>(arena: &'ast Arena, source: &str) -> Result<StatementList<'ast>, Vec<Error>> {
+    parse_only(arena, source, |parser| {
+        parser.parse();
+        parser.body
+    })
+}
+
 #[cfg(test)]
 mod mock {
     use super::*;
@@ -297,6 +444,8 @@ mod mock {
 mod test {
     use super::*;
     use parser::mock::Mock;
+    use ast::expression::SequenceExpression;
+    use ast::statement::ReturnStatement;
 
     #[test]
     fn empty_parse() {
@@ -315,4 +464,79 @@ mod test {
 
         assert_eq!(parse(";;;").unwrap().body(), expected);
     }
+
+    #[test]
+    fn parse_expression_only() {
+        let arena = Arena::new();
+        let mock = Mock::new();
+
+        let expected = mock.ptr(Expression::Sequence(SequenceExpression {
+            body: mock.list([
+                Expression::Identifier("a"),
+                Expression::Identifier("b"),
+            ]),
+        }));
+
+        assert_eq!(parse_expression(&arena, "a, b").unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_expression_trailing_tokens() {
+        let arena = Arena::new();
+
+        let errors = parse_expression(&arena, "a + b;").unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].start, errors[0].end), (5, 6));
+
+        assert!(parse_expression(&arena, "").is_err());
+    }
+
+    #[test]
+    fn parse_statement_only() {
+        let arena = Arena::new();
+        let mock = Mock::new();
+
+        let expected = mock.ptr(Statement::Return(ReturnStatement {
+            value: Some(mock.ptr("foo")),
+        }));
+
+        assert_eq!(parse_statement(&arena, "return foo;").unwrap(), expected);
+
+        let errors = parse_statement(&arena, "foo; bar;").unwrap_err();
+
+        assert_eq!((errors[0].start, errors[0].end), (5, 8));
+    }
+
+    #[test]
+    fn parse_pattern_only() {
+        let arena = Arena::new();
+        let mock = Mock::new();
+
+        let expected = mock.ptr(Pattern::ArrayPattern {
+            elements: mock.list([
+                Pattern::Identifier("a"),
+                Pattern::Identifier("b"),
+            ]),
+        });
+
+        assert_eq!(parse_pattern(&arena, "[a, b]").unwrap(), expected);
+        assert!(parse_pattern(&arena, "a, b").is_err());
+        assert!(parse_pattern(&arena, "a.b").is_err());
+    }
+
+    #[test]
+    fn parse_function_body_only() {
+        let arena = Arena::new();
+        let mock = Mock::new();
+
+        let expected = mock.list([
+            Statement::Return(ReturnStatement {
+                value: Some(mock.ptr("a")),
+            }),
+        ]);
+
+        assert_eq!(parse_function_body(&arena, "return a").unwrap(), expected);
+        assert!(parse_function_body(&arena, "return a }").is_err());
+    }
 }
@@ -229,15 +229,17 @@ const ____: NestedHandler = None;
 const SEQ: NestedHandler = Some(|par, left| {
     par.lexer.consume();
 
+    let mut right = par.expression::<B0>();
     let builder = ListBuilder::new(par.arena, left);
-    builder.push(par.arena, par.expression::<B0>());
+    builder.push(par.arena, right);
 
     while let Comma = par.lexer.token {
         par.lexer.consume();
-        builder.push(par.arena, par.expression::<B0>());
+        right = par.expression::<B0>();
+        builder.push(par.arena, right);
     }
-    let end = par.lexer.end();
-    par.alloc_at_loc(left.start, end, SequenceExpression {
+
+    par.alloc_at_loc(left.start, right.end, SequenceExpression {
         body: builder.as_list()
     })
 });
@@ -265,17 +267,15 @@ const ARRW: NestedHandler = Some(|par, left| {
         _ => NodeList::from(par.arena, left)
     };
 
-    let expression = par.arrow_function_expression(params);
-    let start = left.start;
-    let end = par.lexer.end();
-    par.alloc_at_loc(start, end, expression)
+    par.arrow_function_expression(left.start, params)
 });
 
 const ACCS: NestedHandler = Some(|par, left| {
     let member = par.lexer.accessor_as_str();
-    par.lexer.consume();
+    let end = par.lexer.end_then_consume();
+    let start = end - member.len() as u32;
 
-    let right = par.alloc_in_loc(member);
+    let right = par.alloc_at_loc(start, end, member);
 
     par.alloc_at_loc(left.start, right.end, MemberExpression {
         object: left,
@@ -284,11 +284,11 @@ const ACCS: NestedHandler = Some(|par, left| {
 });
 
 const CALL: NestedHandler = Some(|par, left| {
-    let start = par.lexer.start_then_consume();
+    par.lexer.consume();
     let arguments = par.call_arguments();
     let end = par.lexer.end_then_consume();
 
-    par.alloc_at_loc(start, end, CallExpression {
+    par.alloc_at_loc(left.start, end, CallExpression {
         callee: left,
         arguments,
     })
@@ -297,9 +297,9 @@ const CALL: NestedHandler = Some(|par, left| {
 const CMEM: NestedHandler = Some(|par, left| {
     par.lexer.consume();
     let property = par.expression::<ANY>();
+    let end = par.lexer.end();
 
     expect!(par, BracketClose);
-    let end = par.lexer.end();
 
     par.alloc_at_loc(left.start, end, ComputedMemberExpression {
         object: left,
@@ -1,9 +1,9 @@
 use toolshed::list::{ListBuilder, GrowableList};
-use parser::{Parser, Parse, ANY, B0};
+use parser::{Parser, Parse, SuperUsage, ANY, B0};
 use lexer::Token::*;
 use lexer::Asi;
 use ast::{Node, NodeList, Declarator, DeclarationKind};
-use ast::{Statement, StatementNode, Expression, ExpressionNode, Class, Function, Pattern};
+use ast::{Statement, StatementNode, Expression, ExpressionNode, Class, Pattern};
 use ast::expression::BinaryExpression;
 use ast::statement::{ThrowStatement, ContinueStatement, BreakStatement, ReturnStatement};
 use ast::statement::{TryStatement, CatchClause, IfStatement, WhileStatement, DoStatement};
@@ -34,7 +34,7 @@ static STMT_HANDLERS: [StatementHandler; 108] = [
     CONS, BRK,  DO,   ____, ____, ____, ____, CLAS, ____, RET,  WHL,  ____,
 //  CONST BREAK DO    CASE  ELSE  CATCH EXPRT CLASS EXTND RET   WHILE FINLY
 
-    ____, ____, CONT, FOR,  SWCH, ____, DBGGR, FUNC, THIS, ____, IF,   THRW,
+    SUPR, ____, CONT, FOR,  SWCH, ____, DBGGR, FUNC, THIS, ____, IF,   THRW,
 //  SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 
     IMPT, TRY,  ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
@@ -58,7 +58,7 @@ macro_rules! create_handlers {
 
 /// Shared expression handlers that produce StatementNode<'ast>
 use parser::expression::handlers::{
-    PRN, ARR, OP, NEW, REG, THIS, TRUE, FALS, NULL, UNDE, STR, NUM, BIN, TPLS, TPLE
+    PRN, ARR, OP, NEW, REG, SUPR, THIS, TRUE, FALS, NULL, UNDE, STR, NUM, BIN, TPLS, TPLE
 };
 
 create_handlers! {
@@ -135,22 +135,32 @@ impl<'ast> Parse<'ast> for SwitchCase<'ast> {
         let mut end = par.lexer.end();
         expect!(par, Colon);
 
-        let builder = GrowableList::new();
-
-        loop {
-            match par.lexer.token {
-                Case | Default | BraceClose => break,
-                _ => {
-                    let statement = par.statement();
-                    end = statement.end;
-                    builder.push(par.arena, statement);
+        let consequent = match par.lexer.token {
+            Case | Default | BraceClose | EndOfProgram => NodeList::empty(),
+            _ => {
+                let statement = par.statement();
+                let builder = ListBuilder::new(par.arena, statement);
+
+                end = statement.end;
+
+                loop {
+                    match par.lexer.token {
+                        Case | Default | BraceClose | EndOfProgram => break,
+                        _ => {
+                            let statement = par.statement();
+                            end = statement.end;
+                            builder.push(par.arena, statement);
+                        }
+                    }
                 }
+
+                builder.as_list()
             }
-        }
+        };
 
         par.alloc_at_loc(start, end, SwitchCase {
             test,
-            consequent: builder.as_list()
+            consequent,
         })
     }
 }
@@ -223,7 +233,7 @@ impl<'ast> Parser<'ast> {
     #[inline]
     pub fn function_statement(&mut self) -> StatementNode<'ast> {
         let start = self.lexer.start_then_consume();
-        let function = Function::parse(self);
+        let function = self.function(SuperUsage::Forbidden);
 
         self.alloc_at_loc(start, function.body.end, function)
     }
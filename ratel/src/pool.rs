@@ -0,0 +1,231 @@
+use toolshed::Arena;
+
+use std::cell::Cell;
+use std::sync::Mutex;
+
+use error::Error;
+use module::Module;
+use parser::{parse_in_arena, ParseOptions};
+
+/// Size of a page in `toolshed::Arena`.
+const ARENA_PAGE: usize = 64 * 1024;
+
+/// A pool of `Arena`s that are reset and reused, instead of allocating
+/// fresh memory for every parsed file.
+///
+/// An `Arena` only resets to the start of its current page. If parsing
+/// made it grow, or allocated anything larger than a page, which `Arena`
+/// keeps on the heap until it's dropped, the memory can't be reused, so
+/// such an `Arena` is replaced with a new one instead. The `Arena` is only
+/// reachable through `PooledArena::parse`, so that the pool knows about
+/// everything that is allocated outside of the page.
+#[derive(Default)]
+pub struct ArenaPool {
+    arenas: Mutex<Vec<Reusable>>,
+}
+
+/// `Arena` with the address at which its current page begins.
+struct Reusable {
+    arena: Arena,
+    page: usize,
+
+    /// Whether anything was allocated outside of the page.
+    spilled: Cell<bool>,
+}
+
+impl Reusable {
+    fn new() -> Self {
+        let arena = Arena::new();
+        let page = arena.alloc(0u8) as *const u8 as usize;
+
+        // Safe: the allocation above is not used for anything but its address
+        unsafe { arena.clear() };
+
+        Reusable {
+            arena,
+            page,
+            spilled: Cell::new(false),
+        }
+    }
+
+    /// Reset the `Arena`, requires exclusive access so that nothing
+    /// allocated on it can be alive.
+    fn reset(&mut self) {
+        let probe = self.arena.alloc(0u8) as *const u8 as usize;
+
+        if !self.spilled.get() && probe >= self.page && probe < self.page + ARENA_PAGE {
+            unsafe { self.arena.clear() };
+        } else {
+            *self = Reusable::new();
+        }
+    }
+}
+
+impl ArenaPool {
+    /// Create a new empty `ArenaPool`.
+    #[inline]
+    pub fn new() -> Self {
+        ArenaPool::default()
+    }
+
+    /// Take an `Arena` from the pool, or create a new one if the pool is empty.
+    /// The `Arena` is reset and returned to the pool when the `PooledArena` drops.
+    pub fn get(&self) -> PooledArena<'_> {
+        let reusable = self.arenas
+            .lock()
+            .expect("ArenaPool lock is poisoned")
+            .pop()
+            .unwrap_or_else(Reusable::new);
+
+        PooledArena {
+            pool: self,
+            reusable: Some(reusable),
+        }
+    }
+
+    /// Number of `Arena`s currently waiting in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.arenas.lock().expect("ArenaPool lock is poisoned").len()
+    }
+
+    /// Whether the pool is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// An `Arena` borrowed from an `ArenaPool`. The `Arena` itself isn't exposed,
+/// parse into it with `PooledArena::parse`.
+pub struct PooledArena<'pool> {
+    pool: &'pool ArenaPool,
+    reusable: Option<Reusable>,
+}
+
+impl<'pool> PooledArena<'pool> {
+    /// Parse into the `Arena`, same as `parse_in_arena`. Sources larger
+    /// than a page are copied into memory of their own, which the pool
+    /// only knows to free if they are parsed with this method.
+    pub fn parse(&self, source: &str, options: ParseOptions) -> Result<Module<'_>, Vec<Error>> {
+        let reusable = match self.reusable {
+            Some(ref reusable) => reusable,
+            None => unreachable!(),
+        };
+
+        // The lexer copies the source with a trailing null byte
+        if source.len() + 1 > ARENA_PAGE {
+            reusable.spilled.set(true);
+        }
+
+        parse_in_arena(&reusable.arena, source, options)
+    }
+
+    /// Reset the `Arena` so that its memory can be reused. Taking `&mut self`
+    /// guarantees no AST allocated on the `Arena` outlives the reset.
+    #[inline]
+    pub fn reset(&mut self) {
+        if let Some(ref mut reusable) = self.reusable {
+            reusable.reset();
+        }
+    }
+
+    /// Drop the `Arena` instead of returning it to the pool. Anything allocated
+    /// through `Module::arena` that doesn't fit in a page, such as strings made
+    /// with `Arena::alloc_string` by transforms, is only freed this way.
+    #[inline]
+    pub fn discard(mut self) {
+        self.reusable = None;
+    }
+}
+
+impl<'pool> Drop for PooledArena<'pool> {
+    fn drop(&mut self) {
+        if let Some(mut reusable) = self.reusable.take() {
+            reusable.reset();
+
+            if let Ok(mut arenas) = self.pool.arenas.lock() {
+                arenas.push(reusable);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Address of the first statement of the module.
+    fn first_address(arena: &PooledArena, source: &str) -> usize {
+        let module = arena.parse(source, ParseOptions::default()).unwrap();
+        let statement = module.body().first_element().unwrap();
+
+        statement as *const _ as usize
+    }
+
+    #[test]
+    fn reuses_arenas() {
+        let pool = ArenaPool::new();
+
+        let first = first_address(&pool.get(), "foo();");
+
+        assert_eq!(pool.len(), 1);
+
+        {
+            let arena = pool.get();
+
+            assert_eq!(pool.len(), 0);
+            assert_eq!(first_address(&arena, "bar();"), first);
+        }
+
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn reset_in_place() {
+        let pool = ArenaPool::new();
+        let mut arena = pool.get();
+
+        let first = first_address(&arena, "foo;");
+        arena.reset();
+        let second = first_address(&arena, "foo;");
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn replaces_arenas_with_large_sources() {
+        let pool = ArenaPool::new();
+        let source = "foo();".repeat(ARENA_PAGE / 4);
+
+        {
+            let arena = pool.get();
+
+            arena.parse(&source, ParseOptions::default()).unwrap();
+            assert!(arena.reusable.as_ref().unwrap().spilled.get());
+        }
+
+        let arena = pool.get();
+
+        assert!(!arena.reusable.as_ref().unwrap().spilled.get());
+    }
+
+    #[test]
+    fn replaces_grown_arenas() {
+        let pool = ArenaPool::new();
+
+        let source = "foo();".repeat(ARENA_PAGE / 4);
+
+        {
+            let arena = pool.get();
+
+            arena.parse(&source, ParseOptions::default()).unwrap();
+        }
+
+        let arena = pool.get();
+        let reusable = arena.reusable.as_ref().unwrap();
+        let probe = reusable.arena.alloc(0u8) as *const u8 as usize;
+
+        assert_eq!(probe, reusable.page);
+    }
+}
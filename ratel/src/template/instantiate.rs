@@ -0,0 +1,85 @@
+use toolshed::Arena;
+use toolshed::list::List;
+
+use ast::{Node, NodeList, Pattern, Expression, ExpressionNode, StatementList, CloneHook};
+use template::Substitution;
+
+/// Replaces `$placeholder` identifiers of a template with the nodes
+/// they are substituted with, when the template is cloned.
+#[derive(Clone, Copy)]
+pub struct Substitutions<'ast> {
+    arena: &'ast Arena,
+    list: List<'ast, (&'ast str, Substitution<'ast>)>,
+}
+
+impl<'ast> Substitutions<'ast> {
+    pub fn new(arena: &'ast Arena, substitutions: &[(&str, Substitution<'ast>)]) -> Self {
+        let list = substitutions.iter().map(|&(name, substitution)| {
+            (arena.alloc_str(name), substitution)
+        });
+
+        Substitutions {
+            arena,
+            list: List::from_iter(arena, list),
+        }
+    }
+
+    /// Find the substitution for a `$placeholder` identifier.
+    #[inline]
+    fn find(&self, ident: &str) -> Option<Substitution<'ast>> {
+        if !ident.starts_with('$') {
+            return None;
+        }
+
+        let name = &ident[1..];
+
+        self.list
+            .iter()
+            .find(|&&(key, _)| key == name)
+            .map(|&(_, substitution)| substitution)
+    }
+}
+
+impl<'ast> CloneHook<'ast> for Substitutions<'ast> {
+    fn name(&self, ident: &str) -> Option<&'ast str> {
+        let name = match self.find(ident)? {
+            Substitution::Identifier(name) => Some(name),
+            Substitution::Expression(expression) => match expression.item {
+                Expression::Identifier(name) => Some(name),
+                _ => None,
+            },
+            Substitution::Pattern(pattern) => match pattern.item {
+                Pattern::Identifier(name) => Some(name),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match name {
+            Some(name) => Some(name),
+            None => panic!("Template placeholder {} must be an identifier", ident),
+        }
+    }
+
+    fn expression(&self, ident: &str) -> Option<ExpressionNode<'ast>> {
+        match self.find(ident)? {
+            Substitution::Expression(expression) => Some(expression),
+            _ => None,
+        }
+    }
+
+    fn pattern(&self, ident: &str) -> Option<Node<'ast, Pattern<'ast>>> {
+        match self.find(ident)? {
+            Substitution::Pattern(pattern) => Some(pattern),
+            _ => None,
+        }
+    }
+
+    fn statements(&self, ident: &str) -> Option<StatementList<'ast>> {
+        match self.find(ident)? {
+            Substitution::Statement(statement) => Some(NodeList::from(self.arena, statement)),
+            Substitution::Statements(body) => Some(body),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+/// Instantiates a list of statements from a JavaScript template.
+///
+/// The template source is parsed into the given `Arena` on every use,
+/// nothing is kept around. Identifiers starting with `$` are placeholders,
+/// substituted by the named arguments following the source (without
+/// the `$`):
+///
+/// ```ignore
+/// let body = template!(arena, "var $name = $init;", name = "foo", init = expression);
+/// ```
+#[macro_export]
+macro_rules! template {
+    ($arena:expr, $source:expr $(, $name:ident = $value:expr)* $(,)*) => {
+        $crate::__ratel_template!(parse_statements, statements, $arena, $source $(, $name = $value)*)
+    };
+}
+
+/// Instantiates an expression from a JavaScript template,
+/// see `template!` for details.
+#[macro_export]
+macro_rules! quote_expr {
+    ($arena:expr, $source:expr $(, $name:ident = $value:expr)* $(,)*) => {
+        $crate::__ratel_template!(parse_expression, expression, $arena, $source $(, $name = $value)*)
+    };
+}
+
+/// Instantiates a pattern from a JavaScript template,
+/// see `template!` for details.
+#[macro_export]
+macro_rules! quote_pattern {
+    ($arena:expr, $source:expr $(, $name:ident = $value:expr)* $(,)*) => {
+        $crate::__ratel_template!(parse_pattern, pattern, $arena, $source $(, $name = $value)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ratel_template {
+    ($parse:ident, $instantiate:ident, $arena:expr, $source:expr $(, $name:ident = $value:expr)*) => {{
+        let arena: &$crate::Arena = $arena;
+        let template = $crate::template::Template::$parse(arena, $source)
+            .expect("Invalid JavaScript template");
+
+        template.$instantiate(arena, &[
+            $( (stringify!($name), $crate::template::Substitution::from($value)) ),*
+        ]).expect("Template is parsed as the kind it's instantiated as")
+    }};
+}
@@ -0,0 +1,292 @@
+//! JavaScript source templates with `$placeholders`, parsed once
+//! and instantiated into any `Arena` with substituted nodes.
+//!
+//! See the `template!`, `quote_expr!` and `quote_pattern!` macros.
+
+#[macro_use]
+mod macros;
+mod instantiate;
+
+use toolshed::Arena;
+
+use ast::{Node, Pattern, ExpressionNode, StatementNode, StatementList, DeepClone, Cloner, Spans};
+use error::Error;
+use parser::{parse_in, parse_expression, parse_pattern};
+
+use self::instantiate::Substitutions;
+
+/// Node to substitute a `$placeholder` with.
+#[derive(Debug, Clone, Copy)]
+pub enum Substitution<'ast> {
+    /// Name usable in any position, including member properties,
+    /// property keys, function names and labels.
+    Identifier(&'ast str),
+    Expression(ExpressionNode<'ast>),
+    Pattern(Node<'ast, Pattern<'ast>>),
+    /// Only usable in place of a `$placeholder;` statement.
+    Statement(StatementNode<'ast>),
+    /// Only usable in place of a `$placeholder;` statement, statements
+    /// are spliced into the surrounding list.
+    Statements(StatementList<'ast>),
+}
+
+impl<'ast> From<&'ast str> for Substitution<'ast> {
+    #[inline]
+    fn from(name: &'ast str) -> Self {
+        Substitution::Identifier(name)
+    }
+}
+
+impl<'ast> From<ExpressionNode<'ast>> for Substitution<'ast> {
+    #[inline]
+    fn from(expression: ExpressionNode<'ast>) -> Self {
+        Substitution::Expression(expression)
+    }
+}
+
+impl<'ast> From<Node<'ast, Pattern<'ast>>> for Substitution<'ast> {
+    #[inline]
+    fn from(pattern: Node<'ast, Pattern<'ast>>) -> Self {
+        Substitution::Pattern(pattern)
+    }
+}
+
+impl<'ast> From<StatementNode<'ast>> for Substitution<'ast> {
+    #[inline]
+    fn from(statement: StatementNode<'ast>) -> Self {
+        Substitution::Statement(statement)
+    }
+}
+
+impl<'ast> From<StatementList<'ast>> for Substitution<'ast> {
+    #[inline]
+    fn from(statements: StatementList<'ast>) -> Self {
+        Substitution::Statements(statements)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Root<'t> {
+    Statements(StatementList<'t>),
+    Expression(ExpressionNode<'t>),
+    Pattern(Node<'t, Pattern<'t>>),
+}
+
+/// What a `Template` was parsed as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateKind {
+    Statements,
+    Expression,
+    Pattern,
+}
+
+/// Error returned when instantiating a `Template` as a different kind
+/// than it was parsed as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KindError {
+    pub expected: TemplateKind,
+    pub found: TemplateKind,
+}
+
+/// A parsed JavaScript template.
+#[derive(Debug, Clone, Copy)]
+pub struct Template<'t> {
+    root: Root<'t>,
+}
+
+impl<'t> Template<'t> {
+    /// Parse a template of any number of statements.
+    pub fn parse_statements(arena: &'t Arena, source: &str) -> Result<Self, Vec<Error>> {
+        Ok(Template {
+            root: Root::Statements(parse_in(arena, source)?.body()),
+        })
+    }
+
+    /// Parse a template of a single expression.
+    pub fn parse_expression(arena: &'t Arena, source: &str) -> Result<Self, Vec<Error>> {
+        Ok(Template {
+            root: Root::Expression(parse_expression(arena, source)?),
+        })
+    }
+
+    /// Parse a template of a single binding pattern.
+    pub fn parse_pattern(arena: &'t Arena, source: &str) -> Result<Self, Vec<Error>> {
+        Ok(Template {
+            root: Root::Pattern(parse_pattern(arena, source)?),
+        })
+    }
+
+    /// What the template was parsed as.
+    pub fn kind(&self) -> TemplateKind {
+        match self.root {
+            Root::Statements(_) => TemplateKind::Statements,
+            Root::Expression(_) => TemplateKind::Expression,
+            Root::Pattern(_) => TemplateKind::Pattern,
+        }
+    }
+
+    /// Instantiate a statements template into the `Arena`.
+    pub fn statements<'ast>(&self, arena: &'ast Arena, substitutions: &[(&str, Substitution<'ast>)]) -> Result<StatementList<'ast>, KindError> {
+        match self.root {
+            Root::Statements(body) => Ok(body.deep_clone_with(&cloner(arena, substitutions))),
+            _ => Err(self.kind_error(TemplateKind::Statements)),
+        }
+    }
+
+    /// Instantiate an expression template into the `Arena`.
+    pub fn expression<'ast>(&self, arena: &'ast Arena, substitutions: &[(&str, Substitution<'ast>)]) -> Result<ExpressionNode<'ast>, KindError> {
+        match self.root {
+            Root::Expression(expression) => Ok(expression.deep_clone_with(&cloner(arena, substitutions))),
+            _ => Err(self.kind_error(TemplateKind::Expression)),
+        }
+    }
+
+    /// Instantiate a pattern template into the `Arena`.
+    pub fn pattern<'ast>(&self, arena: &'ast Arena, substitutions: &[(&str, Substitution<'ast>)]) -> Result<Node<'ast, Pattern<'ast>>, KindError> {
+        match self.root {
+            Root::Pattern(pattern) => Ok(pattern.deep_clone_with(&cloner(arena, substitutions))),
+            _ => Err(self.kind_error(TemplateKind::Pattern)),
+        }
+    }
+
+    #[inline]
+    fn kind_error(&self, expected: TemplateKind) -> KindError {
+        KindError {
+            expected,
+            found: self.kind(),
+        }
+    }
+}
+
+/// Cloner instantiating a template with the substitutions.
+fn cloner<'ast>(arena: &'ast Arena, substitutions: &[(&str, Substitution<'ast>)]) -> Cloner<'ast> {
+    let hook = arena.alloc(Substitutions::new(arena, substitutions));
+
+    Cloner::with_hook(arena, Spans::Strip, hook)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use build::Builder;
+    use parser::parse;
+    use ast::{Expression, Literal, OperatorKind};
+    use ast::expression::BinaryExpression;
+
+    #[test]
+    fn template_statements() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let body = template!(&arena, "var $name = $init; $rest; return $name;",
+            name = "foo",
+            init = b.integer(42),
+            rest = b.list(vec![b.expression_statement(b.ident("bar")), b.empty_statement()]),
+        );
+
+        let expected = parse("var foo = 42; bar; ; return foo;").unwrap();
+
+        assert_eq!(body, expected.body());
+    }
+
+    #[test]
+    fn template_expression() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let expression = quote_expr!(&arena, "Object.defineProperty($target, $key, { value: $value })",
+            target = b.this(),
+            key = b.string("foo"),
+            value = b.integer(1),
+        );
+
+        let expected = parse(r#"Object.defineProperty(this, "foo", { value: 1 });"#).unwrap();
+
+        assert_eq!(b.list_from(b.expression_statement(expression)), expected.body());
+    }
+
+    #[test]
+    fn template_expression_names() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let expression = quote_expr!(&arena, "function $name() { return { $key: this.$key } }",
+            name = "foo",
+            key = "bar",
+        );
+
+        let expected = parse("(function foo() { return { bar: this.bar } });").unwrap();
+
+        assert_eq!(b.list_from(b.expression_statement(expression)), expected.body());
+    }
+
+    #[test]
+    fn template_pattern() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let pattern = quote_pattern!(&arena, "{ a, b: $b = $default }",
+            b = b.array_pattern(b.list(vec![b.pattern("c")])),
+            default = b.null(),
+        );
+
+        let expected = Template::parse_pattern(&arena, "{ a, b: [c] = null }").unwrap();
+
+        assert_eq!(pattern, expected.pattern(&arena, &[]).unwrap());
+    }
+
+    #[test]
+    fn template_is_reusable() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+        let template = Template::parse_expression(&arena, "$left + $right").unwrap();
+
+        let first = template.expression(&arena, &[("left", b.integer(1).into()), ("right", b.integer(2).into())]).unwrap();
+        let second = template.expression(&arena, &[("left", b.ident("a").into()), ("right", b.ident("b").into())]).unwrap();
+
+        assert_eq!(first.item, Expression::Binary(BinaryExpression {
+            operator: OperatorKind::Addition,
+            left: b.literal(Literal::Number("1")),
+            right: b.literal(Literal::Number("2")),
+        }));
+        assert_eq!(second.item, Expression::Binary(BinaryExpression {
+            operator: OperatorKind::Addition,
+            left: b.ident("a"),
+            right: b.ident("b"),
+        }));
+    }
+
+    #[test]
+    fn template_unknown_placeholders_are_kept() {
+        let arena = Arena::new();
+        let b = Builder::new(&arena);
+
+        let expression = quote_expr!(&arena, "$($foo)");
+
+        assert_eq!(expression, b.call(b.ident("$"), b.list_from(b.ident("$foo"))));
+    }
+
+    #[test]
+    fn template_errors() {
+        let arena = Arena::new();
+
+        let errors = Template::parse_expression(&arena, "foo +").unwrap_err();
+
+        assert!(errors.iter().all(|error| error.start <= 5 && error.end <= 5));
+
+        assert!(Template::parse_pattern(&arena, "a, b").is_err());
+    }
+
+    #[test]
+    fn template_wrong_kind() {
+        let arena = Arena::new();
+        let template = Template::parse_expression(&arena, "$a + 1").unwrap();
+
+        assert_eq!(template.kind(), TemplateKind::Expression);
+        assert_eq!(template.statements(&arena, &[]).unwrap_err(), KindError {
+            expected: TemplateKind::Statements,
+            found: TemplateKind::Expression,
+        });
+        assert!(template.pattern(&arena, &[]).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+class A extends B {
+  constructor(a) { super(a) }
+  method() { return super.method() }
+  static create() {}
+  get x() { return 1 }
+  set x(value) {}
+  static get y() {}
+  [key]() {}
+}
@@ -0,0 +1,6 @@
+function f(a, b = 1, ...c) {
+  return a
+}
+g = function (d, [e, f], {g: h}) {}
+h = (a, b) => a + b
+i = a => {}
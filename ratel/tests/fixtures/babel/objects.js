@@ -0,0 +1,8 @@
+o = {
+  a: 1,
+  c() {},
+  [d]: 2,
+  "e": 3,
+  4: 5,
+  f: function () {}
+}
@@ -0,0 +1,10 @@
+"use strict"
+'use asm'
+function f() {
+  "use strict"
+  return 1
+}
+g = () => {
+  "use strict"
+}
+"not a directive"
@@ -0,0 +1 @@
+x = ["a", 'b', 42, 0xff, 1.5e3, .5, 0b101, 0o17, true, false, null, /ab+c/gi, `a${b}c`]
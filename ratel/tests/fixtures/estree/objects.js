@@ -0,0 +1,9 @@
+o = {
+  a: 1,
+  b,
+  c() {},
+  [d]: 2,
+  "e": 3,
+  4: 5,
+  f: function () {}
+}
@@ -0,0 +1,77 @@
+//! Compares the output of both AST flavors with reference JSON.
+//!
+//! Every `fixtures/<flavor>/<name>.js` is parsed and serialized, and the
+//! result compared to `<name>.json` next to it, spans included. The
+//! reference JSON is the output of Babel and Acorn with `sourceType` set to
+//! `"module"`, stripped of the fields ratel does not produce (`loc`,
+//! `range`, `comments`, `errors` and `interpreter`). Sources are parsed
+//! without their trailing newline, and JSON numbers are compared by value
+//! so that `1500` matches `1500.0`.
+
+extern crate ratel;
+extern crate serde_json;
+#[macro_use]
+extern crate pretty_assertions;
+
+use std::fs;
+use std::path::Path;
+
+use ratel::AstFlavor;
+use serde_json::Value;
+
+/// Turns every number into a float.
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::Number(number) => {
+            Value::from(number.as_f64().expect("Number must fit a float"))
+        },
+        Value::Array(values) => {
+            Value::Array(values.into_iter().map(normalize).collect())
+        },
+        Value::Object(map) => {
+            Value::Object(map.into_iter().map(|(key, value)| (key, normalize(value))).collect())
+        },
+        value => value,
+    }
+}
+
+fn check_fixtures(flavor: AstFlavor, dir: &str) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(dir);
+
+    let mut sources: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "js"))
+        .collect();
+
+    sources.sort();
+
+    assert!(!sources.is_empty(), "No fixtures in {}", dir.display());
+
+    for path in sources {
+        let source = fs::read_to_string(&path).unwrap();
+        let expected = fs::read_to_string(path.with_extension("json")).unwrap();
+
+        let module = match ratel::parse(source.trim_end()) {
+            Ok(module) => module,
+            Err(errors) => panic!("Failed to parse {}: {:?}", path.display(), errors),
+        };
+
+        let result = serde_json::to_value(module.module().with_flavor(flavor)).unwrap();
+        let expected: Value = serde_json::from_str(&expected).unwrap();
+
+        println!("Checking {}", path.display());
+
+        assert_eq!(normalize(result), normalize(expected));
+    }
+}
+
+#[test]
+fn babel_fixtures() {
+    check_fixtures(AstFlavor::Babel, "babel");
+}
+
+#[test]
+fn estree_fixtures() {
+    check_fixtures(AstFlavor::ESTree, "estree");
+}
@@ -0,0 +1,103 @@
+//! Memory held by an `ArenaPool`, in a binary of its own so that the
+//! allocator only counts allocations made by these tests.
+
+extern crate ratel;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ratel::{ArenaPool, ParseOptions};
+
+/// Counts the bytes currently allocated.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Tests run in parallel, but the count is shared.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// Bytes the memory grew by while running `f` 50 more times, after a first
+/// run that fills the pool.
+fn growth<F: FnMut()>(mut f: F) -> usize {
+    let _serial = SERIAL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    f();
+
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+
+    for _ in 0..50 {
+        f();
+    }
+
+    ALLOCATED.load(Ordering::SeqCst).saturating_sub(baseline)
+}
+
+#[test]
+fn pool_memory_stays_bounded_with_large_sources() {
+    let pool = ArenaPool::new();
+
+    // Few nodes, so that only the copy of the source is larger than a page
+    let source = format!("/*{}*/ foo();", "-".repeat(100_000));
+
+    assert!(source.len() > 64 * 1024);
+
+    let grown = growth(|| {
+        let arena = pool.get();
+
+        arena.parse(&source, ParseOptions::default()).unwrap();
+    });
+
+    assert!(grown < source.len(), "Pool grew by {} bytes", grown);
+}
+
+#[test]
+fn pool_memory_stays_bounded_with_large_modules() {
+    let pool = ArenaPool::new();
+
+    // Small source with enough nodes to fill several pages
+    let source = "foo();".repeat(10_000);
+
+    let grown = growth(|| {
+        let arena = pool.get();
+
+        arena.parse(&source, ParseOptions::default()).unwrap();
+    });
+
+    assert!(grown < 64 * 1024, "Pool grew by {} bytes", grown);
+}
+
+#[test]
+fn pool_memory_stays_bounded_with_discarded_arenas() {
+    let pool = ArenaPool::new();
+
+    let grown = growth(|| {
+        let arena = pool.get();
+
+        {
+            let module = arena.parse("foo();", ParseOptions::default()).unwrap();
+
+            // Such as names generated by a transform
+            module.arena().alloc_string("-".repeat(100_000));
+        }
+
+        arena.discard();
+    });
+
+    assert!(grown < 100_000, "Pool grew by {} bytes", grown);
+}